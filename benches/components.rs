@@ -0,0 +1,190 @@
+//! Benchmarks for parsing and finding the components of
+//! representative graphs, timed separately. Run with `cargo bench`,
+//! optionally followed by `-- <filter>` to only run the cases whose
+//! names contain the filter.
+//!
+//! Set `BENCH_GFA` to the path of a GFA file to also benchmark a
+//! real graph; otherwise a synthetic chain of bubbles stands in for
+//! one.
+
+use std::{
+    io::Cursor,
+    time::{Duration, Instant},
+};
+
+use three_edge_connected::{
+    algorithm,
+    generators::{self, complete_edges, PlantedPartition},
+    graph::FxMapGraph,
+    Graph,
+};
+
+/// Each case is repeated until it has run for at least this long,
+/// and at least `MIN_ITERATIONS` times.
+const TARGET_TIME: Duration = Duration::from_secs(2);
+const MIN_ITERATIONS: usize = 5;
+
+struct Timing {
+    min: Duration,
+    median: Duration,
+    iterations: usize,
+}
+
+fn time<F: FnMut()>(mut f: F) -> Timing {
+    let mut times = Vec::new();
+    let start = Instant::now();
+    while times.len() < MIN_ITERATIONS || start.elapsed() < TARGET_TIME {
+        let iter_start = Instant::now();
+        f();
+        times.push(iter_start.elapsed());
+    }
+    times.sort_unstable();
+    Timing {
+        min: times[0],
+        median: times[times.len() / 2],
+        iterations: times.len(),
+    }
+}
+
+fn report(case: &str, phase: &str, timing: &Timing) {
+    println!(
+        "{:<24} {:<10} median {:>12.3?}  min {:>12.3?}  ({} runs)",
+        case, phase, timing.median, timing.min, timing.iterations
+    );
+}
+
+fn bench_algorithm(case: &str, graph: &FxMapGraph) {
+    let timing = time(|| {
+        std::hint::black_box(algorithm::find_components(graph));
+    });
+    report(case, "algorithm", &timing);
+}
+
+fn bench_gfa(case: &str, gfa: &[u8]) {
+    let timing = time(|| {
+        let graph = Graph::from_gfa_reader(&mut Cursor::new(gfa)).unwrap();
+        std::hint::black_box(graph);
+    });
+    report(case, "parse", &timing);
+
+    let graph = Graph::from_gfa_reader(&mut Cursor::new(gfa)).unwrap();
+    bench_algorithm(case, &graph.graph);
+}
+
+/// `num` complete graphs on `size` nodes, each connected to the
+/// next by two edges, so they're all separate components.
+fn clique_path_edges(num: usize, size: usize) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+    for i in 0..num {
+        edges.extend(complete_edges(i * size, size));
+        if i > 0 {
+            edges.push((i * size - 1, i * size));
+            edges.push((i * size - 2, i * size + 1));
+        }
+    }
+    edges
+}
+
+/// `num` complete graphs on `size` nodes in a cycle, each connected
+/// to the next by one edge, so that the DFS goes around the whole
+/// cycle before it comes back to the first one, along a path through
+/// all of them.
+fn clique_cycle_edges(num: usize, size: usize) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+    for i in 0..num {
+        edges.extend(complete_edges(i * size, size));
+        edges.push((i * size + size - 1, (i + 1) % num * size));
+    }
+    edges
+}
+
+/// A chain of `num` bubbles, as found in pangenome graphs: each
+/// bubble is a pair of parallel paths of `len` nodes between two
+/// anchor nodes, and consecutive bubbles share an anchor.
+fn bubble_chain_edges(num: usize, len: usize) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+    let mut next = num + 1;
+    for anchor in 0..num {
+        for _ in 0..2 {
+            let mut prev = anchor;
+            for _ in 0..len {
+                edges.push((prev, next));
+                prev = next;
+                next += 1;
+            }
+            edges.push((prev, anchor + 1));
+        }
+    }
+    edges
+}
+
+/// A wheel with `num` spokes, whose hub has `num` neighbors.
+fn wheel_edges(num: usize) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+    for rim in 1..=num {
+        edges.push((0, rim));
+        edges.push((rim, rim % num + 1));
+    }
+    edges
+}
+
+/// A GFA of the given edges where each segment has a sequence of
+/// `len` bases, which the parser reads past to get to the links.
+fn gfa_with_sequences(edges: &[(usize, usize)], len: usize) -> Vec<u8> {
+    let num_nodes = edges.iter().map(|&(a, b)| a.max(b) + 1).max();
+    let mut gfa = b"H\tVN:Z:1.0\n".to_vec();
+    for node in 0..num_nodes.unwrap_or(0) {
+        gfa.extend_from_slice(format!("S\t{}\t", node).as_bytes());
+        gfa.extend((0..len).map(|i| b"ACGT"[(node + i) % 4]));
+        gfa.push(b'\n');
+    }
+    for &(from, to) in edges {
+        gfa.extend_from_slice(
+            format!("L\t{}\t+\t{}\t+\t0M\n", from, to).as_bytes(),
+        );
+    }
+    gfa
+}
+
+fn main() {
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with("--"));
+    let enabled =
+        |case: &str| filter.as_ref().is_none_or(|f| case.contains(f.as_str()));
+
+    let mut cases: Vec<(&str, Vec<(usize, usize)>)> = vec![
+        ("dense_clique_1000", complete_edges(0, 1000)),
+        ("clique_path_100k", clique_path_edges(25_000, 4)),
+        ("clique_cycle_100k", clique_cycle_edges(20_000, 5)),
+        ("clique_cycle_1m", clique_cycle_edges(200_000, 5)),
+        (
+            "planted_random_100k",
+            PlantedPartition::random(589, 4000, 46).edges,
+        ),
+        ("grid_500x500", generators::grid_edges(500, 500)),
+        ("wheel_hub_1m", wheel_edges(1_000_000)),
+    ];
+    if std::env::var_os("BENCH_GFA").is_none() {
+        cases.push(("bubble_chain_1m", bubble_chain_edges(100_000, 5)));
+    }
+
+    for (case, edges) in cases {
+        if !enabled(case) {
+            continue;
+        }
+        let mut gfa = Vec::new();
+        generators::write_gfa(&mut gfa, &edges).unwrap();
+        bench_gfa(case, &gfa);
+    }
+
+    if enabled("sequences_100k") {
+        let gfa = gfa_with_sequences(&bubble_chain_edges(10_000, 5), 200);
+        bench_gfa("sequences_100k", &gfa);
+    }
+
+    if let Some(path) = std::env::var_os("BENCH_GFA") {
+        if enabled("real_gfa") {
+            let gfa = std::fs::read(path).expect("could not read BENCH_GFA");
+            bench_gfa("real_gfa", &gfa);
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Runs the algorithm on small random GFA graphs, and checks the
+//! components against the brute-force definition.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use three_edge_connected::{algorithm, brute_force, generators, Graph};
+
+fuzz_target!(|data: &[u8]| {
+    let gfa = generators::gfa_from_bytes(data);
+    let graph = Graph::from_gfa_reader(&mut std::io::Cursor::new(&gfa))
+        .expect("generated GFA should parse");
+
+    let mut components = algorithm::find_components(&graph.graph);
+    components.iter_mut().for_each(|c| c.sort_unstable());
+    components.sort_unstable();
+
+    assert_eq!(components, brute_force::find_components(&graph.graph));
+});
@@ -0,0 +1,61 @@
+//! Feeds arbitrary bytes to each of the input parsers, and runs the
+//! algorithm on whatever graph they produce.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::io::{self, Cursor};
+
+use three_edge_connected::{
+    algorithm,
+    graph::{self, EdgeTable, InternedGraph},
+    lgf, paf, pajek, tgf, vg, Graph,
+};
+
+/// Checks that every node is in exactly one component.
+fn check_components(components: Vec<Vec<usize>>, num_nodes: usize) {
+    let mut nodes: Vec<usize> = components.into_iter().flatten().collect();
+    nodes.sort_unstable();
+    assert!(nodes.into_iter().eq(0..num_nodes));
+}
+
+fn check(graph: io::Result<Graph<Vec<u8>>>) {
+    if let Ok(graph) = graph {
+        let components = algorithm::find_components(&graph.graph);
+        check_components(components, graph.inv_names.len());
+    }
+}
+
+fn check_interned(graph: io::Result<InternedGraph>) {
+    if let Ok(graph) = graph {
+        let components = algorithm::find_components(&graph.graph);
+        check_components(components, graph.names.len());
+    }
+}
+
+/// Builds the graph of the edges of one of the parsers, until the
+/// first error.
+fn check_edges<I>(edges: I)
+where
+    I: IntoIterator<Item = io::Result<(Vec<u8>, Vec<u8>)>>,
+{
+    check_interned(InternedGraph::try_from_named_edges(edges));
+}
+
+fuzz_target!(|data: &[u8]| {
+    check(Graph::from_gfa_reader(&mut Cursor::new(data)));
+    check(Graph::from_edge_list_reader(&mut Cursor::new(data)));
+    let csv = EdgeTable {
+        delimiter: Some(b','),
+        ..EdgeTable::default()
+    };
+    check_edges(graph::edge_table_edges(data, csv));
+    check_edges(graph::adjacency_list_edges(data));
+    check_edges(pajek::pajek_edges(data));
+    check_edges(tgf::tgf_edges(data));
+    check_edges(lgf::lgf_edges(data));
+    check_edges(paf::paf_edges(data, paf::PafFilter::default()));
+    check_edges(vg::vg_edges(data));
+    check(Graph::read_from(&mut Cursor::new(data)));
+    check_interned(InternedGraph::read_from(&mut Cursor::new(data)));
+});
@@ -0,0 +1,162 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// The graph representation used by the algorithm: the nodes must be
+/// numbered `0..num_nodes()`, and each edge must be in the neighbor
+/// lists of both of its ends. This is implemented for the
+/// `FxMapGraph` used by `Graph`, for `IndexedGraph`, and for
+/// `Vec<Vec<usize>>`, slices of neighbor lists, and `CsrAdjacency`,
+/// which are also available without `std`.
+pub trait Adjacency {
+    type Nodes<'a>: Iterator<Item = usize>
+    where
+        Self: 'a;
+
+    fn num_nodes(&self) -> usize;
+
+    /// The nodes in the order they're used as DFS roots.
+    fn nodes(&self) -> Self::Nodes<'_>;
+
+    fn neighbors(&self, node: usize) -> &[usize];
+}
+
+#[cfg(feature = "std")]
+impl Adjacency for crate::graph::FxMapGraph {
+    type Nodes<'a> = core::iter::Copied<
+        std::collections::hash_map::Keys<
+            'a,
+            usize,
+            crate::graph::AdjacencyList,
+        >,
+    >;
+
+    fn num_nodes(&self) -> usize {
+        self.len()
+    }
+
+    fn nodes(&self) -> Self::Nodes<'_> {
+        self.keys().copied()
+    }
+
+    fn neighbors(&self, node: usize) -> &[usize] {
+        &self[&node]
+    }
+}
+
+#[cfg(feature = "std")]
+impl Adjacency for crate::graph::IndexedGraph {
+    type Nodes<'a> = Range<usize>;
+
+    fn num_nodes(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    fn nodes(&self) -> Self::Nodes<'_> {
+        0..self.adjacency.len()
+    }
+
+    fn neighbors(&self, node: usize) -> &[usize] {
+        &self.adjacency[node]
+    }
+}
+
+impl Adjacency for [Vec<usize>] {
+    type Nodes<'a> = Range<usize>;
+
+    fn num_nodes(&self) -> usize {
+        self.len()
+    }
+
+    fn nodes(&self) -> Self::Nodes<'_> {
+        0..self.len()
+    }
+
+    fn neighbors(&self, node: usize) -> &[usize] {
+        &self[node]
+    }
+}
+
+impl Adjacency for Vec<Vec<usize>> {
+    type Nodes<'a> = Range<usize>;
+
+    fn num_nodes(&self) -> usize {
+        self.len()
+    }
+
+    fn nodes(&self) -> Self::Nodes<'_> {
+        0..self.len()
+    }
+
+    fn neighbors(&self, node: usize) -> &[usize] {
+        &self[node]
+    }
+}
+
+/// Borrowed neighbor lists, e.g. of a graph that is stored elsewhere,
+/// so that the algorithm can be run on them without copying.
+impl Adjacency for [&[usize]] {
+    type Nodes<'a>
+        = Range<usize>
+    where
+        Self: 'a;
+
+    fn num_nodes(&self) -> usize {
+        self.len()
+    }
+
+    fn nodes(&self) -> Self::Nodes<'_> {
+        0..self.len()
+    }
+
+    fn neighbors(&self, node: usize) -> &[usize] {
+        self[node]
+    }
+}
+
+/// A graph in compressed sparse row form, borrowed from the caller:
+/// the neighbors of node `n` are `neighbors[offsets[n]..offsets[n + 1]]`,
+/// so there is one more offset than there are nodes. Like the files of
+/// `MmapGraph`, this is the layout of many other graph libraries, which
+/// can then be run without building a `Graph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsrAdjacency<'a> {
+    offsets: &'a [usize],
+    neighbors: &'a [usize],
+}
+
+impl<'a> CsrAdjacency<'a> {
+    /// Returns `None` if the offsets don't start at 0, decrease
+    /// anywhere, or end at a different value than the number of
+    /// neighbors, including if there are no offsets at all.
+    pub fn new(
+        offsets: &'a [usize],
+        neighbors: &'a [usize],
+    ) -> Option<CsrAdjacency<'a>> {
+        if offsets.first() != Some(&0)
+            || offsets.last() != Some(&neighbors.len())
+            || offsets.windows(2).any(|w| w[0] > w[1])
+        {
+            return None;
+        }
+        Some(CsrAdjacency { offsets, neighbors })
+    }
+}
+
+impl Adjacency for CsrAdjacency<'_> {
+    type Nodes<'a>
+        = Range<usize>
+    where
+        Self: 'a;
+
+    fn num_nodes(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn nodes(&self) -> Self::Nodes<'_> {
+        0..self.num_nodes()
+    }
+
+    fn neighbors(&self, node: usize) -> &[usize] {
+        &self.neighbors[self.offsets[node]..self.offsets[node + 1]]
+    }
+}
@@ -1,12 +1,25 @@
-use std::collections::VecDeque;
+use alloc::vec::Vec;
+use core::{fmt, sync::atomic::AtomicBool};
 
-use crate::{graph::FxMapGraph, state::State};
+use crate::{
+    adjacency::Adjacency,
+    cancel::{self, Cancelled},
+    forest::SpanningForest,
+    index::IndexType,
+    progress::Progress,
+    state::{BitVec, CorruptState, State},
+    visitor::Visitor,
+};
 
-#[derive(Debug)]
-enum Inst {
-    Recur(usize, usize),
-    Loop(usize, usize, usize),
-    Return(usize, usize),
+/// The node of the DFS tree that's being visited, with its parent in
+/// the tree and the position of the next edge to loop over in its
+/// neighbor list, so that the edges are only looked at one at a time,
+/// even for nodes with a huge degree.
+struct Frame<'g> {
+    node: usize,
+    parent: usize,
+    neighbors: &'g [usize],
+    cursor: usize,
 }
 
 macro_rules! assert_state_len {
@@ -23,122 +36,549 @@ macro_rules! assert_state_len {
     };
 }
 
-type InstStack = VecDeque<Inst>;
+/// The number of edges between checks of the cancellation flag, and
+/// between progress reports.
+const CHECK_INTERVAL: usize = 1 << 12;
 
-fn run_inst(
-    inst: Inst,
-    stack: &mut InstStack,
-    state: &mut State,
-    graph: &FxMapGraph,
+/// Starts visiting `w`, as a child of `v`, and returns its frame.
+fn discover<'g, Ix, G, V>(
+    w: usize,
+    v: usize,
+    state: &mut State<Ix>,
+    graph: &'g G,
+    visitor: &mut V,
+) -> Frame<'g>
+where
+    Ix: IndexType,
+    G: Adjacency + ?Sized,
+    V: Visitor + ?Sized,
+{
+    state.mut_recur(w);
+    visitor.on_discover(w);
+    // the root of each tree is its own parent
+    if v != w {
+        visitor.on_tree_edge(v, w);
+    }
+    Frame {
+        node: w,
+        parent: v,
+        neighbors: graph.neighbors(w),
+        cursor: 0,
+    }
+}
+
+/// Handles the edge (w, u) of `w`, whose parent is `v`, and returns
+/// whether `u` hasn't been visited yet, so it's a child of `w`, or an
+/// error if the state is corrupt.
+fn visit_edge<Ix, V>(
+    w: usize,
+    v: usize,
+    u: usize,
+    state: &mut State<Ix>,
+    visitor: &mut V,
+) -> Result<bool, CorruptState>
+where
+    Ix: IndexType,
+    V: Visitor + ?Sized,
+{
+    assert_state_len!(state, w);
+    assert_state_len!(state, v);
+    assert_state_len!(state, u);
+    state.degrees[w] += 1.into();
+
+    if !state.visited.get(u) {
+        return Ok(true);
+    }
+    // (w, u) outgoing back-edge of w, i.e. dfs(w) > dfs(u)
+    if u != v && state.is_back_edge(w, u) {
+        if state.pre[u] < state.lowpt[w] {
+            state.try_absorb_path_with_visitor(
+                w,
+                state.next_on_path[w].index(),
+                None,
+                visitor,
+            )?;
+            state.next_on_path[w] = Ix::new(w); // P_w in paper
+            state.lowpt[w] = state.pre[u];
+        }
+    // (w, u) incoming back-edge of w, i.e. dfs(u) > dfs(w)
+    } else if u != v {
+        visitor.on_back_edge(u, w);
+        state.degrees[w] -= 2.into();
+
+        if !state.is_null_path(w) {
+            state.absorb_ancestors_with_visitor(w, u, visitor)?;
+        }
+    }
+    Ok(false)
+}
+
+/// Finishes the child `u` of `w`, once all of its edges are done.
+fn finish_child<Ix, V>(
+    w: usize,
+    u: usize,
+    state: &mut State<Ix>,
+    visitor: &mut V,
+) -> Result<(), CorruptState>
+where
+    Ix: IndexType,
+    V: Visitor + ?Sized,
+{
+    assert_state_len!(state, w);
+    assert_state_len!(state, u);
+    state.num_descendants[w] = Ix::new(
+        state.num_descendants[w].index() + state.num_descendants[u].index(),
+    );
+
+    if state.degrees[u] <= 2.into() {
+        let degree = state.degrees[u];
+        state.degrees[w] += degree - 2.into();
+        state.try_add_component(u)?;
+        visitor.on_component(state.sigma.last().unwrap());
+
+        state.path_u = if state.is_null_path(u) {
+            w // P_u = w + P_u
+        } else {
+            state.next_on_path[u].index() // P_u
+        };
+    } else {
+        // since degree[u] != 2, u can be absorbed
+        state.path_u = u;
+    }
+
+    if state.lowpt[w] <= state.lowpt[u] {
+        // w + P_u in paper
+        state.try_absorb_path_with_visitor(w, state.path_u, None, visitor)?;
+    } else {
+        state.lowpt[w] = state.lowpt[u];
+        // P_w in paper
+        state.try_absorb_path_with_visitor(
+            w,
+            state.next_on_path[w].index(),
+            None,
+            visitor,
+        )?;
+        state.next_on_path[w] = Ix::new(state.path_u);
+    }
+    Ok(())
+}
+
+/// With the `debug-invariants` feature, panics if the state doesn't
+/// satisfy `State::check_invariants`, after each step of the algorithm.
+#[inline]
+fn debug_check<Ix: IndexType, G: Adjacency + ?Sized>(
+    state: &State<Ix>,
+    graph: &G,
+) {
+    #[cfg(feature = "debug-invariants")]
+    if let Err(err) = state.check_invariants(graph) {
+        panic!("invariant violated: {}", err);
+    }
+    #[cfg(not(feature = "debug-invariants"))]
+    let _ = (state, graph);
+}
+
+/// Runs the algorithm on the graph, using the given state, which must
+/// have been initialized for the graph. Afterwards, the components are
+/// in `state.sigma`. `find_components` does this with a new state; this
+/// is for when the state is also needed, e.g. for its memory usage.
+pub fn three_edge_connect<Ix: IndexType, G: Adjacency + ?Sized>(
+    graph: &G,
+    state: &mut State<Ix>,
 ) {
-    match inst {
-        Inst::Recur(w, v) => {
-            state.mut_recur(w);
-
-            graph[&w]
-                .iter()
-                .rev()
-                .for_each(|edge| stack.push_front(Inst::Loop(w, v, *edge)));
+    three_edge_connect_with_visitor(graph, state, &mut ())
+}
+
+/// A violated precondition of the algorithm, found by
+/// `check_input`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidInput {
+    /// The state doesn't have one entry per node of the graph, e.g.
+    /// because it was initialized for another graph.
+    StateLength { state: usize, graph: usize },
+    /// The graph has more nodes than the state's index type supports.
+    TooManyNodes { nodes: usize, max: usize },
+    /// `Adjacency::nodes` returned a node that isn't in the graph.
+    NodeOutOfRange { node: usize },
+    /// `Adjacency::nodes` returned a node more than once.
+    DuplicateNode { node: usize },
+    /// `Adjacency::nodes` didn't return a node of the graph.
+    MissingNode { node: usize },
+    /// A node has a neighbor that isn't in the graph.
+    NeighborOutOfRange { node: usize, neighbor: usize },
+    /// The state's linked lists have a cycle, found while running the
+    /// algorithm, e.g. because its fields were changed after a
+    /// previous run without a `reset`.
+    CorruptState(CorruptState),
+}
+
+impl fmt::Display for InvalidInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidInput::StateLength { state, graph } => write!(
+                f,
+                "state has {} nodes, but the graph has {}",
+                state, graph
+            ),
+            InvalidInput::TooManyNodes { nodes, max } => write!(
+                f,
+                "graph has {} nodes, more than the index type supports ({})",
+                nodes, max
+            ),
+            InvalidInput::NodeOutOfRange { node } => {
+                write!(f, "node {} is out of range", node)
+            }
+            InvalidInput::DuplicateNode { node } => {
+                write!(f, "node {} is listed more than once", node)
+            }
+            InvalidInput::MissingNode { node } => {
+                write!(f, "node {} isn't listed", node)
+            }
+            InvalidInput::NeighborOutOfRange { node, neighbor } => write!(
+                f,
+                "node {} has neighbor {}, which is out of range",
+                node, neighbor
+            ),
+            InvalidInput::CorruptState(err) => {
+                write!(f, "corrupt state: {}", err)
+            }
         }
-        Inst::Loop(w, v, u) => {
-            assert_state_len!(state, w);
-            assert_state_len!(state, v);
-            assert_state_len!(state, u);
-            state.degrees[w] += 1;
-
-            if !state.visited[u] {
-                stack.push_front(Inst::Return(w, u));
-                stack.push_front(Inst::Recur(u, w));
-            } else {
-                // (w, u) outgoing back-edge of w, i.e. dfs(w) > dfs(u)
-                if u != v && state.is_back_edge(w, u) {
-                    if state.pre[u] < state.lowpt[w] {
-                        state.absorb_path(w, state.next_on_path[w], None);
-                        state.next_on_path[w] = w; // P_w in paper
-                        state.lowpt[w] = state.pre[u];
-                    }
-                // (w, u) incoming back-edge of w, i.e. dfs(u) > dfs(w)
-                } else if u != v {
-                    state.degrees[w] -= 2;
-
-                    if !state.is_null_path(w) {
-                        let mut parent = w;
-                        let mut child = state.next_on_path[w];
-
-                        while !state.is_null_path(parent)
-                            && state.pre[child] <= state.pre[u]
-                        // child must have been visited before u
-                            && state.pre[u] < state.pre[child] + state.num_descendants[child]
-                        // child is still an ancestor of u
-                        {
-                            parent = child;
-                            child = state.next_on_path[child];
-                        }
-
-                        // P_w[w..u] in paper
-                        state.absorb_path(
-                            w,
-                            state.next_on_path[w],
-                            Some(parent),
-                        );
-
-                        state.next_on_path[w] = if state.is_null_path(parent) {
-                            w
-                        } else {
-                            state.next_on_path[parent]
-                        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidInput {}
+
+/// Checks that the algorithm can be run on the graph with the state:
+/// that the state has been initialized for the number of nodes of the
+/// graph, that `Adjacency::nodes` returns each node below that number
+/// once, and that every neighbor is one of them. Running the algorithm
+/// on input that fails these checks panics, or gives meaningless
+/// components. This loops over all the edges, so it takes about as
+/// long as reading the graph once.
+pub fn check_input<Ix: IndexType, G: Adjacency + ?Sized>(
+    graph: &G,
+    state: &State<Ix>,
+) -> Result<(), InvalidInput> {
+    let num_nodes = graph.num_nodes();
+    if num_nodes > Ix::MAX_NODES {
+        return Err(InvalidInput::TooManyNodes {
+            nodes: num_nodes,
+            max: Ix::MAX_NODES,
+        });
+    }
+    let lengths = [
+        state.visited.len(),
+        state.next_sigma.len(),
+        state.next_on_path.len(),
+        state.degrees.len(),
+        state.pre.len(),
+        state.lowpt.len(),
+        state.num_descendants.len(),
+    ];
+    if let Some(&len) = lengths.iter().find(|&&len| len != num_nodes) {
+        return Err(InvalidInput::StateLength {
+            state: len,
+            graph: num_nodes,
+        });
+    }
+
+    let mut listed = BitVec::new(num_nodes);
+    for node in graph.nodes() {
+        if node >= num_nodes {
+            return Err(InvalidInput::NodeOutOfRange { node });
+        }
+        if listed.get(node) {
+            return Err(InvalidInput::DuplicateNode { node });
+        }
+        listed.set(node);
+        let neighbors = graph.neighbors(node);
+        if let Some(&neighbor) = neighbors.iter().find(|&&u| u >= num_nodes) {
+            return Err(InvalidInput::NeighborOutOfRange { node, neighbor });
+        }
+    }
+    match (0..num_nodes).find(|&node| !listed.get(node)) {
+        Some(node) => Err(InvalidInput::MissingNode { node }),
+        None => Ok(()),
+    }
+}
+
+/// Like `three_edge_connect`, but returns an error instead of
+/// panicking if the graph or state is invalid, see `check_input`, for
+/// graphs that come from elsewhere, such as a library user's own
+/// `Adjacency` implementation, or if the state turns out to be
+/// corrupt while running, see `CorruptState`.
+pub fn three_edge_connect_checked<Ix: IndexType, G: Adjacency + ?Sized>(
+    graph: &G,
+    state: &mut State<Ix>,
+) -> Result<(), InvalidInput> {
+    check_input(graph, state)?;
+    match run(graph, state, &mut (), None, &mut |_| {}) {
+        Err(Stopped::Corrupt(err)) => Err(InvalidInput::CorruptState(err)),
+        _ => Ok(()),
+    }
+}
+
+/// Like `three_edge_connect`, calling the visitor's methods as the
+/// algorithm runs, see `Visitor`.
+pub fn three_edge_connect_with_visitor<Ix, G, V>(
+    graph: &G,
+    state: &mut State<Ix>,
+    visitor: &mut V,
+) where
+    Ix: IndexType,
+    G: Adjacency + ?Sized,
+    V: Visitor + ?Sized,
+{
+    // can't be cancelled without a flag
+    if let Err(Stopped::Corrupt(err)) =
+        run(graph, state, visitor, None, &mut |_| {})
+    {
+        panic!("{}", err);
+    }
+}
+
+/// Like `three_edge_connect`, but stops with `Cancelled` soon after
+/// the `cancel` flag is set, e.g. from another thread. The state then
+/// has the components that were completed before cancelling in
+/// `state.sigma`, which are actual components of the graph, but the
+/// rest of the state can't be used, other than to `reset` it.
+pub fn three_edge_connect_cancellable<Ix: IndexType, G: Adjacency + ?Sized>(
+    graph: &G,
+    state: &mut State<Ix>,
+    cancel: &AtomicBool,
+) -> Result<(), Cancelled> {
+    run(graph, state, &mut (), Some(cancel), &mut |_| {})
+        .map_err(Stopped::cancelled)
+}
+
+/// Like `three_edge_connect`, calling `progress` every few thousand
+/// steps of the DFS with the number of nodes and edges processed so
+/// far, and once more at the end, e.g. to show a progress bar. Can
+/// also be cancelled, like `three_edge_connect_cancellable`.
+pub fn three_edge_connect_with_progress<Ix, G, F>(
+    graph: &G,
+    state: &mut State<Ix>,
+    cancel: Option<&AtomicBool>,
+    mut progress: F,
+) -> Result<(), Cancelled>
+where
+    Ix: IndexType,
+    G: Adjacency + ?Sized,
+    F: FnMut(Progress),
+{
+    run(graph, state, &mut (), cancel, &mut progress)
+        .map_err(Stopped::cancelled)
+}
+
+/// Why `run` stopped before finishing.
+pub(crate) enum Stopped {
+    Cancelled,
+    /// After the given number of edges, see `Checkpoint::resume`.
+    Paused,
+    Corrupt(CorruptState),
+}
+
+impl Stopped {
+    /// For the functions that can only return `Cancelled`, which panic
+    /// on a corrupt state instead, like `three_edge_connect`.
+    pub(crate) fn cancelled(self) -> Cancelled {
+        match self {
+            Stopped::Cancelled => Cancelled,
+            Stopped::Paused => unreachable!("paused without a limit"),
+            Stopped::Corrupt(err) => panic!("{}", err),
+        }
+    }
+}
+
+impl From<CorruptState> for Stopped {
+    fn from(err: CorruptState) -> Self {
+        Stopped::Corrupt(err)
+    }
+}
+
+/// The frames of a DFS that was stopped, to resume it from, as the
+/// node, its parent, and the position of the next edge in its
+/// neighbor list, from the root down.
+pub(crate) type SavedFrames = Vec<(usize, usize, usize)>;
+
+fn run<Ix, G, V, F>(
+    graph: &G,
+    state: &mut State<Ix>,
+    visitor: &mut V,
+    cancel: Option<&AtomicBool>,
+    progress: &mut F,
+) -> Result<(), Stopped>
+where
+    Ix: IndexType,
+    G: Adjacency + ?Sized,
+    V: Visitor + ?Sized,
+    F: FnMut(Progress) + ?Sized,
+{
+    run_from(
+        graph,
+        state,
+        visitor,
+        cancel,
+        progress,
+        &mut Vec::new(),
+        None,
+    )
+}
+
+/// Like `run`, first finishing the DFS tree in `saved`, if any, and
+/// stopping with `Paused` after about `max_edges` edge ends. When it
+/// stops, since it was paused or cancelled, the frames of the
+/// unfinished tree are saved in `saved`.
+pub(crate) fn run_from<Ix, G, V, F>(
+    graph: &G,
+    state: &mut State<Ix>,
+    visitor: &mut V,
+    cancel: Option<&AtomicBool>,
+    progress: &mut F,
+    saved: &mut SavedFrames,
+    max_edges: Option<usize>,
+) -> Result<(), Stopped>
+where
+    Ix: IndexType,
+    G: Adjacency + ?Sized,
+    V: Visitor + ?Sized,
+    F: FnMut(Progress) + ?Sized,
+{
+    let mut stack: Vec<Frame<'_>> = saved
+        .drain(..)
+        .map(|(node, parent, cursor)| Frame {
+            node,
+            parent,
+            neighbors: graph.neighbors(node),
+            cursor,
+        })
+        .collect();
+    // each edge is looped over from both of its ends
+    let mut edge_ends = 0usize;
+    let first_count = state.count;
+    let report = |state: &State<Ix>, edge_ends: usize| Progress {
+        nodes: state.count - first_count,
+        edges: edge_ends / 2,
+    };
+
+    let resumed = stack.first().map(|frame| frame.node);
+    for n in resumed.into_iter().chain(graph.nodes()) {
+        if stack.is_empty() {
+            if state.visited.get(n) {
+                continue;
+            }
+            // the root is its own parent, since there are no
+            // self-loops for the parent check to skip
+            let root = discover(n, n, state, graph, visitor);
+            debug_check(state, graph);
+            stack.push(root);
+        }
+        while let Some(frame) = stack.last_mut() {
+            let (w, v) = (frame.node, frame.parent);
+            let u = match frame.neighbors.get(frame.cursor) {
+                Some(&u) => u,
+                None => {
+                    stack.pop();
+                    if let Some(parent) = stack.last() {
+                        finish_child(parent.node, w, state, visitor)?;
+                        debug_check(state, graph);
                     }
+                    continue;
                 }
+            };
+            frame.cursor += 1;
+            // self-loops don't affect the edge connectivity, and
+            // would be counted twice as incoming back-edges
+            if u == w {
+                continue;
             }
-        }
-        Inst::Return(w, u) => {
-            assert_state_len!(state, w);
-            assert_state_len!(state, u);
-            state.num_descendants[w] += state.num_descendants[u];
 
-            if state.degrees[u] <= 2 {
-                state.degrees[w] += state.degrees[u] - 2;
-                state.add_component(u);
+            edge_ends += 1;
+            let is_child = visit_edge(w, v, u, state, visitor)?;
+            debug_check(state, graph);
+            if is_child {
+                let child = discover(u, w, state, graph, visitor);
+                debug_check(state, graph);
+                stack.push(child);
+            }
 
-                state.path_u = if state.is_null_path(u) {
-                    w // P_u = w + P_u
+            if edge_ends.is_multiple_of(CHECK_INTERVAL) {
+                progress(report(state, edge_ends));
+                let stopped = if cancel.is_some_and(cancel::is_set) {
+                    Some(Stopped::Cancelled)
+                } else if max_edges.is_some_and(|max| edge_ends >= max) {
+                    Some(Stopped::Paused)
                 } else {
-                    state.next_on_path[u] // P_u
+                    None
                 };
-            } else {
-                // since degree[u] != 2, u can be absorbed
-                state.path_u = u;
-            }
-
-            if state.lowpt[w] <= state.lowpt[u] {
-                // w + P_u in paper
-                state.absorb_path(w, state.path_u, None);
-            } else {
-                state.lowpt[w] = state.lowpt[u];
-                // P_w in paper
-                state.absorb_path(w, state.next_on_path[w], None);
-                state.next_on_path[w] = state.path_u;
+                if let Some(stopped) = stopped {
+                    saved.extend(
+                        stack.iter().map(|f| (f.node, f.parent, f.cursor)),
+                    );
+                    return Err(stopped);
+                }
             }
         }
+        state.try_add_component(n)?;
+        debug_check(state, graph);
+        visitor.on_component(state.sigma.last().unwrap());
     }
+    progress(report(state, edge_ends));
+    Ok(())
 }
 
-fn three_edge_connect(graph: &FxMapGraph, state: &mut State) {
-    let mut stack: InstStack = VecDeque::new();
+pub fn find_components<G: Adjacency + ?Sized>(graph: &G) -> Vec<Vec<usize>> {
+    find_components_with_index::<usize, G>(graph)
+}
 
-    for &n in graph.keys() {
-        if !state.visited[n] {
-            stack.push_front(Inst::Recur(n, 0));
-            while let Some(inst) = stack.pop_front() {
-                run_inst(inst, &mut stack, state, graph);
-            }
-            state.add_component(n);
-        }
-    }
+/// Like `find_components`, but with the given index type for the
+/// algorithm's state, e.g. `u32` to halve its memory usage on graphs
+/// with fewer than `u32::MAX` nodes.
+pub fn find_components_with_index<Ix: IndexType, G: Adjacency + ?Sized>(
+    graph: &G,
+) -> Vec<Vec<usize>> {
+    let mut state: State<Ix> = State::initialize_with_index(graph);
+    three_edge_connect(graph, &mut state);
+    state.sigma.into_inner()
 }
 
-pub fn find_components(graph: &FxMapGraph) -> Vec<Vec<usize>> {
+/// Like `find_components`, also returning the spanning forest of the
+/// algorithm's depth-first search.
+pub fn find_components_with_forest<G: Adjacency + ?Sized>(
+    graph: &G,
+) -> (Vec<Vec<usize>>, SpanningForest) {
     let mut state = State::initialize(graph);
-    three_edge_connect(graph, &mut state);
-    state.sigma
+    let mut forest = SpanningForest::new(graph.num_nodes());
+    three_edge_connect_with_visitor(graph, &mut state, &mut forest);
+    (state.sigma.into_inner(), forest)
+}
+
+/// Removes the components with fewer than `min_size` nodes from the
+/// output of `find_components`, keeping the order of the rest.
+pub fn filter_min_size(
+    components: Vec<Vec<usize>>,
+    min_size: usize,
+) -> Vec<Vec<usize>> {
+    components
+        .into_iter()
+        .filter(|c| c.len() >= min_size)
+        .collect()
+}
+
+/// Sorts the nodes of each component in the output of
+/// `find_components` by the given key, and the components by the key
+/// of their first node. With the node index as the key, this lists the
+/// components and their nodes in the order the nodes first appear in
+/// the input, for graphs built from named edges, which makes the
+/// output of similar graphs easy to compare.
+pub fn sort_components_by_key<K, F>(components: &mut [Vec<usize>], key: F)
+where
+    K: Ord,
+    F: Fn(usize) -> K,
+{
+    for component in components.iter_mut() {
+        component.sort_unstable_by_key(|&node| key(node));
+    }
+    components.sort_unstable_by_key(|c| c.first().map(|&node| key(node)));
 }
@@ -0,0 +1,4951 @@
+use std::{
+    cell::Cell,
+    collections::{hash_map::DefaultHasher, HashSet},
+    fmt,
+    fs::{self, File},
+    hash::Hasher,
+    io::{prelude::*, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    process::{self, Child, ChildStdin, ChildStdout, Stdio},
+    rc::Rc,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use bstr::{io::BufReadExt, ByteSlice};
+use structopt::{
+    clap::{AppSettings, Shell},
+    StructOpt,
+};
+
+use three_edge_connected::{
+    adjacency::Adjacency,
+    algorithm,
+    checkpoint::Checkpoint,
+    connectivity,
+    diff::{self, ChangeKind},
+    forest::SpanningForest,
+    four_edge, generators,
+    graph::{
+        self, AdjacencyList, EdgeTable, IndexedGraph, InternedGraph,
+        MalformedLine, Symmetrize,
+    },
+    graph_file,
+    index::IndexType,
+    lgf,
+    mmap::MmapGraph,
+    names::{IndexNames, NodeNames},
+    output::{self, ComponentIds},
+    paf::{self, PafFilter},
+    pajek, parallel, paths,
+    region::{self, Region},
+    relabel::Relabeling,
+    rgfa,
+    state::{NodeInfo, State},
+    superbubble::OrientedGraph,
+    tgf, vg,
+    visitor::Visitor,
+};
+
+/// Finds the 3-edge-connected components in a graph. Input must be a
+/// bridgeless graph in the GFA format, or an edge list. Output is a
+/// list of 3-edge-connected components, one per line, as
+/// tab-delimited lists of segment names.
+///
+/// Exits with code 1 on invalid arguments, 2 on I/O errors, 3 if the
+/// input can't be parsed, 4 if the input graph is invalid, and 5 if
+/// `--cross-check` finds different components.
+#[derive(StructOpt, Debug, Clone)]
+#[structopt(setting = AppSettings::SubcommandsNegateReqs)]
+struct Opt {
+    #[structopt(flatten)]
+    input: InputOpt,
+
+    /// Output file. If empty, writes on stdout.
+    #[structopt(short, long, parse(from_os_str))]
+    out_file: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    output: OutputOpt,
+
+    /// Number of threads to run the algorithm on. With more than one,
+    /// the graph is split at its cut vertices, and the parts are
+    /// processed in parallel; the components are then sorted by
+    /// node index.
+    #[structopt(short = "j", long, default_value = "1")]
+    threads: usize,
+
+    /// Use 32-bit indices in the algorithm's state, which halves its
+    /// memory usage, for graphs with fewer than 2^32 - 1 nodes.
+    #[structopt(long)]
+    compact_state: bool,
+
+    /// Also find the components with a second, independent algorithm,
+    /// and fail with exit code 5 if they differ. Needs the
+    /// `cross-check` feature. Can't be combined with `--four-edge`,
+    /// `--mmap-dir`, `--no-names`, or `--relabel`.
+    #[structopt(long)]
+    cross_check: bool,
+
+    /// Save the state of the algorithm to this file periodically, and
+    /// resume from it if it exists, so that an interrupted run doesn't
+    /// start over. It must be resumed with the same input and
+    /// `--compact-state`, and is removed once the algorithm has
+    /// finished. Can't be combined with `--forest-out`, `--four-edge`,
+    /// `--mmap-dir`, `--no-names`, `--relabel`, or `--threads`.
+    #[structopt(long, parse(from_os_str))]
+    checkpoint: Option<PathBuf>,
+
+    /// The number of seconds between writes of the `--checkpoint`.
+    #[structopt(long, default_value = "600")]
+    checkpoint_interval: u64,
+
+    /// Keep the estimated memory usage under this many bytes, with an
+    /// optional `K`, `M`, `G`, or `T` suffix, e.g. `16G`. The input is
+    /// read once first to estimate the size of the graph, and if it
+    /// doesn't fit, `--compact-state` is turned on, and then the graph
+    /// is stored in memory-mapped files like with `--mmap-dir`, in a
+    /// temporary directory next to the output file. Fails before
+    /// building the graph if it still doesn't fit. The input must be a
+    /// file.
+    #[structopt(long, parse(try_from_str = parse_size))]
+    max_memory: Option<usize>,
+
+    /// Store the graph in memory-mapped files in this directory,
+    /// instead of in memory, for graphs that don't fit in RAM. The
+    /// node names are still kept in memory, unless `--no-names` is
+    /// given. Can't be combined with `--nodes-file`, `--region`, or
+    /// `--threads`.
+    #[structopt(long, parse(from_os_str))]
+    mmap_dir: Option<PathBuf>,
+
+    /// Use the node names, which must be integers, as the node
+    /// indices, instead of storing them. This saves the memory of the
+    /// names for graphs whose names are dense integers, e.g. `1` to
+    /// `n`, since every index up to the largest name is a node. Can't
+    /// be combined with `--nodes-file`, `--region`, or `--threads`.
+    #[structopt(long)]
+    no_names: bool,
+
+    /// Relabel the nodes in `dfs` or `bfs` order after building the
+    /// graph, so that the algorithm's memory accesses are mostly
+    /// sequential, which is faster on large graphs. Can't be combined
+    /// with `--threads` or `--mmap-dir`.
+    #[structopt(long, possible_values(RelabelOrder::NAMES))]
+    relabel: Option<RelabelOrder>,
+
+    /// Write a report of the GFA paths to this file, with one line per
+    /// path: its name, its number of steps, the number of times it
+    /// moves from one 3-edge-connected component to another, and the
+    /// steps where it does, as the step index and oriented segment,
+    /// such as `12:s4+`. The input is read a second time for the
+    /// paths, so it must be a file. Can't be combined with
+    /// `--mmap-dir`, `--no-names`, or `--relabel`.
+    #[structopt(long, parse(from_os_str))]
+    path_crossings: Option<PathBuf>,
+
+    /// Write the GFA links between segments in different
+    /// 3-edge-connected components to this file, as the link lines of
+    /// the input, with their orientations, overlaps, and tags, e.g. to
+    /// review or remove them to split the graph. The links dropped by
+    /// `--min-overlap` aren't edges, so they're left out. The input is
+    /// read a second time for the links, so it must be a file. Can't
+    /// be combined with `--mmap-dir`, `--no-names`, or `--relabel`.
+    #[structopt(long, parse(from_os_str))]
+    boundary_links: Option<PathBuf>,
+
+    /// Write the components projected onto the reference given by
+    /// `--bed-reference` to this file, as BED intervals named by the
+    /// component index of the main output, from the stable coordinates
+    /// of the segments, or the positions of the steps of the path. The
+    /// input is read a second time for the reference, so it must be a
+    /// file. Can't be combined with `--mmap-dir`, `--no-names`, or
+    /// `--relabel`.
+    #[structopt(long, parse(from_os_str))]
+    bed: Option<PathBuf>,
+
+    /// The stable sequence of an rGFA input, or the GFA path, that
+    /// `--bed` projects the components onto.
+    #[structopt(long)]
+    bed_reference: Option<String>,
+
+    /// Write the names of the nodes that aren't 3-edge-connected to
+    /// any other node, the components of one node, to this file, one
+    /// per line, like `--nodes-file`. They're written whatever the
+    /// `--min-size`. Can't be combined with `--mmap-dir`, `--no-names`,
+    /// or `--relabel`.
+    #[structopt(long, parse(from_os_str))]
+    orphans_out: Option<PathBuf>,
+
+    /// Write the spanning forest of the algorithm's depth-first search
+    /// to this file, as an edge list of the names of the parent and
+    /// the child of each tree edge, in the order the children were
+    /// reached. Can't be combined with `--mmap-dir`, `--no-names`,
+    /// `--relabel`, or `--threads`.
+    #[structopt(long, parse(from_os_str))]
+    forest_out: Option<PathBuf>,
+
+    /// Write the graph as it was built from the input to this file, as
+    /// an edge list of the names of the two nodes of each edge, then
+    /// their node indices, with each edge once and the nodes in index
+    /// order, which can be read back with `--format edgelist`. It's
+    /// written before `--relabel`. Can't be combined with `--mmap-dir`.
+    #[structopt(long, parse(from_os_str))]
+    edges_out: Option<PathBuf>,
+
+    /// Write the graph as it was built from the input to this file, in
+    /// a binary format that's read back much faster than the input is
+    /// parsed, as the input of later runs, e.g. `-i graph.3ecg`. It's
+    /// written before `--relabel`. Can't be combined with `--mmap-dir`
+    /// or `--no-names`.
+    #[structopt(long, parse(from_os_str))]
+    save_graph: Option<PathBuf>,
+
+    /// Write a table to this file with one row per node: its name, its
+    /// degree, the id of its component in the output, or nothing if
+    /// the component was left out, and its preorder number, lowpoint,
+    /// and number of descendants in the algorithm's DFS, which show
+    /// why nodes ended up in different components. Can't be combined
+    /// with `--checkpoint`, `--four-edge`, `--mmap-dir`, `--no-names`,
+    /// `--relabel`, or `--threads`.
+    #[structopt(long, parse(from_os_str))]
+    node_table: Option<PathBuf>,
+
+    /// Add the stable coordinates of the segments of an rGFA input,
+    /// their `SN`, `SO`, and `SR` tags and their lengths, to the `tsv`
+    /// output, in the columns after the node name. The input is read a
+    /// second time for the tags, so it must be a file. Can't be
+    /// combined with `--mmap-dir`, `--no-names`, or `--relabel`.
+    #[structopt(long)]
+    rgfa: bool,
+
+    /// Print the time spent parsing the input, building the graph,
+    /// running the algorithm, and writing the output to stderr. To
+    /// time parsing and graph construction separately, all edges
+    /// are buffered in memory before the graph is built.
+    #[structopt(long)]
+    time: bool,
+
+    /// Parse the input and build the graph, print its numbers of nodes
+    /// and edges and the estimated memory usage of the graph and of the
+    /// algorithm's state, in bytes, and exit without running the
+    /// algorithm, e.g. to check an input or size a job.
+    #[structopt(long)]
+    dry_run: bool,
+
+    /// Keep the output of each run in this directory, under the
+    /// SHA-256 digest of the contents of the input and `--nodes-file`
+    /// and of the options, and write the kept output instead of running
+    /// again when they're the same. The key the digest is of is kept
+    /// next to the output, and checked before the output is used. The
+    /// output is kept uncompressed, so `--compress` and the output file
+    /// can differ between runs. The input must be a file. Can't be
+    /// combined with `--dry-run` or the options that write other files,
+    /// such as `--bed`.
+    #[structopt(long, parse(from_os_str))]
+    cache: Option<PathBuf>,
+
+    /// Keep running after the output is written, and run again
+    /// whenever the input file changes, logging a summary of how the
+    /// components that were written changed, as counted by the `diff`
+    /// subcommand. Runs that fail, e.g. on a half-saved file, are
+    /// logged and retried on the next change. The input must be a file.
+    /// Can't be combined with `--checkpoint` or `--dry-run`.
+    #[structopt(long)]
+    watch: bool,
+
+    #[structopt(flatten)]
+    log: LogOpt,
+
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+enum Command {
+    /// Checks that the input can be parsed, contains edges, and is
+    /// bridgeless, and reports the number of connected components.
+    /// Exits with code 4 if any check fails.
+    Validate {
+        #[structopt(flatten)]
+        input: InputOpt,
+    },
+    /// Reports node and edge counts, the degree distribution, the
+    /// number of self-loops and parallel edges, and the number of
+    /// connected components, without running the algorithm.
+    Stats {
+        #[structopt(flatten)]
+        input: InputOpt,
+    },
+    /// Finds the connected components of the graph, the
+    /// 2-edge-connected components of each, and the 3-edge-connected
+    /// components of those, and prints them as a JSON tree, with the
+    /// node names of the components at each level.
+    Decompose {
+        #[structopt(flatten)]
+        input: InputOpt,
+    },
+    /// Finds the cut vertices and separation pairs of the graph, the
+    /// nodes and pairs of nodes whose removal disconnects the rest of
+    /// their connected component, and prints one per line, as
+    /// `cut_vertex` and the node name, or `separation_pair`, the two
+    /// names, and `within` if both nodes are in the same
+    /// 3-edge-connected component, or `between` otherwise. This takes
+    /// quadratic time in the size of the connected components.
+    VertexConnectivity {
+        #[structopt(flatten)]
+        input: InputOpt,
+    },
+    /// Builds the bridge tree of the graph, with its 2-edge-connected
+    /// components as the nodes and its bridges as the edges, and
+    /// prints it as JSON or DOT. The graph can be split at any of the
+    /// bridges without splitting any component.
+    BridgeTree {
+        #[structopt(flatten)]
+        input: InputOpt,
+
+        /// Output format, one of `json` or `dot`.
+        #[structopt(
+            long,
+            default_value = "json",
+            possible_values(&["json", "dot"])
+        )]
+        output_format: OutputFormat,
+    },
+    /// Finds the superbubbles of a GFA graph, following the
+    /// orientations of the links, and prints one per line, with the
+    /// oriented entrance and exit segments and the number of oriented
+    /// segments in the bubble, including both ends.
+    Superbubbles {
+        #[structopt(flatten)]
+        input: InputOpt,
+    },
+    /// Finds the snarl decomposition of a GFA graph, from its cactus
+    /// graph, and prints one snarl per line, with its oriented start
+    /// and end segments, the index of its chain, and its depth, which
+    /// is the number of snarls it's nested in. The snarls of each
+    /// chain are printed in order, and before the snarls nested in
+    /// them.
+    Snarls {
+        #[structopt(flatten)]
+        input: InputOpt,
+    },
+    /// Compares the components of two graphs that share node names,
+    /// such as an assembly before and after polishing, and prints
+    /// each change on its own line: `split`, `merged`, `rearranged`,
+    /// `changed` (nodes were added or removed), `appeared`, or
+    /// `vanished`, followed by the components before and after, each
+    /// as comma-separated node names, separated by spaces. Components
+    /// that are the same in both aren't printed.
+    Diff {
+        /// The first graph.
+        #[structopt(parse(from_os_str))]
+        before: PathBuf,
+
+        /// The second graph.
+        #[structopt(parse(from_os_str))]
+        after: PathBuf,
+
+        /// Input format of both graphs, one of `gfa`, `edgelist`, `csv`,
+        /// `vg`, `paf`, `pajek`, `tgf`, `lgf`, `adjlist`, `binary`, or
+        /// `parquet`. If not given, the format of each file is detected
+        /// separately.
+        #[structopt(long, possible_values(InputFormat::NAMES))]
+        format: Option<InputFormat>,
+
+        /// Only compare components with at least this many nodes.
+        #[structopt(long, default_value = "2")]
+        min_size: usize,
+
+        #[structopt(flatten)]
+        edges: EdgeOpt,
+    },
+    /// Loads the graph once, finds its components, and answers queries
+    /// about them on a Unix socket until it's stopped, one JSON object
+    /// per line in each direction: `{"query": "component", "segment":
+    /// NAME}` for the component of a segment, `{"query":
+    /// "same_component", "segments": [NAME, ...]}` for whether the
+    /// segments are all in the same component, and `{"query":
+    /// "components", "min_size": N}` for the components with at least
+    /// `N` nodes, 2 if not given. Errors are answered with `{"error":
+    /// MESSAGE}`.
+    Serve {
+        #[structopt(flatten)]
+        input: InputOpt,
+
+        /// Path of the Unix socket to listen on. A socket left there by
+        /// an earlier server is replaced.
+        #[structopt(long, parse(from_os_str))]
+        socket: PathBuf,
+    },
+    /// Finds the components of each of the given input files
+    /// independently, processing several files in parallel. The
+    /// results are written to the output directory, in files named
+    /// after the inputs.
+    Batch {
+        /// Input files. Patterns with `*` and `?` in the file name
+        /// are expanded, in case the shell didn't.
+        #[structopt(parse(from_os_str), required = true)]
+        inputs: Vec<PathBuf>,
+
+        /// Input format of all the files, one of `gfa`, `edgelist`,
+        /// `csv` (an edge list with commas, see `--delimiter`), `vg`,
+        /// `paf`, `pajek`, `tgf`, `lgf`, `adjlist`, `binary`, the format
+        /// of `--save-graph`, or `parquet` (a table with `from` and `to`
+        /// columns, read by the `duckdb` program, with the `parquet`
+        /// feature). If not given, the format of each file is detected
+        /// separately.
+        #[structopt(long, possible_values(InputFormat::NAMES))]
+        format: Option<InputFormat>,
+
+        /// Directory to write the results to.
+        #[structopt(short = "d", long, parse(from_os_str))]
+        out_dir: PathBuf,
+
+        /// Number of files to process in parallel. Defaults to the
+        /// number of available cores.
+        #[structopt(short = "j", long)]
+        threads: Option<usize>,
+
+        #[structopt(flatten)]
+        edges: EdgeOpt,
+
+        #[structopt(flatten)]
+        output: OutputOpt,
+    },
+    /// Generates a graph with a known topology, in the GFA or edge
+    /// list format.
+    Generate {
+        #[structopt(subcommand)]
+        topology: Topology,
+
+        /// Seed for the random number generator used by the random
+        /// topologies.
+        #[structopt(long, default_value = "0", global = true)]
+        seed: u64,
+
+        /// Output format, one of `gfa` or `edgelist`.
+        #[structopt(
+            long,
+            default_value = "gfa",
+            possible_values(InputFormat::TEXT_NAMES),
+            global = true
+        )]
+        format: InputFormat,
+
+        /// Output file. If empty, writes on stdout.
+        #[structopt(short, long, parse(from_os_str), global = true)]
+        out_file: Option<PathBuf>,
+    },
+    /// Writes a shell completion script to stdout.
+    #[structopt(setting = AppSettings::Hidden)]
+    Completions {
+        /// The shell to generate completions for.
+        #[structopt(possible_values(&Shell::variants()))]
+        shell: Shell,
+    },
+}
+
+#[derive(StructOpt, Debug, Clone, Copy)]
+enum Topology {
+    /// The complete graph on `n` nodes.
+    Complete { n: usize },
+    /// The complete bipartite graph between `k` and `l` nodes.
+    Bipartite { k: usize, l: usize },
+    /// Two complete graphs connected by parallel edges between one
+    /// node in each.
+    Bridged { a: usize, b: usize, bridges: usize },
+    /// An Erdős–Rényi graph on `n` nodes, where each edge is included
+    /// with probability `p`.
+    Random { n: usize, p: f64 },
+    /// A grid graph with the given number of rows and columns.
+    Grid { rows: usize, cols: usize },
+}
+
+impl Topology {
+    fn edges(&self, seed: u64) -> Vec<(usize, usize)> {
+        match *self {
+            Topology::Complete { n } => generators::complete_edges(0, n),
+            Topology::Bipartite { k, l } => generators::bipartite_edges(k, l),
+            Topology::Bridged { a, b, bridges } => {
+                generators::bridged_k_edges(a, b, bridges)
+            }
+            Topology::Random { n, p } => {
+                generators::erdos_renyi_edges(n, p, seed)
+            }
+            Topology::Grid { rows, cols } => generators::grid_edges(rows, cols),
+        }
+    }
+}
+
+#[derive(StructOpt, Debug, Clone)]
+struct InputOpt {
+    /// If true, read input GFA on stdin.
+    #[structopt(short, required_unless("in-file"))]
+    stdin: bool,
+
+    /// GFA file to use, must be present if not reading from stdin.
+    #[structopt(short, long, parse(from_os_str), required_unless("stdin"))]
+    in_file: Option<PathBuf>,
+
+    /// Input format, one of `gfa`, `edgelist`, `csv` (an edge list
+    /// with commas, see `--delimiter`), `vg`, `paf`, `pajek`, `tgf`,
+    /// `lgf`, `adjlist`, `binary`, the format of `--save-graph`, or
+    /// `parquet` (a table with `from` and `to` columns, read by the
+    /// `duckdb` program, with the `parquet` feature). If not given,
+    /// the format is detected from the file extension, or from the
+    /// contents when reading stdin or the extension is unknown; vg
+    /// files must have the `.vg` extension.
+    #[structopt(long, possible_values(InputFormat::NAMES))]
+    format: Option<InputFormat>,
+
+    /// Only analyze the subgraph induced by the segments listed in
+    /// this file, one name per line.
+    #[structopt(long, parse(from_os_str))]
+    nodes_file: Option<PathBuf>,
+
+    /// Only analyze the part of the graph in this region of a stable
+    /// sequence of an rGFA input, or of a GFA path, such as
+    /// `chr1:1-5,000,000`, with 1-based, inclusive coordinates, plus
+    /// the segments within `--region-context` links of it. The input
+    /// is read a second time for the region, so it must be a file.
+    #[structopt(long)]
+    region: Option<Region>,
+
+    /// The number of links to follow from the segments in `--region`
+    /// to add the flanking context, such as the alternative alleles of
+    /// an rGFA graph, which aren't on the reference.
+    #[structopt(long, default_value = "1")]
+    region_context: usize,
+
+    #[structopt(flatten)]
+    edges: EdgeOpt,
+}
+
+impl InputOpt {
+    /// Whether only a subgraph of the input is analyzed, with
+    /// `--nodes-file` or `--region`.
+    fn is_subgraph(&self) -> bool {
+        self.nodes_file.is_some() || self.region.is_some()
+    }
+}
+
+/// Options for which of the input edges are used.
+#[derive(StructOpt, Debug, Default, Clone, Copy)]
+struct EdgeOpt {
+    /// Drop GFA links whose overlap is shorter than this many bases
+    /// before building the graph. Links with an unknown overlap,
+    /// `*`, are kept. For PAF input, the length of the alignment
+    /// block is used.
+    #[structopt(long)]
+    min_overlap: Option<usize>,
+
+    /// Drop PAF alignments whose identity, the number of matching
+    /// bases divided by the length of the alignment block, is below
+    /// this fraction.
+    #[structopt(long)]
+    min_identity: Option<f64>,
+
+    /// Drop PAF alignments whose mapping quality is below this.
+    #[structopt(long)]
+    min_mapq: Option<u8>,
+
+    /// The longest unaligned part at an end of both reads of a PAF
+    /// alignment for it to be an overlap, instead of an internal
+    /// match, e.g. from a repeat. Defaults to 1000.
+    #[structopt(long)]
+    max_overhang: Option<usize>,
+
+    /// Read edge lists as directed, from the first node to the
+    /// second, or adjacency lists, from the node of each line to its
+    /// neighbors, with one of the policies `undirected` (the directions
+    /// are ignored, as without this option for edge lists),
+    /// `reciprocated` (only edges that are given in both directions
+    /// are kept, as one edge, the default for adjacency lists), or
+    /// `error` (an edge given in both directions is an error).
+    #[structopt(
+        long,
+        possible_values(&["undirected", "reciprocated", "error"]),
+        parse(try_from_str = parse_symmetrize)
+    )]
+    directed: Option<Symmetrize>,
+
+    /// Field delimiter of edge list input, a single character such as
+    /// `,` or `;`, or `tab`, with double quotes around the names that
+    /// contain it. By default, the fields are separated by whitespace,
+    /// or by commas for `csv` input.
+    #[structopt(long, parse(try_from_str = parse_delimiter))]
+    delimiter: Option<u8>,
+
+    /// The column of edge list input with the first node of each edge,
+    /// from 1 [default: 1].
+    #[structopt(long, parse(try_from_str = parse_column))]
+    source_column: Option<usize>,
+
+    /// The column of edge list input with the second node of each
+    /// edge, from 1 [default: 2].
+    #[structopt(long, parse(try_from_str = parse_column))]
+    target_column: Option<usize>,
+
+    /// Skip the first line of edge list input, a header row.
+    #[structopt(long)]
+    header: bool,
+
+    /// Fail on the first GFA link line that can't be parsed, instead of
+    /// skipping the malformed lines with a warning.
+    #[structopt(long)]
+    strict: bool,
+}
+
+impl EdgeOpt {
+    /// Whether any of the edge filters or the edge list columns are
+    /// given.
+    fn is_set(&self) -> bool {
+        self.min_overlap.is_some()
+            || self.min_identity.is_some()
+            || self.min_mapq.is_some()
+            || self.max_overhang.is_some()
+            || self.directed.is_some()
+            || self.has_table()
+    }
+
+    /// Whether any of the options for the columns of an edge list are
+    /// given.
+    fn has_table(&self) -> bool {
+        self.delimiter.is_some()
+            || self.source_column.is_some()
+            || self.target_column.is_some()
+            || self.header
+    }
+
+    /// The columns of an edge list input in the given format, or `None`
+    /// for a plain edge list, which is split at whitespace.
+    fn edge_table(&self, format: InputFormat) -> Option<EdgeTable> {
+        if format == InputFormat::EdgeList && !self.has_table() {
+            return None;
+        }
+        let csv = Some(b',').filter(|_| format == InputFormat::Csv);
+        Some(EdgeTable {
+            delimiter: self.delimiter.or(csv),
+            from: self.source_column.map_or(0, |column| column - 1),
+            to: self.target_column.map_or(1, |column| column - 1),
+            header: self.header,
+        })
+    }
+}
+
+fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s {
+        "tab" | "\\t" => Ok(b'\t'),
+        _ if s.len() == 1 => Ok(s.as_bytes()[0]),
+        _ => Err(format!(
+            "the delimiter must be a single character, or `tab`: {}",
+            s
+        )),
+    }
+}
+
+fn parse_column(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(0) => Err("columns are numbered from 1".to_string()),
+        Ok(column) => Ok(column),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_symmetrize(s: &str) -> Result<Symmetrize, String> {
+    match s {
+        "undirected" => Ok(Symmetrize::Undirected),
+        "reciprocated" => Ok(Symmetrize::Reciprocated),
+        "error" => Ok(Symmetrize::Reject),
+        _ => Err(format!("unknown directed edge policy: {}", s)),
+    }
+}
+
+#[derive(StructOpt, Debug, Clone)]
+struct OutputOpt {
+    /// Output format, one of `text` (one component per line), `tsv` (one
+    /// row per node with its component index), `json`, `dot` (the whole
+    /// graph, with components as clusters), `gfa` (the whole graph, with
+    /// the component of each segment in a `cc` tag, without the sequences
+    /// and orientations), `odgi` (a table of numeric node ids, like those
+    /// of odgi), `bandage` (a CSV of the colour and component of each node,
+    /// for Bandage), `gexf` (the whole graph, with the component of each
+    /// node as an attribute, for Gephi), `graphml` or `gml` (the same, in
+    /// GraphML or GML), `condensed-gml` (the components as nodes, and the
+    /// number of edges between each pair as weighted edges, in GML),
+    /// `sqlite` (`nodes` and `components` tables in an SQLite database,
+    /// written by the `sqlite3` program), or `parquet` (a `node` and
+    /// `component` table, written by the `duckdb` program, with the
+    /// `parquet` feature). The programs must be on the `PATH`.
+    #[structopt(
+        long,
+        default_value = "text",
+        possible_values(OutputFormat::NAMES)
+    )]
+    output_format: OutputFormat,
+
+    /// Only output components with at least this many segments.
+    #[structopt(long, default_value = "2")]
+    min_size: usize,
+
+    /// List the components in the order their first nodes appear in
+    /// the links or edges of the input, and the nodes of each
+    /// component in that order, which makes the output of runs on
+    /// slightly different graphs easy to diff. Can't be combined with
+    /// `--no-names`.
+    #[structopt(long)]
+    keep_order: bool,
+
+    /// Identify the components by stable ids in every output format
+    /// and in `--bed`: `3ecc_` followed by the smallest name of their
+    /// nodes, with shorter names first, e.g. `3ecc_42`, instead of by
+    /// their index in the output, which changes with the filters. The
+    /// `text` output gets the id in its first column, and the `json`
+    /// output an object with the `id` and `nodes` of each component.
+    #[structopt(long)]
+    stable_ids: bool,
+
+    /// Output the 4-edge-connected components instead, which the
+    /// 3-edge-connected components are split into where three edges
+    /// separate their nodes. This is much slower on graphs with large
+    /// 3-edge-connected components.
+    #[structopt(long)]
+    four_edge: bool,
+
+    /// Compress the output, one of `gzip` or `zstd`, using the
+    /// corresponding external program. Output files ending in `.gz`
+    /// or `.zst` are compressed even without this flag.
+    #[structopt(long, possible_values(Compression::NAMES))]
+    compress: Option<Compression>,
+}
+
+impl OutputOpt {
+    fn component_ids(&self) -> ComponentIds {
+        if self.stable_ids {
+            ComponentIds::Stable
+        } else {
+            ComponentIds::Index
+        }
+    }
+}
+
+#[derive(StructOpt, Debug, Default, Clone)]
+struct LogOpt {
+    /// Increase logging verbosity on stderr. Can be repeated, `-v`
+    /// logs graph sizes, phases, and timings, `-vv` adds debug
+    /// output.
+    #[structopt(short, long, global = true, parse(from_occurrences))]
+    verbose: usize,
+
+    /// Only log errors.
+    #[structopt(short, long, global = true, conflicts_with("verbose"))]
+    quiet: bool,
+}
+
+/// The supported input graph formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputFormat {
+    Gfa,
+    EdgeList,
+    Vg,
+    Paf,
+    Pajek,
+    Tgf,
+    Lgf,
+    AdjList,
+    Binary,
+    Csv,
+    Parquet,
+}
+
+impl InputFormat {
+    const NAMES: &'static [&'static str] = &[
+        "gfa", "edgelist", "csv", "vg", "paf", "pajek", "tgf", "lgf",
+        "adjlist", "binary", "parquet",
+    ];
+    // the formats that `generate` can write
+    const TEXT_NAMES: &'static [&'static str] = &["gfa", "edgelist"];
+
+    fn from_extension(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "gfa" => Some(InputFormat::Gfa),
+            "vg" => Some(InputFormat::Vg),
+            "paf" => Some(InputFormat::Paf),
+            "net" => Some(InputFormat::Pajek),
+            "tgf" => Some(InputFormat::Tgf),
+            "lgf" => Some(InputFormat::Lgf),
+            "adj" | "adjlist" => Some(InputFormat::AdjList),
+            "3ecg" => Some(InputFormat::Binary),
+            "csv" => Some(InputFormat::Csv),
+            "parquet" => Some(InputFormat::Parquet),
+            "txt" | "tsv" | "el" | "edges" | "edgelist" => {
+                Some(InputFormat::EdgeList)
+            }
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from the first line that isn't empty or a
+    /// comment; GFA lines start with a record type and a tab, Pajek
+    /// files with a section line such as `*Vertices`, and LGF files
+    /// with one such as `@nodes`. Adjacency lists start with a node
+    /// and a colon.
+    fn from_contents(buf: &[u8]) -> Self {
+        let first = ByteSlice::lines(buf)
+            .map(|l| l.trim())
+            .find(|l| !l.is_empty() && !matches!(l[0], b'#' | b'%'));
+
+        match first {
+            Some(line) if line[0] == b'*' => InputFormat::Pajek,
+            Some(line) if line[0] == b'@' => InputFormat::Lgf,
+            Some(line) if is_adjacency_line(line) => InputFormat::AdjList,
+            Some(line) if is_paf_line(line) => InputFormat::Paf,
+            Some(line) if line.len() > 1 && line[1] == b'\t' => match line[0] {
+                b'H' | b'S' | b'L' | b'P' | b'C' => InputFormat::Gfa,
+                _ => InputFormat::EdgeList,
+            },
+            Some(_) => InputFormat::EdgeList,
+            None => InputFormat::Gfa,
+        }
+    }
+}
+
+// PAF lines have at least 12 tab-separated columns, with the strand in
+// the fifth
+fn is_paf_line(line: &[u8]) -> bool {
+    let fields: Vec<&[u8]> = line.split_str("\t").collect();
+    fields.len() >= 12 && matches!(fields[4], b"+" | b"-")
+}
+
+// adjacency list lines have a colon at the end of their first field
+fn is_adjacency_line(line: &[u8]) -> bool {
+    line.split(u8::is_ascii_whitespace)
+        .next()
+        .is_some_and(|field| field.len() > 1 && field.ends_with(b":"))
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gfa" => Ok(InputFormat::Gfa),
+            "edgelist" => Ok(InputFormat::EdgeList),
+            "vg" => Ok(InputFormat::Vg),
+            "paf" => Ok(InputFormat::Paf),
+            "pajek" => Ok(InputFormat::Pajek),
+            "tgf" => Ok(InputFormat::Tgf),
+            "lgf" => Ok(InputFormat::Lgf),
+            "adjlist" => Ok(InputFormat::AdjList),
+            "binary" => Ok(InputFormat::Binary),
+            "csv" => Ok(InputFormat::Csv),
+            "parquet" => Ok(InputFormat::Parquet),
+            _ => Err(format!("Unknown input format: {}", s)),
+        }
+    }
+}
+
+/// The node orders for `--relabel`, see `Relabeling`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RelabelOrder {
+    Dfs,
+    Bfs,
+}
+
+impl RelabelOrder {
+    const NAMES: &'static [&'static str] = &["dfs", "bfs"];
+}
+
+impl FromStr for RelabelOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dfs" => Ok(RelabelOrder::Dfs),
+            "bfs" => Ok(RelabelOrder::Bfs),
+            _ => Err(format!("Unknown relabel order: {}", s)),
+        }
+    }
+}
+
+/// The supported output formats, each corresponding to a writer in
+/// `three_edge_connected::output`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Tsv,
+    Json,
+    Dot,
+    Gfa,
+    Odgi,
+    Bandage,
+    Gexf,
+    Graphml,
+    Gml,
+    CondensedGml,
+    Sqlite,
+    Parquet,
+}
+
+impl OutputFormat {
+    const NAMES: &'static [&'static str] = &[
+        "text",
+        "tsv",
+        "json",
+        "dot",
+        "gfa",
+        "odgi",
+        "bandage",
+        "gexf",
+        "graphml",
+        "gml",
+        "condensed-gml",
+        "sqlite",
+        "parquet",
+    ];
+
+    /// The name of the format, as given to `--output-format`, for
+    /// the formats written into a file by an external program, see
+    /// `write_database`.
+    fn database_name(&self) -> Option<&'static str> {
+        match self {
+            OutputFormat::Sqlite => Some("sqlite"),
+            OutputFormat::Parquet => Some("parquet"),
+            _ => None,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "txt",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Json => "json",
+            OutputFormat::Dot => "dot",
+            OutputFormat::Gfa => "gfa",
+            OutputFormat::Odgi => "tsv",
+            OutputFormat::Bandage => "csv",
+            OutputFormat::Gexf => "gexf",
+            OutputFormat::Graphml => "graphml",
+            OutputFormat::Gml | OutputFormat::CondensedGml => "gml",
+            OutputFormat::Sqlite => "db",
+            OutputFormat::Parquet => "parquet",
+        }
+    }
+
+    fn write<W, G, N>(
+        &self,
+        stream: &mut W,
+        graph: &G,
+        names: &N,
+        components: &[Vec<usize>],
+        ids: ComponentIds,
+    ) -> std::io::Result<()>
+    where
+        W: Write,
+        G: Adjacency + ?Sized,
+        N: NodeNames + ?Sized,
+    {
+        match self {
+            OutputFormat::Text => {
+                output::write_text(stream, names, components, ids)
+            }
+            OutputFormat::Tsv => {
+                output::write_tsv(stream, names, components, ids)
+            }
+            OutputFormat::Json => {
+                output::write_json(stream, names, components, ids)
+            }
+            OutputFormat::Dot => {
+                output::write_dot(stream, graph, names, components, ids)
+            }
+            OutputFormat::Gfa => {
+                output::write_gfa(stream, graph, names, components, ids)
+            }
+            OutputFormat::Odgi => {
+                output::write_odgi(stream, names, components, ids)
+            }
+            OutputFormat::Bandage => {
+                output::write_bandage_csv(stream, names, components, ids)
+            }
+            OutputFormat::Gexf => {
+                output::write_gexf(stream, graph, names, components, ids)
+            }
+            OutputFormat::Graphml => {
+                output::write_graphml(stream, graph, names, components, ids)
+            }
+            OutputFormat::Gml => {
+                output::write_gml(stream, graph, names, components, ids)
+            }
+            OutputFormat::CondensedGml => output::write_condensed_gml(
+                stream, graph, names, components, ids,
+            ),
+            OutputFormat::Sqlite => {
+                output::write_sql(stream, names, components, ids)
+            }
+            // the table that `duckdb` reads, see `write_database`
+            OutputFormat::Parquet => {
+                output::write_quoted_tsv(stream, names, components, ids)
+            }
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "tsv" => Ok(OutputFormat::Tsv),
+            "json" => Ok(OutputFormat::Json),
+            "dot" => Ok(OutputFormat::Dot),
+            "gfa" => Ok(OutputFormat::Gfa),
+            "odgi" => Ok(OutputFormat::Odgi),
+            "bandage" => Ok(OutputFormat::Bandage),
+            "gexf" => Ok(OutputFormat::Gexf),
+            "graphml" => Ok(OutputFormat::Graphml),
+            "gml" => Ok(OutputFormat::Gml),
+            "condensed-gml" => Ok(OutputFormat::CondensedGml),
+            "sqlite" => Ok(OutputFormat::Sqlite),
+            "parquet" => Ok(OutputFormat::Parquet),
+            _ => Err(format!("Unknown output format: {}", s)),
+        }
+    }
+}
+
+/// Output compression, done by piping the output through an
+/// external program, and undone the same way for compressed input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    const NAMES: &'static [&'static str] = &["gzip", "zstd"];
+
+    fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "gz" => Some(Compression::Gzip),
+            "zst" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The compression of data starting with these bytes, where BGZF
+    /// is gzip.
+    fn from_magic(buf: &[u8]) -> Option<Self> {
+        if buf.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if buf.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+            Compression::Zstd => "zst",
+        }
+    }
+
+    fn program(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gzip" => Ok(Compression::Gzip),
+            "zstd" => Ok(Compression::Zstd),
+            _ => Err(format!("Unknown compression: {}", s)),
+        }
+    }
+}
+
+/// A writer that compresses everything written to it by piping it
+/// through the compression program, which writes to `dest`.
+struct CompressedWriter {
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+    program: &'static str,
+}
+
+impl CompressedWriter {
+    fn spawn(compression: Compression, dest: Stdio) -> std::io::Result<Self> {
+        let program = compression.program();
+        let mut child = process::Command::new(program)
+            .arg("-c")
+            .stdin(Stdio::piped())
+            .stdout(dest)
+            .spawn()?;
+        let stdin = BufWriter::new(child.stdin.take().unwrap());
+        Ok(CompressedWriter {
+            child,
+            stdin,
+            program,
+        })
+    }
+
+    /// Closes the pipe and waits for the compression program to
+    /// finish writing.
+    fn finish(self) -> std::io::Result<()> {
+        let CompressedWriter {
+            mut child,
+            stdin,
+            program,
+        } = self;
+        drop(stdin.into_inner().map_err(|e| e.into_error())?);
+        let status = child.wait()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(std::io::Error::other(format!(
+                "{} exited with {}",
+                program, status
+            )))
+        }
+    }
+}
+
+impl Write for CompressedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stdin.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stdin.flush()
+    }
+}
+
+/// A reader of the output of `gzip -dcf` on the given file, or on
+/// stdin, which decompresses gzip and BGZF data and passes anything
+/// else through unchanged, or of the decompression program of
+/// `spawn_piped`, or of the `duckdb` query of `spawn_parquet`.
+struct DecompressedReader {
+    child: Child,
+    stdout: ChildStdout,
+    program: &'static str,
+    // the thread copying the input to the program, for `spawn_piped`
+    feeder: Option<JoinHandle<std::io::Result<u64>>>,
+    done: bool,
+}
+
+impl DecompressedReader {
+    fn spawn(in_path: Option<&Path>) -> std::io::Result<Self> {
+        let stdin = match in_path {
+            None => Stdio::inherit(),
+            Some(path) => Stdio::from(File::open(path)?),
+        };
+        let mut child = process::Command::new("gzip")
+            .arg("-dcf")
+            .stdin(stdin)
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().unwrap();
+        Ok(DecompressedReader {
+            child,
+            stdout,
+            program: "gzip",
+            feeder: None,
+            done: false,
+        })
+    }
+
+    /// Decompresses `input`, whose first bytes may already have been
+    /// buffered to detect the compression, by copying it to the
+    /// program on another thread.
+    fn spawn_piped<R: Read + Send + 'static>(
+        compression: Compression,
+        mut input: R,
+    ) -> std::io::Result<Self> {
+        let program = compression.program();
+        let mut child = process::Command::new(program)
+            .arg("-dc")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        let feeder =
+            thread::spawn(move || std::io::copy(&mut input, &mut stdin));
+        let stdout = child.stdout.take().unwrap();
+        Ok(DecompressedReader {
+            child,
+            stdout,
+            program,
+            feeder: Some(feeder),
+            done: false,
+        })
+    }
+}
+
+impl DecompressedReader {
+    /// Runs `duckdb` to write the `from` and `to` columns of the
+    /// Parquet file as tab-separated rows, quoted like CSV when a
+    /// name contains a tab or a double quote. The rows are read line
+    /// by line, so names with line breaks can't be read.
+    fn spawn_parquet(path: &Path) -> std::io::Result<Self> {
+        let path = path.to_string_lossy().replace('\'', "''");
+        let mut child = process::Command::new("duckdb")
+            .arg("-c")
+            .arg(format!(
+                "COPY (SELECT \"from\", \"to\" FROM read_parquet('{}')) \
+                 TO '/dev/stdout' (FORMAT csv, DELIMITER '\t', \
+                 HEADER false)",
+                path
+            ))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().unwrap();
+        Ok(DecompressedReader {
+            child,
+            stdout,
+            program: "duckdb",
+            feeder: None,
+            done: false,
+        })
+    }
+}
+
+impl Read for DecompressedReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.stdout.read(buf)?;
+        if read == 0 && !buf.is_empty() && !self.done {
+            // gzip's errors, e.g. for a corrupt file, are only seen in
+            // its exit status
+            self.done = true;
+            let status = self.child.wait()?;
+            if !status.success() {
+                return Err(std::io::Error::other(format!(
+                    "{} exited with {}",
+                    self.program, status
+                )));
+            }
+            // an error reading the input ends it early, which the
+            // program may not notice
+            if let Some(feeder) = self.feeder.take() {
+                feeder.join().map_err(|_| {
+                    std::io::Error::other("input thread panicked")
+                })??;
+            }
+        }
+        Ok(read)
+    }
+}
+
+/// Log levels, in increasing order of verbosity. The default level
+/// is `Warn`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Error = 0,
+    Warn = 1,
+    Info = 2,
+    Debug = 3,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+static VERBOSITY: AtomicUsize = AtomicUsize::new(Level::Warn as usize);
+
+fn set_verbosity(opt: &LogOpt) {
+    let level = if opt.quiet {
+        Level::Error as usize
+    } else {
+        (Level::Warn as usize + opt.verbose).min(Level::Debug as usize)
+    };
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+fn log_enabled(level: Level) -> bool {
+    level as usize <= VERBOSITY.load(Ordering::Relaxed)
+}
+
+/// Writes a log message to stderr if the given level is enabled.
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {
+        if log_enabled($level) {
+            eprintln!("[{}] {}", $level, format_args!($($arg)*));
+        }
+    };
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => { log!(Level::Info, $($arg)*) };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => { log!(Level::Debug, $($arg)*) };
+}
+
+/// Durations of each phase of a run, reported by `--time`.
+#[derive(Debug, Default)]
+struct Timings {
+    parse: Duration,
+    build: Duration,
+    algorithm: Duration,
+    output: Duration,
+}
+
+impl Timings {
+    fn report(&self) {
+        let total = self.parse + self.build + self.algorithm + self.output;
+        eprintln!("parse\t{:.3}s", self.parse.as_secs_f64());
+        eprintln!("build\t{:.3}s", self.build.as_secs_f64());
+        eprintln!("algorithm\t{:.3}s", self.algorithm.as_secs_f64());
+        eprintln!("output\t{:.3}s", self.output.as_secs_f64());
+        eprintln!("total\t{:.3}s", total.as_secs_f64());
+    }
+}
+
+/// Errors that end the program, each with its own exit code.
+#[derive(Debug)]
+enum CliError {
+    InvalidArgs(String),
+    Io(String, std::io::Error),
+    Parse(String),
+    InvalidGraph(String),
+    #[cfg_attr(not(feature = "cross-check"), allow(dead_code))]
+    Mismatch(String),
+}
+
+impl CliError {
+    fn io(context: impl Into<String>) -> impl FnOnce(std::io::Error) -> Self {
+        let context = context.into();
+        move |err| CliError::Io(context, err)
+    }
+
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::InvalidArgs(_) => 1,
+            CliError::Io(..) => 2,
+            CliError::Parse(_) => 3,
+            CliError::InvalidGraph(_) => 4,
+            CliError::Mismatch(_) => 5,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::InvalidArgs(msg) => write!(f, "{}", msg),
+            CliError::Io(context, err) => write!(f, "{}: {}", context, err),
+            CliError::Parse(msg) => write!(f, "Could not parse input: {}", msg),
+            CliError::InvalidGraph(msg) => {
+                write!(f, "Invalid input graph: {}", msg)
+            }
+            CliError::Mismatch(msg) => {
+                write!(f, "The algorithms found different components: {}", msg)
+            }
+        }
+    }
+}
+
+/// Rejects input that is clearly not one of the text formats, such
+/// as compressed or other binary data, based on the first buffered
+/// bytes.
+fn check_text_input(buf: &[u8]) -> Result<(), CliError> {
+    if buf.starts_with(&[0x1f, 0x8b]) {
+        Err(CliError::Parse("input is gzip-compressed".to_string()))
+    } else if buf.contains(&0) {
+        Err(CliError::Parse("input contains binary data".to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    set_verbosity(&opt.log);
+
+    let result = match opt.cmd {
+        Some(Command::Validate { ref input }) => validate(input),
+        Some(Command::Stats { ref input }) => stats(input),
+        Some(Command::Decompose { ref input }) => decompose(input),
+        Some(Command::VertexConnectivity { ref input }) => {
+            vertex_connectivity(input)
+        }
+        Some(Command::BridgeTree {
+            ref input,
+            output_format,
+        }) => bridge_tree(input, output_format),
+        Some(Command::Superbubbles { ref input }) => superbubbles(input),
+        Some(Command::Snarls { ref input }) => snarls(input),
+        Some(Command::Serve {
+            ref input,
+            ref socket,
+        }) => serve(input, socket),
+        Some(Command::Batch {
+            ref inputs,
+            format,
+            ref out_dir,
+            threads,
+            edges,
+            ref output,
+        }) => batch(inputs, format, out_dir, threads, edges, output),
+        Some(Command::Diff {
+            ref before,
+            ref after,
+            format,
+            min_size,
+            edges,
+        }) => diff_graphs(before, after, format, min_size, edges),
+        Some(Command::Generate {
+            topology,
+            seed,
+            format,
+            ref out_file,
+        }) => generate(topology, seed, format, out_file.as_deref()),
+        Some(Command::Completions { shell }) => {
+            let mut app = Opt::clap();
+            let name = app.get_name().to_string();
+            app.gen_completions_to(name, shell, &mut std::io::stdout());
+            Ok(())
+        }
+        None => run(opt),
+    };
+
+    if let Err(err) = result {
+        if let CliError::Io(_, io_err) = &err {
+            if io_err.kind() == std::io::ErrorKind::BrokenPipe {
+                return;
+            }
+        }
+        log!(Level::Error, "{}", err);
+        process::exit(err.exit_code());
+    }
+}
+
+/// Reads the input graph in the format given by the options, or
+/// the detected format.
+fn load_graph(
+    input: &InputOpt,
+    timings: Option<&mut Timings>,
+) -> Result<InternedGraph, CliError> {
+    let in_path = input.in_file.as_deref().filter(|_| !input.stdin);
+    let graph = read_graph(in_path, input.format, input.edges, timings)?;
+
+    let graph = match &input.nodes_file {
+        None => graph,
+        Some(path) => {
+            let nodes = read_node_names(path)?;
+            let subgraph =
+                graph.induced_subgraph(nodes.iter().map(|n| n.as_slice()));
+            info!(
+                "Restricted graph to {} of {} listed nodes, with {} edges",
+                subgraph.graph.len(),
+                nodes.len(),
+                num_edges(&subgraph)
+            );
+            subgraph
+        }
+    };
+
+    match &input.region {
+        None => Ok(graph),
+        Some(region) => {
+            region_subgraph(in_path, region, input.region_context, &graph)
+        }
+    }
+}
+
+/// Returns the subgraph of the segments in the region, and those within
+/// `context` links of them, for `--region`.
+fn region_subgraph(
+    in_path: Option<&Path>,
+    region: &Region,
+    context: usize,
+    graph: &InternedGraph,
+) -> Result<InternedGraph, CliError> {
+    if in_path.is_none() {
+        return Err(CliError::InvalidArgs(
+            "--region needs an input file".to_string(),
+        ));
+    }
+    let (in_handle, format) = open_input(in_path, None)?;
+    if format != InputFormat::Gfa {
+        return Err(CliError::InvalidArgs(
+            "--region requires GFA input".to_string(),
+        ));
+    }
+    let segments =
+        region::region_segments(in_handle, region).map_err(|err| match err
+            .kind()
+        {
+            std::io::ErrorKind::InvalidInput => {
+                CliError::InvalidArgs(err.to_string())
+            }
+            _ => read_error("Could not read region")(err),
+        })?;
+
+    let in_region: Vec<usize> = segments
+        .iter()
+        .filter_map(|segment| graph.names.find(segment))
+        .collect();
+    if in_region.is_empty() {
+        log!(Level::Warn, "Region contains no linked segments");
+    }
+    let nodes =
+        region::neighborhood(&graph.graph, in_region.iter().copied(), context);
+    let subgraph =
+        graph.induced_subgraph(nodes.iter().map(|&n| graph.names.get(n)));
+    info!(
+        "Restricted graph to {} segments in the region and {} in its \
+         context, with {} edges",
+        in_region.len(),
+        nodes.len() - in_region.len(),
+        num_edges(&subgraph)
+    );
+    Ok(subgraph)
+}
+
+/// Reads a file of node names, one per line, skipping blank lines.
+fn read_node_names(path: &Path) -> Result<Vec<Vec<u8>>, CliError> {
+    let err = || CliError::io(format!("Could not read nodes file {:?}", path));
+    let file = File::open(path).map_err(err())?;
+    let mut names = Vec::new();
+    for line in BufReader::new(file).byte_lines() {
+        let line = line.map_err(err())?;
+        let name = line.trim();
+        if !name.is_empty() {
+            names.push(name.to_vec());
+        }
+    }
+    Ok(names)
+}
+
+/// Opens the given file, or stdin if there is no path, checks that it
+/// looks like text, and detects the format if none is given, from the
+/// file extension or the contents. vg files are binary, and must be
+/// given with `--format vg` or the `.vg` extension; they're
+/// decompressed with `gzip`. Parquet files are read by `duckdb`, so
+/// they can't be read from stdin.
+fn open_input(
+    in_path: Option<&Path>,
+    format: Option<InputFormat>,
+) -> Result<(Box<dyn BufRead>, InputFormat), CliError> {
+    let format =
+        format.or_else(|| in_path.and_then(InputFormat::from_extension));
+    if format == Some(InputFormat::Parquet) {
+        return open_parquet(in_path);
+    }
+    if format == Some(InputFormat::Vg) {
+        match in_path {
+            None => info!("Reading input from stdin"),
+            Some(path) => info!("Reading input from {:?}", path),
+        }
+        debug!("Using input format {:?}", InputFormat::Vg);
+        let reader = DecompressedReader::spawn(in_path)
+            .map_err(CliError::io("Could not decompress input with gzip"))?;
+        return Ok((Box::new(BufReader::new(reader)), InputFormat::Vg));
+    }
+
+    let mut in_handle: Box<dyn BufRead + Send> = {
+        match in_path {
+            None => {
+                info!("Reading input from stdin");
+                Box::new(BufReader::new(std::io::stdin()))
+            }
+            Some(path) => {
+                info!("Reading input from {:?}", path);
+                let file = File::open(path).map_err(CliError::io(format!(
+                    "Could not open file {:?}",
+                    path
+                )))?;
+                Box::new(BufReader::new(file))
+            }
+        }
+    };
+
+    let buf = in_handle
+        .fill_buf()
+        .map_err(CliError::io("Could not read input"))?;
+    if let Some(compression) = Compression::from_magic(buf) {
+        let program = compression.program();
+        debug!("Decompressing input with {}", program);
+        let reader = DecompressedReader::spawn_piped(compression, in_handle)
+            .map_err(CliError::io(format!(
+                "Could not decompress input with {}",
+                program
+            )))?;
+        in_handle = Box::new(BufReader::new(reader));
+    }
+
+    let buf = in_handle
+        .fill_buf()
+        .map_err(CliError::io("Could not read input"))?;
+    let binary = format == Some(InputFormat::Binary)
+        || (format.is_none() && graph_file::is_graph_file(buf));
+    if binary {
+        debug!("Using input format {:?}", InputFormat::Binary);
+        return Ok((in_handle, InputFormat::Binary));
+    }
+    if format.is_none() && buf.starts_with(PARQUET_MAGIC) {
+        drop(in_handle);
+        return open_parquet(in_path);
+    }
+    check_text_input(buf)?;
+
+    let format = format.unwrap_or_else(|| InputFormat::from_contents(buf));
+    debug!("Using input format {:?}", format);
+
+    Ok((in_handle, format))
+}
+
+// the first bytes of Parquet files
+const PARQUET_MAGIC: &[u8] = b"PAR1";
+
+/// Opens the rows of the edges of a Parquet file, see
+/// `DecompressedReader::spawn_parquet`.
+fn open_parquet(
+    in_path: Option<&Path>,
+) -> Result<(Box<dyn BufRead>, InputFormat), CliError> {
+    if !cfg!(feature = "parquet") {
+        return Err(CliError::InvalidArgs(
+            "Parquet input needs the parquet feature".to_string(),
+        ));
+    }
+    let path = in_path.ok_or_else(|| {
+        CliError::InvalidArgs(
+            "Parquet input must be a file, not stdin".to_string(),
+        )
+    })?;
+    debug!("Using input format {:?}", InputFormat::Parquet);
+    let reader = DecompressedReader::spawn_parquet(path)
+        .map_err(CliError::io("Could not run duckdb"))?;
+    Ok((Box::new(BufReader::new(reader)), InputFormat::Parquet))
+}
+
+/// Reads a graph from the given file, or stdin if there is no path.
+/// If no format is given, it's detected from the file extension or
+/// the contents. If `timings` is given, the edges are parsed into
+/// memory before building the graph, to time the phases separately.
+fn read_graph(
+    in_path: Option<&Path>,
+    format: Option<InputFormat>,
+    edge_opt: EdgeOpt,
+    timings: Option<&mut Timings>,
+) -> Result<InternedGraph, CliError> {
+    let (mut in_handle, format) = open_input(in_path, format)?;
+    let read_err = || read_error("Could not read input");
+
+    let start = Instant::now();
+    let graph = match timings {
+        _ if format == InputFormat::Binary => {
+            InternedGraph::read_from(&mut in_handle).map_err(read_err())?
+        }
+        None if format == InputFormat::Gfa => build_from_gfa(
+            &mut in_handle,
+            edge_opt,
+            InternedGraph::from_gfa_reader_with,
+        )?,
+        None => {
+            let edges = input_edges(&mut in_handle, format, edge_opt)?;
+            InternedGraph::try_from_named_edges(edges).map_err(read_err())?
+        }
+        Some(timings) => {
+            let edges = input_edges(&mut in_handle, format, edge_opt)?;
+            let edges: Vec<_> =
+                edges.collect::<std::io::Result<_>>().map_err(read_err())?;
+            timings.parse = start.elapsed();
+            debug!("Parsed {} edges in {:.2?}", edges.len(), timings.parse);
+
+            let build_start = Instant::now();
+            let graph = InternedGraph::from_named_edges(edges);
+            timings.build = build_start.elapsed();
+            graph
+        }
+    };
+    info!(
+        "Built graph with {} nodes and {} edges in {:.2?}",
+        graph.graph.len(),
+        num_edges(&graph),
+        start.elapsed()
+    );
+
+    if graph.graph.is_empty() {
+        log!(Level::Warn, "Input contains no edges");
+    }
+
+    Ok(graph)
+}
+
+/// Runs the algorithm and applies the output filters. Also returns the
+/// memory used by the algorithm's state, except when running on
+/// multiple threads, where each thread has its own. The DFS spanning
+/// forest is recorded in `forest`, if given, which needs one thread.
+fn find_components(
+    graph: &InternedGraph,
+    threads: usize,
+    compact_state: bool,
+    four_edge: bool,
+    forest: Option<&mut SpanningForest>,
+) -> (Vec<Vec<usize>>, Option<usize>) {
+    let start = Instant::now();
+    let (components, state_memory) = if threads > 1 {
+        debug!("Finding 3-edge-connected components on {} threads", threads);
+        (parallel::find_components(&graph.graph, threads), None)
+    } else {
+        debug!("Finding 3-edge-connected components");
+        let (components, memory) = match forest {
+            Some(forest) => run_algorithm(&graph.graph, compact_state, forest),
+            None => run_algorithm(&graph.graph, compact_state, &mut ()),
+        };
+        (components, Some(memory))
+    };
+    info!(
+        "Found {} components ({} with more than one node) in {:.2?}",
+        components.len(),
+        components.iter().filter(|c| c.len() > 1).count(),
+        start.elapsed()
+    );
+    if four_edge {
+        return (refine_four_edge(&graph.graph, components), state_memory);
+    }
+    (components, state_memory)
+}
+
+/// Checks the components against those of the second algorithm, for
+/// `--cross-check`.
+#[cfg(feature = "cross-check")]
+fn cross_check(
+    graph: &graph::FxMapGraph,
+    components: &[Vec<usize>],
+) -> Result<(), CliError> {
+    let start = Instant::now();
+    let result = three_edge_connected::cut_pairs::cross_check_components(
+        graph,
+        components.to_vec(),
+    );
+    match result {
+        Ok(_) => {
+            info!(
+                "Checked the components with the second algorithm in {:.2?}",
+                start.elapsed()
+            );
+            Ok(())
+        }
+        Err(mismatch) => Err(CliError::Mismatch(format!(
+            "{} components, and {} from the second algorithm, which differ \
+             at node {}",
+            mismatch.primary.len(),
+            mismatch.cut_pairs.len(),
+            mismatch.node
+        ))),
+    }
+}
+
+#[cfg(not(feature = "cross-check"))]
+fn cross_check(
+    _graph: &graph::FxMapGraph,
+    _components: &[Vec<usize>],
+) -> Result<(), CliError> {
+    unreachable!("--cross-check is rejected without the feature")
+}
+
+/// Splits the 3-edge-connected components into the 4-edge-connected
+/// components, for `--four-edge`.
+fn refine_four_edge<G: Adjacency + ?Sized>(
+    graph: &G,
+    components: Vec<Vec<usize>>,
+) -> Vec<Vec<usize>> {
+    let start = Instant::now();
+    let num_components = components.len();
+    let components = four_edge::refine_components(graph, components);
+    info!(
+        "Split the {} components into {} 4-edge-connected components in \
+         {:.2?}",
+        num_components,
+        components.len(),
+        start.elapsed()
+    );
+    components
+}
+
+/// Filters the components with `--min-size`, and sorts them with
+/// `--keep-order`, after `find_components`.
+fn filter_components(
+    components: Vec<Vec<usize>>,
+    output: &OutputOpt,
+) -> Vec<Vec<usize>> {
+    let mut components =
+        three_edge_connected::filter_min_size(components, output.min_size);
+    if output.keep_order {
+        three_edge_connected::sort_components_by_key(&mut components, |n| n);
+    }
+    debug!(
+        "{} components left after filtering on size {}",
+        components.len(),
+        output.min_size
+    );
+    components
+}
+
+/// Runs the algorithm, with 32-bit indices in the state if
+/// `compact_state` is set and the graph is small enough. Returns the
+/// components and the memory used by the state.
+fn run_algorithm<G: Adjacency + ?Sized, V: Visitor>(
+    graph: &G,
+    compact_state: bool,
+    visitor: &mut V,
+) -> (Vec<Vec<usize>>, usize) {
+    run_algorithm_with_info(graph, compact_state, visitor, None)
+}
+
+/// Like `run_algorithm`, also keeping the DFS numbers of each node in
+/// `node_info` if given, for `--node-table`.
+fn run_algorithm_with_info<G: Adjacency + ?Sized, V: Visitor>(
+    graph: &G,
+    compact_state: bool,
+    visitor: &mut V,
+    node_info: Option<&mut Vec<Option<NodeInfo>>>,
+) -> (Vec<Vec<usize>>, usize) {
+    fn run<Ix: IndexType, G: Adjacency + ?Sized, V: Visitor>(
+        graph: &G,
+        visitor: &mut V,
+        node_info: Option<&mut Vec<Option<NodeInfo>>>,
+    ) -> (Vec<Vec<usize>>, usize) {
+        let mut state: State<Ix> = State::initialize_with_index(graph);
+        algorithm::three_edge_connect_with_visitor(graph, &mut state, visitor);
+        let memory = state.memory_usage();
+        if let Some(node_info) = node_info {
+            *node_info =
+                (0..graph.num_nodes()).map(|n| state.node_info(n)).collect();
+        }
+        (state.sigma.into_inner(), memory)
+    }
+
+    if compact_state && graph.num_nodes() > u32::MAX_NODES {
+        log!(
+            Level::Warn,
+            "Graph has too many nodes for --compact-state, using 64-bit indices"
+        );
+        run::<usize, G, V>(graph, visitor, node_info)
+    } else if compact_state {
+        run::<u32, G, V>(graph, visitor, node_info)
+    } else {
+        run::<usize, G, V>(graph, visitor, node_info)
+    }
+}
+
+/// The number of edge ends between checks of the time since the last
+/// `--checkpoint` was written.
+const CHECKPOINT_SLICE: usize = 1 << 22;
+
+/// Runs the algorithm for `--checkpoint`, resuming from the file if it
+/// exists, and writing it every `interval`. Returns the components and
+/// the memory used by the state, like `find_components`.
+fn run_checkpointed(
+    graph: &graph::FxMapGraph,
+    path: &Path,
+    interval: Duration,
+    compact_state: bool,
+) -> Result<(Vec<Vec<usize>>, Option<usize>), CliError> {
+    fn run<Ix: IndexType>(
+        graph: &graph::FxMapGraph,
+        path: &Path,
+        interval: Duration,
+    ) -> Result<(Vec<Vec<usize>>, Option<usize>), CliError> {
+        let start = Instant::now();
+        let mut checkpoint = if path.exists() {
+            let err = || CliError::io(format!("Could not read {:?}", path));
+            let file = File::open(path).map_err(err())?;
+            let checkpoint =
+                Checkpoint::<Ix>::read_from(&mut BufReader::new(file), graph)
+                    .map_err(err())?;
+            info!(
+                "Resuming from checkpoint {:?}, with {} of {} nodes visited",
+                path,
+                checkpoint.nodes_visited(),
+                graph.num_nodes()
+            );
+            checkpoint
+        } else {
+            Checkpoint::new(graph)
+        };
+
+        let mut last_write = Instant::now();
+        // there's no cancel flag, so this can only pause
+        while !checkpoint
+            .resume(graph, Some(CHECKPOINT_SLICE), None)
+            .unwrap_or(false)
+        {
+            if last_write.elapsed() >= interval {
+                write_checkpoint(path, &checkpoint, graph)?;
+                last_write = Instant::now();
+            }
+        }
+        if path.exists() {
+            std::fs::remove_file(path).map_err(CliError::io(format!(
+                "Could not remove {:?}",
+                path
+            )))?;
+        }
+
+        let memory = checkpoint.state().memory_usage();
+        let components = checkpoint.into_components().into_inner();
+        info!(
+            "Found {} components ({} with more than one node) in {:.2?}",
+            components.len(),
+            components.iter().filter(|c| c.len() > 1).count(),
+            start.elapsed()
+        );
+        Ok((components, Some(memory)))
+    }
+
+    if compact_state && graph.num_nodes() > u32::MAX_NODES {
+        log!(
+            Level::Warn,
+            "Graph has too many nodes for --compact-state, using 64-bit indices"
+        );
+        run::<usize>(graph, path, interval)
+    } else if compact_state {
+        run::<u32>(graph, path, interval)
+    } else {
+        run::<usize>(graph, path, interval)
+    }
+}
+
+/// Writes the checkpoint to a temporary file next to `path`, which then
+/// replaces it, so that the previous one is kept if this is
+/// interrupted.
+fn write_checkpoint<Ix: IndexType>(
+    path: &Path,
+    checkpoint: &Checkpoint<Ix>,
+    graph: &graph::FxMapGraph,
+) -> Result<(), CliError> {
+    let start = Instant::now();
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    let err = || CliError::io(format!("Could not write {:?}", tmp_path));
+
+    let file = File::create(&tmp_path).map_err(err())?;
+    let mut out = BufWriter::new(file);
+    checkpoint.write_to(&mut out, graph).map_err(err())?;
+    out.into_inner()
+        .map_err(|err| err.into_error())
+        .and_then(|file| file.sync_all())
+        .map_err(err())?;
+    std::fs::rename(&tmp_path, path)
+        .map_err(CliError::io(format!("Could not replace {:?}", path)))?;
+    info!(
+        "Wrote checkpoint {:?}, with {} of {} nodes visited, in {:.2?}",
+        path,
+        checkpoint.nodes_visited(),
+        graph.num_nodes(),
+        start.elapsed()
+    );
+    Ok(())
+}
+
+/// Logs the estimated memory usage of the graph and the algorithm's
+/// state at the end of a run.
+fn report_memory_usage(graph_memory: usize, state_memory: Option<usize>) {
+    match state_memory {
+        Some(state_memory) => info!(
+            "Estimated memory usage: {} for the graph, {} for the \
+             algorithm state",
+            format_bytes(graph_memory),
+            format_bytes(state_memory)
+        ),
+        None => info!(
+            "Estimated memory usage: {} for the graph",
+            format_bytes(graph_memory)
+        ),
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Parses a number of bytes, with an optional binary `K`, `M`, `G`, or
+/// `T` suffix, for `--max-memory`.
+fn parse_size(s: &str) -> Result<usize, String> {
+    let shift = match s.as_bytes().last().map(u8::to_ascii_uppercase) {
+        Some(b'K') => 10,
+        Some(b'M') => 20,
+        Some(b'G') => 30,
+        Some(b'T') => 40,
+        _ => 0,
+    };
+    let digits = if shift == 0 { s } else { &s[..s.len() - 1] };
+    digits
+        .parse::<usize>()
+        .ok()
+        .zip(1usize.checked_shl(shift))
+        .and_then(|(n, unit)| n.checked_mul(unit))
+        .ok_or_else(|| format!("invalid memory size: {}", s))
+}
+
+/// Estimates the number of distinct node names in one pass, without
+/// storing them, with a HyperLogLog sketch of 2^12 registers, which is
+/// within a few percent.
+struct DistinctNames {
+    registers: Vec<u8>,
+}
+
+impl DistinctNames {
+    const BITS: u32 = 12;
+
+    fn new() -> Self {
+        DistinctNames {
+            registers: vec![0; 1 << Self::BITS],
+        }
+    }
+
+    fn insert(&mut self, name: &[u8]) {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(name);
+        let hash = hasher.finish();
+        let ix = (hash >> (64 - Self::BITS)) as usize;
+        let rank =
+            (hash << Self::BITS).leading_zeros().min(64 - Self::BITS) + 1;
+        self.registers[ix] = self.registers[ix].max(rank as u8);
+    }
+
+    fn estimate(&self) -> usize {
+        let m = self.registers.len() as f64;
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&r| 2f64.powi(-i32::from(r)))
+            .sum();
+        let raw = 0.7213 / (1.0 + 1.079 / m) * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        // linear counting is more accurate for few names
+        if raw <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as usize
+        } else {
+            raw.round() as usize
+        }
+    }
+}
+
+/// The size of the input graph, estimated in a first pass over it, for
+/// `--max-memory`.
+struct InputSize {
+    nodes: usize,
+    edges: usize,
+    /// The total length of the distinct node names.
+    name_bytes: usize,
+}
+
+impl InputSize {
+    fn count(opt: &Opt) -> Result<Self, CliError> {
+        let in_path = opt.input.in_file.as_deref().filter(|_| !opt.input.stdin);
+        let (mut in_handle, format) = open_input(in_path, opt.input.format)?;
+        let mut names = DistinctNames::new();
+        let (mut edges, mut total_bytes, mut max_index) = (0, 0, 0);
+        for edge in input_edges(&mut in_handle, format, opt.input.edges)? {
+            let (from, to) =
+                edge.map_err(read_error("Could not read input"))?;
+            edges += 1;
+            for name in [from, to] {
+                if opt.no_names {
+                    let index = graph::parse_index(&name)
+                        .map_err(read_error("Could not read input"))?;
+                    max_index = max_index.max(index + 1);
+                } else {
+                    total_bytes += name.len();
+                    names.insert(&name);
+                }
+            }
+        }
+
+        if opt.no_names {
+            return Ok(InputSize {
+                nodes: max_index,
+                edges,
+                name_bytes: 0,
+            });
+        }
+        let nodes = names.estimate();
+        // the names that occur more often count more towards the
+        // average length, which is close enough
+        let name_bytes = match edges {
+            0 => 0,
+            _ => total_bytes / (2 * edges) * nodes,
+        };
+        Ok(InputSize {
+            nodes,
+            edges,
+            name_bytes,
+        })
+    }
+
+    /// The estimated peak memory usage of a run, with the graph in
+    /// memory or in memory-mapped files, whose pages can be evicted,
+    /// and with 32-bit state indices or not.
+    fn memory_usage(&self, named: bool, compact: bool, mmap: bool) -> usize {
+        use std::mem::size_of;
+
+        let (nodes, ends) = (self.nodes, 2 * self.edges);
+        // each name is stored with its end, and in the slots of a hash
+        // table
+        let names = if named {
+            self.name_bytes + 24 * nodes
+        } else {
+            0
+        };
+        let graph = if mmap {
+            // the degrees, while the files are written
+            nodes * size_of::<usize>()
+        } else {
+            let lists = if named {
+                // the buckets of a hash map, which is at most 7/8 full,
+                // with a control byte each
+                (nodes * 8 / 7).next_power_of_two()
+                    * (size_of::<usize>() + size_of::<AdjacencyList>() + 1)
+            } else {
+                nodes * size_of::<AdjacencyList>()
+            };
+            // the lists longer than the inline ones are on the heap,
+            // with room to grow
+            let spilled = if ends > 4 * nodes {
+                ends * size_of::<usize>() * 3 / 2
+            } else {
+                0
+            };
+            lists + spilled
+        };
+        names + graph + state_memory_usage(nodes, compact)
+    }
+}
+
+/// The estimated peak memory usage of the algorithm's state on a graph
+/// with this many nodes: the vectors of the state and its components,
+/// and the components as vectors once they're taken out of it.
+fn state_memory_usage(nodes: usize, compact: bool) -> usize {
+    let index = if compact && nodes <= u32::MAX_NODES {
+        4
+    } else {
+        8
+    };
+    nodes * (6 * index + 16) + nodes * 40
+}
+
+/// Prints the size of the graph and the estimated memory usage of the
+/// run, for `--dry-run`, like the `stats` subcommand.
+fn report_dry_run(
+    num_nodes: usize,
+    num_edges: usize,
+    graph_memory: usize,
+    opt: &Opt,
+) {
+    println!("nodes\t{}", num_nodes);
+    println!("edges\t{}", num_edges);
+    println!("graph_memory\t{}", graph_memory);
+    println!(
+        "state_memory\t{}",
+        state_memory_usage(num_nodes, opt.compact_state)
+    );
+}
+
+/// A directory for the graph files of `--max-memory`, which is removed
+/// with them when it's dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    /// A directory next to the output file, or in the current
+    /// directory, rather than in the system's temporary directory,
+    /// which may be kept in memory.
+    fn next_to(out_file: Option<&Path>) -> Self {
+        let parent = out_file.and_then(Path::parent).unwrap_or(Path::new(""));
+        TempDir(parent.join(format!("gfa_components-{}.graph", process::id())))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.0) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                log!(Level::Warn, "Could not remove {:?}: {}", self.0, err);
+            }
+        }
+    }
+}
+
+/// Whether any of the options need the graph in memory, so that
+/// `--max-memory` can't store it in files.
+fn needs_graph_in_memory(opt: &Opt) -> bool {
+    opt.input.is_subgraph()
+        || opt.threads > 1
+        || opt.relabel.is_some()
+        || opt.path_crossings.is_some()
+        || opt.boundary_links.is_some()
+        || opt.bed.is_some()
+        || opt.orphans_out.is_some()
+        || opt.cross_check
+        || opt.checkpoint.is_some()
+        || opt.forest_out.is_some()
+        || opt.edges_out.is_some()
+        || opt.save_graph.is_some()
+        || opt.node_table.is_some()
+        || opt.rgfa
+}
+
+/// Estimates the memory usage of the run from a first pass over the
+/// input, and turns on `--compact-state`, and then `--mmap-dir` with a
+/// temporary directory, which is returned, until the estimate is under
+/// the budget, or fails if it never is.
+fn fit_memory_budget(
+    opt: &mut Opt,
+    budget: usize,
+) -> Result<Option<TempDir>, CliError> {
+    let start = Instant::now();
+    let size = InputSize::count(opt)?;
+    info!(
+        "Estimated {} nodes and {} edges in {:.2?}",
+        size.nodes,
+        size.edges,
+        start.elapsed()
+    );
+    let named = !opt.no_names;
+    let mut usage =
+        size.memory_usage(named, opt.compact_state, opt.mmap_dir.is_some());
+    if usage > budget && !opt.compact_state && size.nodes <= u32::MAX_NODES {
+        info!(
+            "Estimated memory usage of {} is over --max-memory, using \
+             --compact-state",
+            format_bytes(usage)
+        );
+        opt.compact_state = true;
+        usage = size.memory_usage(named, true, opt.mmap_dir.is_some());
+    }
+    let mut graph_dir = None;
+    if usage > budget && opt.mmap_dir.is_none() {
+        if needs_graph_in_memory(opt) {
+            return Err(CliError::InvalidArgs(format!(
+                "estimated memory usage of {} is over --max-memory {}, and \
+                 the graph can't be stored in files with --bed, \
+                 --checkpoint, --cross-check, --forest-out, --nodes-file, \
+                 --orphans-out, --path-crossings, --region, --relabel, \
+                 --rgfa or --threads",
+                format_bytes(usage),
+                format_bytes(budget)
+            )));
+        }
+        let dir = TempDir::next_to(opt.out_file.as_deref());
+        info!(
+            "Estimated memory usage of {} is over --max-memory, storing \
+             the graph in {:?}",
+            format_bytes(usage),
+            dir.0
+        );
+        opt.mmap_dir = Some(dir.0.clone());
+        usage = size.memory_usage(named, opt.compact_state, true);
+        graph_dir = Some(dir);
+    }
+    if usage > budget {
+        return Err(CliError::InvalidArgs(format!(
+            "estimated memory usage of {} is over --max-memory {}",
+            format_bytes(usage),
+            format_bytes(budget)
+        )));
+    }
+    info!("Estimated memory usage: {}", format_bytes(usage));
+    Ok(graph_dir)
+}
+
+/// Writes the components to the given file, or stdout if there is
+/// no path.
+fn write_output<G: Adjacency + ?Sized, N: NodeNames + ?Sized>(
+    out_path: Option<&Path>,
+    output: &OutputOpt,
+    graph: &G,
+    names: &N,
+    components: &[Vec<usize>],
+) -> Result<(), CliError> {
+    let format = output.output_format;
+    if format.database_name().is_some() {
+        return write_database(out_path, output, names, components);
+    }
+    write_to_output(out_path, output.compress, |mut out| {
+        format.write(&mut out, graph, names, components, output.component_ids())
+    })
+}
+
+/// Writes the components into the SQLite database or Parquet file at
+/// the path, by running `sqlite3` on it with the SQL script of
+/// `output::write_sql` on its stdin, or `duckdb` with the table of
+/// `output::write_quoted_tsv`.
+fn write_database<N: NodeNames + ?Sized>(
+    out_path: Option<&Path>,
+    output: &OutputOpt,
+    names: &N,
+    components: &[Vec<usize>],
+) -> Result<(), CliError> {
+    let format = output.output_format;
+    let name = format.database_name().unwrap();
+    let path = out_path.ok_or_else(|| {
+        CliError::InvalidArgs(format!(
+            "--output-format {} needs an output file",
+            name
+        ))
+    })?;
+    if output.compress.is_some() || Compression::from_extension(path).is_some()
+    {
+        return Err(CliError::InvalidArgs(format!(
+            "--output-format {} can't be compressed",
+            name
+        )));
+    }
+
+    if format == OutputFormat::Parquet && !cfg!(feature = "parquet") {
+        return Err(CliError::InvalidArgs(
+            "--output-format parquet needs the parquet feature".to_string(),
+        ));
+    }
+
+    let ids = output.component_ids();
+    let (program, mut command) = match format {
+        OutputFormat::Sqlite => {
+            let mut command = process::Command::new("sqlite3");
+            command.arg("-bail").arg(path);
+            ("sqlite3", command)
+        }
+        _ => {
+            let column_type = match ids {
+                ComponentIds::Index => "BIGINT",
+                ComponentIds::Stable => "VARCHAR",
+            };
+            let path = path.to_string_lossy().replace('\'', "''");
+            let mut command = process::Command::new("duckdb");
+            command.arg("-c").arg(format!(
+                "COPY (SELECT node, component FROM read_csv('/dev/stdin', \
+                 delim = '\\t', header = true, quote = '\"', escape = '\"', \
+                 columns = {{'component': '{}', 'node': 'VARCHAR'}})) \
+                 TO '{}' (FORMAT parquet)",
+                column_type, path
+            ));
+            ("duckdb", command)
+        }
+    };
+
+    let start = Instant::now();
+    let run_err = || CliError::io(format!("Could not run {}", program));
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(run_err())?;
+    let mut stdin = BufWriter::new(child.stdin.take().unwrap());
+    let written = match format {
+        OutputFormat::Sqlite => {
+            output::write_sql(&mut stdin, names, components, ids)
+        }
+        _ => output::write_quoted_tsv(&mut stdin, names, components, ids),
+    }
+    .and_then(|_| stdin.flush());
+    drop(stdin);
+    let status = child.wait().map_err(run_err())?;
+    if !status.success() {
+        return Err(CliError::Io(
+            format!("Could not write {:?}", path),
+            std::io::Error::other(format!(
+                "{} exited with {}",
+                program, status
+            )),
+        ));
+    }
+    written.map_err(CliError::io("Could not write output"))?;
+    info!("Wrote components in {:.2?}", start.elapsed());
+    Ok(())
+}
+
+/// Opens the output file, or stdout if there is no path, compressed
+/// with `compress` or according to the extension of the file, and
+/// writes to it with `write`.
+fn write_to_output<F>(
+    out_path: Option<&Path>,
+    compress: Option<Compression>,
+    write: F,
+) -> Result<(), CliError>
+where
+    F: FnOnce(&mut dyn Write) -> std::io::Result<()>,
+{
+    let compression =
+        compress.or_else(|| out_path.and_then(Compression::from_extension));
+
+    let fout = match out_path {
+        None => None,
+        Some(path) => Some(File::create(path).map_err(CliError::io(
+            format!("Could not create file {:?}", path),
+        ))?),
+    };
+
+    let start = Instant::now();
+    let write_err = || CliError::io("Could not write output");
+
+    match compression {
+        None => {
+            let mut out_handle: Box<dyn Write> = match fout {
+                None => Box::new(BufWriter::new(std::io::stdout())),
+                Some(fout) => Box::new(BufWriter::new(fout)),
+            };
+            write(&mut out_handle).map_err(write_err())?;
+            out_handle.flush().map_err(write_err())?;
+        }
+        Some(compression) => {
+            debug!("Compressing output with {}", compression.program());
+            let dest = fout.map_or_else(Stdio::inherit, Stdio::from);
+            let mut out_handle = CompressedWriter::spawn(compression, dest)
+                .map_err(CliError::io(format!(
+                    "Could not run {}",
+                    compression.program()
+                )))?;
+            write(&mut out_handle).map_err(write_err())?;
+            out_handle.finish().map_err(write_err())?;
+        }
+    }
+    info!("Wrote components in {:.2?}", start.elapsed());
+
+    Ok(())
+}
+
+fn num_edges(graph: &InternedGraph) -> usize {
+    graph.graph.values().map(|adj| adj.len()).sum::<usize>() / 2
+}
+
+fn validate(input: &InputOpt) -> Result<(), CliError> {
+    let graph = load_graph(input, None)?;
+    let mut valid = true;
+
+    println!("nodes\t{}", graph.graph.len());
+    println!("edges\t{}", num_edges(&graph));
+    if graph.graph.is_empty() {
+        log!(Level::Error, "Input contains no edges");
+        valid = false;
+    }
+
+    let components = connectivity::connected_components(&graph.graph);
+    println!("connected_components\t{}", components.len());
+    if components.len() > 1 {
+        log!(
+            Level::Warn,
+            "Graph is not connected, found {} connected components",
+            components.len()
+        );
+    }
+
+    let bridges = connectivity::bridges(&graph.graph);
+    println!("bridges\t{}", bridges.len());
+    if !bridges.is_empty() {
+        valid = false;
+        log!(Level::Error, "Graph contains {} bridges", bridges.len());
+        for (from, to) in bridges.iter().take(10) {
+            log!(
+                Level::Error,
+                "Bridge: {}\t{}",
+                graph.names.get(*from).as_bstr(),
+                graph.names.get(*to).as_bstr()
+            );
+        }
+    }
+
+    if valid {
+        info!("Input graph is valid");
+        Ok(())
+    } else {
+        Err(CliError::InvalidGraph("validation failed".to_string()))
+    }
+}
+
+fn stats(input: &InputOpt) -> Result<(), CliError> {
+    let graph = load_graph(input, None)?;
+
+    let stats = graph.stats();
+    let components = connectivity::connected_components(&graph.graph);
+
+    println!("nodes\t{}", stats.num_nodes);
+    println!("edges\t{}", stats.num_edges);
+    println!("self_loops\t{}", stats.self_loops);
+    println!("parallel_edges\t{}", stats.parallel_edges);
+    println!("connected_components\t{}", components.len());
+    for (degree, count) in stats.degrees {
+        println!("degree\t{}\t{}", degree, count);
+    }
+
+    Ok(())
+}
+
+fn decompose(input: &InputOpt) -> Result<(), CliError> {
+    let graph = load_graph(input, None)?;
+
+    let start = Instant::now();
+    let decomposition = connectivity::decompose(&graph.graph);
+    info!(
+        "Found {} connected, {} 2-edge-connected, and {} 3-edge-connected \
+         components in {:.2?}",
+        decomposition.connected.len(),
+        decomposition.two_edge.len(),
+        decomposition.three_edge.len(),
+        start.elapsed()
+    );
+
+    let stdout = std::io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let err = || CliError::io("Could not write output");
+    output::write_decomposition_json(&mut out, &decomposition, &graph.names)
+        .map_err(err())?;
+    out.flush().map_err(err())?;
+
+    Ok(())
+}
+
+fn vertex_connectivity(input: &InputOpt) -> Result<(), CliError> {
+    let graph = load_graph(input, None)?;
+
+    let start = Instant::now();
+    let cut_vertices = connectivity::cut_vertices(&graph.graph);
+    let pairs = connectivity::separation_pairs(&graph.graph);
+    info!(
+        "Found {} cut vertices and {} separation pairs in {:.2?}",
+        cut_vertices.len(),
+        pairs.len(),
+        start.elapsed()
+    );
+
+    let mut component = vec![0; graph.graph.len()];
+    for (ix, nodes) in
+        algorithm::find_components(&graph.graph).iter().enumerate()
+    {
+        for &node in nodes {
+            component[node] = ix;
+        }
+    }
+
+    let stdout = std::io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let err = || CliError::io("Could not write output");
+    for node in cut_vertices {
+        writeln!(out, "cut_vertex\t{}", graph.names.get(node).as_bstr())
+            .map_err(err())?;
+    }
+    for (from, to) in pairs {
+        let within = if component[from] == component[to] {
+            "within"
+        } else {
+            "between"
+        };
+        writeln!(
+            out,
+            "separation_pair\t{}\t{}\t{}",
+            graph.names.get(from).as_bstr(),
+            graph.names.get(to).as_bstr(),
+            within
+        )
+        .map_err(err())?;
+    }
+    out.flush().map_err(err())?;
+
+    Ok(())
+}
+
+fn bridge_tree(
+    input: &InputOpt,
+    output_format: OutputFormat,
+) -> Result<(), CliError> {
+    let graph = load_graph(input, None)?;
+
+    let start = Instant::now();
+    let tree = connectivity::bridge_tree(&graph.graph);
+    info!(
+        "Found {} 2-edge-connected components and {} bridges in {:.2?}",
+        tree.components.len(),
+        tree.edges.len(),
+        start.elapsed()
+    );
+
+    let stdout = std::io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let err = || CliError::io("Could not write output");
+    match output_format {
+        OutputFormat::Dot => {
+            output::write_bridge_tree_dot(&mut out, &tree, &graph.names)
+        }
+        _ => output::write_bridge_tree_json(&mut out, &tree, &graph.names),
+    }
+    .map_err(err())?;
+    out.flush().map_err(err())?;
+
+    Ok(())
+}
+
+/// Reads a GFA graph with the orientations of the links, for the
+/// `superbubbles` and `snarls` subcommands.
+fn read_oriented_graph(
+    input: &InputOpt,
+    command: &str,
+) -> Result<OrientedGraph, CliError> {
+    if input.is_subgraph() || input.edges.is_set() {
+        return Err(CliError::InvalidArgs(format!(
+            "{} can't be combined with --nodes-file, --region, or the edge \
+             filters",
+            command
+        )));
+    }
+    let in_path = input.in_file.as_deref().filter(|_| !input.stdin);
+    let (mut in_handle, format) = open_input(in_path, input.format)?;
+    if format != InputFormat::Gfa {
+        return Err(CliError::InvalidArgs(format!(
+            "{} requires GFA input",
+            command
+        )));
+    }
+
+    let start = Instant::now();
+    let skipped = Rc::new(Cell::new(0));
+    let on_malformed = malformed_lines(input.edges.strict, skipped.clone());
+    let graph =
+        OrientedGraph::from_gfa_reader_with(&mut in_handle, on_malformed)
+            .map_err(read_error("Could not read input"))?;
+    report_malformed(skipped.get());
+    info!(
+        "Built graph with {} segments and {} links in {:.2?}",
+        graph.num_segments(),
+        graph.num_links(),
+        start.elapsed()
+    );
+    Ok(graph)
+}
+
+fn superbubbles(input: &InputOpt) -> Result<(), CliError> {
+    let graph = read_oriented_graph(input, "superbubbles")?;
+
+    let start = Instant::now();
+    let bubbles = graph.superbubbles();
+    info!(
+        "Found {} superbubbles in {:.2?}",
+        bubbles.len(),
+        start.elapsed()
+    );
+
+    let stdout = std::io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let err = || CliError::io("Could not write output");
+    for bubble in bubbles {
+        out.write_all(&graph.node_name(bubble.entrance))
+            .map_err(err())?;
+        out.write_all(b"\t").map_err(err())?;
+        out.write_all(&graph.node_name(bubble.exit))
+            .map_err(err())?;
+        writeln!(out, "\t{}", graph.superbubble_nodes(bubble).len())
+            .map_err(err())?;
+    }
+    out.flush().map_err(err())?;
+
+    Ok(())
+}
+
+fn snarls(input: &InputOpt) -> Result<(), CliError> {
+    let graph = read_oriented_graph(input, "snarls")?;
+
+    let start = Instant::now();
+    let decomposition = graph.snarls();
+    info!(
+        "Found {} snarls in {} chains in {:.2?}",
+        decomposition.snarls.len(),
+        decomposition.chains.len(),
+        start.elapsed()
+    );
+
+    // parents before children, keeping the order within each chain
+    let mut order: Vec<(usize, usize)> = (0..decomposition.snarls.len())
+        .map(|ix| (decomposition.depth(ix), ix))
+        .collect();
+    order.sort_unstable();
+
+    let stdout = std::io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let err = || CliError::io("Could not write output");
+    for (depth, ix) in order {
+        let snarl = decomposition.snarls[ix];
+        out.write_all(&graph.node_name(snarl.start))
+            .map_err(err())?;
+        out.write_all(b"\t").map_err(err())?;
+        out.write_all(&graph.node_name(snarl.end)).map_err(err())?;
+        writeln!(out, "\t{}\t{}", snarl.chain, depth).map_err(err())?;
+    }
+    out.flush().map_err(err())?;
+
+    Ok(())
+}
+
+fn run(opt: Opt) -> Result<(), CliError> {
+    run_named(opt, None)
+}
+
+/// The names of the nodes of each component.
+fn component_names<N: NodeNames + ?Sized>(
+    names: &N,
+    components: &[Vec<usize>],
+) -> Vec<Vec<Vec<u8>>> {
+    components
+        .iter()
+        .map(|c| c.iter().map(|&n| names.name(n).into_owned()).collect())
+        .collect()
+}
+
+/// Runs as `run`, and if `named` is given, sets it to the names of the
+/// nodes of the components that were written, for the summary of
+/// `--watch`. It's left as it is when the run doesn't have the names,
+/// with `--mmap-dir` and `--no-names`, or doesn't find the components,
+/// with a hit in the `--cache`.
+fn run_named(
+    mut opt: Opt,
+    named: Option<&mut Option<Vec<Vec<Vec<u8>>>>>,
+) -> Result<(), CliError> {
+    if opt.no_names && (opt.input.is_subgraph() || opt.threads > 1) {
+        return Err(CliError::InvalidArgs(
+            "--no-names can't be combined with --nodes-file, --region, or \
+             --threads"
+                .to_string(),
+        ));
+    }
+    if opt.no_names && opt.output.keep_order {
+        // the node indices are the names, so their order is lost
+        return Err(CliError::InvalidArgs(
+            "--keep-order can't be combined with --no-names".to_string(),
+        ));
+    }
+    if opt.relabel.is_some() && (opt.mmap_dir.is_some() || opt.threads > 1) {
+        return Err(CliError::InvalidArgs(
+            "--relabel can't be combined with --mmap-dir or --threads"
+                .to_string(),
+        ));
+    }
+    if opt.path_crossings.is_some()
+        && (opt.input.stdin
+            || opt.mmap_dir.is_some()
+            || opt.no_names
+            || opt.relabel.is_some())
+    {
+        return Err(CliError::InvalidArgs(
+            "--path-crossings needs an input file, and can't be combined \
+             with --mmap-dir, --no-names or --relabel"
+                .to_string(),
+        ));
+    }
+    if opt.boundary_links.is_some()
+        && (opt.input.stdin
+            || opt.mmap_dir.is_some()
+            || opt.no_names
+            || opt.relabel.is_some())
+    {
+        return Err(CliError::InvalidArgs(
+            "--boundary-links needs an input file, and can't be combined \
+             with --mmap-dir, --no-names or --relabel"
+                .to_string(),
+        ));
+    }
+    if opt.bed.is_some() != opt.bed_reference.is_some() {
+        return Err(CliError::InvalidArgs(
+            "--bed and --bed-reference must be given together".to_string(),
+        ));
+    }
+    if opt.bed.is_some()
+        && (opt.input.stdin
+            || opt.mmap_dir.is_some()
+            || opt.no_names
+            || opt.relabel.is_some())
+    {
+        return Err(CliError::InvalidArgs(
+            "--bed needs an input file, and can't be combined with \
+             --mmap-dir, --no-names or --relabel"
+                .to_string(),
+        ));
+    }
+    if opt.orphans_out.is_some()
+        && (opt.mmap_dir.is_some() || opt.no_names || opt.relabel.is_some())
+    {
+        return Err(CliError::InvalidArgs(
+            "--orphans-out can't be combined with --mmap-dir, --no-names or \
+             --relabel"
+                .to_string(),
+        ));
+    }
+    if opt.cross_check && !cfg!(feature = "cross-check") {
+        return Err(CliError::InvalidArgs(
+            "--cross-check needs the cross-check feature".to_string(),
+        ));
+    }
+    if opt.cross_check
+        && (opt.output.four_edge
+            || opt.mmap_dir.is_some()
+            || opt.no_names
+            || opt.relabel.is_some())
+    {
+        return Err(CliError::InvalidArgs(
+            "--cross-check can't be combined with --four-edge, --mmap-dir, \
+             --no-names or --relabel"
+                .to_string(),
+        ));
+    }
+    if opt.checkpoint.is_some()
+        && (opt.forest_out.is_some()
+            || opt.output.four_edge
+            || opt.mmap_dir.is_some()
+            || opt.no_names
+            || opt.relabel.is_some()
+            || opt.threads > 1)
+    {
+        return Err(CliError::InvalidArgs(
+            "--checkpoint can't be combined with --forest-out, --four-edge, \
+             --mmap-dir, --no-names, --relabel or --threads"
+                .to_string(),
+        ));
+    }
+    if opt.forest_out.is_some()
+        && (opt.mmap_dir.is_some()
+            || opt.no_names
+            || opt.relabel.is_some()
+            || opt.threads > 1)
+    {
+        return Err(CliError::InvalidArgs(
+            "--forest-out can't be combined with --mmap-dir, --no-names, \
+             --relabel or --threads"
+                .to_string(),
+        ));
+    }
+    if opt.edges_out.is_some() && opt.mmap_dir.is_some() {
+        return Err(CliError::InvalidArgs(
+            "--edges-out can't be combined with --mmap-dir".to_string(),
+        ));
+    }
+    if opt.save_graph.is_some() && (opt.mmap_dir.is_some() || opt.no_names) {
+        return Err(CliError::InvalidArgs(
+            "--save-graph can't be combined with --mmap-dir or --no-names"
+                .to_string(),
+        ));
+    }
+    if opt.node_table.is_some()
+        && (opt.checkpoint.is_some()
+            || opt.output.four_edge
+            || opt.mmap_dir.is_some()
+            || opt.no_names
+            || opt.relabel.is_some()
+            || opt.threads > 1)
+    {
+        return Err(CliError::InvalidArgs(
+            "--node-table can't be combined with --checkpoint, --four-edge, \
+             --mmap-dir, --no-names, --relabel or --threads"
+                .to_string(),
+        ));
+    }
+    if opt.rgfa
+        && (opt.input.stdin
+            || opt.mmap_dir.is_some()
+            || opt.no_names
+            || opt.relabel.is_some()
+            || opt.output.output_format != OutputFormat::Tsv)
+    {
+        return Err(CliError::InvalidArgs(
+            "--rgfa needs an input file and --output-format tsv, and can't \
+             be combined with --mmap-dir, --no-names or --relabel"
+                .to_string(),
+        ));
+    }
+    if let Some(name) = opt.output.output_format.database_name() {
+        if opt.out_file.is_none() && !opt.dry_run {
+            return Err(CliError::InvalidArgs(format!(
+                "--output-format {} needs an output file",
+                name
+            )));
+        }
+    }
+    if opt.max_memory.is_some() && opt.input.stdin {
+        return Err(CliError::InvalidArgs(
+            "--max-memory needs an input file".to_string(),
+        ));
+    }
+    if opt.watch {
+        return run_watch(opt);
+    }
+    if opt.cache.is_some() {
+        return run_cached(opt);
+    }
+    // removes the graph files of `--max-memory` once the run is done
+    let _graph_dir = match opt.max_memory {
+        Some(budget) => fit_memory_budget(&mut opt, budget)?,
+        None => None,
+    };
+    if let Some(dir) = &opt.mmap_dir {
+        return run_mmap(&opt, dir);
+    }
+    if opt.no_names {
+        return run_unnamed(&opt);
+    }
+
+    let mut timings = Timings::default();
+
+    let graph =
+        load_graph(&opt.input, Some(&mut timings).filter(|_| opt.time))?;
+    if graph.graph.is_empty() {
+        return Err(CliError::InvalidGraph(
+            "input contains no edges".to_string(),
+        ));
+    }
+    if let Some(out_path) = &opt.edges_out {
+        write_edges(out_path, &graph.graph, &graph.names)?;
+    }
+    if let Some(out_path) = &opt.save_graph {
+        graph.save(out_path).map_err(CliError::io(format!(
+            "Could not write graph to {:?}",
+            out_path
+        )))?;
+        info!("Saved the graph to {:?}", out_path);
+    }
+    if opt.dry_run {
+        let (nodes, edges) = (graph.graph.len(), num_edges(&graph));
+        report_dry_run(nodes, edges, graph.memory_usage(), &opt);
+        return Ok(());
+    }
+    if let Some(order) = opt.relabel {
+        let names_memory = graph.names.memory_usage();
+        let InternedGraph { graph, names } = graph;
+        return run_relabeled(
+            &opt,
+            order,
+            graph,
+            &names,
+            names_memory,
+            timings,
+            named,
+        );
+    }
+
+    let start = Instant::now();
+    let mut forest = opt
+        .forest_out
+        .as_ref()
+        .map(|_| SpanningForest::new(graph.graph.len()));
+    let mut node_info = opt.node_table.as_ref().map(|_| Vec::new());
+    let (components, state_memory) = match &opt.checkpoint {
+        Some(path) => run_checkpointed(
+            &graph.graph,
+            path,
+            Duration::from_secs(opt.checkpoint_interval),
+            opt.compact_state,
+        )?,
+        None if node_info.is_some() => {
+            let (components, memory) = match forest.as_mut() {
+                Some(forest) => run_algorithm_with_info(
+                    &graph.graph,
+                    opt.compact_state,
+                    forest,
+                    node_info.as_mut(),
+                ),
+                None => run_algorithm_with_info(
+                    &graph.graph,
+                    opt.compact_state,
+                    &mut (),
+                    node_info.as_mut(),
+                ),
+            };
+            (components, Some(memory))
+        }
+        None => find_components(
+            &graph,
+            opt.threads,
+            opt.compact_state,
+            opt.output.four_edge,
+            forest.as_mut(),
+        ),
+    };
+    timings.algorithm = start.elapsed();
+
+    if opt.cross_check {
+        cross_check(&graph.graph, &components)?;
+    }
+    if let (Some(out_path), Some(forest)) = (&opt.forest_out, &forest) {
+        write_forest(out_path, &graph, forest)?;
+    }
+
+    if let Some(out_path) = &opt.path_crossings {
+        let in_path = opt.input.in_file.as_deref().filter(|_| !opt.input.stdin);
+        write_path_crossings(in_path, out_path, &graph, &components)?;
+    }
+    if let Some(out_path) = &opt.boundary_links {
+        let in_path = opt.input.in_file.as_deref().filter(|_| !opt.input.stdin);
+        let min_overlap = opt.input.edges.min_overlap;
+        write_boundary_links(
+            in_path,
+            out_path,
+            &graph,
+            &components,
+            min_overlap,
+        )?;
+    }
+    if let Some(out_path) = &opt.orphans_out {
+        write_orphans(out_path, &graph, &components, opt.output.keep_order)?;
+    }
+    let components = filter_components(components, &opt.output);
+    if let (Some(out_path), Some(node_info)) = (&opt.node_table, &node_info) {
+        let ids = opt.output.component_ids();
+        write_node_table(out_path, &graph, node_info, &components, ids)?;
+    }
+    if let (Some(out_path), Some(reference)) = (&opt.bed, &opt.bed_reference) {
+        let in_path = opt.input.in_file.as_deref().filter(|_| !opt.input.stdin);
+        let ids = opt.output.component_ids();
+        write_bed(in_path, out_path, reference, &graph, &components, ids)?;
+    }
+
+    if let Some(named) = named {
+        *named = Some(component_names(&graph.names, &components));
+    }
+
+    let start = Instant::now();
+    if opt.rgfa {
+        write_rgfa_output(&opt, &graph, &components)?;
+    } else {
+        write_output(
+            opt.out_file.as_deref(),
+            &opt.output,
+            &graph.graph,
+            &graph.names,
+            &components,
+        )?;
+    }
+    timings.output = start.elapsed();
+
+    if opt.time {
+        timings.report();
+    }
+    report_memory_usage(graph.memory_usage(), state_memory);
+
+    Ok(())
+}
+
+/// Writes the output kept in the `--cache` directory for the input and
+/// options, after running with the output going into the directory if
+/// there is none yet.
+fn run_cached(mut opt: Opt) -> Result<(), CliError> {
+    if opt.input.stdin {
+        return Err(CliError::InvalidArgs(
+            "--cache needs an input file".to_string(),
+        ));
+    }
+    if opt.dry_run
+        || opt.bed.is_some()
+        || opt.boundary_links.is_some()
+        || opt.checkpoint.is_some()
+        || opt.edges_out.is_some()
+        || opt.forest_out.is_some()
+        || opt.node_table.is_some()
+        || opt.orphans_out.is_some()
+        || opt.path_crossings.is_some()
+        || opt.save_graph.is_some()
+    {
+        return Err(CliError::InvalidArgs(
+            "--cache can't be combined with --dry-run, --bed, \
+             --boundary-links, --checkpoint, --edges-out, --forest-out, \
+             --node-table, --orphans-out, --path-crossings or --save-graph"
+                .to_string(),
+        ));
+    }
+    let dir = opt.cache.take().unwrap();
+    let key = cache_key(&mut opt)?;
+    let digest = {
+        let mut hasher = Sha256::new();
+        hasher.write(key.as_bytes());
+        hasher.finish()
+    };
+    let cached = dir.join(format!("{}.out", digest));
+    let key_path = dir.join(format!("{}.key", digest));
+    let (out_file, compress) = (opt.out_file.take(), opt.output.compress);
+
+    // the key kept next to the output must match too, so that an entry
+    // is never used for a key other than its own
+    let hit = cached.exists()
+        && fs::read(&key_path).is_ok_and(|kept| kept == key.as_bytes());
+    if hit {
+        info!("Using the cached output {:?}", cached);
+    } else {
+        fs::create_dir_all(&dir).map_err(CliError::io(format!(
+            "Could not create directory {:?}",
+            dir
+        )))?;
+        // renamed once it's complete, so that an interrupted run isn't
+        // cached
+        let partial = dir.join(format!("{}.{}.tmp", digest, process::id()));
+        opt.out_file = Some(partial.clone());
+        opt.output.compress = None;
+        if let Err(err) = run(opt) {
+            let _ = fs::remove_file(&partial);
+            return Err(err);
+        }
+        let partial_key =
+            dir.join(format!("{}.{}.key.tmp", digest, process::id()));
+        fs::write(&partial_key, &key)
+            .and_then(|()| fs::rename(&partial_key, &key_path))
+            .map_err(CliError::io(format!("Could not write {:?}", key_path)))?;
+        fs::rename(&partial, &cached)
+            .map_err(CliError::io(format!("Could not write {:?}", cached)))?;
+    }
+
+    let mut file = File::open(&cached)
+        .map_err(CliError::io(format!("Could not open file {:?}", cached)))?;
+    write_to_output(out_file.as_deref(), compress, |out| {
+        std::io::copy(&mut file, out).map(|_| ())
+    })
+}
+
+/// The modification time and length of the file, which change when
+/// it's written to.
+fn file_version(path: &Path) -> Option<(std::time::SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+fn run_watch(mut opt: Opt) -> Result<(), CliError> {
+    if opt.input.stdin {
+        return Err(CliError::InvalidArgs(
+            "--watch needs an input file".to_string(),
+        ));
+    }
+    if opt.dry_run || opt.checkpoint.is_some() {
+        return Err(CliError::InvalidArgs(
+            "--watch can't be combined with --checkpoint or --dry-run"
+                .to_string(),
+        ));
+    }
+    opt.watch = false;
+    let in_path = opt.input.in_file.clone().unwrap();
+    let poll = Duration::from_millis(500);
+
+    let mut version = file_version(&in_path);
+    let mut previous: Option<Vec<Vec<Vec<u8>>>> = None;
+    loop {
+        let mut named = None;
+        match run_named(opt.clone(), Some(&mut named)) {
+            Ok(()) => match (named, &previous) {
+                (Some(components), Some(before)) => {
+                    log_changes(before, &components);
+                    previous = Some(components);
+                }
+                (Some(components), None) => previous = Some(components),
+                (None, _) => {
+                    debug!("The run has no component names to compare")
+                }
+            },
+            Err(err @ CliError::InvalidArgs(_)) => return Err(err),
+            Err(err) => log!(Level::Error, "{}", err),
+        }
+
+        info!("Watching {:?} for changes", in_path);
+        loop {
+            thread::sleep(poll);
+            let current = file_version(&in_path);
+            if current != version {
+                // wait for the writes to settle, so that a file that's
+                // being saved isn't read halfway
+                thread::sleep(poll);
+                version = file_version(&in_path);
+                if version == current {
+                    break;
+                }
+            }
+        }
+        info!("{:?} changed, running again", in_path);
+    }
+}
+
+/// Logs how many components were unchanged, split, merged, etc.
+/// between two runs of `--watch`, unless logging is `--quiet`.
+fn log_changes(before: &[Vec<Vec<u8>>], after: &[Vec<Vec<u8>>]) {
+    let mut counts: Vec<(ChangeKind, usize)> = Vec::new();
+    for change in diff::diff_components(before, after) {
+        match counts.iter_mut().find(|(kind, _)| *kind == change.kind) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((change.kind, 1)),
+        }
+    }
+    let summary = counts
+        .iter()
+        .map(|(kind, count)| format!("{} {}", count, kind.name()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    log!(Level::Warn, "Components since the last run: {}", summary);
+}
+
+/// The SHA-256 digest, which unlike `DefaultHasher` is the same for
+/// every build of the tool, so that `--cache` entries can be reused.
+struct Sha256 {
+    state: [u32; 8],
+    block: [u8; 64],
+    block_len: usize,
+    len: u64,
+}
+
+impl Sha256 {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1,
+        0x923f82a4, 0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3,
+        0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786,
+        0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+        0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147,
+        0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+        0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+        0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a,
+        0x5b9cca4f, 0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208,
+        0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+    ];
+
+    fn new() -> Self {
+        Sha256 {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f,
+                0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+            ],
+            block: [0; 64],
+            block_len: 0,
+            len: 0,
+        }
+    }
+
+    fn write(&mut self, mut bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+        while !bytes.is_empty() {
+            let take = bytes.len().min(64 - self.block_len);
+            self.block[self.block_len..self.block_len + take]
+                .copy_from_slice(&bytes[..take]);
+            self.block_len += take;
+            bytes = &bytes[take..];
+            if self.block_len == 64 {
+                self.compress();
+                self.block_len = 0;
+            }
+        }
+    }
+
+    fn compress(&mut self) {
+        let mut w = [0u32; 64];
+        for (i, word) in self.block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7)
+                ^ w[i - 15].rotate_right(18)
+                ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17)
+                ^ w[i - 2].rotate_right(19)
+                ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] =
+            self.state;
+        for (k, w) in Self::K.iter().zip(&w) {
+            let s1 =
+                e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(*k)
+                .wrapping_add(*w);
+            let s0 =
+                a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (state, x) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(x);
+        }
+    }
+
+    /// The digest as 64 hexadecimal digits.
+    fn finish(mut self) -> String {
+        let bits = self.len.wrapping_mul(8);
+        self.write(&[0x80]);
+        while self.block_len != 56 {
+            self.write(&[0]);
+        }
+        self.write(&bits.to_be_bytes());
+        self.state.iter().map(|x| format!("{:08x}", x)).collect()
+    }
+}
+
+/// The things the output of `run` depends on, which are written next
+/// to a `--cache` entry as its key: the version of the tool, the
+/// length and digest of the input and of `--nodes-file`, and the
+/// options, except for their paths and those that only change how the
+/// output is written or logged.
+fn cache_key(opt: &mut Opt) -> Result<String, CliError> {
+    let mut key = format!("version\t{}\n", env!("CARGO_PKG_VERSION"));
+    let paths = [opt.input.in_file.take(), opt.input.nodes_file.take()];
+    for (name, path) in ["input", "nodes_file"].iter().zip(&paths) {
+        let path = match path {
+            Some(path) => path,
+            None => continue,
+        };
+        let mut file = File::open(path)
+            .map_err(CliError::io(format!("Could not open file {:?}", path)))?;
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0; 1 << 16];
+        let mut len = 0u64;
+        loop {
+            let read = file.read(&mut buf).map_err(CliError::io(format!(
+                "Could not read file {:?}",
+                path
+            )))?;
+            if read == 0 {
+                break;
+            }
+            hasher.write(&buf[..read]);
+            len += read as u64;
+        }
+        key.push_str(&format!("{}\t{}\t{}\n", name, len, hasher.finish()));
+    }
+
+    let out_file = opt.out_file.take();
+    let compress = opt.output.compress.take();
+    let time = std::mem::take(&mut opt.time);
+    let log = std::mem::take(&mut opt.log);
+    // the options are on one line, as `{:?}` escapes any line breaks
+    key.push_str(&format!("options\t{:?}\n", opt));
+
+    let [in_file, nodes_file] = paths;
+    opt.input.in_file = in_file;
+    opt.input.nodes_file = nodes_file;
+    opt.out_file = out_file;
+    opt.output.compress = compress;
+    opt.time = time;
+    opt.log = log;
+    Ok(key)
+}
+
+/// Writes the `tsv` output with the stable coordinates of the rGFA
+/// input, for `--rgfa`.
+fn write_rgfa_output(
+    opt: &Opt,
+    graph: &InternedGraph,
+    components: &[Vec<usize>],
+) -> Result<(), CliError> {
+    let in_path = opt.input.in_file.as_deref().filter(|_| !opt.input.stdin);
+    let (in_handle, format) = open_input(in_path, None)?;
+    if format != InputFormat::Gfa {
+        return Err(CliError::InvalidArgs(
+            "--rgfa requires GFA input".to_string(),
+        ));
+    }
+    let coordinates = rgfa::rgfa_coordinates(in_handle)
+        .map_err(read_error("Could not read input"))?;
+    if coordinates.is_empty() {
+        log!(Level::Warn, "Input has no segments with rGFA tags");
+    }
+
+    write_to_output(opt.out_file.as_deref(), opt.output.compress, |mut out| {
+        let ids = opt.output.component_ids();
+        output::write_rgfa_tsv(
+            &mut out,
+            &graph.names,
+            components,
+            ids,
+            |node| coordinates.get(graph.names.get(node)),
+        )
+    })
+}
+
+/// Writes the components projected onto the reference, for `--bed`.
+fn write_bed(
+    in_path: Option<&Path>,
+    out_path: &Path,
+    reference: &str,
+    graph: &InternedGraph,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> Result<(), CliError> {
+    let (in_handle, format) = open_input(in_path, None)?;
+    if format != InputFormat::Gfa {
+        return Err(CliError::InvalidArgs(
+            "--bed requires GFA input".to_string(),
+        ));
+    }
+    let intervals =
+        region::reference_intervals(in_handle, reference.as_bytes()).map_err(
+            |err| match err.kind() {
+                std::io::ErrorKind::InvalidInput => {
+                    CliError::InvalidArgs(err.to_string())
+                }
+                _ => read_error("Could not read input")(err),
+            },
+        )?;
+    let node_components = paths::node_components(components, graph.names.len());
+    let component = |name: &[u8]| {
+        graph
+            .names
+            .find(name)
+            .and_then(|node| node_components[node])
+    };
+
+    let file = File::create(out_path).map_err(CliError::io(format!(
+        "Could not create file {:?}",
+        out_path
+    )))?;
+    let mut out = BufWriter::new(file);
+    let err = || CliError::io("Could not write BED file");
+    let result = match ids {
+        ComponentIds::Index => output::write_bed(
+            &mut out,
+            reference.as_bytes(),
+            &intervals,
+            component,
+        ),
+        ComponentIds::Stable => {
+            let ids: Vec<_> = components
+                .iter()
+                .map(|c| output::component_id(&graph.names, c))
+                .collect();
+            let id = |name: &[u8]| component(name).map(|ix| ids[ix].as_bstr());
+            output::write_bed(&mut out, reference.as_bytes(), &intervals, id)
+        }
+    };
+    result.map_err(err())?;
+    out.flush().map_err(err())?;
+    info!(
+        "Projected the components onto {} segments of {}",
+        intervals.len(),
+        reference
+    );
+    Ok(())
+}
+
+/// Writes the names of the nodes in components of their own, for
+/// `--orphans-out`.
+fn write_orphans(
+    out_path: &Path,
+    graph: &InternedGraph,
+    components: &[Vec<usize>],
+    keep_order: bool,
+) -> Result<(), CliError> {
+    let mut orphans: Vec<usize> = components
+        .iter()
+        .filter(|component| component.len() == 1)
+        .map(|component| component[0])
+        .collect();
+    if keep_order {
+        orphans.sort_unstable();
+    }
+
+    let file = File::create(out_path).map_err(CliError::io(format!(
+        "Could not create file {:?}",
+        out_path
+    )))?;
+    let mut out = BufWriter::new(file);
+    let err = || CliError::io("Could not write orphans");
+    for &node in orphans.iter() {
+        out.write_all(graph.names.get(node)).map_err(err())?;
+        out.write_all(b"\n").map_err(err())?;
+    }
+    out.flush().map_err(err())?;
+    info!("Wrote {} orphans to {:?}", orphans.len(), out_path);
+    Ok(())
+}
+
+/// Writes the DFS spanning forest as an edge list, for `--forest-out`.
+fn write_forest(
+    out_path: &Path,
+    graph: &InternedGraph,
+    forest: &SpanningForest,
+) -> Result<(), CliError> {
+    let file = File::create(out_path).map_err(CliError::io(format!(
+        "Could not create file {:?}",
+        out_path
+    )))?;
+    let mut out = BufWriter::new(file);
+    let err = || CliError::io("Could not write spanning forest");
+    let mut num_edges = 0;
+    for (parent, child) in forest.tree_edges() {
+        out.write_all(graph.names.get(parent)).map_err(err())?;
+        out.write_all(b"\t").map_err(err())?;
+        out.write_all(graph.names.get(child)).map_err(err())?;
+        out.write_all(b"\n").map_err(err())?;
+        num_edges += 1;
+    }
+    out.flush().map_err(err())?;
+    info!("Wrote {} tree edges to {:?}", num_edges, out_path);
+    Ok(())
+}
+
+/// Writes the edges of the graph for `--edges-out`. The other end of
+/// each edge is listed with both nodes, so an edge is only written from
+/// the node with the smaller index, and a self-loop, which is listed
+/// twice with the same node, from every other listing.
+fn write_node_table(
+    out_path: &Path,
+    graph: &InternedGraph,
+    node_info: &[Option<NodeInfo>],
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> Result<(), CliError> {
+    let file = File::create(out_path).map_err(CliError::io(format!(
+        "Could not create file {:?}",
+        out_path
+    )))?;
+    let mut out = BufWriter::new(file);
+    output::write_node_table(
+        &mut out,
+        &graph.graph,
+        &graph.names,
+        |node| node_info[node],
+        components,
+        ids,
+    )
+    .and_then(|_| out.flush())
+    .map_err(CliError::io("Could not write node table"))?;
+    info!("Wrote {} nodes to {:?}", node_info.len(), out_path);
+    Ok(())
+}
+
+fn write_edges<G: Adjacency + ?Sized, N: NodeNames + ?Sized>(
+    out_path: &Path,
+    graph: &G,
+    names: &N,
+) -> Result<(), CliError> {
+    let file = File::create(out_path).map_err(CliError::io(format!(
+        "Could not create file {:?}",
+        out_path
+    )))?;
+    let mut out = BufWriter::new(file);
+    let err = || CliError::io("Could not write edges");
+    let mut num_edges = 0;
+    for from in graph.nodes() {
+        let mut self_loop = false;
+        for &to in graph.neighbors(from) {
+            if to == from {
+                self_loop = !self_loop;
+                if !self_loop {
+                    continue;
+                }
+            } else if to < from {
+                continue;
+            }
+            out.write_all(&names.name(from)).map_err(err())?;
+            out.write_all(b"\t").map_err(err())?;
+            out.write_all(&names.name(to)).map_err(err())?;
+            writeln!(out, "\t{}\t{}", from, to).map_err(err())?;
+            num_edges += 1;
+        }
+    }
+    out.flush().map_err(err())?;
+    info!("Wrote {} edges to {:?}", num_edges, out_path);
+    Ok(())
+}
+
+/// Writes the `--boundary-links`, reading the links from the input
+/// file, which must be GFA. Each link line is written back from its
+/// fields, so only trailing whitespace is lost.
+fn write_boundary_links(
+    in_path: Option<&Path>,
+    out_path: &Path,
+    graph: &InternedGraph,
+    components: &[Vec<usize>],
+    min_overlap: Option<usize>,
+) -> Result<(), CliError> {
+    let (in_handle, format) = open_input(in_path, None)?;
+    if format != InputFormat::Gfa {
+        return Err(CliError::InvalidArgs(
+            "--boundary-links requires GFA input".to_string(),
+        ));
+    }
+    let node_components = paths::node_components(components, graph.names.len());
+    let component = |name: &[u8]| {
+        graph
+            .names
+            .find(name)
+            .and_then(|node| node_components[node])
+    };
+
+    let file = File::create(out_path).map_err(CliError::io(format!(
+        "Could not create file {:?}",
+        out_path
+    )))?;
+    let mut out = BufWriter::new(file);
+    let orientation = |reverse: bool| if reverse { b"-" } else { b"+" };
+
+    let mut num_links = 0;
+    graph::for_each_gfa_link(
+        in_handle,
+        |_| Ok(()),
+        |link| {
+            let (from, to) =
+                (component(link.from_segment), component(link.to_segment));
+            // links between segments that aren't in the graph, e.g. outside
+            // of `--nodes-file`, aren't edges either
+            if from.is_none() || to.is_none() || from == to {
+                return Ok(());
+            }
+            if let Some(min_overlap) = min_overlap {
+                let length = graph::overlap_length(link.overlap)?;
+                if length.is_some_and(|length| length < min_overlap) {
+                    return Ok(());
+                }
+            }
+            out.write_all(b"L\t")?;
+            out.write_all(link.from_segment)?;
+            out.write_all(b"\t")?;
+            out.write_all(orientation(link.from_reverse))?;
+            out.write_all(b"\t")?;
+            out.write_all(link.to_segment)?;
+            out.write_all(b"\t")?;
+            out.write_all(orientation(link.to_reverse))?;
+            out.write_all(b"\t")?;
+            out.write_all(link.overlap)?;
+            if !link.tags.is_empty() {
+                out.write_all(b"\t")?;
+                out.write_all(link.tags)?;
+            }
+            out.write_all(b"\n")?;
+            num_links += 1;
+            Ok(())
+        },
+    )
+    .and_then(|()| out.flush())
+    .map_err(read_error("Could not read input or write boundary links"))?;
+    info!("Wrote {} boundary links to {:?}", num_links, out_path);
+
+    Ok(())
+}
+
+/// Writes the `--path-crossings` report, reading the paths from the
+/// input file, which must be GFA.
+fn write_path_crossings(
+    in_path: Option<&Path>,
+    out_path: &Path,
+    graph: &InternedGraph,
+    components: &[Vec<usize>],
+) -> Result<(), CliError> {
+    let (in_handle, format) = open_input(in_path, None)?;
+    if format != InputFormat::Gfa {
+        return Err(CliError::InvalidArgs(
+            "--path-crossings requires GFA input".to_string(),
+        ));
+    }
+    let node_components = paths::node_components(components, graph.names.len());
+    let component = |name: &[u8]| {
+        graph
+            .names
+            .find(name)
+            .and_then(|node| node_components[node])
+    };
+
+    let file = File::create(out_path).map_err(CliError::io(format!(
+        "Could not create file {:?}",
+        out_path
+    )))?;
+    let mut out = BufWriter::new(file);
+    let err = || CliError::io("Could not write path crossings");
+
+    let mut num_paths = 0;
+    for path in paths::gfa_paths(in_handle) {
+        let path = path.map_err(read_error("Could not read input"))?;
+        let crossings = path.crossings(component);
+        num_paths += 1;
+
+        out.write_all(&path.name).map_err(err())?;
+        write!(out, "\t{}\t{}\t", path.steps.len(), crossings.len())
+            .map_err(err())?;
+        if crossings.is_empty() {
+            out.write_all(b".").map_err(err())?;
+        }
+        for (i, crossing) in crossings.iter().enumerate() {
+            let step = &path.steps[crossing.step];
+            let sep = if i == 0 { "" } else { "," };
+            write!(out, "{}{}:", sep, crossing.step).map_err(err())?;
+            out.write_all(&step.segment).map_err(err())?;
+            out.write_all(if step.reverse { b"-" } else { b"+" })
+                .map_err(err())?;
+        }
+        out.write_all(b"\n").map_err(err())?;
+    }
+    out.flush().map_err(err())?;
+    info!(
+        "Wrote path crossings of {} paths to {:?}",
+        num_paths, out_path
+    );
+
+    Ok(())
+}
+
+/// The number of malformed GFA lines that are logged one by one,
+/// before only their total is.
+const MALFORMED_LINES_SHOWN: usize = 10;
+
+/// Handles the GFA link lines that can't be parsed: with `--strict`,
+/// the first one is an error, and otherwise they're skipped with a
+/// warning, counting them in `skipped`.
+fn malformed_lines(
+    strict: bool,
+    skipped: Rc<Cell<usize>>,
+) -> impl FnMut(MalformedLine) -> std::io::Result<()> {
+    move |line| {
+        if strict {
+            return Err(line.into_error());
+        }
+        skipped.set(skipped.get() + 1);
+        if skipped.get() <= MALFORMED_LINES_SHOWN {
+            log!(Level::Warn, "Skipping malformed GFA {}", line);
+        }
+        Ok(())
+    }
+}
+
+fn report_malformed(skipped: usize) {
+    if skipped > 0 {
+        log!(
+            Level::Warn,
+            "Skipped {} malformed GFA lines; use --strict to make them \
+             an error",
+            skipped
+        );
+    }
+}
+
+type NamedEdges<'a> =
+    Box<dyn Iterator<Item = std::io::Result<(Vec<u8>, Vec<u8>)>> + 'a>;
+
+/// Checks that the edge options apply to the given input format.
+fn check_edge_opt(
+    format: InputFormat,
+    edge_opt: EdgeOpt,
+) -> Result<(), CliError> {
+    let invalid = |option: &str, format: &str| {
+        Err(CliError::InvalidArgs(format!(
+            "{} only applies to {} input",
+            option, format
+        )))
+    };
+
+    if format != InputFormat::Paf {
+        if edge_opt.min_identity.is_some() {
+            return invalid("--min-identity", "PAF");
+        }
+        if edge_opt.min_mapq.is_some() {
+            return invalid("--min-mapq", "PAF");
+        }
+        if edge_opt.max_overhang.is_some() {
+            return invalid("--max-overhang", "PAF");
+        }
+    }
+    if edge_opt.directed.is_some()
+        && !matches!(
+            format,
+            InputFormat::EdgeList
+                | InputFormat::Csv
+                | InputFormat::Parquet
+                | InputFormat::AdjList
+        )
+    {
+        return invalid("--directed", "edge list or adjacency list");
+    }
+    if edge_opt.has_table()
+        && !matches!(format, InputFormat::EdgeList | InputFormat::Csv)
+    {
+        return Err(CliError::InvalidArgs(
+            "--delimiter, --source-column, --target-column, and --header \
+             only apply to edge list input"
+                .to_string(),
+        ));
+    }
+    if edge_opt.min_overlap.is_some()
+        && !matches!(format, InputFormat::Gfa | InputFormat::Paf)
+    {
+        return invalid("--min-overlap", "GFA or PAF");
+    }
+
+    if edge_opt.strict && format != InputFormat::Gfa {
+        return invalid("--strict", "GFA");
+    }
+    Ok(())
+}
+
+/// The edges of the input in the given format, filtered according to
+/// the edge options.
+fn input_edges<'a>(
+    in_handle: &'a mut dyn BufRead,
+    format: InputFormat,
+    edge_opt: EdgeOpt,
+) -> Result<NamedEdges<'a>, CliError> {
+    check_edge_opt(format, edge_opt)?;
+
+    Ok(match format {
+        InputFormat::Gfa => {
+            let skipped = Rc::new(Cell::new(0));
+            let on_malformed =
+                malformed_lines(edge_opt.strict, skipped.clone());
+            let links: NamedEdges = match edge_opt.min_overlap {
+                None => {
+                    Box::new(graph::gfa_links_with(in_handle, on_malformed))
+                }
+                Some(min_overlap) => {
+                    Box::new(graph::gfa_links_min_overlap_with(
+                        in_handle,
+                        min_overlap,
+                        on_malformed,
+                    ))
+                }
+            };
+            // the summary is logged once all the links have been read
+            Box::new(links.chain(std::iter::from_fn(move || {
+                report_malformed(skipped.replace(0));
+                None
+            })))
+        }
+        InputFormat::EdgeList | InputFormat::Csv => {
+            let edges: NamedEdges = match edge_opt.edge_table(format) {
+                None => Box::new(graph::edge_list_edges(in_handle)),
+                Some(table) => {
+                    Box::new(graph::edge_table_edges(in_handle, table))
+                }
+            };
+            match edge_opt.directed {
+                None => edges,
+                Some(policy) => {
+                    Box::new(graph::symmetrize_edges(edges, policy))
+                }
+            }
+        }
+        InputFormat::Parquet => {
+            let table = EdgeTable {
+                delimiter: Some(b'\t'),
+                ..EdgeTable::default()
+            };
+            let edges = graph::edge_table_edges(in_handle, table);
+            match edge_opt.directed {
+                None => Box::new(edges),
+                Some(policy) => {
+                    Box::new(graph::symmetrize_edges(edges, policy))
+                }
+            }
+        }
+        InputFormat::Vg => Box::new(vg::vg_edges(in_handle)),
+        InputFormat::Paf => {
+            let default = PafFilter::default();
+            let filter = PafFilter {
+                min_overlap: edge_opt
+                    .min_overlap
+                    .unwrap_or(default.min_overlap),
+                min_identity: edge_opt
+                    .min_identity
+                    .unwrap_or(default.min_identity),
+                min_mapq: edge_opt.min_mapq.unwrap_or(default.min_mapq),
+                max_overhang: edge_opt
+                    .max_overhang
+                    .unwrap_or(default.max_overhang),
+            };
+            Box::new(paf::paf_edges(in_handle, filter))
+        }
+        InputFormat::Pajek => Box::new(pajek::pajek_edges(in_handle)),
+        InputFormat::Tgf => Box::new(tgf::tgf_edges(in_handle)),
+        InputFormat::Lgf => Box::new(lgf::lgf_edges(in_handle)),
+        // adjacency lists usually list each edge from both of its
+        // nodes, which would otherwise be two parallel edges
+        InputFormat::AdjList => Box::new(graph::symmetrize_edges(
+            graph::adjacency_list_edges(in_handle),
+            edge_opt.directed.unwrap_or(Symmetrize::Reciprocated),
+        )),
+        InputFormat::Binary => {
+            let graph = InternedGraph::read_from(in_handle)
+                .map_err(read_error("Could not read input"))?;
+            Box::new(saved_edges(graph))
+        }
+    })
+}
+
+/// The edges of a graph read from a `--save-graph` file, each once,
+/// with the nodes in index order, for the inputs that are read as
+/// edges.
+fn saved_edges(
+    graph: InternedGraph,
+) -> impl Iterator<Item = std::io::Result<(Vec<u8>, Vec<u8>)>> {
+    let InternedGraph { graph, names } = graph;
+    (0..names.len()).flat_map(move |from| {
+        let mut self_loop = false;
+        let mut edges = Vec::new();
+        for &to in graph.get(&from).map_or(&[][..], |adj| adj.as_slice()) {
+            if to == from {
+                self_loop = !self_loop;
+                if !self_loop {
+                    continue;
+                }
+            } else if to < from {
+                continue;
+            }
+            edges.push(Ok((names.get(from).to_vec(), names.get(to).to_vec())));
+        }
+        edges
+    })
+}
+
+type OnMalformed = Box<dyn FnMut(MalformedLine) -> std::io::Result<()>>;
+
+/// Builds the graph of a GFA input with one of the
+/// `from_gfa_reader_with` constructors, which parse the links in place
+/// instead of reading them from `input_edges`, so that only the names
+/// that haven't been seen before are copied.
+fn build_from_gfa<'a, G, F>(
+    in_handle: &'a mut dyn BufRead,
+    edge_opt: EdgeOpt,
+    build: F,
+) -> Result<G, CliError>
+where
+    F: FnOnce(
+        &'a mut dyn BufRead,
+        Option<usize>,
+        OnMalformed,
+    ) -> std::io::Result<G>,
+{
+    check_edge_opt(InputFormat::Gfa, edge_opt)?;
+    let skipped = Rc::new(Cell::new(0));
+    let on_malformed = malformed_lines(edge_opt.strict, skipped.clone());
+    let graph = build(in_handle, edge_opt.min_overlap, Box::new(on_malformed))
+        .map_err(read_error("Could not read input"))?;
+    report_malformed(skipped.get());
+    Ok(graph)
+}
+
+/// Like `CliError::io`, but for errors from parsing the graph, which
+/// are parse errors if the data is invalid, e.g. a name that isn't an
+/// integer with `--no-names`.
+fn read_error(
+    context: impl Into<String>,
+) -> impl FnOnce(std::io::Error) -> CliError {
+    let context = context.into();
+    move |err| {
+        if err.kind() == std::io::ErrorKind::InvalidData {
+            CliError::Parse(err.to_string())
+        } else {
+            CliError::Io(context, err)
+        }
+    }
+}
+
+/// Like `run`, but with the node names used as the node indices, for
+/// `--no-names`. Parsing and graph construction are done in one pass,
+/// so they're timed together.
+fn run_unnamed(opt: &Opt) -> Result<(), CliError> {
+    let mut timings = Timings::default();
+
+    let in_path = opt.input.in_file.as_deref().filter(|_| !opt.input.stdin);
+    let (mut in_handle, format) = open_input(in_path, opt.input.format)?;
+
+    let start = Instant::now();
+    let graph = if format == InputFormat::Gfa {
+        build_from_gfa(
+            &mut in_handle,
+            opt.input.edges,
+            IndexedGraph::from_gfa_reader_with,
+        )?
+    } else {
+        let edges = input_edges(&mut in_handle, format, opt.input.edges)?;
+        IndexedGraph::from_numeric_edges(edges)
+            .map_err(read_error("Could not read input"))?
+    };
+    timings.build = start.elapsed();
+    info!(
+        "Built graph with {} node indices and {} edges in {:.2?}",
+        graph.num_nodes(),
+        graph.num_edges(),
+        timings.build
+    );
+    if graph.num_edges() == 0 {
+        return Err(CliError::InvalidGraph(
+            "input contains no edges".to_string(),
+        ));
+    }
+    if let Some(out_path) = &opt.edges_out {
+        write_edges(out_path, &graph, &IndexNames)?;
+    }
+    if opt.dry_run {
+        let (nodes, edges) = (graph.num_nodes(), graph.num_edges());
+        report_dry_run(nodes, edges, graph.memory_usage(), opt);
+        return Ok(());
+    }
+    if let Some(order) = opt.relabel {
+        return run_relabeled(opt, order, graph, &IndexNames, 0, timings, None);
+    }
+
+    let start = Instant::now();
+    let (components, state_memory) =
+        find_indexed_components(&graph, &opt.output, opt.compact_state);
+    timings.algorithm = start.elapsed();
+
+    let start = Instant::now();
+    write_output(
+        opt.out_file.as_deref(),
+        &opt.output,
+        &graph,
+        &IndexNames,
+        &components,
+    )?;
+    timings.output = start.elapsed();
+
+    if opt.time {
+        timings.report();
+    }
+    report_memory_usage(graph.memory_usage(), Some(state_memory));
+
+    Ok(())
+}
+
+/// The rest of `run` or `run_unnamed` with `--relabel`: relabels the
+/// graph, which is then dropped, and runs the algorithm on the
+/// relabeled graph, whose nodes are mapped back to the names for the
+/// output.
+fn run_relabeled<G: Adjacency, N: NodeNames + ?Sized>(
+    opt: &Opt,
+    order: RelabelOrder,
+    graph: G,
+    names: &N,
+    names_memory: usize,
+    mut timings: Timings,
+    named: Option<&mut Option<Vec<Vec<Vec<u8>>>>>,
+) -> Result<(), CliError> {
+    let start = Instant::now();
+    let relabeling = match order {
+        RelabelOrder::Dfs => Relabeling::dfs(&graph),
+        RelabelOrder::Bfs => Relabeling::bfs(&graph),
+    };
+    let relabeled = relabeling.apply(&graph);
+    drop(graph);
+    timings.build += start.elapsed();
+    info!(
+        "Relabeled the nodes in {:?} order in {:.2?}",
+        order,
+        start.elapsed()
+    );
+
+    let start = Instant::now();
+    let (mut components, state_memory) =
+        find_indexed_components(&relabeled, &opt.output, opt.compact_state);
+    if opt.output.keep_order {
+        three_edge_connected::sort_components_by_key(&mut components, |n| {
+            relabeling.old_index(n)
+        });
+    }
+    timings.algorithm = start.elapsed();
+    if let Some(named) = named {
+        *named = Some(component_names(&relabeling.names(names), &components));
+    }
+
+    let start = Instant::now();
+    write_output(
+        opt.out_file.as_deref(),
+        &opt.output,
+        &relabeled,
+        &relabeling.names(names),
+        &components,
+    )?;
+    timings.output = start.elapsed();
+
+    if opt.time {
+        timings.report();
+    }
+    report_memory_usage(
+        relabeled.memory_usage() + relabeling.memory_usage() + names_memory,
+        Some(state_memory),
+    );
+
+    Ok(())
+}
+
+/// Runs the algorithm on a single thread on a graph whose indices
+/// are the node names, or on a relabeled graph, and applies the
+/// output filters. Indices that aren't used as names are nodes
+/// without any edges, so their single-node components are dropped
+/// even if `--min-size` is 0 or 1; other graphs have no such nodes.
+fn find_indexed_components<G: Adjacency + ?Sized>(
+    graph: &G,
+    output: &OutputOpt,
+    compact_state: bool,
+) -> (Vec<Vec<usize>>, usize) {
+    let start = Instant::now();
+    let (mut components, state_memory) =
+        run_algorithm(graph, compact_state, &mut ());
+    components.retain(|c| c.len() > 1 || !graph.neighbors(c[0]).is_empty());
+    info!(
+        "Found {} components ({} with more than one node) in {:.2?}",
+        components.len(),
+        components.iter().filter(|c| c.len() > 1).count(),
+        start.elapsed()
+    );
+    if output.four_edge {
+        components = refine_four_edge(graph, components);
+    }
+    let components =
+        three_edge_connected::filter_min_size(components, output.min_size);
+    (components, state_memory)
+}
+
+/// Like `run`, but with the graph stored in memory-mapped files in
+/// `dir`. Parsing and graph construction are done in one pass, so
+/// they're timed together.
+fn run_mmap(opt: &Opt, dir: &Path) -> Result<(), CliError> {
+    if opt.input.is_subgraph() || opt.threads > 1 {
+        return Err(CliError::InvalidArgs(
+            "--mmap-dir can't be combined with --nodes-file, --region, or \
+             --threads"
+                .to_string(),
+        ));
+    }
+
+    let mut timings = Timings::default();
+
+    let in_path = opt.input.in_file.as_deref().filter(|_| !opt.input.stdin);
+    let (mut in_handle, format) = open_input(in_path, opt.input.format)?;
+
+    let start = Instant::now();
+    let edges = input_edges(&mut in_handle, format, opt.input.edges)?;
+    let build_err =
+        || read_error(format!("Could not build graph files in {:?}", dir));
+    let (mmap_graph, names) = if opt.no_names {
+        (
+            MmapGraph::build_numeric(edges, dir).map_err(build_err())?,
+            None,
+        )
+    } else {
+        let (graph, names) =
+            MmapGraph::build_interned(edges, dir).map_err(build_err())?;
+        (graph, Some(names))
+    };
+    timings.build = start.elapsed();
+    info!(
+        "Built graph with {} nodes and {} edges in {:?} in {:.2?}",
+        mmap_graph.num_nodes(),
+        mmap_graph.num_edges(),
+        dir,
+        timings.build
+    );
+    if mmap_graph.num_edges() == 0 {
+        return Err(CliError::InvalidGraph(
+            "input contains no edges".to_string(),
+        ));
+    }
+    // the adjacency lists are in the memory-mapped files, so this is
+    // only the node names
+    let names_memory = names.as_ref().map_or(0, |names| names.memory_usage());
+    if opt.dry_run {
+        let (nodes, edges) = (mmap_graph.num_nodes(), mmap_graph.num_edges());
+        report_dry_run(nodes, edges, names_memory, opt);
+        return Ok(());
+    }
+
+    let start = Instant::now();
+    let (components, state_memory) = if names.is_some() {
+        let (components, state_memory) =
+            run_algorithm(&mmap_graph, opt.compact_state, &mut ());
+        info!(
+            "Found {} components ({} with more than one node) in {:.2?}",
+            components.len(),
+            components.iter().filter(|c| c.len() > 1).count(),
+            start.elapsed()
+        );
+        (filter_components(components, &opt.output), state_memory)
+    } else {
+        find_indexed_components(&mmap_graph, &opt.output, opt.compact_state)
+    };
+    timings.algorithm = start.elapsed();
+
+    let start = Instant::now();
+    let out_path = opt.out_file.as_deref();
+    match &names {
+        Some(names) => write_output(
+            out_path,
+            &opt.output,
+            &mmap_graph,
+            names,
+            &components,
+        )?,
+        None => write_output(
+            out_path,
+            &opt.output,
+            &mmap_graph,
+            &IndexNames,
+            &components,
+        )?,
+    }
+    timings.output = start.elapsed();
+
+    if opt.time {
+        timings.report();
+    }
+    report_memory_usage(names_memory, Some(state_memory));
+
+    Ok(())
+}
+
+fn generate(
+    topology: Topology,
+    seed: u64,
+    format: InputFormat,
+    out_path: Option<&Path>,
+) -> Result<(), CliError> {
+    let edges = topology.edges(seed);
+    let num_nodes = edges.iter().map(|&(a, b)| a.max(b) + 1).max();
+    info!("Generated {:?} with {} edges", topology, edges.len());
+
+    let mut out_handle: Box<dyn Write> = match out_path {
+        None => Box::new(BufWriter::new(std::io::stdout())),
+        Some(path) => Box::new(BufWriter::new(File::create(path).map_err(
+            CliError::io(format!("Could not create file {:?}", path)),
+        )?)),
+    };
+
+    let mut write = || -> std::io::Result<()> {
+        match format {
+            InputFormat::Gfa => {
+                writeln!(out_handle, "H\tVN:Z:1.0")?;
+                for node in 0..num_nodes.unwrap_or(0) {
+                    writeln!(out_handle, "S\t{}\t*", node)?;
+                }
+                for (from, to) in edges.iter() {
+                    writeln!(out_handle, "L\t{}\t+\t{}\t+\t0M", from, to)?;
+                }
+            }
+            InputFormat::EdgeList => {
+                for (from, to) in edges.iter() {
+                    writeln!(out_handle, "{}\t{}", from, to)?;
+                }
+            }
+            InputFormat::Vg
+            | InputFormat::Paf
+            | InputFormat::Pajek
+            | InputFormat::Tgf
+            | InputFormat::Lgf
+            | InputFormat::AdjList
+            | InputFormat::Binary
+            | InputFormat::Csv
+            | InputFormat::Parquet => {
+                return Err(std::io::Error::other(format!(
+                    "can't write {:?} graphs",
+                    format
+                )))
+            }
+        }
+        out_handle.flush()
+    };
+
+    write().map_err(CliError::io("Could not write output"))
+}
+
+/// Matches a file name against a pattern where `*` matches any
+/// sequence of characters and `?` matches any single character.
+fn matches_pattern(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            matches_pattern(rest, name)
+                || (!name.is_empty() && matches_pattern(pattern, &name[1..]))
+        }
+        (Some((b'?', rest)), Some((_, name_rest))) => {
+            matches_pattern(rest, name_rest)
+        }
+        (Some((p, rest)), Some((c, name_rest))) => {
+            p == c && matches_pattern(rest, name_rest)
+        }
+        _ => false,
+    }
+}
+
+/// Expands the inputs that contain wildcards in their file name and
+/// don't exist as given, in sorted order.
+fn expand_inputs(inputs: &[PathBuf]) -> Result<Vec<PathBuf>, CliError> {
+    let mut paths = Vec::new();
+
+    for input in inputs {
+        let file_name = input.file_name().and_then(|n| n.to_str());
+        let is_pattern = !input.exists()
+            && file_name.is_some_and(|n| n.contains(&['*', '?'][..]));
+
+        if !is_pattern {
+            paths.push(input.clone());
+            continue;
+        }
+
+        let pattern = file_name.unwrap().as_bytes();
+        let dir = match input.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        let entries = std::fs::read_dir(dir).map_err(CliError::io(format!(
+            "Could not read directory {:?}",
+            dir
+        )))?;
+
+        let mut matched = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(CliError::io(format!(
+                "Could not read directory {:?}",
+                dir
+            )))?;
+            let name = entry.file_name();
+            if let Some(name) = name.to_str() {
+                if matches_pattern(pattern, name.as_bytes()) {
+                    matched.push(input.with_file_name(name));
+                }
+            }
+        }
+
+        if matched.is_empty() {
+            log!(Level::Warn, "No files matched {:?}", input);
+        }
+        matched.sort();
+        paths.extend(matched);
+    }
+
+    Ok(paths)
+}
+
+/// The components of a graph loaded by `serve`, with the component of
+/// each node.
+struct Queries {
+    graph: InternedGraph,
+    components: Vec<Vec<usize>>,
+    component_of: Vec<usize>,
+}
+
+/// A parsed JSON value, as far as `serve` needs one: objects keep
+/// their keys in order, and numbers are only read as `f64`.
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(Vec<u8>),
+    Array(Vec<Json>),
+    Object(Vec<(Vec<u8>, Json)>),
+}
+
+impl Json {
+    fn parse(bytes: &[u8]) -> Result<Json, String> {
+        let mut parser = JsonParser {
+            bytes,
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < bytes.len() {
+            return Err(format!("unexpected data at byte {}", parser.pos));
+        }
+        Ok(value)
+    }
+
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(k, _)| k == key.as_bytes())
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&[u8]> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// How deep arrays and objects can be nested in a query, so that a
+/// client can't overflow the stack of the server.
+const MAX_JSON_DEPTH: usize = 64;
+
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// The number of arrays and objects the parser is inside of.
+    depth: usize,
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn error<T>(&self, expected: &str) -> Result<T, String> {
+        Err(format!("expected {} at byte {}", expected, self.pos))
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() != Some(byte) {
+            return self.error(&format!("'{}'", byte as char));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        if !self.bytes[self.pos..].starts_with(word.as_bytes()) {
+            return self.error("a value");
+        }
+        self.pos += word.len();
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        if let Some(b'{' | b'[') = self.peek() {
+            if self.depth == MAX_JSON_DEPTH {
+                return Err(format!(
+                    "values nested deeper than {} at byte {}",
+                    MAX_JSON_DEPTH, self.pos
+                ));
+            }
+            self.depth += 1;
+            let value = self.nested();
+            self.depth -= 1;
+            return value;
+        }
+        match self.peek() {
+            Some(b'"') => self.string().map(Json::String),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b'-' | b'0'..=b'9') => {
+                let start = self.pos;
+                while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') =
+                    self.peek()
+                {
+                    self.pos += 1;
+                }
+                std::str::from_utf8(&self.bytes[start..self.pos])
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .map(Json::Number)
+                    .ok_or_else(|| format!("invalid number at byte {}", start))
+            }
+            _ => self.error("a value"),
+        }
+    }
+
+    /// Parses the array or object at the current position.
+    fn nested(&mut self) -> Result<Json, String> {
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    fields.push((key, self.value()?));
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Json::Object(fields));
+                        }
+                        _ => return self.error("',' or '}'"),
+                    }
+                }
+            }
+            _ => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Json::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Json::Array(values));
+                        }
+                        _ => return self.error("',' or ']'"),
+                    }
+                }
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<Vec<u8>, String> {
+        if self.peek() != Some(b'"') {
+            return self.error("a string");
+        }
+        self.pos += 1;
+        let mut string = Vec::new();
+        loop {
+            let byte = match self.peek() {
+                Some(byte) => byte,
+                None => return self.error("'\"'"),
+            };
+            self.pos += 1;
+            match byte {
+                b'"' => return Ok(string),
+                b'\\' => {
+                    let escaped = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let hex = self
+                                .bytes
+                                .get(self.pos + 1..self.pos + 5)
+                                .and_then(|hex| std::str::from_utf8(hex).ok())
+                                .and_then(|hex| {
+                                    u32::from_str_radix(hex, 16).ok()
+                                });
+                            self.pos += 4;
+                            // surrogate pairs are replaced, which never
+                            // matches a segment name read from a file
+                            hex.map(|c| {
+                                char::from_u32(c)
+                                    .unwrap_or(char::REPLACEMENT_CHARACTER)
+                            })
+                            .ok_or_else(|| {
+                                format!("invalid escape at byte {}", self.pos)
+                            })?
+                        }
+                        _ => return self.error("an escape"),
+                    };
+                    self.pos += 1;
+                    let mut buf = [0; 4];
+                    string.extend_from_slice(
+                        escaped.encode_utf8(&mut buf).as_bytes(),
+                    );
+                }
+                byte => string.push(byte),
+            }
+        }
+    }
+}
+
+impl Queries {
+    fn new(graph: InternedGraph) -> Self {
+        let components = algorithm::find_components(&graph.graph);
+        let mut component_of = vec![0; graph.graph.len()];
+        for (ix, component) in components.iter().enumerate() {
+            for &node in component {
+                component_of[node] = ix;
+            }
+        }
+        Queries {
+            graph,
+            components,
+            component_of,
+        }
+    }
+
+    fn node(&self, name: &[u8]) -> Result<usize, String> {
+        self.graph.names.find(name).ok_or_else(|| {
+            format!("unknown segment {}", String::from_utf8_lossy(name))
+        })
+    }
+
+    fn write_names<W: Write>(
+        &self,
+        out: &mut W,
+        component: &[usize],
+    ) -> std::io::Result<()> {
+        out.write_all(b"[")?;
+        for (i, &node) in component.iter().enumerate() {
+            if i > 0 {
+                out.write_all(b",")?;
+            }
+            output::write_json_string(out, self.graph.names.get(node))?;
+        }
+        out.write_all(b"]")
+    }
+
+    /// Writes the answer to the query to `out`, or returns why it
+    /// can't be answered.
+    fn answer(&self, query: &Json, out: &mut Vec<u8>) -> Result<(), String> {
+        let write_err = |err: std::io::Error| err.to_string();
+        let kind = query
+            .get("query")
+            .and_then(Json::as_str)
+            .ok_or("missing \"query\"")?;
+        match kind {
+            b"component" => {
+                let name = query
+                    .get("segment")
+                    .and_then(Json::as_str)
+                    .ok_or("missing \"segment\"")?;
+                let ix = self.component_of[self.node(name)?];
+                write!(out, "{{\"component\":{},\"nodes\":", ix)
+                    .map_err(write_err)?;
+                self.write_names(out, &self.components[ix])
+                    .map_err(write_err)?;
+                out.write_all(b"}").map_err(write_err)?;
+            }
+            b"same_component" => {
+                let names = match query.get("segments") {
+                    Some(Json::Array(names)) => names,
+                    _ => return Err("missing \"segments\"".into()),
+                };
+                let mut components = HashSet::new();
+                for name in names {
+                    let name =
+                        name.as_str().ok_or("segments must be strings")?;
+                    components.insert(self.component_of[self.node(name)?]);
+                }
+                write!(out, "{{\"same_component\":{}}}", components.len() <= 1)
+                    .map_err(write_err)?;
+            }
+            b"components" => {
+                let min_size = match query.get("min_size") {
+                    None => 2,
+                    Some(Json::Number(n)) if *n >= 0.0 => *n as usize,
+                    Some(_) => return Err("min_size must be a number".into()),
+                };
+                out.write_all(b"{\"components\":[").map_err(write_err)?;
+                let large =
+                    self.components.iter().filter(|c| c.len() >= min_size);
+                for (i, component) in large.enumerate() {
+                    if i > 0 {
+                        out.write_all(b",").map_err(write_err)?;
+                    }
+                    self.write_names(out, component).map_err(write_err)?;
+                }
+                out.write_all(b"]}").map_err(write_err)?;
+            }
+            _ => {
+                return Err(format!(
+                    "unknown query {}",
+                    String::from_utf8_lossy(kind)
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Answers the queries of one client, until it disconnects.
+    fn handle<S: Read + Write>(&self, stream: S) -> std::io::Result<()> {
+        let mut reader = BufReader::new(stream);
+        let mut line = Vec::new();
+        let mut response = Vec::new();
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                return Ok(());
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            response.clear();
+            let answered = Json::parse(&line)
+                .and_then(|query| self.answer(&query, &mut response));
+            if let Err(message) = answered {
+                response.clear();
+                response.extend_from_slice(b"{\"error\":");
+                output::write_json_string(&mut response, message.as_bytes())?;
+                response.push(b'}');
+            }
+            response.push(b'\n');
+            reader.get_mut().write_all(&response)?;
+        }
+    }
+}
+
+#[cfg(unix)]
+fn serve(input: &InputOpt, socket: &Path) -> Result<(), CliError> {
+    use std::os::unix::{fs::FileTypeExt, net::UnixListener};
+
+    let start = Instant::now();
+    let graph = load_graph(input, None)?;
+    if graph.graph.is_empty() {
+        return Err(CliError::InvalidGraph(
+            "input contains no edges".to_string(),
+        ));
+    }
+    let queries = Queries::new(graph);
+    info!(
+        "Found {} components in {:.2?}",
+        queries.components.len(),
+        start.elapsed()
+    );
+
+    if let Ok(metadata) = fs::symlink_metadata(socket) {
+        if !metadata.file_type().is_socket() {
+            return Err(CliError::InvalidArgs(format!(
+                "{:?} exists and isn't a socket",
+                socket
+            )));
+        }
+        fs::remove_file(socket)
+            .map_err(CliError::io(format!("Could not remove {:?}", socket)))?;
+    }
+    let listener = UnixListener::bind(socket)
+        .map_err(CliError::io(format!("Could not listen on {:?}", socket)))?;
+    info!("Listening on {:?}", socket);
+
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let queries = &queries;
+                    scope.spawn(move || {
+                        if let Err(err) = queries.handle(stream) {
+                            debug!("Client disconnected: {}", err);
+                        }
+                    });
+                }
+                Err(err) => {
+                    log!(Level::Warn, "Could not accept a client: {}", err)
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve(_input: &InputOpt, _socket: &Path) -> Result<(), CliError> {
+    Err(CliError::InvalidArgs(
+        "serve needs Unix sockets, which this platform doesn't have"
+            .to_string(),
+    ))
+}
+
+/// Reads a graph for `diff`, and returns its components with at least
+/// `min_size` nodes, by name.
+fn named_components(
+    in_path: &Path,
+    format: Option<InputFormat>,
+    min_size: usize,
+    edges: EdgeOpt,
+) -> Result<Vec<Vec<Vec<u8>>>, CliError> {
+    let graph = read_graph(Some(in_path), format, edges, None)?;
+    let components = algorithm::find_components(&graph.graph);
+    let components =
+        three_edge_connected::filter_min_size(components, min_size);
+    info!("Found {} components in {:?}", components.len(), in_path);
+    Ok(components
+        .iter()
+        .map(|c| c.iter().map(|&n| graph.names.get(n).to_vec()).collect())
+        .collect())
+}
+
+fn diff_graphs(
+    before_path: &Path,
+    after_path: &Path,
+    format: Option<InputFormat>,
+    min_size: usize,
+    edges: EdgeOpt,
+) -> Result<(), CliError> {
+    let before = named_components(before_path, format, min_size, edges)?;
+    let after = named_components(after_path, format, min_size, edges)?;
+    let changes = diff::diff_components(&before, &after);
+
+    let write_components =
+        |out: &mut dyn Write, components: &[Vec<Vec<u8>>], ixs: &[usize]| {
+            for (i, &ix) in ixs.iter().enumerate() {
+                if i > 0 {
+                    out.write_all(b" ")?;
+                }
+                for (k, name) in components[ix].iter().enumerate() {
+                    if k > 0 {
+                        out.write_all(b",")?;
+                    }
+                    out.write_all(name)?;
+                }
+            }
+            Ok::<(), std::io::Error>(())
+        };
+
+    let stdout = std::io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let err = || CliError::io("Could not write output");
+    let mut counts: Vec<(ChangeKind, usize)> = Vec::new();
+    for change in changes.iter() {
+        match counts.iter_mut().find(|(kind, _)| *kind == change.kind) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((change.kind, 1)),
+        }
+        if change.kind == ChangeKind::Unchanged {
+            continue;
+        }
+        out.write_all(change.kind.name().as_bytes())
+            .map_err(err())?;
+        out.write_all(b"\t").map_err(err())?;
+        write_components(&mut out, &before, &change.before).map_err(err())?;
+        out.write_all(b"\t").map_err(err())?;
+        write_components(&mut out, &after, &change.after).map_err(err())?;
+        out.write_all(b"\n").map_err(err())?;
+    }
+    out.flush().map_err(err())?;
+
+    for (kind, count) in counts {
+        info!("{} {}", count, kind.name());
+    }
+    Ok(())
+}
+
+fn batch(
+    inputs: &[PathBuf],
+    format: Option<InputFormat>,
+    out_dir: &Path,
+    threads: Option<usize>,
+    edges: EdgeOpt,
+    output: &OutputOpt,
+) -> Result<(), CliError> {
+    let inputs = expand_inputs(inputs)?;
+
+    std::fs::create_dir_all(out_dir).map_err(CliError::io(format!(
+        "Could not create directory {:?}",
+        out_dir
+    )))?;
+
+    // each input gets an output file named after its file stem, so
+    // make sure no two inputs would write to the same file
+    let mut out_paths: Vec<PathBuf> = Vec::with_capacity(inputs.len());
+    let mut seen = HashSet::new();
+    for input in inputs.iter() {
+        let stem = input.file_stem().unwrap_or_else(|| input.as_os_str());
+        let mut out_path = out_dir.join(stem);
+        let mut extension = output.output_format.extension().to_string();
+        if let Some(compression) = output.compress {
+            extension.push('.');
+            extension.push_str(compression.extension());
+        }
+        out_path.set_extension(extension);
+        if !seen.insert(out_path.clone()) {
+            return Err(CliError::InvalidArgs(format!(
+                "Multiple inputs would be written to {:?}",
+                out_path
+            )));
+        }
+        out_paths.push(out_path);
+    }
+
+    let threads = threads
+        .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .clamp(1, inputs.len().max(1));
+    info!("Processing {} files on {} threads", inputs.len(), threads);
+
+    let next = AtomicUsize::new(0);
+    let errors: Mutex<Vec<(usize, CliError)>> = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let ix = next.fetch_add(1, Ordering::Relaxed);
+                if ix >= inputs.len() {
+                    break;
+                }
+                let result = read_graph(Some(&inputs[ix]), format, edges, None)
+                    .and_then(|graph| {
+                        let (components, _) = find_components(
+                            &graph,
+                            1,
+                            false,
+                            output.four_edge,
+                            None,
+                        );
+                        let components = filter_components(components, output);
+                        write_output(
+                            Some(&out_paths[ix]),
+                            output,
+                            &graph.graph,
+                            &graph.names,
+                            &components,
+                        )
+                    });
+                match result {
+                    Ok(()) => debug!(
+                        "Wrote results for {:?} to {:?}",
+                        inputs[ix], out_paths[ix]
+                    ),
+                    Err(err) => errors.lock().unwrap().push((ix, err)),
+                }
+            });
+        }
+    });
+
+    let mut errors = errors.into_inner().unwrap();
+    errors.sort_by_key(|(ix, _)| *ix);
+    for (ix, err) in errors.iter() {
+        log!(Level::Error, "{:?}: {}", inputs[*ix], err);
+    }
+
+    info!(
+        "Processed {} of {} files successfully",
+        inputs.len() - errors.len(),
+        inputs.len()
+    );
+
+    match errors.into_iter().next() {
+        Some((_, err)) => Err(err),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,104 @@
+use alloc::{vec, vec::Vec};
+
+use crate::adjacency::Adjacency;
+
+/// Finds the 3-edge-connected components by brute force, directly
+/// from the definition: two nodes are in the same component if they
+/// stay connected after removing any two edges. This takes
+/// O(m^2 (n + m)) time, so it's only useful as a reference for
+/// checking `find_components` on small graphs.
+///
+/// The components are returned with their nodes sorted, and sorted
+/// by their first node.
+pub fn find_components<G: Adjacency + ?Sized>(graph: &G) -> Vec<Vec<usize>> {
+    components_after_removing(graph, 2)
+}
+
+/// Like `find_components`, but for the 4-edge-connected components,
+/// whose nodes stay connected after removing any three edges, in
+/// O(m^3 (n + m)) time, for checking `four_edge::find_components`.
+pub fn find_four_edge_components<G: Adjacency + ?Sized>(
+    graph: &G,
+) -> Vec<Vec<usize>> {
+    components_after_removing(graph, 3)
+}
+
+/// Groups the nodes that stay connected after removing any `removed`
+/// edges.
+fn components_after_removing<G: Adjacency + ?Sized>(
+    graph: &G,
+    removed: usize,
+) -> Vec<Vec<usize>> {
+    let num_nodes = graph.num_nodes();
+
+    // each edge once, keeping parallel edges and self-loops, which
+    // are stored twice in their node's adjacency list
+    let mut edges = Vec::new();
+    for from in graph.nodes() {
+        let mut self_loops = 0;
+        for &to in graph.neighbors(from) {
+            if from < to {
+                edges.push((from, to));
+            } else if from == to {
+                self_loops += 1;
+                if self_loops % 2 == 1 {
+                    edges.push((from, to));
+                }
+            }
+        }
+    }
+
+    // each node's connected component after removing each set of
+    // edges, as their sorted indices, where `num_edges` stands for no
+    // edge, so that fewer edges are also removed
+    let mut signatures: Vec<Vec<usize>> = vec![Vec::new(); num_nodes];
+    let mut parent = vec![0; num_nodes];
+
+    let num_edges = edges.len();
+    let mut removal = vec![0; removed];
+    loop {
+        parent.iter_mut().enumerate().for_each(|(ix, p)| *p = ix);
+
+        for (k, &(from, to)) in edges.iter().enumerate() {
+            if !removal.contains(&k) {
+                let a = find(&mut parent, from);
+                let b = find(&mut parent, to);
+                parent[a] = b;
+            }
+        }
+
+        for (node, signature) in signatures.iter_mut().enumerate() {
+            signature.push(find(&mut parent, node));
+        }
+
+        match removal.iter().rposition(|&ix| ix < num_edges) {
+            None => break,
+            Some(pos) => {
+                let next = removal[pos] + 1;
+                removal[pos..].iter_mut().for_each(|ix| *ix = next);
+            }
+        }
+    }
+
+    let mut nodes: Vec<usize> = (0..num_nodes).collect();
+    nodes.sort_by(|a, b| signatures[*a].cmp(&signatures[*b]).then(a.cmp(b)));
+
+    let mut components: Vec<Vec<usize>> = Vec::new();
+    for (ix, &node) in nodes.iter().enumerate() {
+        if ix > 0 && signatures[nodes[ix - 1]] == signatures[node] {
+            components.last_mut().unwrap().push(node);
+        } else {
+            components.push(vec![node]);
+        }
+    }
+    components.sort_unstable();
+    components
+}
+
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
@@ -0,0 +1,78 @@
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The error returned when a run is cancelled, by setting the flag
+/// given to `algorithm::three_edge_connect_cancellable`, or to a
+/// `CancellableReader`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cancelled")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Cancelled {}
+
+pub(crate) fn is_set(cancel: &AtomicBool) -> bool {
+    cancel.load(Ordering::Relaxed)
+}
+
+/// Wraps a reader so that reading fails once the flag is set, which
+/// cancels any of the parsers, e.g. `Graph::from_gfa_reader`. The
+/// flag is checked each time the buffer is refilled. The error wraps
+/// `Cancelled`, see `is_cancelled`.
+#[cfg(feature = "std")]
+pub struct CancellableReader<'a, R> {
+    inner: R,
+    cancel: &'a AtomicBool,
+}
+
+#[cfg(feature = "std")]
+impl<'a, R> CancellableReader<'a, R> {
+    pub fn new(inner: R, cancel: &'a AtomicBool) -> Self {
+        CancellableReader { inner, cancel }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn check(&self) -> std::io::Result<()> {
+        if is_set(self.cancel) {
+            Err(std::io::Error::other(Cancelled))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> std::io::Read for CancellableReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.check()?;
+        self.inner.read(buf)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::BufRead> std::io::BufRead for CancellableReader<'_, R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.check()?;
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
+
+/// Whether the error is from a cancelled `CancellableReader`.
+#[cfg(feature = "std")]
+pub fn is_cancelled(err: &std::io::Error) -> bool {
+    err.get_ref().is_some_and(|err| err.is::<Cancelled>())
+}
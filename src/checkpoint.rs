@@ -0,0 +1,300 @@
+use alloc::vec::Vec;
+use core::sync::atomic::AtomicBool;
+
+use crate::{
+    adjacency::Adjacency,
+    algorithm::{self, SavedFrames, Stopped},
+    cancel::Cancelled,
+    components::Components,
+    index::IndexType,
+    state::State,
+};
+
+/// A run of the algorithm that can be paused and resumed, and saved to
+/// a file in between with `write_to` and `read_from`, so that a long
+/// run that gets interrupted, e.g. by being preempted on a cluster,
+/// doesn't have to start over. It holds the whole state and the
+/// position of the DFS, which is resumed exactly where it was paused,
+/// so the components are the same as those of an uninterrupted run.
+#[derive(Debug, Clone)]
+pub struct Checkpoint<Ix: IndexType = usize> {
+    state: State<Ix>,
+    frames: SavedFrames,
+    finished: bool,
+}
+
+impl<Ix: IndexType> Checkpoint<Ix> {
+    /// A run of the algorithm on the graph that hasn't started yet.
+    pub fn new<G: Adjacency + ?Sized>(graph: &G) -> Self {
+        Checkpoint {
+            state: State::initialize_with_index(graph),
+            frames: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// The state of the algorithm, with the components completed so
+    /// far in `sigma`.
+    pub fn state(&self) -> &State<Ix> {
+        &self.state
+    }
+
+    /// Whether the run has found all the components.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The number of nodes visited so far.
+    pub fn nodes_visited(&self) -> usize {
+        self.state.count - 1
+    }
+
+    /// Runs the algorithm on the graph from where it was paused, until
+    /// it's finished, or until it has looped over about `max_edges`
+    /// more edges, counting both ends of each, if given, and returns
+    /// whether it's finished. The graph must be the same one, with the
+    /// same numbering of the nodes and order of the neighbors. Stops
+    /// with `Cancelled` soon after the `cancel` flag is set, like
+    /// `algorithm::three_edge_connect_cancellable`, after which the run
+    /// can still be resumed.
+    ///
+    /// Panics if the state isn't valid for the graph, see `CorruptState`.
+    pub fn resume<G: Adjacency + ?Sized>(
+        &mut self,
+        graph: &G,
+        max_edges: Option<usize>,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<bool, Cancelled> {
+        if self.finished {
+            return Ok(true);
+        }
+        let result = algorithm::run_from(
+            graph,
+            &mut self.state,
+            &mut (),
+            cancel,
+            &mut |_| {},
+            &mut self.frames,
+            max_edges.map(|max| max.max(1)),
+        );
+        match result {
+            Ok(()) => {
+                self.finished = true;
+                Ok(true)
+            }
+            Err(Stopped::Paused) => Ok(false),
+            Err(stopped) => Err(stopped.cancelled()),
+        }
+    }
+
+    /// Takes the components found so far, which are all of them once
+    /// the run is finished.
+    pub fn into_components(mut self) -> Components {
+        self.state.take_components()
+    }
+}
+
+#[cfg(feature = "std")]
+mod io {
+    use std::{
+        convert::{TryFrom, TryInto},
+        io::{self, Read, Write},
+        mem::size_of,
+    };
+
+    use super::Checkpoint;
+    use crate::{
+        adjacency::Adjacency, components::Components, index::IndexType,
+        state::State,
+    };
+
+    const MAGIC: &[u8; 8] = b"3ECCKPT\0";
+    const VERSION: u64 = 1;
+
+    fn invalid(message: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid checkpoint: {}", message),
+        )
+    }
+
+    /// The number of edge ends of the graph, which is checked along
+    /// with the number of nodes, so that a checkpoint isn't resumed on
+    /// a different graph by mistake.
+    fn edge_ends<G: Adjacency + ?Sized>(graph: &G) -> u64 {
+        graph.nodes().map(|n| graph.neighbors(n).len() as u64).sum()
+    }
+
+    fn write_u64<W: Write>(writer: &mut W, value: u64) -> io::Result<()> {
+        writer.write_all(&value.to_le_bytes())
+    }
+
+    fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+        let mut bytes = [0; 8];
+        reader.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Reads a value below `bound`, as a `usize`.
+    fn read_below<R: Read>(reader: &mut R, bound: usize) -> io::Result<usize> {
+        let value = read_u64(reader)?;
+        usize::try_from(value)
+            .ok()
+            .filter(|&value| value < bound)
+            .ok_or_else(|| invalid("value out of range"))
+    }
+
+    impl<Ix: IndexType> Checkpoint<Ix> {
+        /// Writes the checkpoint in a binary format, which `read_from`
+        /// reads, with the same index type. The writer should be
+        /// buffered, and writing to a temporary file that's then
+        /// renamed keeps the previous checkpoint if this is interrupted.
+        pub fn write_to<W: Write, G: Adjacency + ?Sized>(
+            &self,
+            writer: &mut W,
+            graph: &G,
+        ) -> io::Result<()> {
+            let state = &self.state;
+            let num_nodes = state.next_sigma.len();
+            writer.write_all(MAGIC)?;
+            for value in [
+                VERSION,
+                size_of::<Ix>() as u64,
+                num_nodes as u64,
+                edge_ends(graph),
+                self.finished as u64,
+                state.count as u64,
+                state.path_u as u64,
+            ] {
+                write_u64(writer, value)?;
+            }
+
+            for node in 0..num_nodes {
+                for value in [
+                    state.next_sigma[node],
+                    state.next_on_path[node],
+                    state.pre[node],
+                    state.lowpt[node],
+                    state.num_descendants[node],
+                ] {
+                    write_u64(writer, value.index() as u64)?;
+                }
+                let degree: i64 = state.degrees[node]
+                    .try_into()
+                    .map_err(|_| invalid("degree out of range"))?;
+                writer.write_all(&degree.to_le_bytes())?;
+                writer.write_all(&[state.visited.get(node) as u8])?;
+            }
+
+            write_u64(writer, state.sigma.len() as u64)?;
+            for size in state.sigma.sizes() {
+                write_u64(writer, size as u64)?;
+            }
+            for &node in state.sigma.nodes() {
+                write_u64(writer, node as u64)?;
+            }
+
+            write_u64(writer, self.frames.len() as u64)?;
+            for &(node, parent, cursor) in &self.frames {
+                for value in [node, parent, cursor] {
+                    write_u64(writer, value as u64)?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Reads a checkpoint written by `write_to`, for resuming the
+        /// run on the same graph. Returns an `InvalidData` error if the
+        /// checkpoint was written for a graph with a different number
+        /// of nodes or edges, or with a different index type, or if its
+        /// state fails `State::check_invariants`.
+        pub fn read_from<R: Read, G: Adjacency + ?Sized>(
+            reader: &mut R,
+            graph: &G,
+        ) -> io::Result<Self> {
+            let mut magic = [0; 8];
+            reader.read_exact(&mut magic)?;
+            if &magic != MAGIC {
+                return Err(invalid("not a checkpoint file"));
+            }
+            if read_u64(reader)? != VERSION {
+                return Err(invalid("unsupported version"));
+            }
+            if read_u64(reader)? != size_of::<Ix>() as u64 {
+                return Err(invalid(
+                    "written with a different index type, e.g. without \
+                     --compact-state",
+                ));
+            }
+            let num_nodes = graph.num_nodes();
+            if read_u64(reader)? != num_nodes as u64
+                || read_u64(reader)? != edge_ends(graph)
+            {
+                return Err(invalid("written for a different graph"));
+            }
+            let finished = read_below(reader, 2)? == 1;
+
+            let mut state: State<Ix> = State::initialize_with_index(graph);
+            // preorder numbers and descendant counts go up to one more
+            // than the number of nodes
+            let bound = num_nodes + 2;
+            state.count = read_below(reader, bound)?;
+            state.path_u = read_below(reader, num_nodes.max(1))?;
+            for node in 0..num_nodes {
+                state.next_sigma[node] =
+                    Ix::new(read_below(reader, num_nodes)?);
+                state.next_on_path[node] =
+                    Ix::new(read_below(reader, num_nodes)?);
+                state.pre[node] = Ix::new(read_below(reader, bound)?);
+                state.lowpt[node] = Ix::new(read_below(reader, bound)?);
+                state.num_descendants[node] =
+                    Ix::new(read_below(reader, bound)?);
+                let degree = read_u64(reader)? as i64;
+                state.degrees[node] = Ix::Degree::try_from(degree)
+                    .map_err(|_| invalid("degree out of range"))?;
+                let mut visited = [0];
+                reader.read_exact(&mut visited)?;
+                match visited[0] {
+                    0 => (),
+                    1 => state.visited.set(node),
+                    _ => return Err(invalid("invalid visited flag")),
+                }
+            }
+
+            let num_components = read_below(reader, num_nodes + 1)?;
+            let mut sizes = Vec::with_capacity(num_components);
+            for _ in 0..num_components {
+                sizes.push(read_below(reader, num_nodes + 1)?);
+            }
+            let mut components = Components::default();
+            let mut nodes = Vec::new();
+            for size in sizes {
+                nodes.clear();
+                for _ in 0..size {
+                    nodes.push(read_below(reader, num_nodes)?);
+                }
+                components.push(nodes.iter().copied());
+            }
+            state.sigma = components;
+
+            let num_frames = read_below(reader, num_nodes + 1)?;
+            let mut frames = Vec::with_capacity(num_frames);
+            for _ in 0..num_frames {
+                let node = read_below(reader, num_nodes)?;
+                let parent = read_below(reader, num_nodes)?;
+                let cursor =
+                    read_below(reader, graph.neighbors(node).len() + 1)?;
+                frames.push((node, parent, cursor));
+            }
+
+            state
+                .check_invariants(graph)
+                .map_err(|err| invalid(&err.to_string()))?;
+            Ok(Checkpoint {
+                state,
+                frames,
+                finished,
+            })
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use alloc::vec::Vec;
+
+use crate::{adjacency::Adjacency, algorithm, index::IndexType, state::State};
+
+/// The 3-edge-connected components of a graph, as lists of node
+/// indices, in the order the algorithm finds them. Every node of the
+/// graph is in exactly one component, which may be just the node.
+///
+/// The nodes of all the components are stored one after the other in
+/// a single vector, with the end of each component in another, so
+/// that graphs with many small components don't need an allocation
+/// for each of them. The components are slices of it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Components {
+    nodes: Vec<usize>,
+    // the component `ix` is `nodes[ends[ix - 1]..ends[ix]]`, starting
+    // from 0 for the first one
+    ends: Vec<usize>,
+}
+
+impl Components {
+    /// Runs the algorithm on the graph, like
+    /// `algorithm::find_components`.
+    pub fn find<G: Adjacency + ?Sized>(graph: &G) -> Components {
+        let mut state = State::initialize(graph);
+        algorithm::three_edge_connect(graph, &mut state);
+        state.take_components()
+    }
+
+    /// The number of components.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Adds a component with the given nodes after the others.
+    pub fn push<I: IntoIterator<Item = usize>>(&mut self, nodes: I) {
+        self.nodes.extend(nodes);
+        self.ends.push(self.nodes.len());
+    }
+
+    /// Keeps the first `len` components and removes the rest.
+    pub fn truncate(&mut self, len: usize) {
+        self.ends.truncate(len);
+        self.nodes.truncate(self.ends.last().map_or(0, |&end| end));
+    }
+
+    /// Removes all the components, keeping the allocations.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.ends.clear();
+    }
+
+    /// The nodes of all the components, one component after the other.
+    pub fn nodes(&self) -> &[usize] {
+        &self.nodes
+    }
+
+    fn start(&self, ix: usize) -> usize {
+        if ix == 0 {
+            0
+        } else {
+            self.ends[ix - 1]
+        }
+    }
+
+    /// The number of nodes in each component.
+    pub fn sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).map(move |ix| self.ends[ix] - self.start(ix))
+    }
+
+    /// The nodes of each component.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            components: self,
+            ix: 0,
+        }
+    }
+
+    /// The nodes of the component with the given index.
+    pub fn get(&self, ix: usize) -> Option<&[usize]> {
+        let end = *self.ends.get(ix)?;
+        Some(&self.nodes[self.start(ix)..end])
+    }
+
+    /// The nodes of the last component.
+    pub fn last(&self) -> Option<&[usize]> {
+        self.len().checked_sub(1).and_then(|ix| self.get(ix))
+    }
+
+    /// The index of the component that contains the node, if any. This
+    /// searches all the components, so use `node_components` to look
+    /// up many nodes.
+    pub fn component_of(&self, node: usize) -> Option<usize> {
+        let pos = self.nodes.iter().position(|&n| n == node)?;
+        Some(self.ends.partition_point(|&end| end <= pos))
+    }
+
+    /// The index of the component of each node in `0..num_nodes`, or
+    /// `None` for nodes that aren't in any component, e.g. after
+    /// `retain_min_size`.
+    pub fn node_components(&self, num_nodes: usize) -> Vec<Option<usize>> {
+        let mut node_components = alloc::vec![None; num_nodes];
+        for (ix, component) in self.iter().enumerate() {
+            for &node in component {
+                node_components[node] = Some(ix);
+            }
+        }
+        node_components
+    }
+
+    /// The components of the nodes in `0..num_nodes` as disjoint sets,
+    /// for looking up the component of any node in constant time, like
+    /// `node_components`, but with the ids of the components
+    /// independent of their order. Nodes that aren't in any component
+    /// are each in a set of their own.
+    pub fn disjoint_sets(&self, num_nodes: usize) -> DisjointSets {
+        let mut roots: Vec<usize> = (0..num_nodes).collect();
+        for component in self.iter() {
+            if let Some(&root) = component.iter().min() {
+                for &node in component {
+                    roots[node] = root;
+                }
+            }
+        }
+        DisjointSets { roots }
+    }
+
+    /// An estimate of the number of bytes of heap memory used by the
+    /// components, like `State::memory_usage`.
+    pub fn memory_usage(&self) -> usize {
+        (self.nodes.capacity() + self.ends.capacity())
+            * core::mem::size_of::<usize>()
+    }
+
+    /// Removes the components with fewer than `min_size` nodes, like
+    /// `filter_min_size`.
+    pub fn retain_min_size(&mut self, min_size: usize) {
+        let (mut start, mut kept) = (0, 0);
+        for ix in 0..self.ends.len() {
+            let end = self.ends[ix];
+            if end - start >= min_size {
+                let new_start = if kept == 0 { 0 } else { self.ends[kept - 1] };
+                self.nodes.copy_within(start..end, new_start);
+                self.ends[kept] = new_start + end - start;
+                kept += 1;
+            }
+            start = end;
+        }
+        self.truncate(kept);
+    }
+
+    /// Replaces each node index with `global[node]`, e.g. to turn the
+    /// components of a chunk of a graph, with the chunk's own node
+    /// indices, into components of the whole graph before `merge`.
+    ///
+    /// Panics if a node isn't an index of `global`.
+    pub fn map_nodes(mut self, global: &[usize]) -> Components {
+        for node in self.nodes.iter_mut() {
+            *node = global[*node];
+        }
+        self
+    }
+
+    /// Merges two sets of components over overlapping sets of nodes,
+    /// such as the components of two chunks of a graph, into the
+    /// finest partition that both refine: nodes that are in the same
+    /// component in either are in the same merged component, and so
+    /// are the nodes joined by a chain of such components. The merged
+    /// components, and their nodes, are in the order they first appear
+    /// in `self` and then `other`.
+    ///
+    /// These are the 3-edge-connected components of the whole graph
+    /// if each pair of its nodes that's 3-edge-connected is connected
+    /// in a single chunk, e.g. if the chunks are the biconnected
+    /// components, which share only cut vertices, as in
+    /// `parallel::find_components`, but not for arbitrary chunks.
+    pub fn merge(self, other: Components) -> Components {
+        let num_nodes = self
+            .nodes
+            .iter()
+            .chain(other.nodes.iter())
+            .max()
+            .map_or(0, |&node| node + 1);
+        let mut parent: Vec<usize> = (0..num_nodes).collect();
+
+        for component in self.iter().chain(other.iter()) {
+            if let Some((&first, rest)) = component.split_first() {
+                for &node in rest {
+                    let a = find(&mut parent, first);
+                    let b = find(&mut parent, node);
+                    parent[a.max(b)] = a.min(b);
+                }
+            }
+        }
+
+        // the index of the merged component of each root, and whether
+        // each node has been added to it
+        let mut index = alloc::vec![usize::MAX; num_nodes];
+        let mut added = alloc::vec![false; num_nodes];
+        let mut components: Vec<Vec<usize>> = Vec::new();
+        for &node in self.nodes.iter().chain(other.nodes.iter()) {
+            if added[node] {
+                continue;
+            }
+            added[node] = true;
+            let root = find(&mut parent, node);
+            if index[root] == usize::MAX {
+                index[root] = components.len();
+                components.push(Vec::new());
+            }
+            components[index[root]].push(node);
+        }
+        Components::from(components)
+    }
+
+    /// Replaces the node indices with node names, such as the
+    /// `names` of an `InternedGraph`.
+    #[cfg(feature = "std")]
+    pub fn into_named<N>(self, names: &N) -> Vec<Vec<Vec<u8>>>
+    where
+        N: crate::names::NodeNames + ?Sized,
+    {
+        self.iter()
+            .map(|c| c.iter().map(|&n| names.name(n).into_owned()).collect())
+            .collect()
+    }
+
+    /// Returns the components as lists of node indices, each in its
+    /// own vector.
+    pub fn into_inner(self) -> Vec<Vec<usize>> {
+        self.iter().map(|c| c.to_vec()).collect()
+    }
+}
+
+/// The 3-edge-connected components of a graph as a fully compressed
+/// union-find structure, from `Components::disjoint_sets`: every node
+/// points directly to the root of its set, which is the smallest node
+/// of its component. Finding the component of a node is a single
+/// lookup, and the roots, one `usize` per node, are the whole
+/// structure, so storing them is enough to rebuild it with
+/// `from_roots`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DisjointSets {
+    roots: Vec<usize>,
+}
+
+impl DisjointSets {
+    /// Rebuilds the sets from the root of each node, as returned by
+    /// `roots`, or returns `None` if they aren't the roots of disjoint
+    /// sets: some node's root is out of range, isn't its own root, or
+    /// is larger than the node.
+    pub fn from_roots(roots: Vec<usize>) -> Option<DisjointSets> {
+        let valid = roots.iter().enumerate().all(|(node, &root)| {
+            root <= node && roots.get(root) == Some(&root)
+        });
+        if valid {
+            Some(DisjointSets { roots })
+        } else {
+            None
+        }
+    }
+
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.roots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// The root of the set of the node, the smallest node of its
+    /// component.
+    ///
+    /// Panics if the node is out of range.
+    pub fn find(&self, node: usize) -> usize {
+        self.roots[node]
+    }
+
+    /// Whether the two nodes are in the same component.
+    pub fn same_set(&self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The number of sets, including those of a single node.
+    pub fn num_sets(&self) -> usize {
+        self.roots
+            .iter()
+            .enumerate()
+            .filter(|&(node, &root)| node == root)
+            .count()
+    }
+
+    /// The root of each node.
+    pub fn roots(&self) -> &[usize] {
+        &self.roots
+    }
+
+    /// The sets as `Components`, ordered by their roots, with the nodes
+    /// of each in increasing order.
+    pub fn to_components(&self) -> Components {
+        // the index of the set of each root, then the sets
+        let mut index = alloc::vec![usize::MAX; self.roots.len()];
+        let mut sets: Vec<Vec<usize>> = Vec::new();
+        for (node, &root) in self.roots.iter().enumerate() {
+            if index[root] == usize::MAX {
+                index[root] = sets.len();
+                sets.push(Vec::new());
+            }
+            sets[index[root]].push(node);
+        }
+        Components::from(sets)
+    }
+}
+
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
+
+impl From<Vec<Vec<usize>>> for Components {
+    fn from(components: Vec<Vec<usize>>) -> Self {
+        let mut flat = Components {
+            nodes: Vec::with_capacity(components.iter().map(Vec::len).sum()),
+            ends: Vec::with_capacity(components.len()),
+        };
+        for component in components {
+            flat.push(component);
+        }
+        flat
+    }
+}
+
+impl core::ops::Index<usize> for Components {
+    type Output = [usize];
+
+    fn index(&self, ix: usize) -> &[usize] {
+        self.get(ix).expect("component index out of range")
+    }
+}
+
+/// An iterator over the nodes of each component, from
+/// `Components::iter`.
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    components: &'a Components,
+    ix: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a [usize];
+
+    fn next(&mut self) -> Option<&'a [usize]> {
+        let component = self.components.get(self.ix)?;
+        self.ix += 1;
+        Some(component)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.components.len() - self.ix;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl<'a> IntoIterator for &'a Components {
+    type Item = &'a [usize];
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Components {
+    type Item = Vec<usize>;
+    type IntoIter = alloc::vec::IntoIter<Vec<usize>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_inner().into_iter()
+    }
+}
+
+impl<Ix: IndexType> State<Ix> {
+    /// Takes the components found by `algorithm::three_edge_connect`,
+    /// leaving the state empty of them.
+    pub fn take_components(&mut self) -> Components {
+        core::mem::take(&mut self.sigma)
+    }
+}
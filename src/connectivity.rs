@@ -0,0 +1,497 @@
+use fxhash::FxHashSet;
+
+use crate::graph::FxMapGraph;
+
+/// Returns the node indices in `graph` in increasing order, used as
+/// DFS roots so the results don't depend on the hash map's
+/// iteration order.
+fn sorted_nodes(graph: &FxMapGraph) -> Vec<usize> {
+    let mut nodes: Vec<usize> = graph.keys().copied().collect();
+    nodes.sort_unstable();
+    nodes
+}
+
+/// Finds the connected components of the graph, each as a list of
+/// node indices in the order they were reached.
+pub fn connected_components(graph: &FxMapGraph) -> Vec<Vec<usize>> {
+    let mut visited = vec![false; graph.len()];
+    let mut components = Vec::new();
+    let mut stack = Vec::new();
+
+    for root in sorted_nodes(graph) {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        stack.push(root);
+
+        let mut component = Vec::new();
+        while let Some(w) = stack.pop() {
+            component.push(w);
+            for &u in graph[&w].iter() {
+                if !visited[u] {
+                    visited[u] = true;
+                    stack.push(u);
+                }
+            }
+        }
+        components.push(component);
+    }
+
+    components
+}
+
+struct BridgeFrame {
+    node: usize,
+    parent: Option<usize>,
+    cursor: usize,
+    skipped_parent: bool,
+}
+
+/// Finds the bridges of the graph, i.e. the edges whose removal
+/// disconnects their endpoints, as `(parent, child)` pairs in the
+/// DFS tree. Parallel edges are never bridges.
+pub fn bridges(graph: &FxMapGraph) -> Vec<(usize, usize)> {
+    let num_nodes = graph.len();
+    // preorder numbers start from 1, so 0 marks unvisited nodes
+    let mut pre = vec![0; num_nodes];
+    let mut lowpt = vec![0; num_nodes];
+    let mut count = 0;
+
+    let mut bridges = Vec::new();
+    let mut stack: Vec<BridgeFrame> = Vec::new();
+
+    for root in sorted_nodes(graph) {
+        if pre[root] != 0 {
+            continue;
+        }
+        count += 1;
+        pre[root] = count;
+        lowpt[root] = count;
+        stack.push(BridgeFrame {
+            node: root,
+            parent: None,
+            cursor: 0,
+            skipped_parent: false,
+        });
+
+        while let Some(frame) = stack.last_mut() {
+            let w = frame.node;
+            let adj = &graph[&w];
+
+            if frame.cursor < adj.len() {
+                let u = adj[frame.cursor];
+                frame.cursor += 1;
+
+                // only the one tree edge to the parent is skipped,
+                // any parallel edges are back-edges
+                if Some(u) == frame.parent && !frame.skipped_parent {
+                    frame.skipped_parent = true;
+                } else if pre[u] == 0 {
+                    count += 1;
+                    pre[u] = count;
+                    lowpt[u] = count;
+                    stack.push(BridgeFrame {
+                        node: u,
+                        parent: Some(w),
+                        cursor: 0,
+                        skipped_parent: false,
+                    });
+                } else {
+                    lowpt[w] = lowpt[w].min(pre[u]);
+                }
+            } else {
+                let parent = frame.parent;
+                stack.pop();
+                if let Some(p) = parent {
+                    lowpt[p] = lowpt[p].min(lowpt[w]);
+                    if lowpt[w] > pre[p] {
+                        bridges.push((p, w));
+                    }
+                }
+            }
+        }
+    }
+
+    bridges
+}
+
+struct BlockFrame {
+    node: usize,
+    parent: Option<usize>,
+    cursor: usize,
+    skipped_parent: bool,
+    // the length of the edge stack before the tree edge to this node
+    // was pushed
+    edge_start: usize,
+}
+
+/// Finds the biconnected components, or blocks, of the graph, each
+/// as a list of its edges. Every edge except self-loops, which are
+/// left out, is in exactly one block, and parallel edges are in the
+/// same block. Two blocks share at most one node, which is a cut
+/// vertex of the graph.
+pub fn biconnected_components(graph: &FxMapGraph) -> Vec<Vec<(usize, usize)>> {
+    let num_nodes = graph.len();
+    // preorder numbers start from 1, so 0 marks unvisited nodes
+    let mut pre = vec![0; num_nodes];
+    let mut lowpt = vec![0; num_nodes];
+    let mut count = 0;
+
+    let mut blocks = Vec::new();
+    let mut edges: Vec<(usize, usize)> = Vec::new();
+    let mut stack: Vec<BlockFrame> = Vec::new();
+
+    for root in sorted_nodes(graph) {
+        if pre[root] != 0 {
+            continue;
+        }
+        count += 1;
+        pre[root] = count;
+        lowpt[root] = count;
+        stack.push(BlockFrame {
+            node: root,
+            parent: None,
+            cursor: 0,
+            skipped_parent: false,
+            edge_start: 0,
+        });
+
+        while let Some(frame) = stack.last_mut() {
+            let w = frame.node;
+            let adj = &graph[&w];
+
+            if frame.cursor < adj.len() {
+                let u = adj[frame.cursor];
+                frame.cursor += 1;
+
+                if Some(u) == frame.parent && !frame.skipped_parent {
+                    frame.skipped_parent = true;
+                } else if pre[u] == 0 {
+                    count += 1;
+                    pre[u] = count;
+                    lowpt[u] = count;
+                    stack.push(BlockFrame {
+                        node: u,
+                        parent: Some(w),
+                        cursor: 0,
+                        skipped_parent: false,
+                        edge_start: edges.len(),
+                    });
+                    edges.push((w, u));
+                } else if pre[u] < pre[w] {
+                    // back-edges are only pushed from the descendant
+                    // end, which also leaves out self-loops
+                    lowpt[w] = lowpt[w].min(pre[u]);
+                    edges.push((w, u));
+                }
+            } else {
+                let parent = frame.parent;
+                let edge_start = frame.edge_start;
+                stack.pop();
+                if let Some(p) = parent {
+                    lowpt[p] = lowpt[p].min(lowpt[w]);
+                    if lowpt[w] >= pre[p] {
+                        blocks.push(edges.split_off(edge_start));
+                    }
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
+struct CutFrame {
+    node: usize,
+    parent: Option<usize>,
+    cursor: usize,
+    children: usize,
+}
+
+/// The state of the searches for cut vertices, which is kept between
+/// the searches of `separation_pairs`. Preorder numbers keep counting
+/// up, so the nodes numbered before a search are unvisited in it.
+struct CutVertices {
+    pre: Vec<usize>,
+    lowpt: Vec<usize>,
+    count: usize,
+    stack: Vec<CutFrame>,
+    cut: Vec<usize>,
+}
+
+impl CutVertices {
+    fn new(num_nodes: usize) -> Self {
+        CutVertices {
+            pre: vec![0; num_nodes],
+            lowpt: vec![0; num_nodes],
+            count: 0,
+            stack: Vec::new(),
+            cut: Vec::new(),
+        }
+    }
+
+    /// Adds the cut vertices of the connected component of `root`, in
+    /// the graph without the `removed` node, to `cut`. Nodes numbered
+    /// in earlier searches count as unvisited if `start` is the count
+    /// before this one.
+    fn search(
+        &mut self,
+        graph: &FxMapGraph,
+        root: usize,
+        removed: Option<usize>,
+        start: usize,
+    ) {
+        self.count += 1;
+        self.pre[root] = self.count;
+        self.lowpt[root] = self.count;
+        self.stack.push(CutFrame {
+            node: root,
+            parent: None,
+            cursor: 0,
+            children: 0,
+        });
+
+        while let Some(frame) = self.stack.last_mut() {
+            let w = frame.node;
+            let adj = &graph[&w];
+
+            if frame.cursor < adj.len() {
+                let u = adj[frame.cursor];
+                frame.cursor += 1;
+                if Some(u) == removed {
+                    continue;
+                }
+                if self.pre[u] <= start {
+                    frame.children += 1;
+                    self.count += 1;
+                    self.pre[u] = self.count;
+                    self.lowpt[u] = self.count;
+                    self.stack.push(CutFrame {
+                        node: u,
+                        parent: Some(w),
+                        cursor: 0,
+                        children: 0,
+                    });
+                } else {
+                    // the edges to the parent only lower the lowpoint
+                    // to the parent's number, which still makes the
+                    // parent a cut vertex
+                    self.lowpt[w] = self.lowpt[w].min(self.pre[u]);
+                }
+            } else {
+                let parent = frame.parent;
+                let children = frame.children;
+                self.stack.pop();
+                match parent {
+                    Some(p) => {
+                        self.lowpt[p] = self.lowpt[p].min(self.lowpt[w]);
+                        // the root is checked by its number of children
+                        if self.lowpt[w] >= self.pre[p] && p != root {
+                            self.cut.push(p);
+                        }
+                    }
+                    None if children > 1 => self.cut.push(w),
+                    None => (),
+                }
+            }
+        }
+    }
+}
+
+/// Finds the cut vertices, or articulation points, of the graph, i.e.
+/// the nodes whose removal disconnects their connected component, in
+/// increasing order. These are the nodes shared by several blocks of
+/// `biconnected_components`.
+pub fn cut_vertices(graph: &FxMapGraph) -> Vec<usize> {
+    let mut search = CutVertices::new(graph.len());
+    for root in sorted_nodes(graph) {
+        if search.pre[root] == 0 {
+            search.search(graph, root, None, 0);
+        }
+    }
+    let mut cut = search.cut;
+    cut.sort_unstable();
+    cut.dedup();
+    cut
+}
+
+/// Finds the separation pairs of the graph, i.e. the pairs of nodes
+/// that disconnect their connected component when both are removed,
+/// although neither does on its own, as `(u, v)` pairs with `u < v`,
+/// in increasing order. A component without cut vertices or
+/// separation pairs, and with at least four nodes, is 3-vertex-connected,
+/// which is a stronger property than being 3-edge-connected: the two
+/// nodes shared by two cliques with more than three nodes each are a
+/// separation pair, although all of their nodes are in the same
+/// 3-edge-connected component.
+///
+/// Each node that isn't a cut vertex is removed in turn, and the cut
+/// vertices of the rest of its connected component are found, which
+/// takes O(n (n + m)) time, unlike Hopcroft and Tarjan's linear-time
+/// algorithm for the triconnected components, which is much more
+/// involved.
+pub fn separation_pairs(graph: &FxMapGraph) -> Vec<(usize, usize)> {
+    let mut is_cut = vec![false; graph.len()];
+    for node in cut_vertices(graph) {
+        is_cut[node] = true;
+    }
+
+    let mut search = CutVertices::new(graph.len());
+    let mut pairs = Vec::new();
+    for node in sorted_nodes(graph) {
+        if is_cut[node] {
+            continue;
+        }
+        // the rest of the component stays connected, so a search from
+        // any neighbor reaches all of it
+        let root = match graph[&node].iter().find(|&&u| u != node) {
+            Some(&root) => root,
+            None => continue,
+        };
+        let start = search.count;
+        search.search(graph, root, Some(node), start);
+        for other in search.cut.drain(..) {
+            if other > node && !is_cut[other] {
+                pairs.push((node, other));
+            }
+        }
+    }
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+/// A bridge of the graph, as an edge of its `BridgeTree`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeEdge {
+    /// The indices of the 2-edge-connected components joined by the
+    /// bridge, in `BridgeTree::components`.
+    pub components: (usize, usize),
+    /// The bridge itself, as `(parent, child)` nodes, like the
+    /// `bridges` it's from.
+    pub bridge: (usize, usize),
+}
+
+/// The bridge tree of a graph, which has the 2-edge-connected
+/// components of the graph as its nodes, and its bridges as the
+/// edges. There's one tree for each connected component of the graph,
+/// so it's a forest if the graph isn't connected. Removing any bridge
+/// splits the graph in two without splitting any 2-edge-connected
+/// component, nor any 3-edge-connected component, which is contained
+/// in one of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BridgeTree {
+    /// The nodes of each 2-edge-connected component, with the
+    /// components in the order of their first node's index.
+    pub components: Vec<Vec<usize>>,
+    /// The index of the component of each node.
+    pub node_components: Vec<usize>,
+    /// The bridges between the components.
+    pub edges: Vec<TreeEdge>,
+}
+
+/// Builds the bridge tree of the graph, from its `bridges` and the
+/// connected components that are left without them.
+pub fn bridge_tree(graph: &FxMapGraph) -> BridgeTree {
+    let bridges = bridges(graph);
+    // a bridge has no parallel edges, so its ends identify it
+    let is_bridge: FxHashSet<(usize, usize)> = bridges
+        .iter()
+        .map(|&(from, to)| (from.min(to), from.max(to)))
+        .collect();
+
+    let mut node_components = vec![usize::MAX; graph.len()];
+    let mut components = Vec::new();
+    let mut stack = Vec::new();
+    for root in sorted_nodes(graph) {
+        if node_components[root] != usize::MAX {
+            continue;
+        }
+        let ix = components.len();
+        node_components[root] = ix;
+        stack.push(root);
+
+        let mut component = Vec::new();
+        while let Some(w) = stack.pop() {
+            component.push(w);
+            for &u in graph[&w].iter() {
+                if node_components[u] == usize::MAX
+                    && !is_bridge.contains(&(w.min(u), w.max(u)))
+                {
+                    node_components[u] = ix;
+                    stack.push(u);
+                }
+            }
+        }
+        component.sort_unstable();
+        components.push(component);
+    }
+
+    let edges = bridges
+        .into_iter()
+        .map(|(from, to)| TreeEdge {
+            components: (node_components[from], node_components[to]),
+            bridge: (from, to),
+        })
+        .collect();
+    BridgeTree {
+        components,
+        node_components,
+        edges,
+    }
+}
+
+/// The nested decomposition of a graph into its connected components,
+/// their 2-edge-connected components, and the 3-edge-connected
+/// components of those, from `decompose`. Each level refines the one
+/// above it, so every component is contained in exactly one component
+/// of the level above.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Decomposition {
+    /// The nodes of each connected component, in increasing order.
+    pub connected: Vec<Vec<usize>>,
+    /// The 2-edge-connected components, as in `BridgeTree::components`.
+    pub two_edge: Vec<Vec<usize>>,
+    /// The 3-edge-connected components, as from `find_components`.
+    pub three_edge: Vec<Vec<usize>>,
+    /// The indices of the 2-edge-connected components in each
+    /// connected component.
+    pub connected_children: Vec<Vec<usize>>,
+    /// The indices of the 3-edge-connected components in each
+    /// 2-edge-connected component.
+    pub two_edge_children: Vec<Vec<usize>>,
+}
+
+/// Finds the connected, 2-edge-connected, and 3-edge-connected
+/// components of the graph, nested in each other.
+pub fn decompose(graph: &FxMapGraph) -> Decomposition {
+    let mut connected = connected_components(graph);
+    connected.iter_mut().for_each(|c| c.sort_unstable());
+    let mut node_connected = vec![0; graph.len()];
+    for (ix, component) in connected.iter().enumerate() {
+        for &node in component {
+            node_connected[node] = ix;
+        }
+    }
+
+    let tree = bridge_tree(graph);
+    let three_edge = crate::algorithm::find_components(graph);
+
+    let mut connected_children = vec![Vec::new(); connected.len()];
+    for (ix, component) in tree.components.iter().enumerate() {
+        connected_children[node_connected[component[0]]].push(ix);
+    }
+    let mut two_edge_children = vec![Vec::new(); tree.components.len()];
+    for (ix, component) in three_edge.iter().enumerate() {
+        two_edge_children[tree.node_components[component[0]]].push(ix);
+    }
+
+    Decomposition {
+        connected,
+        two_edge: tree.components,
+        three_edge,
+        connected_children,
+        two_edge_children,
+    }
+}
@@ -0,0 +1,233 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{adjacency::Adjacency, algorithm};
+
+/// The seed of the random edge labels, so that the results are the
+/// same on every run.
+const SEED: u64 = 0x3ec0_3ec0_3ec0_3ec0;
+
+/// SplitMix64, like `generators::SplitMix64`, which needs `std`.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+struct Frame {
+    node: usize,
+    cursor: usize,
+    skipped_parent: bool,
+}
+
+/// Finds the 3-edge-connected components of the graph with a second
+/// algorithm, which shares no code with `algorithm::find_components`
+/// other than the graph, for checking one against the other.
+///
+/// This is the cycle space sampling of Pritchard and Thurimella
+/// ("Fast computation of small cuts via cycle space sampling", 2011):
+/// each edge that's not in the DFS tree gets a random 64-bit label,
+/// and each tree edge the XOR of the labels of the non-tree edges
+/// whose cycles contain it. Then the bridges are the edges labelled 0,
+/// and two edges are a cut pair if and only if they have the same
+/// label, up to collisions of the random labels. The edges with the
+/// same label lie on one path from the root, and removing them splits
+/// the graph into pieces between them, each of which is marked by
+/// adding a random value to the nodes of its subtrees, so that two
+/// nodes are in the same component if no bridge or cut pair separates
+/// them, i.e. if they end up with the same sum.
+///
+/// This takes O((n + m) log m) time, for the sorting by label, and is
+/// wrong with a probability on the order of m^2 / 2^64, which is
+/// negligible. The labels are always the same, so the result is too.
+/// The components are returned with their nodes sorted, and sorted by
+/// their first node.
+pub fn find_components<G: Adjacency + ?Sized>(graph: &G) -> Vec<Vec<usize>> {
+    let num_nodes = graph.num_nodes();
+    let mut rng = Rng(SEED);
+
+    // preorder numbers start from 1, so 0 marks unvisited nodes
+    let mut pre = vec![0; num_nodes];
+    let mut parent = vec![usize::MAX; num_nodes];
+    let mut preorder = Vec::with_capacity(num_nodes);
+    // the XOR of the labels of the non-tree edges at each node, and
+    // the labels of the non-tree edges, sorted later
+    let mut labels = vec![0u64; num_nodes];
+    let mut back_edge_labels = Vec::new();
+    let mut stack: Vec<Frame> = Vec::new();
+
+    for root in graph.nodes() {
+        if pre[root] != 0 {
+            continue;
+        }
+        preorder.push(root);
+        pre[root] = preorder.len();
+        stack.push(Frame {
+            node: root,
+            cursor: 0,
+            skipped_parent: false,
+        });
+
+        while let Some(frame) = stack.last_mut() {
+            let w = frame.node;
+            let neighbors = graph.neighbors(w);
+            let u = match neighbors.get(frame.cursor) {
+                Some(&u) => u,
+                None => {
+                    stack.pop();
+                    continue;
+                }
+            };
+            frame.cursor += 1;
+
+            if u == w {
+                // self-loops aren't in any cut
+            } else if u == parent[w] && !frame.skipped_parent {
+                // only the one tree edge to the parent is skipped, any
+                // parallel edges are non-tree edges
+                frame.skipped_parent = true;
+            } else if pre[u] == 0 {
+                preorder.push(u);
+                pre[u] = preorder.len();
+                parent[u] = w;
+                stack.push(Frame {
+                    node: u,
+                    cursor: 0,
+                    skipped_parent: false,
+                });
+            } else if pre[u] < pre[w] {
+                // each non-tree edge is labelled once, from its
+                // descendant end
+                let label = rng.next_u64();
+                labels[w] ^= label;
+                labels[u] ^= label;
+                back_edge_labels.push(label);
+            }
+        }
+    }
+    back_edge_labels.sort_unstable();
+
+    // the label of the tree edge to each node is the XOR of the labels
+    // at the nodes of its subtree, as the edges within it cancel out
+    let mut tree_edges = Vec::with_capacity(num_nodes);
+    for &node in preorder.iter().rev() {
+        if parent[node] != usize::MAX {
+            labels[parent[node]] ^= labels[node];
+            tree_edges.push((labels[node], pre[node], node));
+        }
+    }
+    tree_edges.sort_unstable();
+
+    // the random value added to the subtree of each node, starting
+    // with a different one for each DFS tree
+    let mut delta = vec![0u64; num_nodes];
+    for &node in preorder.iter() {
+        if parent[node] == usize::MAX {
+            delta[node] = rng.next_u64();
+        }
+    }
+    for class in tree_edges.chunk_by(|a, b| a.0 == b.0) {
+        let label = class[0].0;
+        if label == 0 {
+            // each bridge cuts off its subtree
+            for &(_, _, node) in class {
+                delta[node] = delta[node].wrapping_add(rng.next_u64());
+            }
+            continue;
+        }
+        // the pieces between consecutive tree edges of the class, from
+        // the root down, and the subtree below the last one, which is
+        // only cut off if a non-tree edge is in the class too
+        for pair in class.windows(2) {
+            let piece = rng.next_u64();
+            let (top, bottom) = (pair[0].2, pair[1].2);
+            delta[top] = delta[top].wrapping_add(piece);
+            delta[bottom] = delta[bottom].wrapping_sub(piece);
+        }
+        if back_edge_labels.binary_search(&label).is_ok() {
+            let last = class[class.len() - 1].2;
+            delta[last] = delta[last].wrapping_add(rng.next_u64());
+        }
+    }
+
+    // the sum of the values of the subtrees each node is in, i.e. of
+    // its ancestors
+    let mut sums: Vec<(u64, usize)> = Vec::with_capacity(num_nodes);
+    let mut sum = vec![0u64; num_nodes];
+    for &node in preorder.iter() {
+        sum[node] = match parent[node] {
+            usize::MAX => delta[node],
+            p => sum[p].wrapping_add(delta[node]),
+        };
+        sums.push((sum[node], node));
+    }
+    sums.sort_unstable();
+
+    let mut components: Vec<Vec<usize>> = sums
+        .chunk_by(|a, b| a.0 == b.0)
+        .map(|group| group.iter().map(|&(_, node)| node).collect())
+        .collect();
+    components.sort_unstable();
+    components
+}
+
+/// The components found by the two algorithms, when they differ, from
+/// `cross_check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The components from `algorithm::find_components`.
+    pub primary: Vec<Vec<usize>>,
+    /// The components from `cut_pairs::find_components`.
+    pub cut_pairs: Vec<Vec<usize>>,
+    /// A node whose component differs between the two.
+    pub node: usize,
+}
+
+/// Runs both `algorithm::find_components` and `find_components` on
+/// the graph, and returns the components, with their nodes sorted and
+/// sorted by their first node, if they're the same partition of the
+/// nodes, or both results otherwise.
+pub fn cross_check<G: Adjacency + ?Sized>(
+    graph: &G,
+) -> Result<Vec<Vec<usize>>, Mismatch> {
+    cross_check_components(graph, algorithm::find_components(graph))
+}
+
+/// Like `cross_check`, for components that were already found with
+/// `algorithm::find_components`, e.g. with a particular state, in any
+/// order.
+pub fn cross_check_components<G: Adjacency + ?Sized>(
+    graph: &G,
+    mut primary: Vec<Vec<usize>>,
+) -> Result<Vec<Vec<usize>>, Mismatch> {
+    primary.iter_mut().for_each(|c| c.sort_unstable());
+    primary.sort_unstable();
+    let cut_pairs = find_components(graph);
+    if primary == cut_pairs {
+        return Ok(primary);
+    }
+
+    // the first node that's in a different component, or missing from
+    // one of the results
+    let component_ix = |components: &[Vec<usize>]| {
+        let mut ix = vec![usize::MAX; graph.num_nodes()];
+        for component in components {
+            for &node in component {
+                ix[node] = component[0];
+            }
+        }
+        ix
+    };
+    let (a, b) = (component_ix(&primary), component_ix(&cut_pairs));
+    let node = (0..graph.num_nodes()).find(|&n| a[n] != b[n]).unwrap_or(0);
+    Err(Mismatch {
+        primary,
+        cut_pairs,
+        node,
+    })
+}
@@ -0,0 +1,134 @@
+use std::hash::Hash;
+
+use fxhash::FxHashMap;
+
+/// How a group of components changed between two sets of components,
+/// see `diff_components`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// One component has the same nodes in both.
+    Unchanged,
+    /// One component gained or lost nodes, without overlapping any
+    /// other component.
+    Changed,
+    /// One component became several.
+    Split,
+    /// Several components became one.
+    Merged,
+    /// Several components became several others, which overlap
+    /// more than one of them.
+    Rearranged,
+    /// A component doesn't share any nodes with the components before.
+    Appeared,
+    /// A component doesn't share any nodes with the components after.
+    Vanished,
+}
+
+impl ChangeKind {
+    /// The name of the change, as in the output of the `diff`
+    /// subcommand.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChangeKind::Unchanged => "unchanged",
+            ChangeKind::Changed => "changed",
+            ChangeKind::Split => "split",
+            ChangeKind::Merged => "merged",
+            ChangeKind::Rearranged => "rearranged",
+            ChangeKind::Appeared => "appeared",
+            ChangeKind::Vanished => "vanished",
+        }
+    }
+}
+
+/// A group of components before and after, which are connected by
+/// sharing nodes, and no other components share nodes with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentChange {
+    pub kind: ChangeKind,
+    /// The indices of the components before, in increasing order.
+    pub before: Vec<usize>,
+    /// The indices of the components after, in increasing order.
+    pub after: Vec<usize>,
+}
+
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
+
+/// Compares two sets of components of the same nodes, such as the
+/// components of two versions of an assembly graph by segment name,
+/// e.g. from `Components::into_named`. The components are grouped by
+/// the nodes they share, so that each node is only in the components
+/// of one group, and each group is one change. The groups are in the
+/// order of their first component before, followed by the components
+/// that appeared, in order.
+pub fn diff_components<T: Hash + Eq>(
+    before: &[Vec<T>],
+    after: &[Vec<T>],
+) -> Vec<ComponentChange> {
+    let mut after_component: FxHashMap<&T, usize> = FxHashMap::default();
+    for (ix, component) in after.iter().enumerate() {
+        for node in component {
+            after_component.insert(node, ix);
+        }
+    }
+
+    // union-find over the components before, then after them
+    let mut parent: Vec<usize> = (0..before.len() + after.len()).collect();
+    // the number of nodes each component before shares with the
+    // components after
+    let mut shared = vec![0; before.len()];
+    for (ix, component) in before.iter().enumerate() {
+        for node in component {
+            if let Some(&other) = after_component.get(node) {
+                shared[ix] += 1;
+                let a = find(&mut parent, ix);
+                let b = find(&mut parent, before.len() + other);
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+
+    let mut group_ix: FxHashMap<usize, usize> = FxHashMap::default();
+    let mut changes: Vec<ComponentChange> = Vec::new();
+    for ix in 0..before.len() + after.len() {
+        let root = find(&mut parent, ix);
+        let group = *group_ix.entry(root).or_insert_with(|| {
+            changes.push(ComponentChange {
+                kind: ChangeKind::Unchanged,
+                before: Vec::new(),
+                after: Vec::new(),
+            });
+            changes.len() - 1
+        });
+        if ix < before.len() {
+            changes[group].before.push(ix);
+        } else {
+            changes[group].after.push(ix - before.len());
+        }
+    }
+
+    for change in changes.iter_mut() {
+        change.kind = match (change.before.as_slice(), change.after.len()) {
+            ([], _) => ChangeKind::Appeared,
+            (_, 0) => ChangeKind::Vanished,
+            (&[ix], 1) => {
+                let same = shared[ix] == before[ix].len()
+                    && before[ix].len() == after[change.after[0]].len();
+                if same {
+                    ChangeKind::Unchanged
+                } else {
+                    ChangeKind::Changed
+                }
+            }
+            ([_], _) => ChangeKind::Split,
+            (_, 1) => ChangeKind::Merged,
+            _ => ChangeKind::Rearranged,
+        };
+    }
+    changes
+}
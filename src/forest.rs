@@ -0,0 +1,67 @@
+use alloc::{vec, vec::Vec};
+
+use crate::visitor::Visitor;
+
+/// The spanning forest of the depth-first search of the algorithm,
+/// with a tree for each connected component. It's recorded by passing
+/// it as the visitor of `algorithm::three_edge_connect_with_visitor`,
+/// or with `algorithm::find_components_with_forest`, so it's the
+/// forest of the same run that found the components, whose
+/// absorptions it explains: each component is absorbed along the
+/// paths of this forest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpanningForest {
+    // the parent of each node, or `usize::MAX` for the roots and the
+    // nodes that haven't been reached
+    parent: Vec<usize>,
+    preorder: Vec<usize>,
+}
+
+impl SpanningForest {
+    /// Creates an empty forest for a graph with the given number of
+    /// nodes, to be filled in by the algorithm.
+    pub fn new(num_nodes: usize) -> Self {
+        SpanningForest {
+            parent: vec![usize::MAX; num_nodes],
+            preorder: Vec::with_capacity(num_nodes),
+        }
+    }
+
+    /// The parent of the node in its tree, or `None` if it's the root.
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        Some(self.parent[node]).filter(|&parent| parent != usize::MAX)
+    }
+
+    /// The nodes in the order the search reached them, which is the
+    /// order of their preorder numbers in the algorithm's state.
+    pub fn preorder(&self) -> &[usize] {
+        &self.preorder
+    }
+
+    /// The roots of the trees, one per connected component, in the
+    /// order they were searched from.
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.preorder
+            .iter()
+            .copied()
+            .filter(move |&node| self.parent[node] == usize::MAX)
+    }
+
+    /// The edges of the forest, as `(parent, child)` pairs, in the
+    /// order the children were reached.
+    pub fn tree_edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.preorder
+            .iter()
+            .filter_map(move |&node| Some((self.parent(node)?, node)))
+    }
+}
+
+impl Visitor for SpanningForest {
+    fn on_discover(&mut self, node: usize) {
+        self.preorder.push(node);
+    }
+
+    fn on_tree_edge(&mut self, parent: usize, child: usize) {
+        self.parent[child] = parent;
+    }
+}
@@ -0,0 +1,393 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{adjacency::Adjacency, algorithm};
+
+/// The edge connectivity that the components of this module have.
+const K: usize = 4;
+
+/// Finds the 4-edge-connected components of the graph: two nodes are
+/// in the same component if they stay connected after removing any
+/// three edges. Each one is a subset of a 3-edge-connected component,
+/// which are found first, with `algorithm::find_components`, and then
+/// split with `refine_components`.
+pub fn find_components<G: Adjacency + ?Sized>(graph: &G) -> Vec<Vec<usize>> {
+    refine_components(graph, algorithm::find_components(graph))
+}
+
+/// Splits the 3-edge-connected components of the graph, as returned
+/// by `algorithm::find_components`, into its 4-edge-connected
+/// components, so that the 3-edge-connected components don't have to
+/// be found again if they're already known. The components of each
+/// 3-edge-connected component are in place of it, in the same order.
+///
+/// Each component with more than one node is split with maximum flow
+/// computations between its nodes, with every edge as a unit of
+/// capacity, which stop after four augmenting paths. The nodes are
+/// compared to their closest ancestor in a breadth-first search that
+/// is known to be in the component being built, and each augmenting
+/// path is searched for from both ends, until either side runs out,
+/// so that most of the searches stay local. This still takes O(n m)
+/// time in the worst case, for the n nodes in the components with more
+/// than one node, unlike the linear-time algorithms for
+/// 4-edge-connectivity.
+pub fn refine_components<G: Adjacency + ?Sized>(
+    graph: &G,
+    components: Vec<Vec<usize>>,
+) -> Vec<Vec<usize>> {
+    if components.iter().all(|c| c.len() < 2) {
+        return components;
+    }
+    let mut refiner = Refiner::new(FlowGraph::new(graph));
+
+    let mut refined = Vec::with_capacity(components.len());
+    let mut work = Vec::new();
+    for component in components {
+        if component.len() < 2 {
+            refined.push(component);
+            continue;
+        }
+        work.push(component);
+        while let Some(set) = work.pop() {
+            refined.push(refiner.split(set, &mut work));
+        }
+    }
+    refined
+}
+
+/// The state of `refine_components`, where each set of nodes that may
+/// still be in the same component is marked with its own `round`.
+struct Refiner {
+    flow: FlowGraph,
+    round: usize,
+    // the round of the set each node is in, and of the component
+    // that's being built from it, if the node is known to be in it
+    set: Vec<usize>,
+    class: Vec<usize>,
+    // the breadth-first search from the first node of the set
+    visited: Vec<usize>,
+    tree_parent: Vec<usize>,
+    order: Vec<usize>,
+}
+
+impl Refiner {
+    fn new(flow: FlowGraph) -> Self {
+        let num_nodes = flow.offsets.len() - 1;
+        Refiner {
+            flow,
+            round: 0,
+            set: vec![0; num_nodes],
+            class: vec![0; num_nodes],
+            visited: vec![0; num_nodes],
+            tree_parent: vec![usize::MAX; num_nodes],
+            order: Vec::new(),
+        }
+    }
+
+    /// Returns the component of the first node of the set, and adds
+    /// the nodes that are cut off from it by fewer than K edges to
+    /// `work`, as new sets, since they can't be in the same component.
+    fn split(
+        &mut self,
+        set: Vec<usize>,
+        work: &mut Vec<Vec<usize>>,
+    ) -> Vec<usize> {
+        self.round += 1;
+        let round = self.round;
+        for &node in &set {
+            self.set[node] = round;
+        }
+        let source = set[0];
+        self.search_from(source, set.len());
+
+        self.class[source] = round;
+        let mut class = vec![source];
+        for ix in 1..self.order.len() {
+            let sink = self.order[ix];
+            if self.set[sink] != round {
+                continue;
+            }
+            let mut closest = self.tree_parent[sink];
+            while self.class[closest] != round {
+                closest = self.tree_parent[closest];
+            }
+
+            if self.flow.has_flow(closest, sink) {
+                self.class[sink] = round;
+                class.push(sink);
+                continue;
+            }
+            // the component is on the side of `closest`, as its nodes
+            // are connected by at least K edges
+            let mut split = vec![sink];
+            self.set[sink] = 0;
+            for &node in &self.order[ix + 1..] {
+                if self.set[node] == round && !self.flow.on_source_side(node) {
+                    self.set[node] = 0;
+                    split.push(node);
+                }
+            }
+            work.push(split);
+        }
+        class
+    }
+
+    /// A breadth-first search from the source, which stops once it has
+    /// reached the given number of nodes of the current set, with
+    /// those nodes in `order` in the order they were reached.
+    fn search_from(&mut self, source: usize, set_size: usize) {
+        let round = self.round;
+        let mut queue = vec![source];
+        self.visited[source] = round;
+        self.order.clear();
+        self.order.push(source);
+        let mut next = 0;
+        while self.order.len() < set_size {
+            let node = queue[next];
+            next += 1;
+            let arcs = &self.flow.arcs
+                [self.flow.offsets[node]..self.flow.offsets[node + 1]];
+            for &(neighbor, _) in arcs {
+                if self.visited[neighbor] == round {
+                    continue;
+                }
+                self.visited[neighbor] = round;
+                self.tree_parent[neighbor] = node;
+                queue.push(neighbor);
+                if self.set[neighbor] == round {
+                    self.order.push(neighbor);
+                }
+            }
+        }
+    }
+}
+
+/// Each edge of the graph once, with the arcs of both of its
+/// directions, for the flow computations of `refine_components`.
+struct FlowGraph {
+    // the arcs of each node are `arcs[offsets[n]..offsets[n + 1]]`,
+    // each with the node at its other end and its edge
+    offsets: Vec<usize>,
+    arcs: Vec<(usize, usize)>,
+    // the ends of each edge, whose flow is from the first to the second
+    ends: Vec<(usize, usize)>,
+    // the net flow on each edge, from its first end, as -1, 0, or 1
+    flow: Vec<i8>,
+    changed: Vec<usize>,
+    search: Search,
+}
+
+/// The state of the current search for an augmenting path, from both
+/// the source and the sink. A node has been reached from the source
+/// if its `from_source` is the current `round`, and likewise for the
+/// sink, so that nothing has to be cleared between searches.
+struct Search {
+    round: usize,
+    from_source: Vec<usize>,
+    from_sink: Vec<usize>,
+    // the edge each node was reached by
+    parent: Vec<usize>,
+    source_queue: Vec<usize>,
+    sink_queue: Vec<usize>,
+    // which side was exhausted in the last search that didn't find a
+    // path, which is one side of a minimum cut
+    exhausted_source: bool,
+}
+
+impl FlowGraph {
+    fn new<G: Adjacency + ?Sized>(graph: &G) -> Self {
+        let num_nodes = graph.num_nodes();
+        let mut degrees = vec![0; num_nodes + 1];
+        let mut ends = Vec::new();
+        for from in graph.nodes() {
+            // each edge is in the neighbor lists of both of its ends,
+            // and self-loops can't carry any flow
+            for &to in graph.neighbors(from) {
+                if from < to {
+                    ends.push((from, to));
+                    degrees[from] += 1;
+                    degrees[to] += 1;
+                }
+            }
+        }
+
+        let mut offsets = Vec::with_capacity(num_nodes + 1);
+        let mut sum = 0;
+        for degree in degrees {
+            offsets.push(sum);
+            sum += degree;
+        }
+        let mut next = offsets.clone();
+        let mut arcs = vec![(0, 0); sum];
+        for (edge, &(from, to)) in ends.iter().enumerate() {
+            arcs[next[from]] = (to, edge);
+            next[from] += 1;
+            arcs[next[to]] = (from, edge);
+            next[to] += 1;
+        }
+
+        FlowGraph {
+            offsets,
+            arcs,
+            flow: vec![0; ends.len()],
+            ends,
+            changed: Vec::new(),
+            search: Search {
+                round: 0,
+                from_source: vec![0; num_nodes],
+                from_sink: vec![0; num_nodes],
+                parent: vec![usize::MAX; num_nodes],
+                source_queue: Vec::new(),
+                sink_queue: Vec::new(),
+                exhausted_source: false,
+            },
+        }
+    }
+
+    fn other_end(&self, edge: usize, node: usize) -> usize {
+        let (from, to) = self.ends[edge];
+        if from == node {
+            to
+        } else {
+            from
+        }
+    }
+
+    /// The flow that can still be sent from `from` along the edge.
+    fn residual(&self, from: usize, edge: usize) -> i8 {
+        let flow = self.flow[edge];
+        if self.ends[edge].0 == from {
+            1 - flow
+        } else {
+            1 + flow
+        }
+    }
+
+    fn push(&mut self, from: usize, edge: usize) {
+        if self.ends[edge].0 == from {
+            self.flow[edge] += 1;
+        } else {
+            self.flow[edge] -= 1;
+        }
+        self.changed.push(edge);
+    }
+
+    /// Whether K edge-disjoint paths connect the source and the sink.
+    /// If they don't, `on_source_side` gives the side of a minimum cut
+    /// between them that each node is on, until the next call.
+    fn has_flow(&mut self, source: usize, sink: usize) -> bool {
+        let mut found = true;
+        for _ in 0..K {
+            if !self.augment(source, sink) {
+                found = false;
+                break;
+            }
+        }
+        for edge in self.changed.drain(..) {
+            self.flow[edge] = 0;
+        }
+        found
+    }
+
+    fn on_source_side(&self, node: usize) -> bool {
+        let search = &self.search;
+        if search.exhausted_source {
+            search.from_source[node] == search.round
+        } else {
+            search.from_sink[node] != search.round
+        }
+    }
+
+    /// Searches for an augmenting path and sends a unit of flow along
+    /// it, alternating between the two sides of the search one node
+    /// at a time, or returns false if either side runs out of nodes.
+    fn augment(&mut self, source: usize, sink: usize) -> bool {
+        let search = &mut self.search;
+        search.round += 1;
+        let round = search.round;
+        search.from_source[source] = round;
+        search.from_sink[sink] = round;
+        search.source_queue.clear();
+        search.sink_queue.clear();
+        search.source_queue.push(source);
+        search.sink_queue.push(sink);
+        let (mut source_next, mut sink_next) = (0, 0);
+
+        loop {
+            let node = match self.search.source_queue.get(source_next) {
+                None => {
+                    self.search.exhausted_source = true;
+                    return false;
+                }
+                Some(&node) => node,
+            };
+            source_next += 1;
+            for ix in self.offsets[node]..self.offsets[node + 1] {
+                let (next, edge) = self.arcs[ix];
+                if self.residual(node, edge) == 0
+                    || self.search.from_source[next] == round
+                {
+                    continue;
+                }
+                if self.search.from_sink[next] == round {
+                    self.push(node, edge);
+                    self.push_path(source, sink, node, next);
+                    return true;
+                }
+                self.search.from_source[next] = round;
+                self.search.parent[next] = edge;
+                self.search.source_queue.push(next);
+            }
+
+            let node = match self.search.sink_queue.get(sink_next) {
+                None => {
+                    self.search.exhausted_source = false;
+                    return false;
+                }
+                Some(&node) => node,
+            };
+            sink_next += 1;
+            for ix in self.offsets[node]..self.offsets[node + 1] {
+                let (prev, edge) = self.arcs[ix];
+                // the flow has to be able to go from `prev` to `node`
+                if self.residual(prev, edge) == 0
+                    || self.search.from_sink[prev] == round
+                {
+                    continue;
+                }
+                if self.search.from_source[prev] == round {
+                    self.push(prev, edge);
+                    self.push_path(source, sink, prev, node);
+                    return true;
+                }
+                self.search.from_sink[prev] = round;
+                self.search.parent[prev] = edge;
+                self.search.sink_queue.push(prev);
+            }
+        }
+    }
+
+    /// Sends the flow along the paths of the search from the source to
+    /// `from`, and from `to` to the sink, which `augment` joined.
+    fn push_path(
+        &mut self,
+        source: usize,
+        sink: usize,
+        from: usize,
+        to: usize,
+    ) {
+        let mut node = from;
+        while node != source {
+            let edge = self.search.parent[node];
+            let prev = self.other_end(edge, node);
+            self.push(prev, edge);
+            node = prev;
+        }
+        let mut node = to;
+        while node != sink {
+            let edge = self.search.parent[node];
+            let next = self.other_end(edge, node);
+            self.push(node, edge);
+            node = next;
+        }
+    }
+}
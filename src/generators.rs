@@ -0,0 +1,296 @@
+use std::io::{self, Write};
+
+use crate::graph::Graph;
+
+/// The SplitMix64 pseudorandom number generator, used by the random
+/// graph models. It's small and fast, and the same seed always gives
+/// the same sequence, which is all that's needed for reproducible
+/// graphs.
+#[derive(Debug, Clone)]
+pub struct SplitMix64(pub u64);
+
+impl SplitMix64 {
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a float uniformly distributed in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns an integer uniformly distributed in `0..n`, where `n`
+    /// must be greater than zero.
+    pub fn next_below(&mut self, n: usize) -> usize {
+        (self.next_f64() * n as f64) as usize
+    }
+}
+
+/// The edges of the complete graph on the nodes `offset..offset + n`.
+pub fn complete_edges(offset: usize, n: usize) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+    for i in offset..offset + n {
+        for j in (i + 1)..offset + n {
+            edges.push((i, j));
+        }
+    }
+    edges
+}
+
+/// The edges of the complete bipartite graph between the nodes
+/// `0..k` and `k..k + l`.
+pub fn bipartite_edges(k: usize, l: usize) -> Vec<(usize, usize)> {
+    let mut edges = Vec::with_capacity(k * l);
+    for a in 0..k {
+        for b in 0..l {
+            edges.push((a, k + b));
+        }
+    }
+    edges
+}
+
+/// The edges of the complete graphs on `0..k_a` and `k_a..k_a + k_b`,
+/// connected by `bridges` parallel edges between the last node of
+/// the first and the first node of the second.
+pub fn bridged_k_edges(
+    k_a: usize,
+    k_b: usize,
+    bridges: usize,
+) -> Vec<(usize, usize)> {
+    let mut edges = complete_edges(0, k_a);
+    edges.extend(complete_edges(k_a, k_b));
+    if k_a > 0 && k_b > 0 {
+        edges.extend((0..bridges).map(|_| (k_a - 1, k_a)));
+    }
+    edges
+}
+
+/// The edges of an Erdős–Rényi graph on the nodes `0..n`, where each
+/// of the possible edges is included with probability `p`.
+pub fn erdos_renyi_edges(n: usize, p: f64, seed: u64) -> Vec<(usize, usize)> {
+    let mut rng = SplitMix64(seed);
+    let mut edges = Vec::new();
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if rng.next_f64() < p {
+                edges.push((i, j));
+            }
+        }
+    }
+    edges
+}
+
+/// The edges of the grid graph with the given number of rows and
+/// columns, where node `r * cols + c` is in row `r` and column `c`.
+pub fn grid_edges(rows: usize, cols: usize) -> Vec<(usize, usize)> {
+    let ix = |r: usize, c: usize| r * cols + c;
+    let mut edges = Vec::new();
+    for r in 0..rows {
+        for c in 0..cols {
+            if c + 1 < cols {
+                edges.push((ix(r, c), ix(r, c + 1)));
+            }
+            if r + 1 < rows {
+                edges.push((ix(r, c), ix(r + 1, c)));
+            }
+        }
+    }
+    edges
+}
+
+/// The complete graph on `n` nodes, which is 3-edge-connected for
+/// `n >= 4`.
+pub fn complete_graph(n: usize) -> Graph<usize> {
+    Graph::from_edges(complete_edges(0, n).into_iter())
+}
+
+/// The complete bipartite graph between `k` and `l` nodes.
+pub fn bipartite_graph(k: usize, l: usize) -> Graph<usize> {
+    Graph::from_edges(bipartite_edges(k, l).into_iter())
+}
+
+/// Two complete graphs on `k_a` and `k_b` nodes, connected by
+/// `bridges` parallel edges. With fewer than three bridges, the two
+/// complete graphs are separate 3-edge-connected components.
+pub fn bridged_k_graphs(
+    k_a: usize,
+    k_b: usize,
+    bridges: usize,
+) -> Graph<usize> {
+    Graph::from_edges(bridged_k_edges(k_a, k_b, bridges).into_iter())
+}
+
+/// An Erdős–Rényi random graph on at most `n` nodes; nodes without
+/// edges are not part of the graph.
+pub fn erdos_renyi_graph(n: usize, p: f64, seed: u64) -> Graph<usize> {
+    Graph::from_edges(erdos_renyi_edges(n, p, seed).into_iter())
+}
+
+/// The grid graph with the given number of rows and columns.
+pub fn grid_graph(rows: usize, cols: usize) -> Graph<usize> {
+    Graph::from_edges(grid_edges(rows, cols).into_iter())
+}
+
+/// A random bridgeless graph with a known partition into
+/// 3-edge-connected components, for property-based testing.
+///
+/// The graph is built from complete graphs on at least four nodes,
+/// connected in a random tree. Each tree edge is realized as either
+/// two or three edges between random nodes of the two complete
+/// graphs; two edges form a 2-edge cut that keeps them in separate
+/// components, while three edges merge them. Node names are randomly
+/// permuted, and the edges shuffled, so that the algorithm sees a
+/// different traversal order for each seed.
+#[derive(Debug, Clone)]
+pub struct PlantedPartition {
+    pub edges: Vec<(usize, usize)>,
+    /// The expected components, each sorted, and sorted by their
+    /// first node.
+    pub components: Vec<Vec<usize>>,
+}
+
+impl PlantedPartition {
+    /// Generates a random instance with `num_cliques` complete graphs,
+    /// each on `4..=max_clique_size` nodes.
+    pub fn random(
+        seed: u64,
+        num_cliques: usize,
+        max_clique_size: usize,
+    ) -> PlantedPartition {
+        assert!(max_clique_size >= 4);
+        let mut rng = SplitMix64(seed);
+
+        let mut cliques: Vec<(usize, usize)> = Vec::with_capacity(num_cliques);
+        let mut edges = Vec::new();
+        let mut num_nodes = 0;
+        for _ in 0..num_cliques {
+            let size = 4 + rng.next_below(max_clique_size - 3);
+            edges.extend(complete_edges(num_nodes, size));
+            cliques.push((num_nodes, size));
+            num_nodes += size;
+        }
+
+        // union-find over the cliques, merged by the strong tree edges
+        let mut group: Vec<usize> = (0..num_cliques).collect();
+        fn find(group: &mut [usize], mut x: usize) -> usize {
+            while group[x] != x {
+                group[x] = group[group[x]];
+                x = group[x];
+            }
+            x
+        }
+
+        let random_node = |rng: &mut SplitMix64, clique: usize| {
+            let (offset, size) = cliques[clique];
+            offset + rng.next_below(size)
+        };
+
+        for child in 1..num_cliques {
+            let parent = rng.next_below(child);
+            let strong = rng.next_below(2) == 0;
+            let connections = if strong { 3 } else { 2 };
+            for _ in 0..connections {
+                let from = random_node(&mut rng, parent);
+                let to = random_node(&mut rng, child);
+                edges.push((from, to));
+            }
+            if strong {
+                let (a, b) =
+                    (find(&mut group, parent), find(&mut group, child));
+                group[a] = b;
+            }
+        }
+
+        // random relabeling and edge order
+        let mut labels: Vec<usize> = (0..num_nodes).collect();
+        shuffle(&mut rng, &mut labels);
+        for (from, to) in edges.iter_mut() {
+            *from = labels[*from];
+            *to = labels[*to];
+        }
+        shuffle(&mut rng, &mut edges);
+
+        let mut components: Vec<Vec<usize>> = vec![Vec::new(); num_cliques];
+        for (clique, &(offset, size)) in cliques.iter().enumerate() {
+            let root = find(&mut group, clique);
+            components[root].extend((offset..offset + size).map(|n| labels[n]));
+        }
+        let mut components: Vec<_> =
+            components.into_iter().filter(|c| !c.is_empty()).collect();
+        components.iter_mut().for_each(|c| c.sort_unstable());
+        components.sort_unstable();
+
+        PlantedPartition { edges, components }
+    }
+
+    pub fn graph(&self) -> Graph<usize> {
+        Graph::from_edges(self.edges.iter().copied())
+    }
+}
+
+/// The largest number of nodes and edges in the graphs produced by
+/// `gfa_from_bytes`, small enough for `brute_force::find_components`.
+pub const FUZZ_MAX_NODES: usize = 12;
+pub const FUZZ_MAX_EDGES: usize = 24;
+
+/// Interprets arbitrary bytes as a small multigraph, for fuzzing. The
+/// first byte picks the number of nodes, and each following pair of
+/// bytes is an edge, so any input gives a valid graph, including
+/// self-loops and parallel edges.
+pub fn edges_from_bytes(data: &[u8]) -> Vec<(usize, usize)> {
+    let (first, rest) = match data.split_first() {
+        Some(split) => split,
+        None => return Vec::new(),
+    };
+    let num_nodes = 1 + *first as usize % FUZZ_MAX_NODES;
+    rest.chunks_exact(2)
+        .take(FUZZ_MAX_EDGES)
+        .map(|pair| {
+            (pair[0] as usize % num_nodes, pair[1] as usize % num_nodes)
+        })
+        .collect()
+}
+
+/// Writes the edges as a GFA graph, with a segment for each node and
+/// a link for each edge. The link orientations vary with the edge
+/// index, since they don't affect the components.
+pub fn write_gfa<W: Write>(
+    stream: &mut W,
+    edges: &[(usize, usize)],
+) -> io::Result<()> {
+    writeln!(stream, "H\tVN:Z:1.0")?;
+    let num_nodes = edges.iter().map(|&(a, b)| a.max(b) + 1).max();
+    for node in 0..num_nodes.unwrap_or(0) {
+        writeln!(stream, "S\t{}\t*", node)?;
+    }
+    for (ix, &(from, to)) in edges.iter().enumerate() {
+        let from_orient = if ix % 2 == 0 { '+' } else { '-' };
+        let to_orient = if ix % 3 == 0 { '-' } else { '+' };
+        writeln!(
+            stream,
+            "L\t{}\t{}\t{}\t{}\t0M",
+            from, from_orient, to, to_orient
+        )?;
+    }
+    Ok(())
+}
+
+/// Interprets arbitrary bytes as a GFA graph, combining
+/// `edges_from_bytes` and `write_gfa`.
+pub fn gfa_from_bytes(data: &[u8]) -> Vec<u8> {
+    let mut gfa = Vec::new();
+    write_gfa(&mut gfa, &edges_from_bytes(data)).unwrap();
+    gfa
+}
+
+/// Fisher-Yates shuffle.
+fn shuffle<T>(rng: &mut SplitMix64, slice: &mut [T]) {
+    for i in (1..slice.len()).rev() {
+        let j = rng.next_below(i + 1);
+        slice.swap(i, j);
+    }
+}
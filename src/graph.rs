@@ -1,12 +1,25 @@
-use std::io::prelude::*;
+#[cfg(feature = "gfa")]
+use std::fmt;
+use std::{borrow::Borrow, hash::Hash, io, io::prelude::*};
 
 use fxhash::FxHashMap;
 
-use bstr::io::*;
+#[cfg(feature = "gfa")]
+use bstr::{io::*, ByteSlice};
 
-use gfa::parser::{GFAParser, GFAParserBuilder};
+#[cfg(feature = "gfa")]
+use crate::labels::EdgeLabel;
 
-pub type AdjacencyList = Vec<usize>;
+use crate::{
+    adjacency::Adjacency,
+    labels::EdgeLabels,
+    names::{parse_decimal, NameArena},
+    small_vec::SmallVec,
+};
+
+/// Most nodes have few neighbors, so the first four are stored
+/// inline in the hash map, see `SmallVec`.
+pub type AdjacencyList = SmallVec<usize, 4>;
 pub type FxMapGraph = FxHashMap<usize, AdjacencyList>;
 
 /// An adjacency list representation of a generic graph, including the
@@ -17,6 +30,115 @@ pub type FxMapGraph = FxHashMap<usize, AdjacencyList>;
 pub struct Graph<N> {
     pub graph: FxMapGraph,
     pub inv_names: Vec<N>,
+    /// The labels of the edges, such as the overlaps and tags of the
+    /// GFA links, if the graph was built with them, e.g. by
+    /// `Graph::from_gfa_reader_labeled`.
+    pub edge_labels: Option<EdgeLabels>,
+}
+
+/// Node name types whose heap memory can be measured, for
+/// `Graph::memory_usage`.
+pub trait HeapSize {
+    /// The number of bytes of heap memory owned by the value.
+    fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for usize {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl HeapSize for Vec<u8> {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<N: HeapSize> Graph<N> {
+    /// Returns an estimate of the number of bytes of heap memory used
+    /// by the graph: the hash map and the adjacency lists, the node
+    /// names, and the edge labels.
+    pub fn memory_usage(&self) -> usize {
+        let names = self.inv_names.capacity() * std::mem::size_of::<N>()
+            + self.inv_names.iter().map(|n| n.heap_size()).sum::<usize>();
+        let labels = self.edge_labels.as_ref().map_or(0, |l| l.memory_usage());
+
+        adjacency_memory_usage(&self.graph) + names + labels
+    }
+}
+
+/// The heap memory used by the hash map and the adjacency lists.
+fn adjacency_memory_usage(graph: &FxMapGraph) -> usize {
+    // hashbrown stores each entry with one control byte
+    let map = graph.capacity()
+        * (std::mem::size_of::<usize>()
+            + std::mem::size_of::<AdjacencyList>()
+            + 1);
+    let adjacency: usize = graph.values().map(|adj| adj.heap_size()).sum();
+    map + adjacency
+}
+
+/// Incrementally builds a `Graph<N>` from edges between named nodes,
+/// assigning consecutive indices to the names in the order they are
+/// first seen.
+pub(crate) struct GraphBuilder<N> {
+    graph: FxMapGraph,
+    name_map: FxHashMap<N, usize>,
+    inv_names: Vec<N>,
+}
+
+impl<N: Hash + Eq + Clone> GraphBuilder<N> {
+    pub(crate) fn new() -> Self {
+        GraphBuilder {
+            graph: FxHashMap::default(),
+            name_map: FxHashMap::default(),
+            inv_names: Vec::new(),
+        }
+    }
+
+    pub(crate) fn get_ix<Q>(&mut self, name: &Q) -> usize
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = N> + ?Sized,
+    {
+        if let Some(ix) = self.name_map.get(name) {
+            *ix
+        } else {
+            let ix = self.name_map.len();
+            self.name_map.insert(name.to_owned(), ix);
+            self.inv_names.push(name.to_owned());
+            ix
+        }
+    }
+
+    /// Adds the edge, and returns the indices of its nodes.
+    pub(crate) fn add_edge<Q>(&mut self, from: &Q, to: &Q) -> (usize, usize)
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = N> + ?Sized,
+    {
+        let from_ix = self.get_ix(from);
+        let to_ix = self.get_ix(to);
+
+        self.graph.entry(from_ix).or_default().push(to_ix);
+        self.graph.entry(to_ix).or_default().push(from_ix);
+        (from_ix, to_ix)
+    }
+
+    pub(crate) fn build(self) -> Graph<N> {
+        Graph {
+            graph: self.graph,
+            inv_names: self.inv_names,
+            edge_labels: None,
+        }
+    }
 }
 
 impl Graph<usize> {
@@ -28,30 +150,13 @@ impl Graph<usize> {
     where
         I: Iterator<Item = (usize, usize)>,
     {
-        let mut graph: FxHashMap<usize, AdjacencyList> = FxHashMap::default();
-        let mut name_map: FxHashMap<usize, usize> = FxHashMap::default();
-        let mut inv_names = Vec::new();
-
-        let mut get_ix = |name: usize| {
-            if let Some(ix) = name_map.get(&name) {
-                *ix
-            } else {
-                let ix = name_map.len();
-                name_map.insert(name, ix);
-                inv_names.push(name);
-                ix
-            }
-        };
+        let mut builder = GraphBuilder::new();
 
         for (from, to) in input {
-            let from_ix = get_ix(from);
-            let to_ix = get_ix(to);
-
-            graph.entry(from_ix).or_default().push(to_ix);
-            graph.entry(to_ix).or_default().push(from_ix);
+            builder.add_edge(&from, &to);
         }
 
-        Graph { graph, inv_names }
+        builder.build()
     }
 }
 
@@ -59,46 +164,1137 @@ impl Graph<Vec<u8>> {
     /// Constructs an adjacency list representation of the given GFA
     /// file input stream, parsing the GFA line-by-line and only
     /// keeping the links. Returns the graph as an adjacency list and
-    /// a map from graph indices to GFA segment names.
-    pub fn from_gfa_reader<T: BufRead>(reader: &mut T) -> Graph<Vec<u8>> {
-        let lines = &mut reader.byte_lines();
+    /// a map from graph indices to GFA segment names, or the first
+    /// error encountered when reading from `reader`.
+    #[cfg(feature = "gfa")]
+    pub fn from_gfa_reader<T: BufRead>(
+        reader: &mut T,
+    ) -> io::Result<Graph<Vec<u8>>> {
+        let mut builder = GraphBuilder::new();
+
+        for_each_gfa_edge(reader, None, skip_malformed, |from, to| {
+            builder.add_edge(from, to);
+            Ok(())
+        })?;
+
+        Ok(builder.build())
+    }
+
+    /// Like `Graph::from_gfa_reader`, but also stores the label of
+    /// each edge in `edge_labels`: the overlap of its link, and the
+    /// tags of the link with the given names, e.g. `["ID", "RC"]`,
+    /// which can be empty to only keep the overlaps.
+    #[cfg(feature = "gfa")]
+    pub fn from_gfa_reader_labeled<T: BufRead>(
+        reader: &mut T,
+        tags: &[&str],
+    ) -> io::Result<Graph<Vec<u8>>> {
+        let mut builder = GraphBuilder::new();
+        let mut labels = EdgeLabels::new();
+
+        for_each_gfa_link(reader, skip_malformed, |link| {
+            let (from, to) =
+                builder.add_edge(link.from_segment, link.to_segment);
+            let kept = link.tags.split(|&b| b == b'\t').filter(|tag| {
+                tags.iter().any(|name| {
+                    tag.strip_prefix(name.as_bytes())
+                        .is_some_and(|rest| rest.first() == Some(&b':'))
+                })
+            });
+            let label = EdgeLabel {
+                overlap: link.overlap.to_vec(),
+                tags: kept.map(<[u8]>::to_vec).collect(),
+            };
+            labels.insert(from, to, label);
+            Ok(())
+        })?;
+
+        let mut graph = builder.build();
+        graph.edge_labels = Some(labels);
+        Ok(graph)
+    }
 
-        let parser: GFAParser<Vec<u8>, ()> = GFAParserBuilder {
-            links: true,
-            ..GFAParserBuilder::none()
+    /// Constructs an adjacency list representation from a
+    /// whitespace-delimited edge list, with one edge per line given
+    /// as the names of its two nodes. Blank lines, lines starting
+    /// with `#`, and lines with fewer than two fields are skipped,
+    /// and any fields after the first two are ignored.
+    pub fn from_edge_list_reader<T: BufRead>(
+        reader: &mut T,
+    ) -> io::Result<Graph<Vec<u8>>> {
+        let mut builder = GraphBuilder::new();
+
+        for edge in edge_list_edges(reader) {
+            let (from, to) = edge?;
+            builder.add_edge(from.as_slice(), to.as_slice());
         }
-        .build();
 
-        let gfa_lines =
-            lines.filter_map(move |l| parser.parse_gfa_line(&l.unwrap()).ok());
+        Ok(builder.build())
+    }
+}
 
-        let mut graph: FxHashMap<usize, AdjacencyList> = FxHashMap::default();
-        let mut name_map: FxHashMap<Vec<u8>, usize> = FxHashMap::default();
-        let mut inv_names = Vec::new();
+/// A link line of a GFA file that couldn't be parsed, see
+/// `gfa_links_with`.
+#[cfg(feature = "gfa")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MalformedLine {
+    /// The 1-based number of the line in the file.
+    pub line_number: usize,
+    /// What is wrong with the line.
+    pub message: String,
+}
 
-        let mut get_ix = |name: &[u8]| {
-            if let Some(ix) = name_map.get(name) {
-                *ix
-            } else {
-                let ix = name_map.len();
-                name_map.insert(name.into(), ix);
-                inv_names.push(name.into());
-                ix
+#[cfg(feature = "gfa")]
+impl MalformedLine {
+    /// An `InvalidData` error for the line, to stop parsing at it.
+    pub fn into_error(self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("malformed GFA {}", self),
+        )
+    }
+}
+
+#[cfg(feature = "gfa")]
+impl fmt::Display for MalformedLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line_number, self.message)
+    }
+}
+
+#[cfg(feature = "gfa")]
+fn skip_malformed(_: MalformedLine) -> io::Result<()> {
+    Ok(())
+}
+
+/// Parses the given GFA stream line-by-line, producing the segment
+/// names of each link. This is the parsing step of
+/// `Graph::from_gfa_reader`, which can be combined with
+/// `Graph::from_named_edges` to do the parsing and graph
+/// construction separately. Links that can't be parsed are skipped,
+/// see `gfa_links_with`.
+#[cfg(feature = "gfa")]
+pub fn gfa_links<T: BufRead>(
+    reader: T,
+) -> impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>> {
+    gfa_links_with(reader, skip_malformed)
+}
+
+/// Like `gfa_links`, but calls `on_malformed` with each link line
+/// that can't be parsed, which is then skipped, or ends the parsing
+/// with the error that `on_malformed` returns, e.g. from
+/// `MalformedLine::into_error`. Only the link lines are parsed, so
+/// other lines are skipped without being checked.
+#[cfg(feature = "gfa")]
+pub fn gfa_links_with<T, F>(
+    reader: T,
+    on_malformed: F,
+) -> impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>>
+where
+    T: BufRead,
+    F: FnMut(MalformedLine) -> io::Result<()>,
+{
+    GfaLinkLines::new(reader, None, on_malformed)
+}
+
+/// Like `gfa_links`, but drops the links whose overlap is shorter
+/// than `min_overlap`, see `overlap_length`. Links with an unknown
+/// overlap, `*`, are kept, and an overlap that isn't a valid CIGAR
+/// string is an `InvalidData` error.
+#[cfg(feature = "gfa")]
+pub fn gfa_links_min_overlap<T: BufRead>(
+    reader: T,
+    min_overlap: usize,
+) -> impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>> {
+    gfa_links_min_overlap_with(reader, min_overlap, skip_malformed)
+}
+
+/// Like `gfa_links_min_overlap`, with the malformed lines handled
+/// like in `gfa_links_with`.
+#[cfg(feature = "gfa")]
+pub fn gfa_links_min_overlap_with<T, F>(
+    reader: T,
+    min_overlap: usize,
+    on_malformed: F,
+) -> impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>>
+where
+    T: BufRead,
+    F: FnMut(MalformedLine) -> io::Result<()>,
+{
+    GfaLinkLines::new(reader, Some(min_overlap), on_malformed)
+}
+
+/// A link of a GFA file, borrowed from the line it was parsed from,
+/// see `for_each_gfa_link`.
+#[cfg(feature = "gfa")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GfaLink<'a> {
+    pub from_segment: &'a [u8],
+    pub from_reverse: bool,
+    pub to_segment: &'a [u8],
+    pub to_reverse: bool,
+    /// The overlap, a CIGAR string or `*`, see `overlap_length`.
+    pub overlap: &'a [u8],
+    /// The optional tags after the overlap, separated by tabs, or
+    /// nothing.
+    pub tags: &'a [u8],
+}
+
+/// Parses a GFA line if it's a link, or returns `Ok(None)` otherwise.
+/// Only the link lines are split into fields, the other lines, most
+/// of which are segments with their sequences, are skipped by their
+/// first byte. The record type must be at the start of the line, as in
+/// the GFA spec, and the tags after the overlap are only split off.
+#[cfg(feature = "gfa")]
+fn parse_link(line: &[u8]) -> Result<Option<GfaLink<'_>>, &'static str> {
+    if line.first() != Some(&b'L') {
+        return Ok(None);
+    }
+    let mut fields = line.trim_end().splitn_str(7, "\t");
+    if fields.next() != Some(b"L") {
+        return Ok(None);
+    }
+    let mut field = || fields.next().ok_or("missing required fields");
+    let orientation = |orient: &[u8]| match orient {
+        b"+" => Ok(false),
+        b"-" => Ok(true),
+        _ => Err("invalid orientation"),
+    };
+
+    Ok(Some(GfaLink {
+        from_segment: segment_name(field()?)?,
+        from_reverse: orientation(field()?)?,
+        to_segment: segment_name(field()?)?,
+        to_reverse: orientation(field()?)?,
+        overlap: field()?,
+        tags: fields.next().unwrap_or_default(),
+    }))
+}
+
+/// Checks that a segment name is printable ASCII that doesn't start
+/// with `*` or `=`, as in the GFA spec.
+#[cfg(feature = "gfa")]
+fn segment_name(name: &[u8]) -> Result<&[u8], &'static str> {
+    let printable = |b: &u8| (b'!'..=b'~').contains(b);
+    match name.split_first() {
+        Some((first, rest))
+            if printable(first)
+                && !matches!(first, b'*' | b'=')
+                && rest.iter().all(printable) =>
+        {
+            Ok(name)
+        }
+        _ => Err("invalid segment name"),
+    }
+}
+
+/// Whether a link is kept with the given `min_overlap`, see
+/// `gfa_links_min_overlap`.
+#[cfg(feature = "gfa")]
+fn keep_link(
+    link: &GfaLink<'_>,
+    min_overlap: Option<usize>,
+) -> io::Result<bool> {
+    match min_overlap {
+        None => Ok(true),
+        Some(min_overlap) => Ok(overlap_length(link.overlap)?
+            .is_none_or(|length| length >= min_overlap)),
+    }
+}
+
+/// Calls `f` with each link of the given GFA stream. The links borrow
+/// from a buffer that is reused for every line, so nothing is
+/// allocated while parsing, and the names only need to be copied when
+/// they're new, e.g. into a `NameArena`; this is how the graphs are
+/// built by their `from_gfa_reader` constructors. The malformed lines
+/// are handled like in `gfa_links_with`, and the first error from `f`
+/// or `on_malformed` ends the parsing.
+#[cfg(feature = "gfa")]
+pub fn for_each_gfa_link<T, F, G>(
+    reader: T,
+    mut on_malformed: F,
+    mut f: G,
+) -> io::Result<()>
+where
+    T: BufRead,
+    F: FnMut(MalformedLine) -> io::Result<()>,
+    G: FnMut(GfaLink<'_>) -> io::Result<()>,
+{
+    let mut line_number = 0;
+    reader.for_byte_line(|line| {
+        line_number += 1;
+        match parse_link(line) {
+            Ok(Some(link)) => f(link)?,
+            Ok(None) => (),
+            Err(message) => on_malformed(MalformedLine {
+                line_number,
+                message: message.to_string(),
+            })?,
+        }
+        Ok(true)
+    })
+}
+
+/// Like `for_each_gfa_link`, but only with the segment names of the
+/// links that are kept with the given `min_overlap`.
+#[cfg(feature = "gfa")]
+fn for_each_gfa_edge<T, F, G>(
+    reader: T,
+    min_overlap: Option<usize>,
+    on_malformed: F,
+    mut f: G,
+) -> io::Result<()>
+where
+    T: BufRead,
+    F: FnMut(MalformedLine) -> io::Result<()>,
+    G: FnMut(&[u8], &[u8]) -> io::Result<()>,
+{
+    for_each_gfa_link(reader, on_malformed, |link| {
+        if keep_link(&link, min_overlap)? {
+            f(link.from_segment, link.to_segment)?;
+        }
+        Ok(())
+    })
+}
+
+/// The iterator of `gfa_links_with` and `gfa_links_min_overlap_with`,
+/// which reads the lines into a reused buffer and only allocates the
+/// two names of each link.
+#[cfg(feature = "gfa")]
+struct GfaLinkLines<T, F> {
+    reader: T,
+    min_overlap: Option<usize>,
+    on_malformed: F,
+    line: Vec<u8>,
+    line_number: usize,
+}
+
+#[cfg(feature = "gfa")]
+impl<T, F> GfaLinkLines<T, F> {
+    fn new(reader: T, min_overlap: Option<usize>, on_malformed: F) -> Self {
+        GfaLinkLines {
+            reader,
+            min_overlap,
+            on_malformed,
+            line: Vec::new(),
+            line_number: 0,
+        }
+    }
+}
+
+#[cfg(feature = "gfa")]
+impl<T, F> Iterator for GfaLinkLines<T, F>
+where
+    T: BufRead,
+    F: FnMut(MalformedLine) -> io::Result<()>,
+{
+    type Item = io::Result<(Vec<u8>, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Err(err) => return Some(Err(err)),
+                Ok(0) => return None,
+                Ok(_) => self.line_number += 1,
+            }
+            match parse_link(&self.line) {
+                Ok(Some(link)) => match keep_link(&link, self.min_overlap) {
+                    Ok(true) => {
+                        let names = (
+                            link.from_segment.to_vec(),
+                            link.to_segment.to_vec(),
+                        );
+                        return Some(Ok(names));
+                    }
+                    Ok(false) => (),
+                    Err(err) => return Some(Err(err)),
+                },
+                Ok(None) => (),
+                Err(message) => {
+                    let malformed = MalformedLine {
+                        line_number: self.line_number,
+                        message: message.to_string(),
+                    };
+                    if let Err(err) = (self.on_malformed)(malformed) {
+                        return Some(Err(err));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parses the overlap of a GFA link, a CIGAR string such as `10M` or
+/// `5M1I4M`, and returns its length, which is the larger of the
+/// number of bases it covers on each of the two segments. Returns
+/// `None` if the overlap is `*`, i.e. unknown.
+#[cfg(feature = "gfa")]
+pub fn overlap_length(overlap: &[u8]) -> io::Result<Option<usize>> {
+    if overlap == b"*" {
+        return Ok(None);
+    }
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid link overlap {:?}", overlap.as_bstr()),
+        )
+    };
+
+    let (mut from_length, mut to_length) = (0usize, 0usize);
+    let mut count: Option<usize> = None;
+    for &b in overlap {
+        if b.is_ascii_digit() {
+            let digit = (b - b'0') as usize;
+            count = count
+                .unwrap_or(0)
+                .checked_mul(10)
+                .and_then(|c| c.checked_add(digit));
+            if count.is_none() {
+                return Err(invalid());
             }
+            continue;
+        }
+
+        let count = count.take().ok_or_else(invalid)?;
+        match b {
+            b'M' | b'=' | b'X' => {
+                from_length += count;
+                to_length += count;
+            }
+            b'D' | b'N' => from_length += count,
+            b'I' | b'S' => to_length += count,
+            b'H' | b'P' => (),
+            _ => return Err(invalid()),
+        }
+    }
+    if count.is_some() {
+        return Err(invalid());
+    }
+
+    Ok(Some(from_length.max(to_length)))
+}
+
+/// Parses the given edge list line-by-line, producing the node names
+/// of each edge. See `Graph::from_edge_list_reader` for the format.
+pub fn edge_list_edges<T: BufRead>(
+    reader: T,
+) -> impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>> {
+    reader.split(b'\n').filter_map(|line| {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => return Some(Err(err)),
+        };
+        if line.first() == Some(&b'#') {
+            return None;
+        }
+
+        let mut fields = line
+            .split(|b| b.is_ascii_whitespace())
+            .filter(|f| !f.is_empty());
+
+        match (fields.next(), fields.next()) {
+            (Some(from), Some(to)) => Some(Ok((from.to_vec(), to.to_vec()))),
+            _ => None,
+        }
+    })
+}
+
+/// How the lines of a table of edges, such as a CSV export, are split
+/// into the two nodes of each edge, for `edge_table_edges`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgeTable {
+    /// The field delimiter, e.g. `b','`, or `None` to split the fields
+    /// at runs of whitespace, like `edge_list_edges`. With a delimiter,
+    /// fields in double quotes can contain it, with `""` for a quote.
+    pub delimiter: Option<u8>,
+    /// The column of the first node of each edge, from 0.
+    pub from: usize,
+    /// The column of the second node of each edge, from 0.
+    pub to: usize,
+    /// Whether the first line is a header row, which is skipped.
+    pub header: bool,
+}
+
+impl Default for EdgeTable {
+    fn default() -> Self {
+        EdgeTable {
+            delimiter: None,
+            from: 0,
+            to: 1,
+            header: false,
+        }
+    }
+}
+
+/// Splits the line into its fields, unquoting the quoted ones.
+fn split_table_line(line: &[u8], delimiter: u8) -> Vec<Vec<u8>> {
+    let mut fields = Vec::new();
+    let mut field = Vec::new();
+    let mut quoted = false;
+    let mut bytes = line.iter().copied().peekable();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'"' if quoted && bytes.peek() == Some(&b'"') => {
+                field.push(b'"');
+                bytes.next();
+            }
+            b'"' if quoted => quoted = false,
+            b'"' if field.is_empty() => quoted = true,
+            _ if b == delimiter && !quoted => {
+                fields.push(std::mem::take(&mut field))
+            }
+            _ => field.push(b),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Parses the given table of edges line-by-line, producing the node
+/// names in the two chosen columns of each line. Blank lines and lines
+/// starting with `#` are skipped, as is the header row. A line without
+/// both columns, or with an empty name in either, is an `InvalidData`
+/// error, since the columns were chosen for every line.
+pub fn edge_table_edges<T: BufRead>(
+    reader: T,
+    table: EdgeTable,
+) -> impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>> {
+    let mut header = table.header;
+    reader.split(b'\n').filter_map(move |line| {
+        let mut line = match line {
+            Ok(line) => line,
+            Err(err) => return Some(Err(err)),
+        };
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if std::mem::take(&mut header)
+            || line.first() == Some(&b'#')
+            || line.trim_ascii().is_empty()
+        {
+            return None;
+        }
+
+        let fields = match table.delimiter {
+            None => line
+                .split(|b| b.is_ascii_whitespace())
+                .filter(|f| !f.is_empty())
+                .map(<[u8]>::to_vec)
+                .collect(),
+            Some(delimiter) => split_table_line(&line, delimiter),
+        };
+        let field = |column: usize| {
+            fields.get(column).filter(|name| !name.is_empty()).cloned()
+        };
+        match (field(table.from), field(table.to)) {
+            (Some(from), Some(to)) => Some(Ok((from, to))),
+            _ => Some(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "missing node in columns {} and {} of edge line {:?}",
+                    table.from + 1,
+                    table.to + 1,
+                    String::from_utf8_lossy(&line)
+                ),
+            ))),
+        }
+    })
+}
+
+/// Parses the given adjacency list line-by-line, producing the node
+/// names of each edge from the node of a line to each of its
+/// neighbors. Each line is a node, then a colon, then its neighbors,
+/// separated by whitespace, e.g. `a: b c`; the colon is the first one
+/// followed by whitespace or the end of the line, so names can contain
+/// colons too. Lines starting with `#` and empty lines are skipped, and
+/// other lines without a colon are an `InvalidData` error. The edges
+/// are directed, so an edge that is listed from both of its nodes, as
+/// it usually is, is produced twice; `symmetrize_edges` with
+/// `Symmetrize::Reciprocated` reads each such pair as one edge.
+pub fn adjacency_list_edges<T: BufRead>(
+    reader: T,
+) -> impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>> {
+    reader.split(b'\n').flat_map(|line| {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => return vec![Err(err)],
+        };
+        let line = line.trim_ascii();
+        if line.is_empty() || line[0] == b'#' {
+            return Vec::new();
+        }
+
+        let colon = (0..line.len()).find(|&ix| {
+            line[ix] == b':'
+                && line.get(ix + 1).is_none_or(u8::is_ascii_whitespace)
+        });
+        let node = colon.map(|ix| line[..ix].trim_ascii());
+        match (node, colon) {
+            (Some(node), Some(colon)) if !node.is_empty() => line[colon + 1..]
+                .split(|b| b.is_ascii_whitespace())
+                .filter(|f| !f.is_empty())
+                .map(|neighbor| Ok((node.to_vec(), neighbor.to_vec())))
+                .collect(),
+            _ => vec![Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "invalid adjacency list line {:?}, expected `node: \
+                     neighbors`",
+                    String::from_utf8_lossy(line)
+                ),
+            ))],
+        }
+    })
+}
+
+/// How to read edges that are directed, e.g. from an edge list of a
+/// directed graph, where `a b` and `b a` are different edges, since
+/// the algorithm only works on undirected graphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetrize {
+    /// Ignore the directions, so that an edge given in both
+    /// directions is two parallel edges. This is how edges are read
+    /// without `symmetrize_edges`.
+    Undirected,
+    /// Only keep the edges that are also given in the opposite
+    /// direction, each such pair as one undirected edge. Self-loops
+    /// are their own opposite, so they're all kept.
+    Reciprocated,
+    /// Like `Undirected`, but an edge that is also given in the
+    /// opposite direction is an `InvalidData` error, for inputs that
+    /// must not be directed.
+    Reject,
+}
+
+/// Applies the `Symmetrize` policy to the directed edges from e.g.
+/// `edge_list_edges`. The edges are produced as they're read, except
+/// that, with `Reciprocated`, each pair of opposite edges is produced
+/// when the second one is read. Keeps a hash map of the edges seen so
+/// far, other than with `Undirected`.
+pub fn symmetrize_edges<I>(
+    edges: I,
+    policy: Symmetrize,
+) -> impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>>
+where
+    I: IntoIterator<Item = io::Result<(Vec<u8>, Vec<u8>)>>,
+{
+    // the number of edges in each direction that haven't been
+    // matched with an opposite edge yet
+    let mut unmatched: FxHashMap<(Vec<u8>, Vec<u8>), usize> =
+        FxHashMap::default();
+
+    edges.into_iter().filter_map(move |edge| {
+        let (from, to) = match edge {
+            Ok(edge) => edge,
+            Err(err) => return Some(Err(err)),
         };
+        if policy == Symmetrize::Undirected || from == to {
+            return Some(Ok((from, to)));
+        }
+
+        // `Undirected` was handled above, so `_` is `Reject` below
+        let reverse = (to, from);
+        match (policy, unmatched.get_mut(&reverse)) {
+            (Symmetrize::Reciprocated, Some(count)) => {
+                *count -= 1;
+                if *count == 0 {
+                    unmatched.remove(&reverse);
+                }
+                Some(Ok(reverse))
+            }
+            (_, Some(_)) => Some(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "edge {} {} is also given in the opposite direction",
+                    String::from_utf8_lossy(&reverse.1),
+                    String::from_utf8_lossy(&reverse.0)
+                ),
+            ))),
+            (Symmetrize::Reciprocated, None) => {
+                let (to, from) = reverse;
+                *unmatched.entry((from, to)).or_insert(0) += 1;
+                None
+            }
+            (_, None) => {
+                let (to, from) = reverse;
+                unmatched.insert((from.clone(), to.clone()), 1);
+                Some(Ok((from, to)))
+            }
+        }
+    })
+}
+
+impl<N: Hash + Eq + Clone> Graph<N> {
+    /// Construct an adjacency graph from an iterator over edges
+    /// between named nodes, assigning consecutive indices to the
+    /// names in the order they are first seen.
+    pub fn from_named_edges<I>(input: I) -> Graph<N>
+    where
+        I: IntoIterator<Item = (N, N)>,
+    {
+        let mut builder = GraphBuilder::new();
+
+        for (from, to) in input {
+            builder.add_edge(&from, &to);
+        }
+
+        builder.build()
+    }
+
+    /// Returns the subgraph induced by the given nodes, i.e. the
+    /// nodes together with all edges between them. Names that don't
+    /// exist in the graph are ignored. The nodes keep their relative
+    /// index order, and nodes without any edges in the subgraph are
+    /// kept with empty adjacency lists.
+    pub fn induced_subgraph<'a, I>(&self, nodes: I) -> Graph<N>
+    where
+        I: IntoIterator<Item = &'a N>,
+        N: 'a,
+    {
+        let name_map: FxHashMap<&N, usize> = self
+            .inv_names
+            .iter()
+            .enumerate()
+            .map(|(ix, name)| (name, ix))
+            .collect();
+
+        let mut keep = vec![false; self.inv_names.len()];
+        for name in nodes {
+            if let Some(&ix) = name_map.get(name) {
+                keep[ix] = true;
+            }
+        }
+
+        let (graph, kept) = induced_adjacency(&self.graph, &keep);
+        let inv_names =
+            kept.iter().map(|&ix| self.inv_names[ix].clone()).collect();
+        let edge_labels = self
+            .edge_labels
+            .as_ref()
+            .map(|labels| labels.remap(|ix| kept.binary_search(&ix).ok()));
 
-        for line in gfa_lines {
-            if let gfa::gfa::Line::Link(link) = line {
-                let from_ix = get_ix(link.from_segment.as_ref());
-                let to_ix = get_ix(link.to_segment.as_ref());
+        Graph {
+            graph,
+            inv_names,
+            edge_labels,
+        }
+    }
+}
+
+impl<N> Graph<N> {
+    /// Removes one edge between the nodes with the given indices, and
+    /// returns false if there is no such edge. The nodes are kept,
+    /// even without any edges left, so that the node indices and
+    /// names stay the same, and the algorithm can be run again with a
+    /// reused `State`, see `State::reset`. The label of the last edge
+    /// added between the nodes is removed with it.
+    pub fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        let removed = remove_adjacency_edge(&mut self.graph, from, to);
+        if let (true, Some(labels)) = (removed, &mut self.edge_labels) {
+            labels.remove(from, to);
+        }
+        removed
+    }
+}
+
+/// Removes one edge from the adjacency lists of both its ends, where
+/// self-loops are in the list twice.
+fn remove_adjacency_edge(
+    graph: &mut FxMapGraph,
+    from: usize,
+    to: usize,
+) -> bool {
+    let position = |graph: &FxMapGraph, w: usize, u: usize| {
+        graph
+            .get(&w)
+            .and_then(|adj| adj.iter().position(|&x| x == u))
+    };
+
+    match position(graph, from, to) {
+        None => false,
+        Some(ix) => {
+            graph.get_mut(&from).unwrap().swap_remove(ix);
+            let ix = position(graph, to, from).unwrap();
+            graph.get_mut(&to).unwrap().swap_remove(ix);
+            true
+        }
+    }
+}
+
+/// Returns the subgraph of `graph` induced by the nodes marked in
+/// `keep`, and the original indices of the kept nodes, which are given
+/// consecutive indices in the same order.
+fn induced_adjacency(
+    graph: &FxMapGraph,
+    keep: &[bool],
+) -> (FxMapGraph, Vec<usize>) {
+    let mut new_ix = vec![usize::MAX; keep.len()];
+    let mut kept = Vec::new();
+    for (ix, _) in keep.iter().enumerate().filter(|(_, &k)| k) {
+        new_ix[ix] = kept.len();
+        kept.push(ix);
+    }
+
+    let mut subgraph = FxMapGraph::default();
+    for (new, &ix) in kept.iter().enumerate() {
+        let adj = graph.get(&ix).map(|a| a.as_slice()).unwrap_or(&[]);
+        let new_adj = adj
+            .iter()
+            .filter(|&&u| new_ix[u] != usize::MAX)
+            .map(|&u| new_ix[u])
+            .collect();
+        subgraph.insert(new, new_adj);
+    }
+
+    (subgraph, kept)
+}
 
-                graph.entry(from_ix).or_default().push(to_ix);
-                graph.entry(to_ix).or_default().push(from_ix);
+/// Returns the edges of the subgraph induced by each component, with
+/// the nodes numbered by their position in the component, so that
+/// `components[i][a]` and `components[i][b]` are the ends of each
+/// edge `(a, b)` in the `i`th list. Parallel edges are kept, and
+/// self-loops are included once.
+///
+/// This is the input expected by the constructors of other graph
+/// libraries, e.g. `petgraph::graph::UnGraph::<(), ()>::from_edges`,
+/// for running other algorithms on each component.
+pub fn component_edges<G: Adjacency + ?Sized>(
+    graph: &G,
+    components: &[Vec<usize>],
+) -> Vec<Vec<(usize, usize)>> {
+    let mut local_ix = vec![usize::MAX; graph.num_nodes()];
+
+    components
+        .iter()
+        .map(|component| {
+            for (ix, &node) in component.iter().enumerate() {
+                local_ix[node] = ix;
+            }
+
+            let mut edges = Vec::new();
+            for (a, &from) in component.iter().enumerate() {
+                // each edge is in the adjacency lists of both its
+                // ends, and self-loops are in the same list twice
+                let mut self_loop = false;
+                for &to in graph.neighbors(from) {
+                    let b = local_ix[to];
+                    if b == usize::MAX || b < a {
+                        continue;
+                    }
+                    if b == a {
+                        self_loop = !self_loop;
+                        if !self_loop {
+                            continue;
+                        }
+                    }
+                    edges.push((a, b));
+                }
+            }
+
+            for &node in component.iter() {
+                local_ix[node] = usize::MAX;
             }
+            edges
+        })
+        .collect()
+}
+
+/// Like `Graph<Vec<u8>>`, but with the node names interned in a
+/// `NameArena`, which uses much less memory for graphs with many
+/// nodes.
+#[derive(Default)]
+pub struct InternedGraph {
+    pub graph: FxMapGraph,
+    pub names: NameArena,
+}
+
+impl InternedGraph {
+    /// Construct an adjacency graph from edges between named nodes,
+    /// assigning consecutive indices to the names in the order they
+    /// are first seen.
+    pub fn from_named_edges<I, B>(input: I) -> InternedGraph
+    where
+        I: IntoIterator<Item = (B, B)>,
+        B: AsRef<[u8]>,
+    {
+        let mut graph = InternedGraph::default();
+        for (from, to) in input {
+            graph.add_edge(from.as_ref(), to.as_ref());
         }
+        graph
+    }
+
+    /// Like `InternedGraph::from_named_edges`, but returns the first
+    /// error from the input, e.g. from `gfa_links`.
+    pub fn try_from_named_edges<I, B>(input: I) -> io::Result<InternedGraph>
+    where
+        I: IntoIterator<Item = io::Result<(B, B)>>,
+        B: AsRef<[u8]>,
+    {
+        let mut graph = InternedGraph::default();
+        for edge in input {
+            let (from, to) = edge?;
+            graph.add_edge(from.as_ref(), to.as_ref());
+        }
+        Ok(graph)
+    }
+
+    /// Like `Graph::from_gfa_reader`.
+    #[cfg(feature = "gfa")]
+    pub fn from_gfa_reader<T: BufRead>(
+        reader: &mut T,
+    ) -> io::Result<InternedGraph> {
+        InternedGraph::from_gfa_reader_with(reader, None, skip_malformed)
+    }
+
+    /// Like `InternedGraph::from_gfa_reader`, but with the links
+    /// shorter than `min_overlap` dropped, if it's given, as in
+    /// `gfa_links_min_overlap`, and the malformed lines handled like
+    /// in `gfa_links_with`.
+    #[cfg(feature = "gfa")]
+    pub fn from_gfa_reader_with<T, F>(
+        reader: T,
+        min_overlap: Option<usize>,
+        on_malformed: F,
+    ) -> io::Result<InternedGraph>
+    where
+        T: BufRead,
+        F: FnMut(MalformedLine) -> io::Result<()>,
+    {
+        let mut graph = InternedGraph::default();
+        for_each_gfa_edge(reader, min_overlap, on_malformed, |from, to| {
+            graph.add_edge(from, to);
+            Ok(())
+        })?;
+        Ok(graph)
+    }
+
+    /// Like `Graph::from_edge_list_reader`.
+    pub fn from_edge_list_reader<T: BufRead>(
+        reader: &mut T,
+    ) -> io::Result<InternedGraph> {
+        InternedGraph::try_from_named_edges(edge_list_edges(reader))
+    }
+
+    fn add_edge(&mut self, from: &[u8], to: &[u8]) {
+        let from_ix = self.names.get_or_insert(from);
+        let to_ix = self.names.get_or_insert(to);
+
+        self.graph.entry(from_ix).or_default().push(to_ix);
+        self.graph.entry(to_ix).or_default().push(from_ix);
+    }
+
+    /// Like `Graph::remove_edge`.
+    pub fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        remove_adjacency_edge(&mut self.graph, from, to)
+    }
+
+    /// Removes one edge between the nodes with the given names, and
+    /// returns false if either name or the edge doesn't exist.
+    pub fn remove_named_edge(&mut self, from: &[u8], to: &[u8]) -> bool {
+        match (self.names.find(from), self.names.find(to)) {
+            (Some(from), Some(to)) => self.remove_edge(from, to),
+            _ => false,
+        }
+    }
+
+    /// Like `Graph::induced_subgraph`.
+    pub fn induced_subgraph<'a, I>(&self, nodes: I) -> InternedGraph
+    where
+        I: IntoIterator<Item = &'a [u8]>,
+    {
+        let mut keep = vec![false; self.names.len()];
+        for name in nodes {
+            if let Some(ix) = self.names.find(name) {
+                keep[ix] = true;
+            }
+        }
+
+        let (graph, kept) = induced_adjacency(&self.graph, &keep);
+        let mut names = NameArena::new();
+        for &ix in kept.iter() {
+            names.get_or_insert(self.names.get(ix));
+        }
+
+        InternedGraph { graph, names }
+    }
+
+    /// Like `Graph::memory_usage`.
+    pub fn memory_usage(&self) -> usize {
+        adjacency_memory_usage(&self.graph) + self.names.memory_usage()
+    }
+}
+
+/// A graph whose node names are integers, which are used directly as
+/// the node indices, so that no names are stored at all. Every index
+/// up to the largest name is a node, with the unused ones having no
+/// edges, so this is only smaller than `InternedGraph` when the names
+/// are dense, e.g. `1` to `n`.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct IndexedGraph {
+    pub adjacency: Vec<AdjacencyList>,
+}
+
+impl IndexedGraph {
+    pub fn from_edges<I>(input: I) -> IndexedGraph
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        let mut graph = IndexedGraph::default();
+        for (from, to) in input {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+
+    /// Constructs the graph from edges between nodes whose names are
+    /// parsed with `parse_index`, returning the first error from the
+    /// input or a name that isn't an integer.
+    pub fn from_numeric_edges<I, B>(input: I) -> io::Result<IndexedGraph>
+    where
+        I: IntoIterator<Item = io::Result<(B, B)>>,
+        B: AsRef<[u8]>,
+    {
+        let mut graph = IndexedGraph::default();
+        for (num_edges, edge) in input.into_iter().enumerate() {
+            let (from, to) = edge?;
+            graph.add_numeric_edge(
+                parse_index(from.as_ref())?,
+                parse_index(to.as_ref())?,
+                num_edges,
+            )?;
+        }
+        Ok(graph)
+    }
+
+    /// Like `Graph::from_gfa_reader`, but the segment names must be
+    /// integers.
+    #[cfg(feature = "gfa")]
+    pub fn from_gfa_reader<T: BufRead>(
+        reader: &mut T,
+    ) -> io::Result<IndexedGraph> {
+        IndexedGraph::from_gfa_reader_with(reader, None, skip_malformed)
+    }
+
+    /// Like `InternedGraph::from_gfa_reader_with`, but the segment
+    /// names must be integers.
+    #[cfg(feature = "gfa")]
+    pub fn from_gfa_reader_with<T, F>(
+        reader: T,
+        min_overlap: Option<usize>,
+        on_malformed: F,
+    ) -> io::Result<IndexedGraph>
+    where
+        T: BufRead,
+        F: FnMut(MalformedLine) -> io::Result<()>,
+    {
+        let mut graph = IndexedGraph::default();
+        let mut num_edges = 0;
+        for_each_gfa_edge(reader, min_overlap, on_malformed, |from, to| {
+            let (from, to) = (parse_index(from)?, parse_index(to)?);
+            graph.add_numeric_edge(from, to, num_edges)?;
+            num_edges += 1;
+            Ok(())
+        })?;
+        Ok(graph)
+    }
+
+    /// Like `Graph::from_edge_list_reader`, but the node names must
+    /// be integers.
+    pub fn from_edge_list_reader<T: BufRead>(
+        reader: &mut T,
+    ) -> io::Result<IndexedGraph> {
+        IndexedGraph::from_numeric_edges(edge_list_edges(reader))
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize) {
+        if from.max(to) >= self.adjacency.len() {
+            self.adjacency
+                .resize_with(from.max(to) + 1, Default::default);
+        }
+        self.adjacency[from].push(to);
+        self.adjacency[to].push(from);
+    }
+
+    /// Adds an edge between nodes named in the input, after
+    /// `num_edges` others, checking them with `check_index` first.
+    fn add_numeric_edge(
+        &mut self,
+        from: usize,
+        to: usize,
+        num_edges: usize,
+    ) -> io::Result<()> {
+        check_index(from, num_edges)?;
+        check_index(to, num_edges)?;
+        self.add_edge(from, to);
+        Ok(())
+    }
+
+    /// Like `Graph::remove_edge`.
+    pub fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        let position = |graph: &IndexedGraph, w: usize, u: usize| {
+            graph.adjacency.get(w)?.iter().position(|&x| x == u)
+        };
+
+        match position(self, from, to) {
+            None => false,
+            Some(ix) => {
+                self.adjacency[from].swap_remove(ix);
+                let ix = position(self, to, from).unwrap();
+                self.adjacency[to].swap_remove(ix);
+                true
+            }
+        }
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.adjacency.iter().map(|adj| adj.len()).sum::<usize>() / 2
+    }
+
+    /// Like `Graph::memory_usage`, without any names.
+    pub fn memory_usage(&self) -> usize {
+        self.adjacency.capacity() * std::mem::size_of::<AdjacencyList>()
+            + self
+                .adjacency
+                .iter()
+                .map(|adj| adj.heap_size())
+                .sum::<usize>()
+    }
+}
+
+/// Parses a node name as a node index, for `IndexedGraph`. Only
+/// plain decimal integers are accepted, without a sign or leading
+/// zeros, so that formatting the index gives back the same name.
+pub fn parse_index(name: &[u8]) -> io::Result<usize> {
+    parse_decimal(name).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "node name {:?} is not an integer",
+                String::from_utf8_lossy(name)
+            ),
+        )
+    })
+}
 
-        Graph { graph, inv_names }
+/// Returns an `InvalidData` error if the node index is so much larger
+/// than the number of edges read so far that allocating a node for
+/// every index up to it would likely run out of memory, as it would
+/// for a single large name, e.g. a hash. Such names can't be used as
+/// indices, by `IndexedGraph` or `MmapGraph::build_numeric`.
+pub fn check_index(index: usize, num_edges: usize) -> io::Result<usize> {
+    let max_index = num_edges.saturating_mul(4).saturating_add(1 << 24);
+    if index > max_index {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "node name {} is too large to be used as an index, after {} edges",
+                index, num_edges
+            ),
+        ));
     }
+    Ok(index)
 }
 
 impl<N: Clone> Graph<N> {
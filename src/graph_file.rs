@@ -0,0 +1,328 @@
+use std::{
+    convert::{TryFrom, TryInto},
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::{
+    graph::{AdjacencyList, FxMapGraph, Graph, InternedGraph},
+    names::NameArena,
+};
+
+/// The first bytes of a graph file, for telling it apart from the text
+/// formats.
+pub const MAGIC: &[u8; 8] = b"3ECGRPH\0";
+const VERSION: u64 = 1;
+
+/// Whether the bytes are the start of a graph file written by
+/// `InternedGraph::save`.
+pub fn is_graph_file(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid graph file: {}", message),
+    )
+}
+
+fn write_u64<W: Write + ?Sized>(writer: &mut W, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn read_u64<R: Read + ?Sized>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_usize<R: Read + ?Sized>(reader: &mut R) -> io::Result<usize> {
+    usize::try_from(read_u64(reader)?)
+        .map_err(|_| invalid("value out of range"))
+}
+
+/// Writes the graph with the nodes in index order: a header, the end
+/// of each name in the name bytes, the name bytes, then the offsets of
+/// the neighbors of each node and the neighbors, as a CSR adjacency.
+/// The neighbors are 4 bytes each, unless there are too many nodes,
+/// and all the other values are 8 bytes, little-endian.
+fn write_graph<'a, W, N, A>(
+    writer: &mut W,
+    num_nodes: usize,
+    name: N,
+    neighbors: A,
+) -> io::Result<()>
+where
+    W: Write + ?Sized,
+    N: Fn(usize) -> &'a [u8],
+    A: Fn(usize) -> &'a [usize],
+{
+    let name_bytes: usize = (0..num_nodes).map(|ix| name(ix).len()).sum();
+    let num_neighbors: usize =
+        (0..num_nodes).map(|ix| neighbors(ix).len()).sum();
+    let width = if num_nodes <= u32::MAX as usize { 4 } else { 8 };
+
+    writer.write_all(MAGIC)?;
+    for value in [
+        VERSION,
+        width,
+        num_nodes as u64,
+        name_bytes as u64,
+        num_neighbors as u64,
+    ] {
+        write_u64(writer, value)?;
+    }
+
+    let mut end = 0;
+    for ix in 0..num_nodes {
+        end += name(ix).len();
+        write_u64(writer, end as u64)?;
+    }
+    for ix in 0..num_nodes {
+        writer.write_all(name(ix))?;
+    }
+
+    let mut offset = 0;
+    write_u64(writer, 0)?;
+    for ix in 0..num_nodes {
+        offset += neighbors(ix).len();
+        write_u64(writer, offset as u64)?;
+    }
+    for ix in 0..num_nodes {
+        for &neighbor in neighbors(ix) {
+            match width {
+                4 => writer.write_all(&(neighbor as u32).to_le_bytes())?,
+                _ => write_u64(writer, neighbor as u64)?,
+            }
+        }
+    }
+    writer.flush()
+}
+
+/// Reads a graph written by `write_graph`, passing each name to
+/// `add_name` and each adjacency list to `add_node`, both in index
+/// order. Returns an `InvalidData` error if the file isn't consistent,
+/// e.g. if it was cut off.
+fn read_graph<R, N, A>(
+    reader: &mut R,
+    mut add_name: N,
+    mut add_node: A,
+) -> io::Result<()>
+where
+    R: Read + ?Sized,
+    N: FnMut(usize, &[u8]) -> io::Result<()>,
+    A: FnMut(usize, AdjacencyList),
+{
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a graph file"));
+    }
+    if read_u64(reader)? != VERSION {
+        return Err(invalid("unsupported version"));
+    }
+    let width = read_u64(reader)?;
+    if width != 4 && width != 8 {
+        return Err(invalid("invalid index width"));
+    }
+    let num_nodes = read_usize(reader)?;
+    let name_bytes = read_usize(reader)?;
+    let num_neighbors = read_usize(reader)?;
+
+    // the vectors grow as the values are read, instead of being
+    // allocated from the header, so that a corrupt header fails
+    // instead of running out of memory
+    let read_ends = |reader: &mut R, count, last| -> io::Result<Vec<usize>> {
+        let mut ends = Vec::new();
+        let mut previous = 0;
+        for _ in 0..count {
+            let end = read_usize(reader)?;
+            if end < previous || end > last {
+                return Err(invalid("offset out of range"));
+            }
+            ends.push(end);
+            previous = end;
+        }
+        if previous != last {
+            return Err(invalid("inconsistent offsets"));
+        }
+        Ok(ends)
+    };
+
+    let ends = read_ends(reader, num_nodes, name_bytes)?;
+    let mut name = Vec::new();
+    let mut start = 0;
+    for (ix, &end) in ends.iter().enumerate() {
+        name.resize(end - start, 0);
+        reader.read_exact(&mut name)?;
+        add_name(ix, &name)?;
+        start = end;
+    }
+    drop(ends);
+
+    if read_u64(reader)? != 0 {
+        return Err(invalid("inconsistent offsets"));
+    }
+    let offsets = read_ends(reader, num_nodes, num_neighbors)?;
+    let mut start = 0;
+    let mut bytes = [0; 8];
+    for (ix, &end) in offsets.iter().enumerate() {
+        let mut adjacency = AdjacencyList::new();
+        for _ in start..end {
+            let neighbor = match width {
+                4 => {
+                    reader.read_exact(&mut bytes[..4])?;
+                    u32::from_le_bytes(bytes[..4].try_into().unwrap()) as u64
+                }
+                _ => {
+                    reader.read_exact(&mut bytes)?;
+                    u64::from_le_bytes(bytes)
+                }
+            };
+            match usize::try_from(neighbor) {
+                Ok(neighbor) if neighbor < num_nodes => {
+                    adjacency.push(neighbor)
+                }
+                _ => return Err(invalid("neighbor out of range")),
+            }
+        }
+        add_node(ix, adjacency);
+        start = end;
+    }
+    Ok(())
+}
+
+// a file that was cut off is invalid, rather than an I/O error
+fn truncated(err: io::Error) -> io::Error {
+    match err.kind() {
+        io::ErrorKind::UnexpectedEof => invalid("unexpected end of file"),
+        _ => err,
+    }
+}
+
+fn create(path: &Path) -> io::Result<BufWriter<File>> {
+    Ok(BufWriter::new(File::create(path)?))
+}
+
+fn open(path: &Path) -> io::Result<BufReader<File>> {
+    Ok(BufReader::new(File::open(path)?))
+}
+
+fn neighbors_or_empty(graph: &FxMapGraph, ix: usize) -> &[usize] {
+    graph.get(&ix).map_or(&[], |adjacency| adjacency.as_slice())
+}
+
+impl InternedGraph {
+    /// Writes the graph in a compact binary format, with the names and
+    /// the adjacency lists in the order of the node indices, which
+    /// `InternedGraph::read_from` reads much faster than the graph can
+    /// be parsed from GFA. The node indices and the order of the
+    /// neighbors are kept, so the components are the same. The
+    /// writer should be buffered.
+    pub fn write_to<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        write_graph(
+            writer,
+            self.names.len(),
+            |ix| self.names.get(ix),
+            |ix| neighbors_or_empty(&self.graph, ix),
+        )
+    }
+
+    /// Reads a graph written by `InternedGraph::write_to`. Returns an
+    /// `InvalidData` error if it isn't a graph file, or if it's
+    /// inconsistent, e.g. if it's been cut off. A node that had no
+    /// entry in the adjacency map gets an empty one. The reader should
+    /// be buffered.
+    pub fn read_from<R: Read + ?Sized>(
+        reader: &mut R,
+    ) -> io::Result<InternedGraph> {
+        let mut graph = InternedGraph::default();
+        let InternedGraph {
+            graph: adjacency,
+            names,
+        } = &mut graph;
+        read_graph(
+            reader,
+            |ix, name| {
+                if names.get_or_insert(name) != ix {
+                    return Err(invalid("duplicate node name"));
+                }
+                Ok(())
+            },
+            |ix, neighbors| {
+                adjacency.insert(ix, neighbors);
+            },
+        )
+        .map_err(truncated)?;
+        Ok(graph)
+    }
+
+    /// Writes the graph to a file with `InternedGraph::write_to`.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        self.write_to(&mut create(path)?)
+    }
+
+    /// Reads a graph saved with `InternedGraph::save`.
+    pub fn load(path: &Path) -> io::Result<InternedGraph> {
+        InternedGraph::read_from(&mut open(path)?)
+    }
+}
+
+impl Graph<Vec<u8>> {
+    /// Like `InternedGraph::write_to`, in the same format. The edge
+    /// labels aren't written.
+    pub fn write_to<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        write_graph(
+            writer,
+            self.inv_names.len(),
+            |ix| self.inv_names[ix].as_slice(),
+            |ix| neighbors_or_empty(&self.graph, ix),
+        )
+    }
+
+    /// Like `InternedGraph::read_from`, without any edge labels.
+    pub fn read_from<R: Read + ?Sized>(
+        reader: &mut R,
+    ) -> io::Result<Graph<Vec<u8>>> {
+        let mut graph = FxMapGraph::default();
+        let mut inv_names = Vec::new();
+        let mut names = NameArena::new();
+        read_graph(
+            reader,
+            |ix, name| {
+                if names.get_or_insert(name) != ix {
+                    return Err(invalid("duplicate node name"));
+                }
+                inv_names.push(name.to_vec());
+                Ok(())
+            },
+            |ix, neighbors| {
+                graph.insert(ix, neighbors);
+            },
+        )
+        .map_err(truncated)?;
+        Ok(Graph {
+            graph,
+            inv_names,
+            edge_labels: None,
+        })
+    }
+
+    /// Writes the graph to a file with `Graph::write_to`.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        self.write_to(&mut create(path)?)
+    }
+
+    /// Reads a graph saved with `Graph::save`.
+    pub fn load(path: &Path) -> io::Result<Graph<Vec<u8>>> {
+        Graph::read_from(&mut open(path)?)
+    }
+}
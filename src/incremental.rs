@@ -0,0 +1,256 @@
+use alloc::{vec, vec::Vec};
+
+use crate::algorithm;
+
+const NONE: usize = usize::MAX;
+
+/// A change to the graph, for `IncrementalComponents::apply`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    Insert(usize, usize),
+    Remove(usize, usize),
+}
+
+/// The 3-edge-connected components of a graph that's edited one edge
+/// at a time, or in batches, e.g. by an interactive tool, without
+/// running the algorithm on the whole graph after each edit.
+///
+/// Adding an edge can only merge components, and only when its ends
+/// are in different components of the same connected component: an
+/// edge within a component doesn't cross any cut of at most two edges
+/// between components, and an edge between connected components is a
+/// bridge. So `insert_edge` does nothing else in those cases, which
+/// only takes a union-find lookup, and otherwise reruns the algorithm
+/// on the connected component of the edge. Removing an edge can split
+/// components anywhere in its connected component, so that's always
+/// rerun, or both connected components, if the edge was a bridge.
+///
+/// Each component is identified by its smallest node, so the
+/// identifier of a component only changes when it's merged into a
+/// component with a smaller node.
+#[derive(Default, Debug, Clone)]
+pub struct IncrementalComponents {
+    adjacency: Vec<Vec<usize>>,
+    // the smallest node in the component of each node
+    label: Vec<usize>,
+    // union-find over the nodes, for the connected components; after
+    // removing edges, it's rebuilt for the recomputed nodes
+    parent: Vec<usize>,
+    // the index of each node in the connected component being
+    // recomputed, or `NONE`
+    local_ix: Vec<usize>,
+}
+
+impl IncrementalComponents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Finds the components of the graph with the given edges, by
+    /// running the algorithm once. The nodes are `0..` the largest
+    /// node in the edges.
+    pub fn from_edges<I>(edges: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        let mut components = IncrementalComponents::new();
+        for (from, to) in edges {
+            components.add_nodes(from.max(to) + 1);
+            components.adjacency[from].push(to);
+            components.adjacency[to].push(from);
+            components.union(from, to);
+        }
+
+        let sigma = algorithm::find_components(&components.adjacency);
+        for component in sigma {
+            let min = component.iter().copied().min().unwrap();
+            for node in component {
+                components.label[node] = min;
+            }
+        }
+        components
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// The adjacency lists of the graph so far.
+    pub fn graph(&self) -> &[Vec<usize>] {
+        &self.adjacency
+    }
+
+    /// Adds nodes without edges, each in its own component, so that
+    /// the graph has at least `num_nodes` nodes.
+    pub fn add_nodes(&mut self, num_nodes: usize) {
+        for node in self.num_nodes()..num_nodes {
+            self.adjacency.push(Vec::new());
+            self.label.push(node);
+            self.parent.push(node);
+            self.local_ix.push(NONE);
+        }
+    }
+
+    /// Adds an edge, adding its ends as nodes if they're not in the
+    /// graph yet, and updates the components. Returns true if any
+    /// components were merged.
+    pub fn insert_edge(&mut self, from: usize, to: usize) -> bool {
+        self.apply(Some(Edit::Insert(from, to)))
+    }
+
+    /// Removes one edge between the nodes, if there is one, and
+    /// updates the components. Returns true if any components were
+    /// split.
+    pub fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        self.apply(Some(Edit::Remove(from, to)))
+    }
+
+    /// Applies the edits in order, and then updates the components,
+    /// rerunning the algorithm once on each connected component that
+    /// was changed by the edits. Removing an edge that doesn't exist
+    /// does nothing. Returns true if any components changed.
+    ///
+    /// Once an edit needs the algorithm to be rerun, the components
+    /// aren't up to date for the following edits, so they're all
+    /// rerun, even those that wouldn't need it on their own.
+    pub fn apply<I: IntoIterator<Item = Edit>>(&mut self, edits: I) -> bool {
+        let mut dirty = Vec::new();
+
+        for edit in edits {
+            match edit {
+                Edit::Insert(from, to) => {
+                    self.add_nodes(from.max(to) + 1);
+                    self.adjacency[from].push(to);
+                    self.adjacency[to].push(from);
+
+                    let connected = self.find(from) == self.find(to);
+                    self.union(from, to);
+                    let unchanged =
+                        !connected || self.label[from] == self.label[to];
+                    if !dirty.is_empty() || !unchanged {
+                        dirty.push(from);
+                    }
+                }
+                Edit::Remove(from, to) => {
+                    if self.remove_adjacency(from, to) {
+                        dirty.push(from);
+                        dirty.push(to);
+                    }
+                }
+            }
+        }
+
+        self.recompute(&dirty)
+    }
+
+    fn remove_adjacency(&mut self, from: usize, to: usize) -> bool {
+        let position = |adjacency: &[Vec<usize>], w: usize, u: usize| {
+            adjacency.get(w)?.iter().position(|&x| x == u)
+        };
+
+        match position(&self.adjacency, from, to) {
+            None => false,
+            Some(ix) => {
+                self.adjacency[from].swap_remove(ix);
+                let ix = position(&self.adjacency, to, from).unwrap();
+                self.adjacency[to].swap_remove(ix);
+                true
+            }
+        }
+    }
+
+    /// The identifier of the node's component, which is its smallest
+    /// node.
+    pub fn component(&self, node: usize) -> usize {
+        self.label[node]
+    }
+
+    pub fn same_component(&self, a: usize, b: usize) -> bool {
+        self.label[a] == self.label[b]
+    }
+
+    /// Returns all the components, with their nodes sorted, and
+    /// sorted by their first node, like `brute_force::find_components`.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        // components are created in the order of their smallest node
+        let mut position = vec![NONE; self.num_nodes()];
+        let mut components: Vec<Vec<usize>> = Vec::new();
+        for (node, &label) in self.label.iter().enumerate() {
+            if position[label] == NONE {
+                position[label] = components.len();
+                components.push(Vec::new());
+            }
+            components[position[label]].push(node);
+        }
+        components
+    }
+
+    /// Reruns the algorithm on the connected components of the given
+    /// nodes, each only once, and returns true if any labels changed.
+    fn recompute(&mut self, roots: &[usize]) -> bool {
+        let mut changed = false;
+        let mut recomputed = Vec::new();
+
+        for &root in roots {
+            if self.local_ix[root] != NONE {
+                continue;
+            }
+
+            let mut nodes = vec![root];
+            self.local_ix[root] = 0;
+            let mut next = 0;
+            while next < nodes.len() {
+                let w = nodes[next];
+                for &u in self.adjacency[w].iter() {
+                    if self.local_ix[u] == NONE {
+                        self.local_ix[u] = nodes.len();
+                        nodes.push(u);
+                    }
+                }
+                next += 1;
+            }
+
+            let local_adjacency: Vec<Vec<usize>> = nodes
+                .iter()
+                .map(|&w| {
+                    self.adjacency[w]
+                        .iter()
+                        .map(|&u| self.local_ix[u])
+                        .collect()
+                })
+                .collect();
+
+            for component in algorithm::find_components(&local_adjacency) {
+                let min = component.iter().map(|&ix| nodes[ix]).min().unwrap();
+                for ix in component {
+                    changed |= self.label[nodes[ix]] != min;
+                    self.label[nodes[ix]] = min;
+                }
+            }
+
+            // removed edges may have split the connected component
+            for &w in nodes.iter() {
+                self.parent[w] = root;
+            }
+            recomputed.extend(nodes);
+        }
+
+        for w in recomputed {
+            self.local_ix[w] = NONE;
+        }
+        changed
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parent[a] = b;
+    }
+}
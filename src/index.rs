@@ -0,0 +1,67 @@
+use core::{
+    convert::{TryFrom, TryInto},
+    fmt::Debug,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
+
+/// The integer types that can be used for node indices and preorder
+/// numbers in the algorithm's `State`. `usize` works for any graph,
+/// while `u32` halves the size of the state, for graphs with fewer
+/// than `u32::MAX` nodes.
+pub trait IndexType: Copy + Ord + Default + Debug {
+    /// The signed type used for the node degrees in the state, which
+    /// converts from and to `i64` for `Checkpoint`.
+    type Degree: Copy
+        + PartialOrd
+        + Default
+        + Debug
+        + From<i8>
+        + TryFrom<i64>
+        + TryInto<i64>
+        + Add<Output = Self::Degree>
+        + Sub<Output = Self::Degree>
+        + AddAssign
+        + SubAssign;
+
+    /// The largest number of nodes the state can be used with. The
+    /// preorder numbers start from 1, so this is one less than the
+    /// largest value of the type.
+    const MAX_NODES: usize;
+
+    fn new(ix: usize) -> Self;
+
+    fn index(self) -> usize;
+}
+
+impl IndexType for usize {
+    type Degree = isize;
+
+    const MAX_NODES: usize = usize::MAX - 1;
+
+    #[inline]
+    fn new(ix: usize) -> Self {
+        ix
+    }
+
+    #[inline]
+    fn index(self) -> usize {
+        self
+    }
+}
+
+impl IndexType for u32 {
+    type Degree = i32;
+
+    const MAX_NODES: usize = u32::MAX as usize - 1;
+
+    #[inline]
+    fn new(ix: usize) -> Self {
+        debug_assert!(ix <= u32::MAX as usize);
+        ix as u32
+    }
+
+    #[inline]
+    fn index(self) -> usize {
+        self as usize
+    }
+}
@@ -0,0 +1,205 @@
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+use crate::{adjacency::Adjacency, index::IndexType, state::State};
+
+/// An invariant of the algorithm's `State` that doesn't hold, found
+/// by `State::check_invariants`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// The state doesn't have one entry per node of the graph.
+    StateLength { state: usize, graph: usize },
+    /// The next node in the sigma set of a visited node hasn't been
+    /// visited.
+    SigmaUnvisited { node: usize },
+    /// Two visited nodes have the same next node in their sigma sets,
+    /// so `next_sigma` isn't a permutation of the visited nodes.
+    SigmaNotPermutation { node: usize },
+    /// A completed component isn't the sigma set of its first node.
+    ComponentNotSigma { component: usize },
+    /// The path of a visited node goes through an unvisited node.
+    PathUnvisited { node: usize },
+    /// The path of a visited node has a cycle, instead of ending at a
+    /// node that's its own next node.
+    PathCycle { node: usize },
+    /// The degree of a node is larger than the number of edges of the
+    /// nodes in its sigma set.
+    DegreeTooLarge { node: usize },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantViolation::StateLength { state, graph } => write!(
+                f,
+                "state has {} nodes, but the graph has {}",
+                state, graph
+            ),
+            InvariantViolation::SigmaUnvisited { node } => write!(
+                f,
+                "the next node in the sigma set of node {} isn't visited",
+                node
+            ),
+            InvariantViolation::SigmaNotPermutation { node } => write!(
+                f,
+                "node {} is the next node in more than one sigma set",
+                node
+            ),
+            InvariantViolation::ComponentNotSigma { component } => write!(
+                f,
+                "component {} isn't the sigma set of its first node",
+                component
+            ),
+            InvariantViolation::PathUnvisited { node } => write!(
+                f,
+                "the path of node {} goes through an unvisited node",
+                node
+            ),
+            InvariantViolation::PathCycle { node } => {
+                write!(f, "the path of node {} has a cycle", node)
+            }
+            InvariantViolation::DegreeTooLarge { node } => write!(
+                f,
+                "node {} has a larger degree than the edges of its sigma set",
+                node
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvariantViolation {}
+
+impl<Ix: IndexType> State<Ix> {
+    /// Checks the invariants of the state, at any point of a run of
+    /// the algorithm on `graph`, among the nodes visited so far:
+    ///
+    /// - `next_sigma` is a permutation of the visited nodes, so that
+    ///   the sigma sets are its cycles and don't overlap,
+    /// - each component in `sigma` is the sigma set of its first node,
+    /// - following `next_on_path` from any visited node ends at a node
+    ///   that's its own next node, without a cycle,
+    /// - no node has a larger degree than the number of edges of the
+    ///   nodes in its sigma set, not counting self-loops, as each
+    ///   absorbed node adds its own degree, minus the edge between.
+    ///
+    /// This takes O(n + m) time, so checking after every step of the
+    /// algorithm, as the `debug-invariants` feature does, makes it
+    /// quadratic; it's meant for tests and for changing the algorithm.
+    pub fn check_invariants<G: Adjacency + ?Sized>(
+        &self,
+        graph: &G,
+    ) -> Result<(), InvariantViolation> {
+        let num_nodes = graph.num_nodes();
+        let lengths = [
+            self.visited.len(),
+            self.next_sigma.len(),
+            self.next_on_path.len(),
+            self.degrees.len(),
+        ];
+        if let Some(&len) = lengths.iter().find(|&&len| len != num_nodes) {
+            return Err(InvariantViolation::StateLength {
+                state: len,
+                graph: num_nodes,
+            });
+        }
+        let visited = |node: usize| self.visited.get(node);
+
+        let mut has_prev = vec![false; num_nodes];
+        for node in (0..num_nodes).filter(|&n| visited(n)) {
+            let next = self.next_sigma[node].index();
+            if next >= num_nodes || !visited(next) {
+                return Err(InvariantViolation::SigmaUnvisited { node });
+            }
+            if has_prev[next] {
+                return Err(InvariantViolation::SigmaNotPermutation {
+                    node: next,
+                });
+            }
+            has_prev[next] = true;
+        }
+
+        // each component has to be a whole cycle, in any order
+        let mut in_component = vec![false; num_nodes];
+        for (ix, component) in self.sigma.iter().enumerate() {
+            let not_sigma =
+                InvariantViolation::ComponentNotSigma { component: ix };
+            let first = match component.first() {
+                Some(&first) if first < num_nodes && visited(first) => first,
+                _ => return Err(not_sigma),
+            };
+            for &node in component {
+                if node >= num_nodes || in_component[node] {
+                    return Err(not_sigma);
+                }
+                in_component[node] = true;
+            }
+            let mut node = first;
+            for _ in 0..component.len() {
+                node = self.next_sigma[node].index();
+                if !in_component[node] {
+                    return Err(not_sigma);
+                }
+            }
+            if node != first {
+                return Err(not_sigma);
+            }
+        }
+
+        // the paths form a forest whose roots point to themselves: 1
+        // marks the nodes on the path being followed, and 2 those whose
+        // path is known to end
+        let mut mark = vec![0u8; num_nodes];
+        let mut followed = Vec::new();
+        for start in (0..num_nodes).filter(|&n| visited(n)) {
+            let mut node = start;
+            while mark[node] == 0 {
+                mark[node] = 1;
+                followed.push(node);
+                let next = self.next_on_path[node].index();
+                if next >= num_nodes || !visited(next) {
+                    return Err(InvariantViolation::PathUnvisited { node });
+                }
+                if next == node {
+                    break;
+                }
+                if mark[next] == 1 {
+                    return Err(InvariantViolation::PathCycle { node: start });
+                }
+                node = next;
+            }
+            for node in followed.drain(..) {
+                mark[node] = 2;
+            }
+        }
+
+        // the number of edges of each sigma set, by its first node in
+        // the order of the nodes
+        let mut set_edges = vec![Ix::Degree::default(); num_nodes];
+        let mut set_of = vec![usize::MAX; num_nodes];
+        for first in (0..num_nodes).filter(|&n| visited(n)) {
+            if set_of[first] != usize::MAX {
+                continue;
+            }
+            let mut node = first;
+            loop {
+                set_of[node] = first;
+                for &neighbor in graph.neighbors(node) {
+                    if neighbor != node {
+                        set_edges[first] += 1.into();
+                    }
+                }
+                node = self.next_sigma[node].index();
+                if node == first {
+                    break;
+                }
+            }
+        }
+        for node in (0..num_nodes).filter(|&n| visited(n)) {
+            if self.degrees[node] > set_edges[set_of[node]] {
+                return Err(InvariantViolation::DegreeTooLarge { node });
+            }
+        }
+        Ok(())
+    }
+}
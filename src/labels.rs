@@ -0,0 +1,126 @@
+use fxhash::FxHashMap;
+
+/// The data of one edge, from the GFA link it was parsed from: its
+/// overlap, and the tags that were kept, see
+/// `Graph::from_gfa_reader_labeled`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EdgeLabel {
+    /// The overlap, a CIGAR string or `*`.
+    pub overlap: Vec<u8>,
+    /// The kept tags, each as `TAG:TYPE:VALUE`, in the order of the
+    /// link line.
+    pub tags: Vec<Vec<u8>>,
+}
+
+impl EdgeLabel {
+    /// The value of the tag with the given name, e.g. `b"ID"`, without
+    /// its name and type.
+    pub fn tag(&self, name: &[u8]) -> Option<&[u8]> {
+        self.tags.iter().find_map(|tag| {
+            let rest = tag.strip_prefix(name)?.strip_prefix(b":")?;
+            rest.get(2..).filter(|_| rest.get(1) == Some(&b':'))
+        })
+    }
+
+    fn heap_size(&self) -> usize {
+        self.overlap.capacity()
+            + self.tags.capacity() * std::mem::size_of::<Vec<u8>>()
+            + self.tags.iter().map(Vec::capacity).sum::<usize>()
+    }
+}
+
+/// The labels of the edges of a graph, stored next to its adjacency
+/// lists and looked up by the two nodes of an edge, in either order.
+/// Parallel edges have a label each, in the order they were added, as
+/// does a self-loop, even though it's in its node's adjacency list
+/// twice.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EdgeLabels {
+    labels: FxHashMap<(usize, usize), Vec<EdgeLabel>>,
+    len: usize,
+}
+
+fn key(from: usize, to: usize) -> (usize, usize) {
+    (from.min(to), from.max(to))
+}
+
+impl EdgeLabels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of labeled edges.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds the label of another edge between the two nodes.
+    pub fn insert(&mut self, from: usize, to: usize, label: EdgeLabel) {
+        self.labels.entry(key(from, to)).or_default().push(label);
+        self.len += 1;
+    }
+
+    /// The labels of the edges between the two nodes, which is empty
+    /// if there are none.
+    pub fn get(&self, from: usize, to: usize) -> &[EdgeLabel] {
+        self.labels.get(&key(from, to)).map_or(&[], Vec::as_slice)
+    }
+
+    /// Removes the label of the last edge added between the two nodes,
+    /// e.g. along with the edge in `Graph::remove_edge`.
+    pub fn remove(&mut self, from: usize, to: usize) -> Option<EdgeLabel> {
+        let key = key(from, to);
+        let labels = self.labels.get_mut(&key)?;
+        let label = labels.pop();
+        if labels.is_empty() {
+            self.labels.remove(&key);
+        }
+        self.len -= label.is_some() as usize;
+        label
+    }
+
+    /// Each labeled edge with its label, with the smaller node first,
+    /// in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &EdgeLabel)> {
+        self.labels.iter().flat_map(|(&(from, to), labels)| {
+            labels.iter().map(move |label| (from, to, label))
+        })
+    }
+
+    /// The labels of the edges between the nodes that `new_ix` maps to
+    /// a new index, with the nodes renumbered, e.g. for a subgraph.
+    pub(crate) fn remap<F>(&self, new_ix: F) -> EdgeLabels
+    where
+        F: Fn(usize) -> Option<usize>,
+    {
+        let mut remapped = EdgeLabels::new();
+        for (from, to, label) in self.iter() {
+            if let (Some(from), Some(to)) = (new_ix(from), new_ix(to)) {
+                remapped.insert(from, to, label.clone());
+            }
+        }
+        remapped
+    }
+
+    /// Returns an estimate of the number of bytes of heap memory used
+    /// by the labels.
+    pub fn memory_usage(&self) -> usize {
+        use std::mem::size_of;
+
+        let map = self.labels.capacity()
+            * (size_of::<(usize, usize)>() + size_of::<Vec<EdgeLabel>>() + 1);
+        let labels: usize = self
+            .labels
+            .values()
+            .map(|labels| {
+                labels.capacity() * size_of::<EdgeLabel>()
+                    + labels.iter().map(EdgeLabel::heap_size).sum::<usize>()
+            })
+            .sum();
+        map + labels
+    }
+}
@@ -0,0 +1,218 @@
+use std::io::{self, BufRead};
+
+use fxhash::FxHashSet;
+
+/// The section of an LGF file that the lines are in, from the last
+/// line starting with `@`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    /// Before any section, or in one that isn't read, such as
+    /// `@attributes`.
+    Other,
+    /// `@nodes`, `@red_nodes`, and `@blue_nodes`, with the column of
+    /// the labels, once the line of the map names is read.
+    Nodes(Option<usize>),
+    /// `@arcs` and `@edges`, after the line of the map names.
+    Edges,
+}
+
+/// The edges of a graph in the LEMON graph format, from `lgf_edges`.
+pub struct LgfEdges<R> {
+    reader: R,
+    line: Vec<u8>,
+    section: Section,
+    // whether the first line of the section, the map names, was read
+    has_maps: bool,
+    labels: FxHashSet<Vec<u8>>,
+    // whether there was a nodes section to check the edges against
+    has_nodes: bool,
+    failed: bool,
+}
+
+/// Parses the edges of a graph in the LEMON graph format, the `.lgf`
+/// files of the LEMON library: a `@nodes` section with a `label`
+/// column, and `@arcs` or `@edges` sections with the labels of the two
+/// nodes of each edge, and the maps, such as weights, which are
+/// ignored. The nodes are named by their labels. Arcs are read as
+/// undirected edges, the nodes of bipartite graphs, in `@red_nodes` and
+/// `@blue_nodes`, are read like any others, and the other sections,
+/// such as `@attributes`, are skipped, as are the lines starting with
+/// `#`.
+pub fn lgf_edges<R: BufRead>(reader: R) -> LgfEdges<R> {
+    LgfEdges {
+        reader,
+        line: Vec::new(),
+        section: Section::Other,
+        has_maps: false,
+        labels: FxHashSet::default(),
+        has_nodes: false,
+        failed: false,
+    }
+}
+
+fn invalid(message: &str, line: &[u8]) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "{} in LGF line {:?}",
+            message,
+            String::from_utf8_lossy(line.trim_ascii())
+        ),
+    )
+}
+
+/// Splits off the first token of the line, a word or a string in
+/// double quotes with backslash escapes, and returns its value with
+/// the rest of the line.
+fn next_token(line: &[u8]) -> io::Result<Option<(Vec<u8>, &[u8])>> {
+    let start = match line.iter().position(|b| !b.is_ascii_whitespace()) {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+    let rest = &line[start..];
+    if rest[0] != b'"' {
+        let end = rest
+            .iter()
+            .position(u8::is_ascii_whitespace)
+            .unwrap_or(rest.len());
+        return Ok(Some((rest[..end].to_vec(), &rest[end..])));
+    }
+    let mut token = Vec::new();
+    let mut bytes = rest[1..].iter().enumerate();
+    while let Some((ix, &b)) = bytes.next() {
+        match b {
+            b'"' => return Ok(Some((token, &rest[ix + 2..]))),
+            b'\\' => {
+                let escaped = match bytes.next() {
+                    Some((_, b'n')) => b'\n',
+                    Some((_, b't')) => b'\t',
+                    Some((_, b'r')) => b'\r',
+                    Some((_, &b)) => b,
+                    None => break,
+                };
+                token.push(escaped);
+            }
+            _ => token.push(b),
+        }
+    }
+    Err(invalid("unterminated string", line))
+}
+
+impl<R: BufRead> LgfEdges<R> {
+    fn parse_header(&mut self) {
+        let keyword = self.line.trim_ascii()[1..]
+            .split(u8::is_ascii_whitespace)
+            .next()
+            .unwrap_or(b"");
+        self.section = match keyword {
+            b"nodes" | b"red_nodes" | b"blue_nodes" => {
+                self.has_nodes = true;
+                Section::Nodes(None)
+            }
+            b"arcs" | b"edges" => Section::Edges,
+            _ => Section::Other,
+        };
+        self.has_maps = false;
+    }
+
+    /// Reads the map names of a nodes section, for the column of the
+    /// labels.
+    fn parse_maps(&mut self) -> io::Result<()> {
+        self.has_maps = true;
+        if let Section::Nodes(_) = self.section {
+            let mut rest = &self.line[..];
+            let mut column = 0;
+            while let Some((map, next)) = next_token(rest)? {
+                if map == b"label" {
+                    self.section = Section::Nodes(Some(column));
+                    return Ok(());
+                }
+                rest = next;
+                column += 1;
+            }
+            return Err(invalid("missing label map", &self.line));
+        }
+        Ok(())
+    }
+
+    fn parse_node(&mut self, column: usize) -> io::Result<()> {
+        let mut rest = &self.line[..];
+        for _ in 0..column {
+            rest = match next_token(rest)? {
+                Some((_, next)) => next,
+                None => break,
+            };
+        }
+        match next_token(rest)? {
+            Some((label, _)) => {
+                if !self.labels.insert(label) {
+                    return Err(invalid("duplicate node label", &self.line));
+                }
+                Ok(())
+            }
+            None => Err(invalid("missing node label", &self.line)),
+        }
+    }
+
+    fn parse_edge(&self) -> io::Result<(Vec<u8>, Vec<u8>)> {
+        let line = &self.line;
+        let (from, rest) =
+            next_token(line)?.ok_or_else(|| invalid("missing node", line))?;
+        let (to, _) =
+            next_token(rest)?.ok_or_else(|| invalid("missing node", line))?;
+        for label in [&from, &to] {
+            if self.has_nodes && !self.labels.contains(label) {
+                return Err(invalid("unknown node label", line));
+            }
+        }
+        Ok((from, to))
+    }
+
+    fn parse_line(&mut self) -> io::Result<Option<(Vec<u8>, Vec<u8>)>> {
+        match self.line.iter().find(|b| !b.is_ascii_whitespace()) {
+            None | Some(b'#') => return Ok(None),
+            Some(b'@') => {
+                self.parse_header();
+                return Ok(None);
+            }
+            Some(_) => (),
+        }
+        if self.section == Section::Other {
+            return Ok(None);
+        }
+        if !self.has_maps {
+            self.parse_maps()?;
+            return Ok(None);
+        }
+        match self.section {
+            Section::Nodes(Some(column)) => self.parse_node(column)?,
+            Section::Edges => return self.parse_edge().map(Some),
+            _ => (),
+        }
+        Ok(None)
+    }
+}
+
+impl<R: BufRead> Iterator for LgfEdges<R> {
+    type Item = io::Result<(Vec<u8>, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed {
+            self.line.clear();
+            let result = match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => self.parse_line(),
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(None) => (),
+                Ok(Some(edge)) => return Some(Ok(edge)),
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
@@ -1,6 +1,84 @@
+//! Finds the 3-edge-connected components of graphs.
+//!
+//! The `std` feature, enabled by default, provides the `Graph` type
+//! with edge list parsing, and the output writers. Without it, the
+//! crate is `no_std` and only needs `alloc`, and the algorithm can be
+//! run on any graph implementing `adjacency::Adjacency`, such as a
+//! `Vec<Vec<usize>>`. The `gfa` feature, also enabled by default, adds
+//! the GFA parsers and the modules built on them, such as `paths` and
+//! `superbubble`, along with the `gfa` and `bstr` dependencies.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod adjacency;
 pub mod algorithm;
+pub mod brute_force;
+pub mod cancel;
+pub mod checkpoint;
+pub mod components;
+#[cfg(feature = "std")]
+pub mod connectivity;
+#[cfg(feature = "cross-check")]
+pub mod cut_pairs;
+#[cfg(feature = "std")]
+pub mod diff;
+pub mod forest;
+pub mod four_edge;
+#[cfg(feature = "std")]
+pub mod generators;
+#[cfg(feature = "std")]
 pub mod graph;
+#[cfg(feature = "std")]
+pub mod graph_file;
+pub mod incremental;
+pub mod index;
+pub mod invariants;
+#[cfg(feature = "std")]
+pub mod labels;
+#[cfg(feature = "std")]
+pub mod lgf;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "std")]
+pub mod names;
+#[cfg(feature = "std")]
+pub mod output;
+#[cfg(feature = "std")]
+pub mod paf;
+#[cfg(feature = "std")]
+pub mod pajek;
+#[cfg(feature = "std")]
+pub mod parallel;
+#[cfg(feature = "gfa")]
+pub mod paths;
+pub mod progress;
+#[cfg(feature = "gfa")]
+pub mod region;
+#[cfg(feature = "std")]
+pub mod relabel;
+#[cfg(feature = "gfa")]
+pub mod rgfa;
+pub mod small_vec;
+#[cfg(feature = "gfa")]
+pub mod snarl;
 pub mod state;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "gfa")]
+pub mod superbubble;
+#[cfg(feature = "std")]
+pub mod tgf;
+#[cfg(feature = "std")]
+pub mod validate;
+#[cfg(feature = "std")]
+pub mod vg;
+pub mod visitor;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-pub use algorithm::find_components;
+pub use algorithm::{filter_min_size, find_components, sort_components_by_key};
+pub use components::Components;
+#[cfg(feature = "std")]
 pub use graph::Graph;
@@ -0,0 +1,256 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    hash::Hash,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use fxhash::FxHashMap;
+use memmap::{Mmap, MmapMut};
+
+use crate::{
+    adjacency::Adjacency,
+    graph::{check_index, parse_index},
+    names::NameArena,
+};
+
+const OFFSETS_FILE: &str = "offsets.bin";
+const NEIGHBORS_FILE: &str = "neighbors.bin";
+const EDGES_FILE: &str = "edges.tmp";
+
+/// A graph whose adjacency lists are stored on disk, in
+/// memory-mapped files, so that graphs larger than RAM can be
+/// processed; only the algorithm's state, which is a handful of
+/// words per node, is kept in memory.
+///
+/// The graph is stored in two files in a directory: `offsets.bin`,
+/// with the start of each node's neighbors, and `neighbors.bin`,
+/// with all the neighbor lists one after the other. Both are arrays
+/// of native-endian `usize`s, so they can only be read on the same
+/// kind of machine as they were written on.
+pub struct MmapGraph {
+    offsets: Mmap,
+    // empty files can't be mapped, so this is `None` if the graph
+    // has no edges
+    neighbors: Option<Mmap>,
+}
+
+impl MmapGraph {
+    /// Builds the graph files in `dir` from the named edges, and
+    /// returns the graph together with the node names, indexed in the
+    /// order they're first seen, like `Graph::from_named_edges`. The
+    /// edges are first written to a temporary file in `dir`, so that
+    /// the whole edge list is never in memory.
+    pub fn build<N, I>(edges: I, dir: &Path) -> io::Result<(MmapGraph, Vec<N>)>
+    where
+        N: Hash + Eq + Clone,
+        I: IntoIterator<Item = io::Result<(N, N)>>,
+    {
+        let mut name_map: FxHashMap<N, usize> = FxHashMap::default();
+        let mut inv_names = Vec::new();
+        let mut get_ix = |name: N| {
+            *name_map.entry(name.clone()).or_insert_with(|| {
+                inv_names.push(name);
+                inv_names.len() - 1
+            })
+        };
+
+        let edges = edges
+            .into_iter()
+            .map(|edge| edge.map(|(from, to)| (get_ix(from), get_ix(to))));
+        let graph = MmapGraph::build_indexed(edges, dir)?;
+
+        Ok((graph, inv_names))
+    }
+
+    /// Like `MmapGraph::build`, but with the names interned in a
+    /// `NameArena`.
+    pub fn build_interned<B, I>(
+        edges: I,
+        dir: &Path,
+    ) -> io::Result<(MmapGraph, NameArena)>
+    where
+        B: AsRef<[u8]>,
+        I: IntoIterator<Item = io::Result<(B, B)>>,
+    {
+        let mut names = NameArena::new();
+        let edges = edges.into_iter().map(|edge| {
+            edge.map(|(from, to)| {
+                let from = names.get_or_insert(from.as_ref());
+                (from, names.get_or_insert(to.as_ref()))
+            })
+        });
+        let graph = MmapGraph::build_indexed(edges, dir)?;
+
+        Ok((graph, names))
+    }
+
+    /// Like `MmapGraph::build`, but with the node names parsed as
+    /// indices with `graph::parse_index`, like `IndexedGraph`, so that
+    /// no names are kept in memory. The indices are checked with
+    /// `graph::check_index`.
+    pub fn build_numeric<B, I>(edges: I, dir: &Path) -> io::Result<MmapGraph>
+    where
+        B: AsRef<[u8]>,
+        I: IntoIterator<Item = io::Result<(B, B)>>,
+    {
+        let edges = edges.into_iter().enumerate().map(|(num_edges, edge)| {
+            let (from, to) = edge?;
+            let from = check_index(parse_index(from.as_ref())?, num_edges)?;
+            let to = check_index(parse_index(to.as_ref())?, num_edges)?;
+            Ok((from, to))
+        });
+        MmapGraph::build_indexed(edges, dir)
+    }
+
+    /// Builds the graph files from edges between node indices, which
+    /// must be consecutive from 0.
+    fn build_indexed<I>(edges: I, dir: &Path) -> io::Result<MmapGraph>
+    where
+        I: Iterator<Item = io::Result<(usize, usize)>>,
+    {
+        fs::create_dir_all(dir)?;
+
+        let mut degrees: Vec<usize> = Vec::new();
+        let mut num_edges = 0;
+
+        let edges_path = dir.join(EDGES_FILE);
+        let mut edges_out = BufWriter::new(File::create(&edges_path)?);
+
+        for edge in edges {
+            let (from, to) = edge?;
+            if from.max(to) >= degrees.len() {
+                degrees.resize(from.max(to) + 1, 0);
+            }
+            degrees[from] += 1;
+            degrees[to] += 1;
+            num_edges += 1;
+            edges_out.write_all(bytemuck::bytes_of(&[from, to]))?;
+        }
+        edges_out.flush()?;
+        drop(edges_out);
+
+        // prefix sums of the degrees; `degrees` is then reused as the
+        // position of the next neighbor of each node
+        let mut offsets_out =
+            BufWriter::new(File::create(dir.join(OFFSETS_FILE))?);
+        let mut offset = 0usize;
+        for degree in degrees.iter_mut() {
+            offsets_out.write_all(bytemuck::bytes_of(&offset))?;
+            let next = offset + *degree;
+            *degree = offset;
+            offset = next;
+        }
+        offsets_out.write_all(bytemuck::bytes_of(&offset))?;
+        offsets_out.flush()?;
+        drop(offsets_out);
+
+        let neighbors_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(dir.join(NEIGHBORS_FILE))?;
+        let size = 2 * num_edges * std::mem::size_of::<usize>();
+        neighbors_file.set_len(size as u64)?;
+
+        if num_edges > 0 {
+            let mut map = unsafe { MmapMut::map_mut(&neighbors_file)? };
+            let neighbors: &mut [usize] = cast_slice_mut(&mut map)?;
+
+            let mut edges_in = BufReader::new(File::open(&edges_path)?);
+            let mut edge = [0usize; 2];
+            for _ in 0..num_edges {
+                edges_in.read_exact(bytemuck::bytes_of_mut(&mut edge))?;
+                let [from, to] = edge;
+                neighbors[degrees[from]] = to;
+                degrees[from] += 1;
+                neighbors[degrees[to]] = from;
+                degrees[to] += 1;
+            }
+            map.flush()?;
+        }
+        fs::remove_file(&edges_path)?;
+
+        MmapGraph::open(dir)
+    }
+
+    /// Opens a graph previously built in `dir` by `MmapGraph::build`.
+    ///
+    /// The files must not be modified while the graph is open.
+    pub fn open(dir: &Path) -> io::Result<MmapGraph> {
+        let offsets_file = File::open(dir.join(OFFSETS_FILE))?;
+        let neighbors_file = File::open(dir.join(NEIGHBORS_FILE))?;
+
+        let offsets = unsafe { Mmap::map(&offsets_file)? };
+        let neighbors = if neighbors_file.metadata()?.len() > 0 {
+            Some(unsafe { Mmap::map(&neighbors_file)? })
+        } else {
+            None
+        };
+
+        let graph = MmapGraph { offsets, neighbors };
+
+        let offsets = cast_slice(&graph.offsets)?;
+        let num_neighbors = match &graph.neighbors {
+            None => 0,
+            Some(neighbors) => cast_slice(neighbors)?.len(),
+        };
+        let num_nodes = offsets.len().saturating_sub(1);
+        if offsets.last() != Some(&num_neighbors)
+            || offsets.windows(2).any(|w| w[0] > w[1])
+            || graph.neighbors().iter().any(|&u| u >= num_nodes)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "inconsistent graph files",
+            ));
+        }
+
+        Ok(graph)
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.neighbors().len() / 2
+    }
+
+    fn offsets(&self) -> &[usize] {
+        bytemuck::cast_slice(&self.offsets)
+    }
+
+    fn neighbors(&self) -> &[usize] {
+        match &self.neighbors {
+            None => &[],
+            Some(neighbors) => bytemuck::cast_slice(neighbors),
+        }
+    }
+}
+
+impl Adjacency for MmapGraph {
+    type Nodes<'a> = std::ops::Range<usize>;
+
+    fn num_nodes(&self) -> usize {
+        self.offsets().len() - 1
+    }
+
+    fn nodes(&self) -> Self::Nodes<'_> {
+        0..self.num_nodes()
+    }
+
+    fn neighbors(&self, node: usize) -> &[usize] {
+        let offsets = self.offsets();
+        &self.neighbors()[offsets[node]..offsets[node + 1]]
+    }
+}
+
+fn cast_slice(bytes: &[u8]) -> io::Result<&[usize]> {
+    bytemuck::try_cast_slice(bytes).map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidData, "misaligned graph file")
+    })
+}
+
+fn cast_slice_mut(bytes: &mut [u8]) -> io::Result<&mut [usize]> {
+    bytemuck::try_cast_slice_mut(bytes).map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidData, "misaligned graph file")
+    })
+}
@@ -0,0 +1,283 @@
+use std::{borrow::Cow, collections::hash_map::Entry, hash::Hasher};
+
+use fxhash::{FxHashMap, FxHasher};
+
+/// The names of the nodes of a graph, by node index, as used by the
+/// output writers.
+pub trait NodeNames {
+    fn name(&self, ix: usize) -> Cow<'_, [u8]>;
+}
+
+impl NodeNames for [Vec<u8>] {
+    fn name(&self, ix: usize) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self[ix])
+    }
+}
+
+impl NodeNames for Vec<Vec<u8>> {
+    fn name(&self, ix: usize) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self[ix])
+    }
+}
+
+/// The names of graphs whose node names are their indices, such as
+/// `IndexedGraph`, which are formatted when needed instead of being
+/// stored.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct IndexNames;
+
+impl NodeNames for IndexNames {
+    fn name(&self, ix: usize) -> Cow<'_, [u8]> {
+        Cow::Owned(ix.to_string().into_bytes())
+    }
+}
+
+/// Interned node names, stored one after the other in a single byte
+/// arena, instead of as one heap allocation per name in both a hash
+/// map and a vector. The names are looked up with an open addressing
+/// hash table of indices into the arena, which takes a few bytes per
+/// name on top of the names themselves.
+///
+/// As long as all the names are decimal integers, as in graphs from
+/// vg or Cactus, they're instead looked up by their value, without
+/// hashing their bytes: in a dense table for values up to about twice
+/// the number of names, and in a hash map of the values otherwise. The
+/// first name that isn't an integer switches the arena to the hash
+/// table for good.
+#[derive(Default, Debug, Clone)]
+pub struct NameArena {
+    bytes: Vec<u8>,
+    // the end of each name in `bytes`, whose start is the end of the
+    // previous name
+    ends: Vec<usize>,
+    // a power of two number of slots, each either 0 for an empty
+    // slot, or a name index plus one in the upper bits and part of
+    // the name's hash in the lower `TAG_BITS`, so that most
+    // mismatches are found without looking at the arena
+    table: Vec<u64>,
+    // until `hashed` is set, the index plus one of each integer name,
+    // or 0, indexed by the name's value, and the indices of the names
+    // whose values are too large for `dense` so far
+    dense: Vec<usize>,
+    sparse: FxHashMap<usize, usize>,
+    hashed: bool,
+}
+
+impl NameArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Returns the name with the given index.
+    pub fn get(&self, ix: usize) -> &[u8] {
+        let start = if ix == 0 { 0 } else { self.ends[ix - 1] };
+        &self.bytes[start..self.ends[ix]]
+    }
+
+    /// Returns the index of the given name, if it's in the arena.
+    pub fn find(&self, name: &[u8]) -> Option<usize> {
+        if !self.hashed {
+            let value = parse_decimal(name)?;
+            return match self.dense.get(value) {
+                Some(&entry) => entry.checked_sub(1),
+                None => self.sparse.get(&value).copied(),
+            };
+        }
+        if self.table.is_empty() {
+            return None;
+        }
+        match self.table[self.slot(name, hash(name))] {
+            0 => None,
+            entry => Some(entry_ix(entry)),
+        }
+    }
+
+    /// Returns the index of the given name, adding it with the next
+    /// index if it's not already in the arena.
+    pub fn get_or_insert(&mut self, name: &[u8]) -> usize {
+        if !self.hashed {
+            match parse_decimal(name) {
+                Some(value) => return self.get_or_insert_integer(name, value),
+                None => self.switch_to_hashed(),
+            }
+        }
+
+        // keep the load factor at most 3/4
+        if 4 * (self.len() + 1) > 3 * self.table.len() {
+            self.grow();
+        }
+
+        let hash = hash(name);
+        let slot = self.slot(name, hash);
+        match self.table[slot] {
+            0 => {
+                let ix = self.len();
+                self.bytes.extend_from_slice(name);
+                self.ends.push(self.bytes.len());
+                self.table[slot] = entry(ix, hash);
+                ix
+            }
+            entry => entry_ix(entry),
+        }
+    }
+
+    // the dense table is kept at most about twice as long as the
+    // number of names, so it takes about as much memory as the hash
+    // table would
+    fn dense_limit(&self) -> usize {
+        2 * (self.len() + 1) + DENSE_SLACK
+    }
+
+    fn get_or_insert_integer(&mut self, name: &[u8], value: usize) -> usize {
+        // the dense table only ever doubles, so that the sparse names
+        // are only moved into it a few times
+        let len = (2 * self.dense.len()).max(value.saturating_add(1));
+        if value >= self.dense.len() && len <= self.dense_limit() {
+            self.dense.resize(len, 0);
+            let dense = &mut self.dense;
+            self.sparse
+                .retain(|&value, &mut ix| match dense.get_mut(value) {
+                    Some(entry) => {
+                        *entry = ix + 1;
+                        false
+                    }
+                    None => true,
+                });
+        }
+
+        let ix = self.len();
+        match self.dense.get_mut(value) {
+            Some(0) => self.dense[value] = ix + 1,
+            Some(&mut entry) => return entry - 1,
+            None => match self.sparse.entry(value) {
+                Entry::Occupied(entry) => return *entry.get(),
+                Entry::Vacant(entry) => {
+                    entry.insert(ix);
+                }
+            },
+        }
+        self.bytes.extend_from_slice(name);
+        self.ends.push(self.bytes.len());
+        ix
+    }
+
+    fn switch_to_hashed(&mut self) {
+        self.hashed = true;
+        self.dense = Vec::new();
+        self.sparse = FxHashMap::default();
+        if !self.is_empty() {
+            let capacity =
+                (4 * (self.len() + 1) / 3 + 1).next_power_of_two().max(16);
+            self.rehash(capacity);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        (0..self.len()).map(move |ix| self.get(ix))
+    }
+
+    /// The number of bytes of heap memory used by the arena.
+    pub fn memory_usage(&self) -> usize {
+        use std::mem::size_of;
+        self.bytes.capacity()
+            + self.ends.capacity() * size_of::<usize>()
+            + self.table.capacity() * size_of::<u64>()
+            + self.dense.capacity() * size_of::<usize>()
+            + self.sparse.capacity() * 2 * size_of::<usize>()
+    }
+
+    /// Finds the slot of the name, or the empty slot where it would be
+    /// inserted, using linear probing. The table must not be empty.
+    fn slot(&self, name: &[u8], hash: u64) -> usize {
+        let mask = self.table.len() - 1;
+        let mut slot = self.home_slot(hash);
+        loop {
+            match self.table[slot] {
+                0 => return slot,
+                entry
+                    if entry & TAG_MASK == tag(hash)
+                        && self.get(entry_ix(entry)) == name =>
+                {
+                    return slot
+                }
+                _ => slot = (slot + 1) & mask,
+            }
+        }
+    }
+
+    // the high bits of the hash, since FxHash's low bits are poorly
+    // mixed, which makes for long probes on names like "1", "2", ...
+    fn home_slot(&self, hash: u64) -> usize {
+        (hash >> (64 - self.table.len().trailing_zeros())) as usize
+    }
+
+    fn grow(&mut self) {
+        self.rehash((2 * self.table.len()).max(16));
+    }
+
+    fn rehash(&mut self, capacity: usize) {
+        self.table = vec![0; capacity];
+        let mask = capacity - 1;
+        for ix in 0..self.len() {
+            let hash = hash(self.get(ix));
+            let mut slot = self.home_slot(hash);
+            while self.table[slot] != 0 {
+                slot = (slot + 1) & mask;
+            }
+            self.table[slot] = entry(ix, hash);
+        }
+    }
+}
+
+impl NodeNames for NameArena {
+    fn name(&self, ix: usize) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.get(ix))
+    }
+}
+
+/// How much longer than twice the number of names the dense table can
+/// be, so that graphs whose names start at e.g. 1000 also use it.
+const DENSE_SLACK: usize = 1 << 12;
+
+const TAG_BITS: u32 = 16;
+const TAG_MASK: u64 = (1 << TAG_BITS) - 1;
+
+fn tag(hash: u64) -> u64 {
+    hash & TAG_MASK
+}
+
+fn entry(ix: usize, hash: u64) -> u64 {
+    ((ix as u64 + 1) << TAG_BITS) | tag(hash)
+}
+
+fn entry_ix(entry: u64) -> usize {
+    (entry >> TAG_BITS) as usize - 1
+}
+
+fn hash(name: &[u8]) -> u64 {
+    let mut hasher = FxHasher::default();
+    hasher.write(name);
+    hasher.finish()
+}
+
+/// Parses a name as a plain decimal integer, without a sign or
+/// leading zeros, so that formatting the value gives back the name.
+pub(crate) fn parse_decimal(name: &[u8]) -> Option<usize> {
+    if name.is_empty() || (name[0] == b'0' && name.len() > 1) {
+        return None;
+    }
+    name.iter().try_fold(0usize, |value, &b| {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        value.checked_mul(10)?.checked_add((b - b'0') as usize)
+    })
+}
@@ -0,0 +1,1111 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Write},
+};
+
+use crate::{
+    adjacency::Adjacency,
+    connectivity::{BridgeTree, Decomposition},
+    names::{parse_decimal, NodeNames},
+    state::NodeInfo,
+};
+#[cfg(feature = "gfa")]
+use crate::{region::ReferenceInterval, rgfa::StableCoordinate};
+
+/// How the outputs refer to each component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentIds {
+    /// Its zero-based index among the components that are written.
+    Index,
+    /// Its `component_id`, which doesn't change when other components
+    /// are left out, e.g. by a different minimum size, or reordered,
+    /// so that the components of different outputs can be matched.
+    Stable,
+}
+
+impl ComponentIds {
+    fn label<N: NodeNames + ?Sized>(
+        self,
+        inv_names: &N,
+        ix: usize,
+        component: &[usize],
+    ) -> Label {
+        match self {
+            ComponentIds::Index => Label::Index(ix),
+            ComponentIds::Stable => {
+                Label::Stable(component_id(inv_names, component))
+            }
+        }
+    }
+}
+
+/// The id of one component in the output, from `ComponentIds`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Label {
+    Index(usize),
+    Stable(Vec<u8>),
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Label::Index(ix) => write!(f, "{}", ix),
+            Label::Stable(id) => write!(f, "{}", String::from_utf8_lossy(id)),
+        }
+    }
+}
+
+/// The index in `components` of the component of each node, if it's
+/// in one, and the id of each component, for the outputs with a row
+/// per node of the graph.
+fn node_labels<N: NodeNames + ?Sized>(
+    num_nodes: usize,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> (Vec<Option<usize>>, Vec<Label>) {
+    let mut component_of = vec![None; num_nodes];
+    let mut labels = Vec::with_capacity(components.len());
+    for (ix, component) in components.iter().enumerate() {
+        for &node in component {
+            component_of[node] = Some(ix);
+        }
+        labels.push(ids.label(inv_names, ix, component));
+    }
+    (component_of, labels)
+}
+
+/// Calls `f` with each edge of the graph once, as the nodes of its two
+/// ends, from the given nodes in order, with the smaller index first.
+/// Each edge is stored in the adjacency lists of both its ends, and a
+/// self-loop twice in the same list.
+fn for_each_edge<G, F>(graph: &G, nodes: &[usize], mut f: F) -> io::Result<()>
+where
+    G: Adjacency + ?Sized,
+    F: FnMut(usize, usize) -> io::Result<()>,
+{
+    for &from in nodes {
+        let mut self_loop = false;
+        for &to in graph.neighbors(from).iter().filter(|&&to| from <= to) {
+            if from == to {
+                self_loop = !self_loop;
+                if !self_loop {
+                    continue;
+                }
+            }
+            f(from, to)?;
+        }
+    }
+    Ok(())
+}
+
+/// The stable id of a component, `3ecc_` followed by the smallest of
+/// the names of its nodes, with shorter names first, which is the
+/// numeric order for integer names, e.g. `3ecc_42`. As the components
+/// don't share any nodes, their ids are distinct, and the id of a
+/// component only changes if it loses that node, or gains a smaller
+/// one.
+pub fn component_id<N: NodeNames + ?Sized>(
+    inv_names: &N,
+    component: &[usize],
+) -> Vec<u8> {
+    let smallest = component
+        .iter()
+        .map(|&node| inv_names.name(node))
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    let mut id = b"3ecc_".to_vec();
+    id.extend_from_slice(&smallest.unwrap_or_default());
+    id
+}
+
+/// Writes each component on its own line, as tab-delimited node
+/// names in the node index order, preceded by the id of the component
+/// with `ComponentIds::Stable`.
+pub fn write_text<W: Write, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()> {
+    for (ix, component) in components.iter().enumerate() {
+        if ids == ComponentIds::Stable {
+            write!(stream, "{}\t", ids.label(inv_names, ix, component))?;
+        }
+        for (i, j) in component.iter().enumerate() {
+            if i > 0 {
+                write!(
+                    stream,
+                    "\t{}",
+                    String::from_utf8_lossy(&inv_names.name(*j))
+                )?;
+            } else {
+                write!(
+                    stream,
+                    "{}",
+                    String::from_utf8_lossy(&inv_names.name(*j))
+                )?;
+            }
+        }
+        writeln!(stream)?;
+    }
+    Ok(())
+}
+
+/// Writes one row per node, with the id of the component in the first
+/// column and the node name in the second, preceded by a header row.
+pub fn write_tsv<W: Write, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()> {
+    writeln!(stream, "component\tnode")?;
+    for (ix, component) in components.iter().enumerate() {
+        let label = ids.label(inv_names, ix, component);
+        for j in component {
+            writeln!(
+                stream,
+                "{}\t{}",
+                label,
+                String::from_utf8_lossy(&inv_names.name(*j))
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Like `write_tsv`, with each field that's empty or contains a tab, a
+/// double quote, or a line break in double quotes, and its double
+/// quotes doubled, the quoting of CSV that e.g. DuckDB's `read_csv`
+/// unquotes, so that any name reads back as it was.
+pub fn write_quoted_tsv<W: Write, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()> {
+    writeln!(stream, "component\tnode")?;
+    for (ix, component) in components.iter().enumerate() {
+        let label = ids.label(inv_names, ix, component).to_string();
+        for j in component {
+            write_quoted_field(stream, label.as_bytes())?;
+            stream.write_all(b"\t")?;
+            let name =
+                String::from_utf8_lossy(&inv_names.name(*j)).into_owned();
+            write_quoted_field(stream, name.as_bytes())?;
+            stream.write_all(b"\n")?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes one field of `write_quoted_tsv`.
+fn write_quoted_field<W: Write>(
+    stream: &mut W,
+    field: &[u8],
+) -> io::Result<()> {
+    let needs_quotes = field.is_empty()
+        || field
+            .iter()
+            .any(|b| matches!(b, b'\t' | b'"' | b'\n' | b'\r'));
+    if !needs_quotes {
+        return stream.write_all(field);
+    }
+    stream.write_all(b"\"")?;
+    for (i, part) in field.split(|&b| b == b'"').enumerate() {
+        if i > 0 {
+            stream.write_all(b"\"\"")?;
+        }
+        stream.write_all(part)?;
+    }
+    stream.write_all(b"\"")
+}
+
+/// Writes one row per node, in the node index order, with its name,
+/// its degree, the id of its component, and its preorder number,
+/// lowpoint, and number of descendants from `node_info`, e.g.
+/// `State::node_info`, preceded by a header row. The component is
+/// empty for nodes that aren't in any of `components`, e.g. those left
+/// out by a minimum size, and the DFS numbers are empty for nodes
+/// `node_info` returns `None` for.
+pub fn write_node_table<W, G, N, F>(
+    stream: &mut W,
+    graph: &G,
+    inv_names: &N,
+    node_info: F,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()>
+where
+    W: Write,
+    G: Adjacency + ?Sized,
+    N: NodeNames + ?Sized,
+    F: Fn(usize) -> Option<NodeInfo>,
+{
+    let (component_of, labels) =
+        node_labels(graph.num_nodes(), inv_names, components, ids);
+
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort_unstable();
+
+    writeln!(stream, "node\tdegree\tcomponent\tpre\tlowpt\tdescendants")?;
+    for node in nodes {
+        write!(
+            stream,
+            "{}\t{}\t",
+            String::from_utf8_lossy(&inv_names.name(node)),
+            graph.neighbors(node).len()
+        )?;
+        if let Some(ix) = component_of[node] {
+            write!(stream, "{}", labels[ix])?;
+        }
+        match node_info(node) {
+            Some(info) => writeln!(
+                stream,
+                "\t{}\t{}\t{}",
+                info.pre, info.lowpt, info.num_descendants
+            )?,
+            None => writeln!(stream, "\t\t\t")?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes the components as an SQL script for SQLite, which replaces
+/// the `components` table, with the `id` and `size` of each component,
+/// and the `nodes` table, with the `name` and `component` of each node
+/// in one of them, in a single transaction, e.g. for `sqlite3
+/// results.db < script.sql`. The ids are integers with
+/// `ComponentIds::Index`, and text otherwise.
+pub fn write_sql<W: Write, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()> {
+    // rows per INSERT statement, which parses much faster than one
+    // statement per row
+    const ROWS: usize = 500;
+
+    let id_type = match ids {
+        ComponentIds::Index => "INTEGER",
+        ComponentIds::Stable => "TEXT",
+    };
+    let labels: Vec<Label> = components
+        .iter()
+        .enumerate()
+        .map(|(ix, component)| ids.label(inv_names, ix, component))
+        .collect();
+    let write_label = |stream: &mut W, label: &Label| match label {
+        Label::Index(ix) => write!(stream, "{}", ix),
+        Label::Stable(id) => write_sql_string(stream, id),
+    };
+
+    writeln!(stream, "BEGIN;")?;
+    writeln!(stream, "DROP TABLE IF EXISTS nodes;")?;
+    writeln!(stream, "DROP TABLE IF EXISTS components;")?;
+    writeln!(
+        stream,
+        "CREATE TABLE components (id {} PRIMARY KEY, size INTEGER NOT NULL);",
+        id_type
+    )?;
+    writeln!(
+        stream,
+        "CREATE TABLE nodes (name TEXT PRIMARY KEY, component {} NOT NULL \
+         REFERENCES components (id));",
+        id_type
+    )?;
+
+    for (i, (label, component)) in labels.iter().zip(components).enumerate() {
+        if i % ROWS == 0 {
+            write!(stream, "INSERT INTO components VALUES\n(")?;
+        } else {
+            write!(stream, ",\n(")?;
+        }
+        write_label(stream, label)?;
+        write!(stream, ", {})", component.len())?;
+        if i % ROWS == ROWS - 1 || i == components.len() - 1 {
+            writeln!(stream, ";")?;
+        }
+    }
+
+    let rows = components
+        .iter()
+        .zip(&labels)
+        .flat_map(|(component, label)| {
+            component.iter().map(move |&node| (node, label))
+        });
+    let num_rows: usize = components.iter().map(Vec::len).sum();
+    for (i, (node, label)) in rows.enumerate() {
+        if i % ROWS == 0 {
+            write!(stream, "INSERT INTO nodes VALUES\n(")?;
+        } else {
+            write!(stream, ",\n(")?;
+        }
+        write_sql_string(stream, &inv_names.name(node))?;
+        write!(stream, ", ")?;
+        write_label(stream, label)?;
+        write!(stream, ")")?;
+        if i % ROWS == ROWS - 1 || i == num_rows - 1 {
+            writeln!(stream, ";")?;
+        }
+    }
+
+    writeln!(stream, "CREATE INDEX nodes_component ON nodes (component);")?;
+    writeln!(stream, "COMMIT;")
+}
+
+/// Writes the bytes as an SQL string literal, with invalid UTF-8
+/// replaced.
+fn write_sql_string<W: Write>(stream: &mut W, bytes: &[u8]) -> io::Result<()> {
+    let string = String::from_utf8_lossy(bytes);
+    write!(stream, "'{}'", string.replace('\'', "''"))
+}
+
+/// Like `write_tsv`, with the stable coordinates of each segment of an
+/// rGFA file, its `SN`, `SO`, and `SR` tags and its length, in four more
+/// columns, or `*` for segments without them.
+#[cfg(feature = "gfa")]
+pub fn write_rgfa_tsv<'a, W, N, F>(
+    stream: &mut W,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+    coordinate: F,
+) -> io::Result<()>
+where
+    W: Write,
+    N: NodeNames + ?Sized,
+    F: Fn(usize) -> Option<&'a StableCoordinate>,
+{
+    writeln!(stream, "component\tnode\tSN\tSO\tSR\tLN")?;
+    for (ix, component) in components.iter().enumerate() {
+        let label = ids.label(inv_names, ix, component);
+        for &j in component {
+            write!(
+                stream,
+                "{}\t{}",
+                label,
+                String::from_utf8_lossy(&inv_names.name(j))
+            )?;
+            match coordinate(j) {
+                None => writeln!(stream, "\t*\t*\t*\t*")?,
+                Some(coordinate) => {
+                    write!(
+                        stream,
+                        "\t{}\t{}\t{}\t",
+                        String::from_utf8_lossy(&coordinate.name),
+                        coordinate.offset,
+                        coordinate.rank
+                    )?;
+                    match coordinate.length {
+                        None => writeln!(stream, "*")?,
+                        Some(length) => writeln!(stream, "{}", length)?,
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes the components projected onto a reference as BED, with one
+/// line per run of consecutive intervals, from `reference_intervals`,
+/// whose segments are in the same component, and the id of the
+/// component from `component`, such as its index or `component_id`,
+/// in the name column. Segments that aren't in any of the components
+/// are skipped, and end a run.
+#[cfg(feature = "gfa")]
+pub fn write_bed<W, F, L>(
+    stream: &mut W,
+    reference: &[u8],
+    intervals: &[ReferenceInterval],
+    component: F,
+) -> io::Result<()>
+where
+    W: Write,
+    F: Fn(&[u8]) -> Option<L>,
+    L: PartialEq + fmt::Display,
+{
+    let mut write_run = |(ix, start, end): (L, usize, usize)| {
+        writeln!(
+            stream,
+            "{}\t{}\t{}\t{}",
+            String::from_utf8_lossy(reference),
+            start,
+            end,
+            ix
+        )
+    };
+
+    let mut run: Option<(L, usize, usize)> = None;
+    for interval in intervals {
+        let ix = component(&interval.segment);
+        match (&mut run, &ix) {
+            (Some((run_ix, _, end)), Some(ix))
+                if run_ix == ix && interval.start <= *end =>
+            {
+                *end = (*end).max(interval.end);
+            }
+            _ => {
+                if let Some(run) = run.take() {
+                    write_run(run)?;
+                }
+                run = ix.map(|ix| (ix, interval.start, interval.end));
+            }
+        }
+    }
+    if let Some(run) = run {
+        write_run(run)?;
+    }
+    Ok(())
+}
+
+/// Writes the components as a JSON array of arrays of node names, or
+/// with `ComponentIds::Stable`, of objects with the id of the
+/// component in `id` and the array of node names in `nodes`.
+pub fn write_json<W: Write, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()> {
+    write!(stream, "[")?;
+    for (i, component) in components.iter().enumerate() {
+        if i > 0 {
+            write!(stream, ",")?;
+        }
+        write!(stream, "\n  ")?;
+        if ids == ComponentIds::Stable {
+            write!(stream, "{{\"id\": ")?;
+            write_json_string(stream, &component_id(inv_names, component))?;
+            write!(stream, ", \"nodes\": ")?;
+        }
+        write!(stream, "[")?;
+        for (k, j) in component.iter().enumerate() {
+            if k > 0 {
+                write!(stream, ", ")?;
+            }
+            write_json_string(stream, &inv_names.name(*j))?;
+        }
+        write!(stream, "]")?;
+        if ids == ComponentIds::Stable {
+            write!(stream, "}}")?;
+        }
+    }
+    writeln!(stream, "\n]")
+}
+
+/// Writes the graph in the DOT format, with each component as a
+/// cluster subgraph, named `cluster_` and the id of the component.
+/// Nodes that aren't in any of the given components are placed
+/// outside the clusters.
+pub fn write_dot<W: Write, G: Adjacency + ?Sized, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    graph: &G,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()> {
+    writeln!(stream, "graph {{")?;
+    for (ix, component) in components.iter().enumerate() {
+        match ids.label(inv_names, ix, component) {
+            Label::Index(ix) => {
+                writeln!(stream, "  subgraph cluster_{} {{", ix)?
+            }
+            Label::Stable(id) => {
+                write!(stream, "  subgraph ")?;
+                write_dot_id(stream, &[&b"cluster_"[..], &id].concat())?;
+                writeln!(stream, " {{")?;
+            }
+        }
+        for j in component {
+            write!(stream, "    ")?;
+            write_dot_id(stream, &inv_names.name(*j))?;
+            writeln!(stream, ";")?;
+        }
+        writeln!(stream, "  }}")?;
+    }
+
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort_unstable();
+
+    for_each_edge(graph, &nodes, |from, to| {
+        write!(stream, "  ")?;
+        write_dot_id(stream, &inv_names.name(from))?;
+        write!(stream, " -- ")?;
+        write_dot_id(stream, &inv_names.name(to))?;
+        writeln!(stream, ";")
+    })?;
+    writeln!(stream, "}}")
+}
+
+/// Writes the graph as GFA 1, with a segment line without a sequence
+/// for each node, tagged with the id of its component in a `cc` tag,
+/// an integer with `ComponentIds::Index`, and a link line for each
+/// edge, from the `+` strand to the `+` strand, since the graph
+/// doesn't keep the orientations. Nodes that aren't in any of the
+/// given components have no `cc`. Each edge is written once, and
+/// self-loops too.
+pub fn write_gfa<W: Write, G: Adjacency + ?Sized, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    graph: &G,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()> {
+    let (component_of, labels) =
+        node_labels(graph.num_nodes(), inv_names, components, ids);
+    let tag_type = match ids {
+        ComponentIds::Index => 'i',
+        ComponentIds::Stable => 'Z',
+    };
+
+    writeln!(stream, "H\tVN:Z:1.0")?;
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort_unstable();
+    for &node in nodes.iter() {
+        write!(stream, "S\t")?;
+        stream.write_all(&inv_names.name(node))?;
+        write!(stream, "\t*")?;
+        if let Some(ix) = component_of[node] {
+            write!(stream, "\tcc:{}:{}", tag_type, labels[ix])?;
+        }
+        writeln!(stream)?;
+    }
+    for_each_edge(graph, &nodes, |from, to| {
+        write!(stream, "L\t")?;
+        stream.write_all(&inv_names.name(from))?;
+        write!(stream, "\t+\t")?;
+        stream.write_all(&inv_names.name(to))?;
+        writeln!(stream, "\t+\t*")
+    })
+}
+
+/// Writes the graph in the GEXF format of Gephi, with the node names
+/// as the labels and the id of each node's component in its
+/// `component` attribute, which Gephi can partition and colour the
+/// nodes by. Nodes that aren't in any of the given components have no
+/// value for it. Each edge is written once, and self-loops too.
+pub fn write_gexf<W: Write, G: Adjacency + ?Sized, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    graph: &G,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()> {
+    let (component_of, labels) =
+        node_labels(graph.num_nodes(), inv_names, components, ids);
+    let attribute_type = match ids {
+        ComponentIds::Index => "integer",
+        ComponentIds::Stable => "string",
+    };
+
+    writeln!(stream, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        stream,
+        "<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">"
+    )?;
+    writeln!(stream, "  <graph defaultedgetype=\"undirected\">")?;
+    writeln!(stream, "    <attributes class=\"node\">")?;
+    writeln!(
+        stream,
+        "      <attribute id=\"0\" title=\"component\" type=\"{}\"/>",
+        attribute_type
+    )?;
+    writeln!(stream, "    </attributes>")?;
+
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort_unstable();
+
+    writeln!(stream, "    <nodes>")?;
+    for &node in nodes.iter() {
+        write!(stream, "      <node id=\"{}\" label=\"", node)?;
+        write_xml_text(stream, &inv_names.name(node))?;
+        match component_of[node] {
+            Some(ix) => {
+                writeln!(stream, "\">")?;
+                write!(
+                    stream,
+                    "        <attvalues><attvalue for=\"0\" value=\""
+                )?;
+                write_xml_text(stream, labels[ix].to_string().as_bytes())?;
+                writeln!(stream, "\"/></attvalues>")?;
+                writeln!(stream, "      </node>")?;
+            }
+            None => writeln!(stream, "\"/>")?,
+        }
+    }
+    writeln!(stream, "    </nodes>")?;
+
+    writeln!(stream, "    <edges>")?;
+    let mut edge_id = 0;
+    for_each_edge(graph, &nodes, |from, to| {
+        writeln!(
+            stream,
+            "      <edge id=\"{}\" source=\"{}\" target=\"{}\"/>",
+            edge_id, from, to
+        )?;
+        edge_id += 1;
+        Ok(())
+    })?;
+    writeln!(stream, "    </edges>")?;
+    writeln!(stream, "  </graph>")?;
+    writeln!(stream, "</gexf>")
+}
+
+/// Writes the graph in the GraphML format, with the node names as the
+/// node ids, and the id of each node's component in its `component`
+/// data, e.g. for NetworkX's `read_graphml`. Nodes that aren't in any
+/// of the given components have no `component`. Each edge is written
+/// once, and self-loops too.
+pub fn write_graphml<W, G, N>(
+    stream: &mut W,
+    graph: &G,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()>
+where
+    W: Write,
+    G: Adjacency + ?Sized,
+    N: NodeNames + ?Sized,
+{
+    let (component_of, labels) =
+        node_labels(graph.num_nodes(), inv_names, components, ids);
+    let attribute_type = match ids {
+        ComponentIds::Index => "int",
+        ComponentIds::Stable => "string",
+    };
+
+    writeln!(stream, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        stream,
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+    )?;
+    writeln!(
+        stream,
+        "  <key id=\"component\" for=\"node\" attr.name=\"component\" \
+         attr.type=\"{}\"/>",
+        attribute_type
+    )?;
+    writeln!(stream, "  <graph id=\"G\" edgedefault=\"undirected\">")?;
+
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort_unstable();
+
+    for &node in nodes.iter() {
+        write!(stream, "    <node id=\"")?;
+        write_xml_text(stream, &inv_names.name(node))?;
+        match component_of[node] {
+            Some(ix) => {
+                write!(stream, "\"><data key=\"component\">")?;
+                write_xml_text(stream, labels[ix].to_string().as_bytes())?;
+                writeln!(stream, "</data></node>")?;
+            }
+            None => writeln!(stream, "\"/>")?,
+        }
+    }
+
+    for_each_edge(graph, &nodes, |from, to| {
+        write!(stream, "    <edge source=\"")?;
+        write_xml_text(stream, &inv_names.name(from))?;
+        write!(stream, "\" target=\"")?;
+        write_xml_text(stream, &inv_names.name(to))?;
+        writeln!(stream, "\"/>")
+    })?;
+    writeln!(stream, "  </graph>")?;
+    writeln!(stream, "</graphml>")
+}
+
+/// Writes the graph in the GML format, with the node indices as the
+/// ids, the node names as the labels, and the id of each node's
+/// component as its `component`, an integer with
+/// `ComponentIds::Index`. Nodes that aren't in any of the given
+/// components have no `component`. Each edge is written once, and
+/// self-loops too, so the graph is marked as a `multigraph`.
+pub fn write_gml<W: Write, G: Adjacency + ?Sized, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    graph: &G,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()> {
+    let (component_of, labels) =
+        node_labels(graph.num_nodes(), inv_names, components, ids);
+
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort_unstable();
+
+    writeln!(stream, "graph [\n  directed 0\n  multigraph 1")?;
+    for &node in nodes.iter() {
+        write!(stream, "  node [\n    id {}\n    label ", node)?;
+        write_gml_string(stream, &inv_names.name(node))?;
+        writeln!(stream)?;
+        if let Some(ix) = component_of[node] {
+            write!(stream, "    component ")?;
+            write_gml_label(stream, &labels[ix])?;
+            writeln!(stream)?;
+        }
+        writeln!(stream, "  ]")?;
+    }
+    for_each_edge(graph, &nodes, |from, to| {
+        writeln!(
+            stream,
+            "  edge [\n    source {}\n    target {}\n  ]",
+            from, to
+        )
+    })?;
+    writeln!(stream, "]")
+}
+
+/// Writes the condensed graph in the GML format, with a node for each
+/// of the given components, with its id as its `label` and its number
+/// of nodes as its `size`, and an edge between two components for the
+/// edges of the graph between them, with their number as its `weight`.
+/// This is a graph of how the components are held together, e.g. by
+/// pairs of edges. Nodes that aren't in any of the components are left
+/// out, along with their edges, so all of the nodes are only in it if
+/// the components are all of them.
+pub fn write_condensed_gml<W, G, N>(
+    stream: &mut W,
+    graph: &G,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()>
+where
+    W: Write,
+    G: Adjacency + ?Sized,
+    N: NodeNames + ?Sized,
+{
+    let (component_of, labels) =
+        node_labels(graph.num_nodes(), inv_names, components, ids);
+
+    let mut nodes: Vec<usize> = graph.nodes().collect();
+    nodes.sort_unstable();
+    let mut weights = BTreeMap::new();
+    for_each_edge(graph, &nodes, |from, to| {
+        if let (Some(a), Some(b)) = (component_of[from], component_of[to]) {
+            if a != b {
+                *weights.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+            }
+        }
+        Ok(())
+    })?;
+
+    writeln!(stream, "graph [\n  directed 0")?;
+    for (ix, component) in components.iter().enumerate() {
+        write!(stream, "  node [\n    id {}\n    label ", ix)?;
+        write_gml_string(stream, labels[ix].to_string().as_bytes())?;
+        writeln!(stream, "\n    size {}\n  ]", component.len())?;
+    }
+    for ((a, b), weight) in weights {
+        writeln!(
+            stream,
+            "  edge [\n    source {}\n    target {}\n    weight {}\n  ]",
+            a, b, weight
+        )?;
+    }
+    writeln!(stream, "]")
+}
+
+fn write_gml_label<W: Write>(stream: &mut W, label: &Label) -> io::Result<()> {
+    match label {
+        Label::Index(ix) => write!(stream, "{}", ix),
+        Label::Stable(id) => write_gml_string(stream, id),
+    }
+}
+
+/// Writes the bytes as a GML string, which is ASCII without quotes,
+/// with the rest as character references, as NetworkX writes them.
+fn write_gml_string<W: Write>(stream: &mut W, bytes: &[u8]) -> io::Result<()> {
+    write!(stream, "\"")?;
+    for c in String::from_utf8_lossy(bytes).chars() {
+        match c {
+            '&' => write!(stream, "&amp;")?,
+            '"' => write!(stream, "&quot;")?,
+            ' '..='~' => write!(stream, "{}", c)?,
+            c => write!(stream, "&#{};", c as u32)?,
+        }
+    }
+    write!(stream, "\"")
+}
+
+/// Writes the bytes as XML text, escaped for an attribute value or an
+/// element, with invalid UTF-8 and the control characters XML doesn't
+/// allow replaced.
+fn write_xml_text<W: Write>(stream: &mut W, bytes: &[u8]) -> io::Result<()> {
+    for c in String::from_utf8_lossy(bytes).chars() {
+        match c {
+            '&' => write!(stream, "&amp;")?,
+            '<' => write!(stream, "&lt;")?,
+            '>' => write!(stream, "&gt;")?,
+            '"' => write!(stream, "&quot;")?,
+            '\t' | '\n' | '\r' => write!(stream, "&#{};", c as u32)?,
+            c if (c as u32) < 0x20 => write!(stream, "\u{fffd}")?,
+            c => write!(stream, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes one row per node, as a CSV file with the `Name`, `Colour`,
+/// and `Component` columns that Bandage imports as custom labels and
+/// colours, to show the components on the assembly graph. Each
+/// component gets its own colour, from a hash of its id, so that the
+/// colours of stable ids are the same in every output. Nodes that
+/// aren't in any of the components are left out, and keep Bandage's
+/// own colour.
+pub fn write_bandage_csv<W: Write, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()> {
+    writeln!(stream, "Name,Colour,Component")?;
+    for (ix, component) in components.iter().enumerate() {
+        let label = ids.label(inv_names, ix, component).to_string();
+        let colour = label_colour(&label);
+        for &j in component {
+            write_csv_field(stream, &inv_names.name(j))?;
+            write!(stream, ",{},", colour)?;
+            write_csv_field(stream, label.as_bytes())?;
+            writeln!(stream)?;
+        }
+    }
+    Ok(())
+}
+
+/// A bright colour for a component id, as `#rrggbb`, with the hue
+/// taken from an FNV-1a hash of the id.
+fn label_colour(label: &str) -> String {
+    let hash = label.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    // HSV with a saturation of 0.7 and a value of 0.9
+    let hue = (hash % 360) as f64 / 60.0;
+    let (max, min) = (0.9, 0.9 * 0.3);
+    let mid = min + (max - min) * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (max, mid, min),
+        1 => (mid, max, min),
+        2 => (min, max, mid),
+        3 => (min, mid, max),
+        4 => (mid, min, max),
+        _ => (max, min, mid),
+    };
+    let byte = |c: f64| (c * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", byte(r), byte(g), byte(b))
+}
+
+/// Writes a CSV field, quoted if it contains a comma, a quote, or a
+/// line break.
+fn write_csv_field<W: Write>(stream: &mut W, bytes: &[u8]) -> io::Result<()> {
+    let field = String::from_utf8_lossy(bytes);
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        write!(stream, "\"{}\"", field.replace('"', "\"\""))
+    } else {
+        write!(stream, "{}", field)
+    }
+}
+
+/// Writes one row per node, in the format of odgi's node tables such
+/// as those of `odgi degree`, with a `#node.id` header, so that the
+/// components can be used with the odgi toolchain, e.g. to select the
+/// nodes of a component for `odgi extract`. The rows are sorted by
+/// node id, with the id of the component and its number of nodes. The
+/// node names must be the positive integers that odgi uses as node
+/// ids.
+pub fn write_odgi<W: Write, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    inv_names: &N,
+    components: &[Vec<usize>],
+    ids: ComponentIds,
+) -> io::Result<()> {
+    let mut rows = Vec::with_capacity(components.iter().map(Vec::len).sum());
+    for (ix, component) in components.iter().enumerate() {
+        for &j in component {
+            let name = inv_names.name(j);
+            let id =
+                parse_decimal(&name).filter(|&id| id > 0).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                        "node name {:?} is not a positive integer, as odgi \
+                         node ids are",
+                        String::from_utf8_lossy(&name)
+                    ),
+                    )
+                })?;
+            rows.push((id, ix));
+        }
+    }
+    rows.sort_unstable();
+
+    writeln!(stream, "#node.id\tcomponent.id\tcomponent.size")?;
+    let labels: Vec<Label> = components
+        .iter()
+        .enumerate()
+        .map(|(ix, component)| ids.label(inv_names, ix, component))
+        .collect();
+    for (id, ix) in rows {
+        let (label, size) = (&labels[ix], components[ix].len());
+        writeln!(stream, "{}\t{}\t{}", id, label, size)?;
+    }
+    Ok(())
+}
+
+/// Writes the bridge tree in the DOT format, with a node for each
+/// 2-edge-connected component, named by its index and labelled with
+/// its number of nodes, and an edge for each bridge, labelled with
+/// the names of its ends.
+pub fn write_bridge_tree_dot<W: Write, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    tree: &BridgeTree,
+    inv_names: &N,
+) -> io::Result<()> {
+    writeln!(stream, "graph {{")?;
+    for (ix, component) in tree.components.iter().enumerate() {
+        writeln!(stream, "  {} [label=\"{} nodes\"];", ix, component.len())?;
+    }
+    for edge in tree.edges.iter() {
+        let (from, to) = edge.bridge;
+        let mut label = inv_names.name(from).into_owned();
+        label.extend_from_slice(b" -- ");
+        label.extend_from_slice(&inv_names.name(to));
+        write!(
+            stream,
+            "  {} -- {} [label=",
+            edge.components.0, edge.components.1
+        )?;
+        write_dot_id(stream, &label)?;
+        writeln!(stream, "];")?;
+    }
+    writeln!(stream, "}}")
+}
+
+/// Writes the bridge tree as a JSON object, with the node names of
+/// each 2-edge-connected component in `components`, and each bridge
+/// in `bridges`, with the indices of the components it joins in
+/// `components` and the names of its ends in `edge`.
+pub fn write_bridge_tree_json<W: Write, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    tree: &BridgeTree,
+    inv_names: &N,
+) -> io::Result<()> {
+    write!(stream, "{{\n  \"components\": [")?;
+    for (i, component) in tree.components.iter().enumerate() {
+        if i > 0 {
+            write!(stream, ",")?;
+        }
+        write!(stream, "\n    [")?;
+        for (k, j) in component.iter().enumerate() {
+            if k > 0 {
+                write!(stream, ", ")?;
+            }
+            write_json_string(stream, &inv_names.name(*j))?;
+        }
+        write!(stream, "]")?;
+    }
+    write!(stream, "\n  ],\n  \"bridges\": [")?;
+    for (i, edge) in tree.edges.iter().enumerate() {
+        if i > 0 {
+            write!(stream, ",")?;
+        }
+        write!(
+            stream,
+            "\n    {{\"components\": [{}, {}], \"edge\": [",
+            edge.components.0, edge.components.1
+        )?;
+        write_json_string(stream, &inv_names.name(edge.bridge.0))?;
+        write!(stream, ", ")?;
+        write_json_string(stream, &inv_names.name(edge.bridge.1))?;
+        write!(stream, "]}}")?;
+    }
+    writeln!(stream, "\n  ]\n}}")
+}
+
+/// Writes the decomposition as a JSON tree: an array of the connected
+/// components, each an object with its node names in `nodes` and its
+/// 2-edge-connected components in `two_edge`, which are objects with
+/// their own `nodes` and their 3-edge-connected components in
+/// `three_edge`, as arrays of node names.
+pub fn write_decomposition_json<W: Write, N: NodeNames + ?Sized>(
+    stream: &mut W,
+    decomposition: &Decomposition,
+    inv_names: &N,
+) -> io::Result<()> {
+    let write_names = |stream: &mut W, nodes: &[usize]| -> io::Result<()> {
+        write!(stream, "[")?;
+        for (k, j) in nodes.iter().enumerate() {
+            if k > 0 {
+                write!(stream, ", ")?;
+            }
+            write_json_string(stream, &inv_names.name(*j))?;
+        }
+        write!(stream, "]")
+    };
+
+    write!(stream, "[")?;
+    for (i, component) in decomposition.connected.iter().enumerate() {
+        if i > 0 {
+            write!(stream, ",")?;
+        }
+        write!(stream, "\n  {{\"nodes\": ")?;
+        write_names(stream, component)?;
+        write!(stream, ", \"two_edge\": [")?;
+        let children = &decomposition.connected_children[i];
+        for (k, &two_edge) in children.iter().enumerate() {
+            if k > 0 {
+                write!(stream, ",")?;
+            }
+            write!(stream, "\n    {{\"nodes\": ")?;
+            write_names(stream, &decomposition.two_edge[two_edge])?;
+            write!(stream, ", \"three_edge\": [")?;
+            let children = &decomposition.two_edge_children[two_edge];
+            for (l, &three_edge) in children.iter().enumerate() {
+                if l > 0 {
+                    write!(stream, ", ")?;
+                }
+                write_names(stream, &decomposition.three_edge[three_edge])?;
+            }
+            write!(stream, "]}}")?;
+        }
+        write!(stream, "\n  ]}}")?;
+    }
+    writeln!(stream, "\n]")
+}
+
+/// Writes the bytes as a JSON string, with invalid UTF-8 replaced.
+pub fn write_json_string<W: Write>(
+    stream: &mut W,
+    bytes: &[u8],
+) -> io::Result<()> {
+    write!(stream, "\"")?;
+    for c in String::from_utf8_lossy(bytes).chars() {
+        match c {
+            '"' => write!(stream, "\\\"")?,
+            '\\' => write!(stream, "\\\\")?,
+            '\n' => write!(stream, "\\n")?,
+            '\t' => write!(stream, "\\t")?,
+            '\r' => write!(stream, "\\r")?,
+            c if (c as u32) < 0x20 => write!(stream, "\\u{:04x}", c as u32)?,
+            c => write!(stream, "{}", c)?,
+        }
+    }
+    write!(stream, "\"")
+}
+
+fn write_dot_id<W: Write>(stream: &mut W, bytes: &[u8]) -> io::Result<()> {
+    write!(stream, "\"")?;
+    for c in String::from_utf8_lossy(bytes).chars() {
+        match c {
+            '"' => write!(stream, "\\\"")?,
+            '\\' => write!(stream, "\\\\")?,
+            c => write!(stream, "{}", c)?,
+        }
+    }
+    write!(stream, "\"")
+}
@@ -0,0 +1,190 @@
+use std::io::{self, BufRead};
+
+use fxhash::FxHashSet;
+
+use crate::names::parse_decimal;
+
+/// Which overlaps of a PAF file are edges of the overlap graph, see
+/// `paf_edges`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PafFilter {
+    /// The shortest alignment block, the 11th column, that is used.
+    pub min_overlap: usize,
+    /// The lowest identity, the number of matching bases divided by
+    /// the alignment block length, that is used.
+    pub min_identity: f64,
+    /// The lowest mapping quality, the 12th column, that is used.
+    pub min_mapq: u8,
+    /// The longest unaligned part of both reads at an end of the
+    /// alignment for it to still count as an overlap, instead of a
+    /// partial match, e.g. from a repeat.
+    pub max_overhang: usize,
+}
+
+impl Default for PafFilter {
+    fn default() -> Self {
+        PafFilter {
+            min_overlap: 0,
+            min_identity: 0.0,
+            min_mapq: 0,
+            max_overhang: 1000,
+        }
+    }
+}
+
+/// An alignment between two reads, from one line of a PAF file.
+#[derive(Debug)]
+struct Overlap<'a> {
+    query: &'a [u8],
+    query_len: usize,
+    query_start: usize,
+    query_end: usize,
+    reverse: bool,
+    target: &'a [u8],
+    target_len: usize,
+    target_start: usize,
+    target_end: usize,
+    matches: usize,
+    block_len: usize,
+    mapq: u8,
+}
+
+fn parse_line(line: &[u8]) -> io::Result<Overlap<'_>> {
+    let invalid = |message: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} in PAF line {:?}",
+                message,
+                String::from_utf8_lossy(line)
+            ),
+        )
+    };
+    let fields: Vec<&[u8]> = line.split(|&b| b == b'\t').take(12).collect();
+    if fields.len() < 12 {
+        return Err(invalid("fewer than 12 columns"));
+    }
+    let int = |ix: usize| {
+        parse_decimal(fields[ix]).ok_or_else(|| invalid("invalid integer"))
+    };
+    let reverse = match fields[4] {
+        b"+" => false,
+        b"-" => true,
+        _ => return Err(invalid("invalid strand")),
+    };
+    let overlap = Overlap {
+        query: fields[0],
+        query_len: int(1)?,
+        query_start: int(2)?,
+        query_end: int(3)?,
+        reverse,
+        target: fields[5],
+        target_len: int(6)?,
+        target_start: int(7)?,
+        target_end: int(8)?,
+        matches: int(9)?,
+        block_len: int(10)?,
+        mapq: int(11)?.min(255) as u8,
+    };
+    if overlap.query_start > overlap.query_end
+        || overlap.query_end > overlap.query_len
+        || overlap.target_start > overlap.target_end
+        || overlap.target_end > overlap.target_len
+    {
+        return Err(invalid("alignment out of range"));
+    }
+    Ok(overlap)
+}
+
+impl Overlap<'_> {
+    fn passes(&self, filter: &PafFilter) -> bool {
+        let identity = if self.block_len == 0 {
+            0.0
+        } else {
+            self.matches as f64 / self.block_len as f64
+        };
+        self.block_len >= filter.min_overlap
+            && identity >= filter.min_identity
+            && self.mapq >= filter.min_mapq
+    }
+
+    /// Whether the alignment reaches, up to `max_overhang`, an end of
+    /// one of the reads at each end of the alignment, and neither read
+    /// is contained in the other, as in miniasm's classification.
+    fn is_dovetail(&self, max_overhang: usize) -> bool {
+        // the target coordinates on the query's strand
+        let (target_start, target_end) = if self.reverse {
+            (
+                self.target_len - self.target_end,
+                self.target_len - self.target_start,
+            )
+        } else {
+            (self.target_start, self.target_end)
+        };
+        let query_right = self.query_len - self.query_end;
+        let target_right = self.target_len - target_end;
+
+        let left_overhang = self.query_start.min(target_start);
+        let right_overhang = query_right.min(target_right);
+        if left_overhang > max_overhang || right_overhang > max_overhang {
+            return false;
+        }
+
+        // with both overhangs short, a read is contained if it has the
+        // shorter unaligned part at both ends
+        let query_contained =
+            self.query_start <= target_start && query_right <= target_right;
+        let target_contained =
+            target_start <= self.query_start && target_right <= query_right;
+        !query_contained && !target_contained
+    }
+}
+
+/// Builds the overlap graph of the alignments in a PAF file, such as
+/// the all-versus-all read overlaps from `minimap2 -x ava-ont`: the
+/// reads are the nodes, and there is an edge between two reads if any
+/// of their alignments passes the filter and is a dovetail overlap.
+/// Self-alignments, containments, and alignments that end in the
+/// middle of both reads aren't edges. Each pair of reads is an edge
+/// at most once, however many alignments it has, and in whichever
+/// order. The strands are ignored, as the components of the graph
+/// don't depend on them.
+pub fn paf_edges<T: BufRead>(
+    reader: T,
+    filter: PafFilter,
+) -> impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>> {
+    let mut seen: FxHashSet<(Vec<u8>, Vec<u8>)> = FxHashSet::default();
+    reader.split(b'\n').filter_map(move |line| {
+        let mut line = match line {
+            Err(err) => return Some(Err(err)),
+            Ok(line) => line,
+        };
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return None;
+        }
+        let overlap = match parse_line(&line) {
+            Err(err) => return Some(Err(err)),
+            Ok(overlap) => overlap,
+        };
+        if overlap.query == overlap.target
+            || !overlap.passes(&filter)
+            || !overlap.is_dovetail(filter.max_overhang)
+        {
+            return None;
+        }
+
+        let (from, to) = if overlap.query <= overlap.target {
+            (overlap.query, overlap.target)
+        } else {
+            (overlap.target, overlap.query)
+        };
+        let pair = (from.to_vec(), to.to_vec());
+        if !seen.insert(pair.clone()) {
+            return None;
+        }
+        Some(Ok(pair))
+    })
+}
@@ -0,0 +1,243 @@
+use std::io::{self, BufRead};
+
+use fxhash::FxHashSet;
+
+use crate::names::parse_decimal;
+
+/// The section of a Pajek file that the lines are in, from the last
+/// line starting with `*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    /// Before any section, or in one that doesn't have edges, such as
+    /// `*Network`.
+    Other,
+    Vertices,
+    /// `*Edges` and `*Arcs`, with one edge per line.
+    Pairs,
+    /// `*Edgeslist` and `*Arcslist`, with a vertex and all its
+    /// neighbors on each line.
+    Lists,
+}
+
+/// The edges of a graph in the Pajek format, from `pajek_edges`.
+pub struct PajekEdges<R> {
+    reader: R,
+    line: Vec<u8>,
+    section: Section,
+    // the number of vertices of the `*Vertices` line, if there was one
+    num_vertices: Option<usize>,
+    labels: Vec<Option<Vec<u8>>>,
+    labels_checked: bool,
+    edges: std::vec::IntoIter<(Vec<u8>, Vec<u8>)>,
+    failed: bool,
+}
+
+/// Parses the edges of a graph in the Pajek format, the `.net` files
+/// of Pajek and of network analysis tools such as igraph and NetworkX:
+/// an optional `*Vertices` section that numbers the vertices from 1,
+/// with a label for each, and `*Edges` or `*Edgeslist` sections of the
+/// pairs of vertex numbers, or of a vertex and its neighbors. The
+/// vertices are named by their labels, or by their numbers if they
+/// don't have one, and the labels must be distinct. `*Arcs` and
+/// `*Arcslist` are read as undirected edges too, and the weights and
+/// other attributes of the lines are ignored, as are the lines
+/// starting with `%`. Vertices without edges aren't in the graph, like
+/// segments without links in GFA, and `*Matrix` sections are an error.
+pub fn pajek_edges<R: BufRead>(reader: R) -> PajekEdges<R> {
+    PajekEdges {
+        reader,
+        line: Vec::new(),
+        section: Section::Other,
+        num_vertices: None,
+        labels: Vec::new(),
+        labels_checked: false,
+        edges: Vec::new().into_iter(),
+        failed: false,
+    }
+}
+
+fn invalid(message: &str, line: &[u8]) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "{} in Pajek line {:?}",
+            message,
+            String::from_utf8_lossy(line)
+        ),
+    )
+}
+
+/// Splits off the first field of the line, a word or a string in
+/// double quotes, without the quotes, and returns it with the rest.
+fn next_field(line: &[u8]) -> io::Result<Option<(&[u8], &[u8])>> {
+    let start = match line.iter().position(|b| !b.is_ascii_whitespace()) {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+    let rest = &line[start..];
+    if rest[0] == b'"' {
+        let end = rest[1..]
+            .iter()
+            .position(|&b| b == b'"')
+            .ok_or_else(|| invalid("unterminated quote", line))?
+            + 1;
+        Ok(Some((&rest[1..end], &rest[end + 1..])))
+    } else {
+        let end = rest
+            .iter()
+            .position(u8::is_ascii_whitespace)
+            .unwrap_or(rest.len());
+        Ok(Some((&rest[..end], &rest[end..])))
+    }
+}
+
+impl<R: BufRead> PajekEdges<R> {
+    /// The number of a vertex in an edge line, checked against the
+    /// `*Vertices` line.
+    fn vertex(&self, field: &[u8], line: &[u8]) -> io::Result<usize> {
+        let vertex = parse_decimal(field)
+            .filter(|&v| v > 0)
+            .ok_or_else(|| invalid("invalid vertex number", line))?;
+        match self.num_vertices {
+            Some(n) if vertex > n => {
+                Err(invalid("vertex number out of range", line))
+            }
+            _ => Ok(vertex),
+        }
+    }
+
+    fn name(&self, vertex: usize) -> Vec<u8> {
+        match self.labels.get(vertex - 1) {
+            Some(Some(label)) => label.clone(),
+            _ => vertex.to_string().into_bytes(),
+        }
+    }
+
+    /// Checks that the labels are distinct, and that none of them is
+    /// the number of another vertex without a label, before they're
+    /// used as names.
+    fn check_labels(&mut self) -> io::Result<()> {
+        self.labels_checked = true;
+        let mut seen = FxHashSet::default();
+        for vertex in 1..=self.labels.len() {
+            let name = self.name(vertex);
+            if !seen.insert(name.clone()) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Pajek vertex {} has the same label as another, {:?}",
+                        vertex,
+                        String::from_utf8_lossy(&name)
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_header(&mut self) -> io::Result<()> {
+        let line = &self.line;
+        let (keyword, rest) = next_field(line)?.unwrap_or((b"", b""));
+        match keyword.to_ascii_lowercase().as_slice() {
+            b"*vertices" => {
+                let count = next_field(rest)?
+                    .and_then(|(count, _)| parse_decimal(count))
+                    .ok_or_else(|| {
+                        invalid("invalid number of vertices", line)
+                    })?;
+                self.num_vertices = Some(count);
+                self.section = Section::Vertices;
+            }
+            b"*edges" | b"*arcs" => self.section = Section::Pairs,
+            b"*edgeslist" | b"*arcslist" => self.section = Section::Lists,
+            b"*matrix" => {
+                return Err(invalid("unsupported adjacency matrix", line))
+            }
+            _ => self.section = Section::Other,
+        }
+        Ok(())
+    }
+
+    fn parse_vertex(&mut self) -> io::Result<()> {
+        let line = &self.line;
+        let (number, rest) = next_field(line)?.unwrap();
+        let vertex = parse_decimal(number)
+            .filter(|&v| v > 0 && Some(v) <= self.num_vertices)
+            .ok_or_else(|| invalid("invalid vertex number", line))?;
+        if let Some((label, _)) = next_field(rest)? {
+            if self.labels.len() < vertex {
+                self.labels.resize(vertex, None);
+            }
+            self.labels[vertex - 1] = Some(label.to_vec());
+        }
+        Ok(())
+    }
+
+    fn parse_edges(&mut self) -> io::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        if !self.labels_checked {
+            self.check_labels()?;
+        }
+        let line = &self.line;
+        let (from, mut rest) = next_field(line)?.unwrap();
+        let from = self.vertex(from, line)?;
+        let mut edges = Vec::new();
+        while let Some((to, next)) = next_field(rest)? {
+            edges.push((self.name(from), self.name(self.vertex(to, line)?)));
+            rest = next;
+            // the rest of an `*Edges` line is the weight and attributes
+            if self.section == Section::Pairs {
+                break;
+            }
+        }
+        if edges.is_empty() && self.section == Section::Pairs {
+            return Err(invalid("missing vertex", line));
+        }
+        Ok(edges)
+    }
+}
+
+impl<R: BufRead> Iterator for PajekEdges<R> {
+    type Item = io::Result<(Vec<u8>, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(edge) = self.edges.next() {
+                return Some(Ok(edge));
+            }
+            if self.failed {
+                return None;
+            }
+            self.line.clear();
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => (),
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+            while self.line.last().is_some_and(u8::is_ascii_whitespace) {
+                self.line.pop();
+            }
+            let first = self.line.iter().find(|b| !b.is_ascii_whitespace());
+            let result = match first {
+                None | Some(b'%') => continue,
+                Some(b'*') => self.parse_header().map(|_| Vec::new()),
+                Some(_) => match self.section {
+                    Section::Other => continue,
+                    Section::Vertices => {
+                        self.parse_vertex().map(|_| Vec::new())
+                    }
+                    Section::Pairs | Section::Lists => self.parse_edges(),
+                },
+            };
+            match result {
+                Ok(edges) => self.edges = edges.into_iter(),
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
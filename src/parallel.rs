@@ -0,0 +1,261 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
+
+use fxhash::FxHashMap;
+
+use crate::{algorithm, connectivity, graph::FxMapGraph};
+
+/// Finds the 3-edge-connected components using up to `threads`
+/// threads, also within a single connected component.
+///
+/// The graph is first split into its connected components, with
+/// `connected_parts`, which are processed independently, each with a
+/// state of its own size. The parts with more than their share of the
+/// nodes, which would keep one thread busy for longer than the rest,
+/// are split further into their biconnected components, or blocks.
+/// This gives the same result as `find_components`: nodes in different
+/// blocks can only be connected by paths through the cut vertices
+/// between them, and since 3-edge-connectivity is an equivalence
+/// relation, it restricts to each block, and the components of the
+/// graph are found by merging the components of the blocks that share
+/// a cut vertex. Chains of bubbles in pangenome graphs have many cut
+/// vertices, but a graph that's one big block gets no speedup.
+///
+/// The components are returned with their nodes sorted, and sorted
+/// by their first node.
+pub fn find_components(graph: &FxMapGraph, threads: usize) -> Vec<Vec<usize>> {
+    let threads = threads.max(1);
+    let parts = connected_parts(graph, threads);
+    let share = graph.len() / threads;
+
+    let mut tasks = Vec::new();
+    for part in parts.iter().filter(|part| part.len() > 1) {
+        if threads == 1 || part.len() <= share {
+            let edges: usize = part.iter().map(|n| graph[n].len()).sum();
+            tasks.push((edges / 2, Task::Part(part)));
+            continue;
+        }
+        let blocks = if part.len() == graph.len() {
+            connectivity::biconnected_components(graph)
+        } else {
+            // the subgraph needs consecutive node indices, which are
+            // mapped back in the blocks
+            let subgraph: FxMapGraph = part
+                .iter()
+                .enumerate()
+                .map(|(ix, node)| {
+                    let neighbors = graph[node].iter();
+                    (ix, neighbors.map(|&n| local_ix(part, n)).collect())
+                })
+                .collect();
+            let mut blocks = connectivity::biconnected_components(&subgraph);
+            for edge in blocks.iter_mut().flatten() {
+                *edge = (part[edge.0], part[edge.1]);
+            }
+            blocks
+        };
+        // blocks with fewer than three edges have no 3-edge-connected
+        // pairs
+        tasks.extend(
+            blocks
+                .into_iter()
+                .filter(|block| block.len() >= 3)
+                .map(|block| (block.len(), Task::Block(block))),
+        );
+    }
+    // the largest tasks are started first
+    tasks.sort_by_key(|&(size, _)| std::cmp::Reverse(size));
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Vec<usize>>> = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.min(tasks.len().max(1)) {
+            scope.spawn(|| {
+                let mut found = Vec::new();
+                loop {
+                    let ix = next.fetch_add(1, Ordering::Relaxed);
+                    let components = match tasks.get(ix) {
+                        None => break,
+                        Some((_, Task::Part(nodes))) => {
+                            part_components(graph, nodes)
+                        }
+                        Some((_, Task::Block(edges))) => {
+                            block_components(edges)
+                        }
+                    };
+                    found
+                        .extend(components.into_iter().filter(|c| c.len() > 1));
+                }
+                results.lock().unwrap().extend(found);
+            });
+        }
+    });
+
+    // merge the components that share a cut vertex
+    let mut parent: Vec<usize> = (0..graph.len()).collect();
+    for component in results.into_inner().unwrap() {
+        let root = find(&mut parent, component[0]);
+        for &node in component[1..].iter() {
+            let other = find(&mut parent, node);
+            parent[other] = root;
+        }
+    }
+
+    let mut components: FxHashMap<usize, Vec<usize>> = FxHashMap::default();
+    for node in 0..graph.len() {
+        let root = find(&mut parent, node);
+        components.entry(root).or_default().push(node);
+    }
+    let mut components: Vec<_> = components.into_values().collect();
+    components.sort_unstable();
+    components
+}
+
+/// A connected component, given as its nodes, or a block of one, given
+/// as its edges, to run the algorithm on.
+enum Task<'a> {
+    Part(&'a [usize]),
+    Block(Vec<(usize, usize)>),
+}
+
+/// Finds the connected components of the graph with a union-find over
+/// atomic parents, with the nodes split among up to `threads` threads,
+/// which link the roots of the ends of their edges concurrently. The
+/// components are returned with their nodes sorted, and sorted by
+/// decreasing size, then by their first node, so that the largest can
+/// be scheduled first.
+pub fn connected_parts(graph: &FxMapGraph, threads: usize) -> Vec<Vec<usize>> {
+    let num_nodes = graph.len();
+    let parent: Vec<AtomicUsize> =
+        (0..num_nodes).map(AtomicUsize::new).collect();
+    let chunk = num_nodes.div_ceil(threads.max(1)).max(1);
+
+    std::thread::scope(|scope| {
+        for start in (0..num_nodes).step_by(chunk) {
+            let parent = &parent;
+            scope.spawn(move || {
+                for node in start..(start + chunk).min(num_nodes) {
+                    for &other in graph[&node].iter() {
+                        if node < other {
+                            union(parent, node, other);
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    // the nodes grouped by their root, which is the smallest node of
+    // each component, in increasing order
+    let roots: Vec<usize> = (0..num_nodes)
+        .map(|node| find_root(&parent, node))
+        .collect();
+    let mut part_ix = vec![usize::MAX; num_nodes];
+    let mut parts: Vec<Vec<usize>> = Vec::new();
+    for (node, &root) in roots.iter().enumerate() {
+        if part_ix[root] == usize::MAX {
+            part_ix[root] = parts.len();
+            parts.push(Vec::new());
+        }
+        parts[part_ix[root]].push(node);
+    }
+    parts.sort_by_key(|part| std::cmp::Reverse(part.len()));
+    parts
+}
+
+/// Follows the parents to the root, halving the path on the way. The
+/// parent of a node is never larger than it, so no cycle can form, and
+/// a failed exchange only means that another thread moved the node
+/// closer to the root first.
+fn find_root(parent: &[AtomicUsize], mut x: usize) -> usize {
+    loop {
+        let p = parent[x].load(Ordering::Relaxed);
+        if p == x {
+            return x;
+        }
+        let grandparent = parent[p].load(Ordering::Relaxed);
+        let _ = parent[x].compare_exchange_weak(
+            p,
+            grandparent,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+        x = grandparent;
+    }
+}
+
+/// Links the roots of `a` and `b`, the larger one below the smaller,
+/// retrying if the larger one stopped being a root in between.
+fn union(parent: &[AtomicUsize], a: usize, b: usize) {
+    loop {
+        let (a, b) = (find_root(parent, a), find_root(parent, b));
+        if a == b {
+            return;
+        }
+        let (low, high) = (a.min(b), a.max(b));
+        if parent[high]
+            .compare_exchange(high, low, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+        {
+            return;
+        }
+    }
+}
+
+/// Runs the algorithm on a single connected component, given as its
+/// sorted nodes, and returns the components with the original node
+/// indices.
+fn part_components(graph: &FxMapGraph, nodes: &[usize]) -> Vec<Vec<usize>> {
+    let adjacency: Vec<Vec<usize>> = nodes
+        .iter()
+        .map(|node| graph[node].iter().map(|&n| local_ix(nodes, n)).collect())
+        .collect();
+
+    algorithm::find_components(&adjacency)
+        .into_iter()
+        .map(|c| c.into_iter().map(|ix| nodes[ix]).collect())
+        .collect()
+}
+
+/// Runs the algorithm on a single block, given as its edges, and
+/// returns the components with the original node indices.
+fn block_components(edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let mut local_ix: FxHashMap<usize, usize> = FxHashMap::default();
+    let mut nodes = Vec::new();
+    let mut adjacency: Vec<Vec<usize>> = Vec::new();
+
+    for &(from, to) in edges {
+        let mut get_ix = |node: usize| {
+            *local_ix.entry(node).or_insert_with(|| {
+                nodes.push(node);
+                adjacency.push(Vec::new());
+                nodes.len() - 1
+            })
+        };
+        let (from_ix, to_ix) = (get_ix(from), get_ix(to));
+        adjacency[from_ix].push(to_ix);
+        adjacency[to_ix].push(from_ix);
+    }
+
+    algorithm::find_components(&adjacency)
+        .into_iter()
+        .map(|c| c.into_iter().map(|ix| nodes[ix]).collect())
+        .collect()
+}
+
+/// The index of a node within a connected component, given as its
+/// sorted nodes, which is its position in them.
+fn local_ix(nodes: &[usize], node: usize) -> usize {
+    nodes.binary_search(&node).unwrap()
+}
+
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
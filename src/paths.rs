@@ -0,0 +1,110 @@
+use std::io::{self, BufRead};
+
+use bstr::io::BufReadExt;
+use gfa::{
+    gfa::{Line, Orientation},
+    parser::{GFAParser, GFAParserBuilder},
+};
+
+/// A path of a GFA file, from a `P` line, with the oriented segments
+/// it visits in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GfaPath {
+    pub name: Vec<u8>,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub segment: Vec<u8>,
+    pub reverse: bool,
+}
+
+/// A step of a path that is in a different component than the step
+/// before it. `step` is the index of the step in the path, and `from`
+/// and `to` the components of the two steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crossing {
+    pub step: usize,
+    pub from: Option<usize>,
+    pub to: Option<usize>,
+}
+
+/// Parses the paths of the given GFA stream, skipping all other
+/// lines, or returns the first error encountered when reading from
+/// `reader`.
+pub fn gfa_paths<T: BufRead>(
+    reader: T,
+) -> impl Iterator<Item = io::Result<GfaPath>> {
+    let parser: GFAParser<Vec<u8>, ()> = GFAParserBuilder {
+        paths: true,
+        ..GFAParserBuilder::none()
+    }
+    .build();
+
+    reader.byte_lines().filter_map(move |line| match line {
+        Err(err) => Some(Err(err)),
+        Ok(line) => match parser.parse_gfa_line(&line) {
+            Ok(Line::Path(path)) => Some(Ok(GfaPath {
+                steps: path
+                    .iter()
+                    .map(|(segment, orient)| Step {
+                        segment: segment.to_vec(),
+                        reverse: orient == Orientation::Backward,
+                    })
+                    .collect(),
+                name: path.path_name,
+            })),
+            _ => None,
+        },
+    })
+}
+
+/// The index of the component of each node, given the components
+/// as returned by `find_components`, for `GfaPath::crossings`. Nodes
+/// that aren't in any component, e.g. because the small components
+/// were filtered out, have none.
+pub fn node_components(
+    components: &[Vec<usize>],
+    num_nodes: usize,
+) -> Vec<Option<usize>> {
+    let mut node_components = vec![None; num_nodes];
+    for (ix, component) in components.iter().enumerate() {
+        for &node in component {
+            node_components[node] = Some(ix);
+        }
+    }
+    node_components
+}
+
+impl GfaPath {
+    /// Finds the steps where the path moves from one component to
+    /// another, given the component of each segment name. Since each
+    /// step is linked to the next one, a path that crosses between
+    /// 3-edge-connected components goes through a cut of at most two
+    /// links, which are fragile regions of the graph. Steps on
+    /// segments that aren't in any component have none, and moving
+    /// between two of them isn't counted.
+    pub fn crossings<F>(&self, component: F) -> Vec<Crossing>
+    where
+        F: Fn(&[u8]) -> Option<usize>,
+    {
+        let mut crossings = Vec::new();
+        let mut components = self.steps.iter().map(|s| component(&s.segment));
+        let mut prev = match components.next() {
+            None => return crossings,
+            Some(first) => first,
+        };
+        for (step, to) in components.enumerate() {
+            if to != prev {
+                crossings.push(Crossing {
+                    step: step + 1,
+                    from: prev,
+                    to,
+                });
+            }
+            prev = to;
+        }
+        crossings
+    }
+}
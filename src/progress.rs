@@ -0,0 +1,81 @@
+/// The progress of a run of the algorithm, as reported by
+/// `algorithm::three_edge_connect_with_progress`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of nodes visited by the DFS so far, out of
+    /// `graph.num_nodes()`.
+    pub nodes: usize,
+    /// The number of edges processed from both of their ends so far.
+    /// Self-loops are skipped, so this ends at the number of other
+    /// edges.
+    pub edges: usize,
+}
+
+/// The number of bytes between calls of the `ProgressReader`
+/// callback.
+#[cfg(feature = "std")]
+const REPORT_INTERVAL: u64 = 1 << 20;
+
+/// Wraps a reader, calling `progress` with the total number of bytes
+/// read so far about every megabyte, so that the progress of any of
+/// the parsers, e.g. `Graph::from_gfa_reader`, can be shown as a
+/// fraction of the input file size.
+#[cfg(feature = "std")]
+pub struct ProgressReader<R, F> {
+    inner: R,
+    bytes: u64,
+    next_report: u64,
+    progress: F,
+}
+
+#[cfg(feature = "std")]
+impl<R, F: FnMut(u64)> ProgressReader<R, F> {
+    pub fn new(inner: R, progress: F) -> Self {
+        ProgressReader {
+            inner,
+            bytes: 0,
+            next_report: REPORT_INTERVAL,
+            progress,
+        }
+    }
+
+    /// The number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn advance(&mut self, amt: usize) {
+        self.bytes += amt as u64;
+        if self.bytes >= self.next_report {
+            (self.progress)(self.bytes);
+            self.next_report = self.bytes + REPORT_INTERVAL;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read, F: FnMut(u64)> std::io::Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let amt = self.inner.read(buf)?;
+        self.advance(amt);
+        Ok(amt)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::BufRead, F: FnMut(u64)> std::io::BufRead
+    for ProgressReader<R, F>
+{
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.advance(amt);
+    }
+}
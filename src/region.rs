@@ -0,0 +1,232 @@
+use std::{
+    io::{self, BufRead},
+    str::FromStr,
+};
+
+use bstr::{io::BufReadExt, ByteSlice};
+use fxhash::FxHashMap;
+
+use crate::{adjacency::Adjacency, names::parse_decimal, rgfa};
+
+/// A range of a reference sequence, either a stable sequence of an
+/// rGFA file or a path of a GFA file. Parsed from `name`,
+/// `name:start-end`, or `name:start`, with 1-based, inclusive
+/// coordinates that may contain commas, like `chr1:1-5,000,000`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub name: Vec<u8>,
+    /// The 0-based start of the range.
+    pub start: usize,
+    /// The 0-based, exclusive end of the range, or `None` if it
+    /// extends to the end of the sequence.
+    pub end: Option<usize>,
+}
+
+impl Region {
+    /// Whether the range overlaps the interval `[start, end)`.
+    pub fn overlaps(&self, start: usize, end: usize) -> bool {
+        end > self.start && self.end.is_none_or(|e| start < e)
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let position = |p: &str| {
+            p.replace(',', "")
+                .parse::<usize>()
+                .ok()
+                .filter(|&p| p > 0)
+                .ok_or_else(|| format!("invalid position {:?} in region", p))
+        };
+
+        // sequence names may contain colons themselves, so only a
+        // suffix that looks like a range is one
+        let (name, range) = match s.rsplit_once(':') {
+            Some((name, range))
+                if range.starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                (name, Some(range))
+            }
+            _ => (s, None),
+        };
+        if name.is_empty() {
+            return Err(format!("missing sequence name in region {:?}", s));
+        }
+        let (start, end) = match range.map(|r| r.split_once('-')) {
+            None => (0, None),
+            Some(None) => (position(range.unwrap())? - 1, None),
+            Some(Some((start, end))) => {
+                let (start, end) = (position(start)?, position(end)?);
+                if end < start {
+                    return Err(format!(
+                        "region {:?} ends before it starts",
+                        s
+                    ));
+                }
+                (start - 1, Some(end))
+            }
+        };
+        Ok(Region {
+            name: name.as_bytes().to_vec(),
+            start,
+            end,
+        })
+    }
+}
+
+/// The interval of a segment on a reference sequence, with 0-based,
+/// half-open coordinates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceInterval {
+    pub segment: Vec<u8>,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Finds the intervals of the segments of a GFA file on a reference
+/// sequence, which is either a stable sequence of an rGFA file, where
+/// the intervals are the stable coordinates of its segments, or a path,
+/// where the interval of each step starts at the total length of the
+/// segments before it. Both are used if the file has both, as an rGFA
+/// file with paths may. The intervals are sorted by their start.
+///
+/// Returns an `InvalidInput` error if there is neither a stable
+/// sequence nor a path with the given name, and an `InvalidData`
+/// error if a segment on the path has no length, from its sequence or
+/// `LN` tag. rGFA segments without a length are given a length of 1.
+pub fn reference_intervals<T: BufRead>(
+    reader: T,
+    reference: &[u8],
+) -> io::Result<Vec<ReferenceInterval>> {
+    let mut lengths: FxHashMap<Vec<u8>, Option<usize>> = FxHashMap::default();
+    let mut path: Option<Vec<Vec<u8>>> = None;
+    let mut intervals = Vec::new();
+    let mut found = false;
+
+    for line in reader.byte_lines() {
+        let line = line?;
+        if line.starts_with(b"S\t") {
+            let mut fields = line.split_str("\t").skip(1);
+            let (name, sequence) = match (fields.next(), fields.next()) {
+                (Some(name), Some(sequence)) => (name, sequence),
+                _ => continue,
+            };
+            let length = fields
+                .find_map(|tag| tag.strip_prefix(b"LN:i:"))
+                .and_then(parse_decimal)
+                .or_else(|| Some(sequence.len()).filter(|_| sequence != b"*"));
+            lengths.insert(name.to_vec(), length);
+
+            if let Some((_, coordinate)) = rgfa::parse_segment(&line)? {
+                if coordinate.name == reference {
+                    found = true;
+                    intervals.push(ReferenceInterval {
+                        segment: name.to_vec(),
+                        start: coordinate.offset,
+                        end: coordinate.end().unwrap_or(coordinate.offset + 1),
+                    });
+                }
+            }
+        } else if line.starts_with(b"P\t") {
+            let mut fields = line.split_str("\t").skip(1);
+            if fields.next() != Some(reference) {
+                continue;
+            }
+            let steps = fields.next().unwrap_or(b"");
+            path = Some(
+                steps
+                    .split_str(",")
+                    .filter(|step| !step.is_empty())
+                    .map(|step| step[..step.len() - 1].to_vec())
+                    .collect(),
+            );
+        }
+    }
+
+    if let Some(steps) = path {
+        found = true;
+        let mut position = 0;
+        for segment in steps {
+            let length =
+                lengths.get(&segment).copied().flatten().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "segment {} of path {} has no length",
+                            segment.as_bstr(),
+                            reference.as_bstr()
+                        ),
+                    )
+                })?;
+            intervals.push(ReferenceInterval {
+                segment,
+                start: position,
+                end: position + length.max(1),
+            });
+            position += length;
+        }
+    }
+
+    if !found {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("no stable sequence or path named {}", reference.as_bstr()),
+        ));
+    }
+    intervals.sort_by_key(|interval| interval.start);
+    Ok(intervals)
+}
+
+/// Finds the segments of a GFA file that are in the given region,
+/// which are those whose `reference_intervals` on the region's
+/// sequence overlap it, in the order of the reference, without
+/// duplicates.
+pub fn region_segments<T: BufRead>(
+    reader: T,
+    region: &Region,
+) -> io::Result<Vec<Vec<u8>>> {
+    let mut seen = fxhash::FxHashSet::default();
+    Ok(reference_intervals(reader, &region.name)?
+        .into_iter()
+        .filter(|interval| region.overlaps(interval.start, interval.end))
+        .map(|interval| interval.segment)
+        .filter(|segment| seen.insert(segment.clone()))
+        .collect())
+}
+
+/// The nodes that are at most `hops` edges away from any of the given
+/// nodes, including the nodes themselves, in the order they're found
+/// by a breadth-first search. This is the context around a region,
+/// such as the alternative alleles of an rGFA file, whose segments
+/// aren't on the reference sequence.
+pub fn neighborhood<G, I>(graph: &G, nodes: I, hops: usize) -> Vec<usize>
+where
+    G: Adjacency + ?Sized,
+    I: IntoIterator<Item = usize>,
+{
+    let mut seen = vec![false; graph.num_nodes()];
+    let mut found: Vec<usize> = nodes
+        .into_iter()
+        .filter(|&n| !std::mem::replace(&mut seen[n], true))
+        .collect();
+
+    let mut level = 0..found.len();
+    for _ in 0..hops {
+        if level.is_empty() {
+            break;
+        }
+        let next = found.len();
+        for ix in level {
+            for &u in graph.neighbors(found[ix]) {
+                if !seen[u] {
+                    seen[u] = true;
+                    found.push(u);
+                }
+            }
+        }
+        level = next..found.len();
+    }
+    found
+}
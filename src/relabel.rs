@@ -0,0 +1,179 @@
+use std::{borrow::Cow, collections::VecDeque};
+
+use crate::{
+    adjacency::Adjacency,
+    graph::{AdjacencyList, IndexedGraph},
+    names::NodeNames,
+};
+
+/// A renumbering of the nodes of a graph, so that nodes that are
+/// close in the graph get close indices. The algorithm's state is a
+/// handful of arrays indexed by node, which it accesses in the order
+/// of the DFS, so on large graphs with e.g. hashed or arbitrary node
+/// indices most accesses are cache misses; with the nodes relabeled
+/// in DFS order, they're mostly sequential.
+///
+/// The relabeled graph from `apply` has the same components, with the
+/// nodes given by their new indices, which `names` maps back to the
+/// original names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relabeling {
+    // the old index of each new index
+    old: Vec<usize>,
+    // the new index of each old index
+    new: Vec<usize>,
+}
+
+impl Relabeling {
+    /// Numbers the nodes in the preorder of the DFS that the algorithm
+    /// does, with the roots in the order of `graph.nodes()`, so that
+    /// the algorithm visits the relabeled graph's nodes in index
+    /// order, and in the same order as the original graph's.
+    pub fn dfs<G: Adjacency + ?Sized>(graph: &G) -> Relabeling {
+        let mut old = Vec::with_capacity(graph.num_nodes());
+        let mut visited = vec![false; graph.num_nodes()];
+        // each node on the DFS path, and the position of its next
+        // neighbor to look at
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+
+        for root in graph.nodes() {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            old.push(root);
+            stack.push((root, 0));
+
+            while let Some((w, next)) = stack.last_mut() {
+                match graph.neighbors(*w).get(*next) {
+                    None => {
+                        stack.pop();
+                    }
+                    Some(&u) => {
+                        *next += 1;
+                        if !visited[u] {
+                            visited[u] = true;
+                            old.push(u);
+                            stack.push((u, 0));
+                        }
+                    }
+                }
+            }
+        }
+
+        Relabeling::from_order(old)
+    }
+
+    /// Numbers the nodes in breadth-first order, with the roots in the
+    /// order of `graph.nodes()`, which keeps the neighbors of each node
+    /// close together.
+    pub fn bfs<G: Adjacency + ?Sized>(graph: &G) -> Relabeling {
+        let mut old = Vec::with_capacity(graph.num_nodes());
+        let mut visited = vec![false; graph.num_nodes()];
+        let mut queue = VecDeque::new();
+
+        for root in graph.nodes() {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            queue.push_back(root);
+
+            while let Some(w) = queue.pop_front() {
+                old.push(w);
+                for &u in graph.neighbors(w) {
+                    if !visited[u] {
+                        visited[u] = true;
+                        queue.push_back(u);
+                    }
+                }
+            }
+        }
+
+        Relabeling::from_order(old)
+    }
+
+    fn from_order(old: Vec<usize>) -> Relabeling {
+        let mut new = vec![0; old.len()];
+        for (new_ix, &old_ix) in old.iter().enumerate() {
+            new[old_ix] = new_ix;
+        }
+        Relabeling { old, new }
+    }
+
+    pub fn len(&self) -> usize {
+        self.old.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.old.is_empty()
+    }
+
+    /// The number of bytes of heap memory used by the relabeling.
+    pub fn memory_usage(&self) -> usize {
+        (self.old.capacity() + self.new.capacity())
+            * std::mem::size_of::<usize>()
+    }
+
+    /// The new index of the node with the given index in the original
+    /// graph.
+    pub fn new_index(&self, old: usize) -> usize {
+        self.new[old]
+    }
+
+    /// The index in the original graph of the node with the given new
+    /// index.
+    pub fn old_index(&self, new: usize) -> usize {
+        self.old[new]
+    }
+
+    /// Builds the relabeled graph, keeping the order of each node's
+    /// neighbors. The graph must be the one the relabeling was made
+    /// for.
+    pub fn apply<G: Adjacency + ?Sized>(&self, graph: &G) -> IndexedGraph {
+        let adjacency = self
+            .old
+            .iter()
+            .map(|&w| {
+                let mut neighbors = AdjacencyList::new();
+                neighbors
+                    .extend(graph.neighbors(w).iter().map(|&u| self.new[u]));
+                neighbors
+            })
+            .collect();
+        IndexedGraph { adjacency }
+    }
+
+    /// Maps the nodes of components of the relabeled graph back to
+    /// their indices in the original graph.
+    pub fn restore(&self, mut components: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        for node in components.iter_mut().flatten() {
+            *node = self.old[*node];
+        }
+        components
+    }
+
+    /// The names of the relabeled graph's nodes, given the names of
+    /// the original graph, for the output writers.
+    pub fn names<'a, N: NodeNames + ?Sized>(
+        &'a self,
+        names: &'a N,
+    ) -> RelabeledNames<'a, N> {
+        RelabeledNames {
+            relabeling: self,
+            names,
+        }
+    }
+}
+
+/// See `Relabeling::names`.
+pub struct RelabeledNames<'a, N: ?Sized> {
+    relabeling: &'a Relabeling,
+    names: &'a N,
+}
+
+impl<N: NodeNames + ?Sized> NodeNames for RelabeledNames<'_, N> {
+    fn name(&self, ix: usize) -> Cow<'_, [u8]> {
+        self.names.name(self.relabeling.old_index(ix))
+    }
+}
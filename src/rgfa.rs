@@ -0,0 +1,96 @@
+use std::io::{self, BufRead};
+
+use bstr::{io::BufReadExt, ByteSlice};
+use fxhash::FxHashMap;
+
+use crate::names::parse_decimal;
+
+/// The stable coordinate of a segment of an rGFA file: the segment is
+/// at offset `offset` of the stable sequence `name`, which comes from
+/// the assembly of the given `rank`, where rank 0 is the reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StableCoordinate {
+    /// The `SN` tag.
+    pub name: Vec<u8>,
+    /// The `SO` tag.
+    pub offset: usize,
+    /// The `SR` tag.
+    pub rank: usize,
+    /// The length of the segment, from its `LN` tag or its sequence,
+    /// if either is given.
+    pub length: Option<usize>,
+}
+
+impl StableCoordinate {
+    /// The end of the segment on the stable sequence, if its length is
+    /// known.
+    pub fn end(&self) -> Option<usize> {
+        self.length.map(|length| self.offset + length)
+    }
+}
+
+/// Reads the stable coordinates of the segments of an rGFA file, from
+/// the `SN`, `SO`, and `SR` tags of its `S` lines, which are all
+/// required. Segments without the tags, such as all the segments of a
+/// GFA file that isn't rGFA, are skipped; an `S` line with only some
+/// of them, or an invalid value, is an error.
+pub fn rgfa_coordinates<T: BufRead>(
+    reader: T,
+) -> io::Result<FxHashMap<Vec<u8>, StableCoordinate>> {
+    let mut coordinates = FxHashMap::default();
+    for line in reader.byte_lines() {
+        let line = line?;
+        if let Some((segment, coordinate)) = parse_segment(&line)? {
+            coordinates.insert(segment, coordinate);
+        }
+    }
+    Ok(coordinates)
+}
+
+pub(crate) fn parse_segment(
+    line: &[u8],
+) -> io::Result<Option<(Vec<u8>, StableCoordinate)>> {
+    let mut fields = line.split_str("\t");
+    if fields.next() != Some(b"S") {
+        return Ok(None);
+    }
+    let invalid = |message: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} in rGFA line {:?}", message, line.as_bstr()),
+        )
+    };
+    let segment = fields.next().ok_or_else(|| invalid("missing name"))?;
+    let sequence = fields.next().ok_or_else(|| invalid("missing sequence"))?;
+
+    let (mut name, mut offset, mut rank, mut length) = (None, None, None, None);
+    let integer = |value: &[u8]| {
+        parse_decimal(value).ok_or_else(|| invalid("invalid integer tag"))
+    };
+    for tag in fields {
+        match tag.get(..5) {
+            Some(b"SN:Z:") => name = Some(tag[5..].to_vec()),
+            Some(b"SO:i:") => offset = Some(integer(&tag[5..])?),
+            Some(b"SR:i:") => rank = Some(integer(&tag[5..])?),
+            Some(b"LN:i:") => length = Some(integer(&tag[5..])?),
+            _ => (),
+        }
+    }
+    if sequence != b"*" && length.is_none() {
+        length = Some(sequence.len());
+    }
+
+    match (name, offset, rank) {
+        (None, None, None) => Ok(None),
+        (Some(name), Some(offset), Some(rank)) => Ok(Some((
+            segment.to_vec(),
+            StableCoordinate {
+                name,
+                offset,
+                rank,
+                length,
+            },
+        ))),
+        _ => Err(invalid("missing SN, SO, or SR tag")),
+    }
+}
@@ -0,0 +1,170 @@
+use alloc::vec::Vec;
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// A vector that stores up to `N` elements inline, and moves them to
+/// the heap when it grows larger. Used for the adjacency lists, since
+/// most nodes in assembly graphs have degree four or less, which saves
+/// an allocation per node and a pointer indirection when the algorithm
+/// reads its neighbors.
+#[derive(Clone)]
+pub struct SmallVec<T: Copy + Default, const N: usize> {
+    repr: Repr<T, N>,
+}
+
+#[derive(Clone)]
+enum Repr<T: Copy + Default, const N: usize> {
+    Inline { len: usize, items: [T; N] },
+    Heap(Vec<T>),
+}
+
+impl<T: Copy + Default, const N: usize> SmallVec<T, N> {
+    pub fn new() -> Self {
+        SmallVec {
+            repr: Repr::Inline {
+                len: 0,
+                items: [T::default(); N],
+            },
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        match &mut self.repr {
+            Repr::Inline { len, items } if *len < N => {
+                items[*len] = item;
+                *len += 1;
+            }
+            Repr::Inline { items, .. } => {
+                let mut heap = Vec::with_capacity(2 * N.max(1));
+                heap.extend_from_slice(&items[..]);
+                heap.push(item);
+                self.repr = Repr::Heap(heap);
+            }
+            Repr::Heap(heap) => heap.push(item),
+        }
+    }
+
+    /// Removes the element at `index`, replacing it with the last
+    /// element. Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        match &mut self.repr {
+            Repr::Inline { len, items } => {
+                assert!(index < *len, "index out of bounds");
+                let item = items[index];
+                items[index] = items[*len - 1];
+                *len -= 1;
+                item
+            }
+            Repr::Heap(heap) => heap.swap_remove(index),
+        }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        match &self.repr {
+            Repr::Inline { len, items } => &items[..*len],
+            Repr::Heap(heap) => heap,
+        }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        match &mut self.repr {
+            Repr::Inline { len, items } => &mut items[..*len],
+            Repr::Heap(heap) => heap,
+        }
+    }
+
+    /// Whether the elements have been moved to the heap.
+    pub fn spilled(&self) -> bool {
+        matches!(self.repr, Repr::Heap(_))
+    }
+
+    /// The number of bytes of heap memory used, which is zero while
+    /// the elements are stored inline.
+    pub fn heap_size(&self) -> usize {
+        match &self.repr {
+            Repr::Inline { .. } => 0,
+            Repr::Heap(heap) => heap.capacity() * core::mem::size_of::<T>(),
+        }
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Default for SmallVec<T, N> {
+    fn default() -> Self {
+        SmallVec::new()
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T: Copy + Default, const N: usize> DerefMut for SmallVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Copy + Default + fmt::Debug, const N: usize> fmt::Debug
+    for SmallVec<T, N>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Copy + Default + PartialEq, const N: usize> PartialEq
+    for SmallVec<T, N>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Copy + Default + Eq, const N: usize> Eq for SmallVec<T, N> {}
+
+impl<T: Copy + Default, const N: usize> Extend<T> for SmallVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T: Copy + Default, const N: usize> core::iter::FromIterator<T>
+    for SmallVec<T, N>
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = SmallVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T: Copy + Default, const N: usize> From<Vec<T>> for SmallVec<T, N> {
+    fn from(vec: Vec<T>) -> Self {
+        if vec.len() <= N {
+            vec.into_iter().collect()
+        } else {
+            SmallVec {
+                repr: Repr::Heap(vec),
+            }
+        }
+    }
+}
+
+impl<'a, T: Copy + Default, const N: usize> IntoIterator
+    for &'a SmallVec<T, N>
+{
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
@@ -0,0 +1,332 @@
+use crate::{algorithm, graph::IndexedGraph, superbubble::OrientedGraph};
+
+/// The cactus graph of an `OrientedGraph`, as in Paten et al. (2018),
+/// "Superbubbles, Ultrabubbles, and Cacti". Each segment has a left
+/// side, `2 * ix`, where its forward strand starts, and a right side,
+/// `2 * ix + 1`. The sides that are joined by links are merged into one
+/// vertex, and so are all the sides without links, the tips; the
+/// 3-edge-connected components of the graph with one edge per segment,
+/// between the vertices of its sides, are then merged into one vertex
+/// each. The segments are the edges of the cactus graph, each of which
+/// is in at most one cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CactusGraph {
+    // the vertex of each side
+    vertices: Vec<usize>,
+    num_vertices: usize,
+    tips: Option<usize>,
+}
+
+/// A snarl, bounded by two oriented nodes of an `OrientedGraph`, like
+/// those of a `Superbubble`: `start` points into the snarl, and `end`
+/// points out of it. `chain` is the index of the chain the snarl is in,
+/// and `parent` the index of the snarl that chain is in, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snarl {
+    pub start: usize,
+    pub end: usize,
+    pub chain: usize,
+    pub parent: Option<usize>,
+}
+
+/// A chain, which is a cycle of the cactus graph, with its segments in
+/// order. The snarls of the chain are between consecutive segments,
+/// other than at the vertex where the chain is attached to its parent
+/// snarl, or to the root. A chain of a single segment has no snarls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chain {
+    pub segments: Vec<usize>,
+    pub parent: Option<usize>,
+}
+
+/// The snarl tree of `OrientedGraph::snarls`: snarls contain chains,
+/// which contain snarls. The snarls of each chain are in the order of
+/// the chain.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SnarlDecomposition {
+    pub snarls: Vec<Snarl>,
+    pub chains: Vec<Chain>,
+}
+
+impl SnarlDecomposition {
+    /// The snarls that aren't inside any other snarl.
+    pub fn top_level(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.snarls.len())
+            .filter(move |&ix| self.snarls[ix].parent.is_none())
+    }
+
+    /// The number of snarls that contain the given snarl.
+    pub fn depth(&self, snarl: usize) -> usize {
+        let mut depth = 0;
+        let mut parent = self.snarls[snarl].parent;
+        while let Some(ix) = parent {
+            depth += 1;
+            parent = self.snarls[ix].parent;
+        }
+        depth
+    }
+}
+
+fn find_root(parents: &mut [usize], mut x: usize) -> usize {
+    while parents[x] != x {
+        parents[x] = parents[parents[x]];
+        x = parents[x];
+    }
+    x
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let a = find_root(parents, a);
+    let b = find_root(parents, b);
+    parents[a.max(b)] = a.min(b);
+}
+
+impl CactusGraph {
+    pub fn num_vertices(&self) -> usize {
+        self.num_vertices
+    }
+
+    /// The number of edges, one per segment.
+    pub fn num_edges(&self) -> usize {
+        self.vertices.len() / 2
+    }
+
+    /// The vertex that the given side is merged into.
+    pub fn vertex(&self, side: usize) -> usize {
+        self.vertices[side]
+    }
+
+    /// The vertices of the left and right sides of a segment.
+    pub fn ends(&self, segment: usize) -> (usize, usize) {
+        (self.vertices[2 * segment], self.vertices[2 * segment + 1])
+    }
+
+    /// The vertex of the tips, if there are any, which is the root of
+    /// the snarl decomposition.
+    pub fn tips(&self) -> Option<usize> {
+        self.tips
+    }
+
+    /// Finds the chains and snarls, with a depth-first search from the
+    /// tips, or from an arbitrary vertex in parts of the graph without
+    /// tips, such as a circular genome, where the vertex the search
+    /// starts from isn't the boundary of any snarl. Each back edge of
+    /// the search closes a cycle, which is a chain; segments that
+    /// aren't in any cycle, which are only found in graphs with tangled
+    /// strands, are bridges between separate snarl trees.
+    pub fn snarls(&self) -> SnarlDecomposition {
+        const NONE: usize = usize::MAX;
+
+        let mut incident = vec![Vec::new(); self.num_vertices];
+        let mut loops = Vec::new();
+        for segment in 0..self.num_edges() {
+            let (left, right) = self.ends(segment);
+            if left == right {
+                loops.push(segment);
+            } else {
+                incident[left].push(segment);
+                incident[right].push(segment);
+            }
+        }
+
+        let mut decomposition = SnarlDecomposition::default();
+        // the vertex each chain is attached at, and the snarl at each
+        // vertex on the chain it's reached from, to find the parents
+        let mut attachments = Vec::new();
+        let mut vertex_snarl = vec![NONE; self.num_vertices];
+
+        let mut visited = vec![false; self.num_vertices];
+        let mut on_stack = vec![false; self.num_vertices];
+        let mut parent_edge = vec![NONE; self.num_vertices];
+        let mut parent = vec![NONE; self.num_vertices];
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+
+        let roots = self.tips.into_iter().chain(0..self.num_vertices);
+        for root in roots {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            on_stack[root] = true;
+            stack.push((root, 0));
+
+            while let Some((v, next)) = stack.last_mut() {
+                let v = *v;
+                let segment = match incident[v].get(*next) {
+                    None => {
+                        on_stack[v] = false;
+                        stack.pop();
+                        continue;
+                    }
+                    Some(&segment) => segment,
+                };
+                *next += 1;
+                if segment == parent_edge[v] {
+                    continue;
+                }
+                let (left, right) = self.ends(segment);
+                let u = if left == v { right } else { left };
+
+                if !visited[u] {
+                    visited[u] = true;
+                    on_stack[u] = true;
+                    parent_edge[u] = segment;
+                    parent[u] = v;
+                    stack.push((u, 0));
+                } else if on_stack[u] {
+                    // a back edge to an ancestor, u, which the cycle is
+                    // attached at
+                    let mut path = vec![v];
+                    while *path.last().unwrap() != u {
+                        path.push(parent[*path.last().unwrap()]);
+                    }
+                    path.pop();
+                    path.reverse();
+
+                    let mut segments: Vec<usize> =
+                        path.iter().map(|&x| parent_edge[x]).collect();
+                    segments.push(segment);
+                    let mut bounds: Vec<(usize, usize)> = path
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &x)| {
+                            let start = self.side_at(segments[i], x) ^ 1;
+                            (start, self.side_at(segments[i + 1], x))
+                        })
+                        .collect();
+
+                    // the direction of the search is arbitrary, so the
+                    // chain is given on the strand where most of its
+                    // boundaries are forward
+                    let reverse: usize =
+                        bounds.iter().map(|&(s, e)| (s & 1) + (e & 1)).sum();
+                    if reverse > bounds.len() {
+                        segments.reverse();
+                        path.reverse();
+                        bounds.reverse();
+                        for bound in bounds.iter_mut() {
+                            let (start, end) = *bound;
+                            *bound = (end ^ 1, start ^ 1);
+                        }
+                    }
+
+                    let chain = decomposition.chains.len();
+                    for (&x, &(start, end)) in path.iter().zip(bounds.iter()) {
+                        vertex_snarl[x] = decomposition.snarls.len();
+                        decomposition.snarls.push(Snarl {
+                            start,
+                            end,
+                            chain,
+                            parent: None,
+                        });
+                    }
+                    decomposition.chains.push(Chain {
+                        segments,
+                        parent: None,
+                    });
+                    attachments.push(u);
+                }
+            }
+        }
+
+        for segment in loops {
+            decomposition.chains.push(Chain {
+                segments: vec![segment],
+                parent: None,
+            });
+            attachments.push(self.ends(segment).0);
+        }
+
+        let SnarlDecomposition { snarls, chains } = &mut decomposition;
+        for (chain, &vertex) in chains.iter_mut().zip(attachments.iter()) {
+            chain.parent = Some(vertex_snarl[vertex]).filter(|&s| s != NONE);
+        }
+        for snarl in snarls.iter_mut() {
+            snarl.parent = chains[snarl.chain].parent;
+        }
+        decomposition
+    }
+
+    // the side of the segment that is in the given vertex, where the
+    // segment isn't a loop
+    fn side_at(&self, segment: usize, vertex: usize) -> usize {
+        if self.vertices[2 * segment] == vertex {
+            2 * segment
+        } else {
+            2 * segment + 1
+        }
+    }
+}
+
+impl OrientedGraph {
+    /// Builds the cactus graph, see `CactusGraph`. A link from `a+` to
+    /// `b-` joins the right side of `a` and the right side of `b`.
+    pub fn cactus_graph(&self) -> CactusGraph {
+        let num_sides = 2 * self.num_segments();
+        let mut parents: Vec<usize> = (0..num_sides).collect();
+        let mut linked = vec![false; num_sides];
+        for (from, to) in self.links() {
+            // an oriented node leaves the segment at the opposite side
+            // of the one it enters at
+            union(&mut parents, from ^ 1, to);
+            linked[from ^ 1] = true;
+            linked[to] = true;
+        }
+        let mut tips = (0..num_sides).filter(|&side| !linked[side]);
+        if let Some(first) = tips.next() {
+            for side in tips {
+                union(&mut parents, first, side);
+            }
+        }
+
+        // number the merged sides, then merge their 3-edge-connected
+        // components
+        let mut groups = vec![usize::MAX; num_sides];
+        let mut num_groups = 0;
+        for side in 0..num_sides {
+            let root = find_root(&mut parents, side);
+            if groups[root] == usize::MAX {
+                groups[root] = num_groups;
+                num_groups += 1;
+            }
+            groups[side] = groups[root];
+        }
+        let mut graph = IndexedGraph::from_edges(
+            (0..self.num_segments())
+                .map(|s| (groups[2 * s], groups[2 * s + 1]))
+                .filter(|(left, right)| left != right),
+        );
+        graph.adjacency.resize_with(num_groups, Default::default);
+
+        let mut component = vec![0; num_groups];
+        let components = algorithm::find_components(&graph);
+        for (ix, nodes) in components.iter().enumerate() {
+            for &group in nodes {
+                component[group] = ix;
+            }
+        }
+
+        let vertices: Vec<usize> =
+            groups.iter().map(|&group| component[group]).collect();
+        let tips = (0..num_sides)
+            .find(|&side| !linked[side])
+            .map(|side| vertices[side]);
+        CactusGraph {
+            vertices,
+            num_vertices: components.len(),
+            tips,
+        }
+    }
+
+    /// Finds the snarl decomposition of the graph, from its cactus
+    /// graph, which consists of the snarls that vg calls chain pair
+    /// snarls: each snarl is bounded by two segments that are adjacent
+    /// in a chain, and the chains attached between them are nested in
+    /// it. Unlike superbubbles, snarls may contain cycles and both
+    /// strands of a segment, and a snarl and its reverse, from the
+    /// reversed end to the reversed start, are the same snarl, which
+    /// is only given once.
+    pub fn snarls(&self) -> SnarlDecomposition {
+        self.cactus_graph().snarls()
+    }
+}
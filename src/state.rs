@@ -1,50 +1,213 @@
-use crate::graph::FxMapGraph;
+use alloc::{vec, vec::Vec};
+use core::fmt;
 
+use crate::{
+    adjacency::Adjacency, components::Components, index::IndexType,
+    visitor::Visitor,
+};
+
+/// A vector of bits, used for the visited flags, which takes an eighth
+/// of the memory of a `Vec<bool>`.
 #[derive(Default, Debug, Clone)]
-pub struct State {
-    pub degrees: Vec<isize>,
-    pub next_sigma: Vec<usize>,
-    pub next_on_path: Vec<usize>,
-    pub visited: Vec<bool>,
-    pub pre: Vec<usize>,
-    pub lowpt: Vec<usize>,
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    pub fn new(len: usize) -> BitVec {
+        BitVec {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears all bits and sets the length, keeping the allocation.
+    pub fn reset(&mut self, len: usize) {
+        self.words.clear();
+        self.words.resize(len.div_ceil(64), 0);
+        self.len = len;
+    }
+
+    #[inline]
+    pub fn get(&self, ix: usize) -> bool {
+        assert!(ix < self.len);
+        self.words[ix / 64] & (1 << (ix % 64)) != 0
+    }
+
+    #[inline]
+    pub fn set(&mut self, ix: usize) {
+        assert!(ix < self.len);
+        self.words[ix / 64] |= 1 << (ix % 64);
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.words.capacity() * core::mem::size_of::<u64>()
+    }
+}
+
+/// The DFS numbers of a node after a run of the algorithm, from
+/// `State::node_info`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeInfo {
+    /// The preorder number of the node, counting from 1 in the order
+    /// the nodes were reached.
+    pub pre: usize,
+    /// The lowest preorder number reached by a back-edge from the
+    /// node's subtree, or the node's own if it's lower, as updated by
+    /// the absorptions.
+    pub lowpt: usize,
+    /// The number of nodes in the node's DFS subtree, including it.
+    pub num_descendants: usize,
+}
+
+/// The state of the algorithm. The `Ix` type parameter is the integer
+/// type used for the node indices and preorder numbers, see
+/// `IndexType`; the components in `sigma` always use `usize`.
+#[derive(Default, Debug, Clone)]
+pub struct State<Ix: IndexType = usize> {
+    pub degrees: Vec<Ix::Degree>,
+    pub next_sigma: Vec<Ix>,
+    pub next_on_path: Vec<Ix>,
+    pub visited: BitVec,
+    pub pre: Vec<Ix>,
+    pub lowpt: Vec<Ix>,
     pub count: usize,
-    pub num_descendants: Vec<usize>,
+    pub num_descendants: Vec<Ix>,
     pub path_u: usize,
-    pub sigma: Vec<Vec<usize>>,
+    pub sigma: Components,
 }
 
 impl State {
-    pub fn initialize(graph: &FxMapGraph) -> State {
-        let num_nodes = graph.len();
+    /// Creates the state for running the algorithm on `graph`, with
+    /// `usize` indices.
+    pub fn initialize<G: Adjacency + ?Sized>(graph: &G) -> State {
+        State::initialize_with_index(graph)
+    }
+}
+
+impl<Ix: IndexType> State<Ix> {
+    /// Creates the state for running the algorithm on `graph`, with
+    /// the index type `Ix`.
+    ///
+    /// Panics if the graph has more nodes than `Ix::MAX_NODES`.
+    pub fn initialize_with_index<G: Adjacency + ?Sized>(graph: &G) -> Self {
+        let num_nodes = graph.num_nodes();
+        assert!(
+            num_nodes <= Ix::MAX_NODES,
+            "graph has {} nodes, more than the index type supports",
+            num_nodes
+        );
 
         State {
             count: 1,
-            next_sigma: vec![0; num_nodes],
-            next_on_path: vec![0; num_nodes],
-            pre: vec![0; num_nodes],
-            lowpt: vec![0; num_nodes],
-            num_descendants: vec![1; num_nodes],
-            degrees: vec![0; num_nodes],
-            visited: vec![false; num_nodes],
-            sigma: Vec::new(),
+            next_sigma: vec![Ix::default(); num_nodes],
+            next_on_path: vec![Ix::default(); num_nodes],
+            pre: vec![Ix::default(); num_nodes],
+            lowpt: vec![Ix::default(); num_nodes],
+            num_descendants: vec![Ix::new(1); num_nodes],
+            degrees: vec![Ix::Degree::default(); num_nodes],
+            visited: BitVec::new(num_nodes),
+            sigma: Components::default(),
             path_u: 0,
         }
     }
 
+    /// Resets the state for running the algorithm again on `graph`,
+    /// e.g. after edges have been removed from it, reusing the
+    /// allocations. The previous components in `sigma` are cleared.
+    ///
+    /// Panics if the graph has more nodes than `Ix::MAX_NODES`.
+    pub fn reset<G: Adjacency + ?Sized>(&mut self, graph: &G) {
+        let num_nodes = graph.num_nodes();
+        assert!(
+            num_nodes <= Ix::MAX_NODES,
+            "graph has {} nodes, more than the index type supports",
+            num_nodes
+        );
+
+        fn fill<T: Clone>(v: &mut Vec<T>, len: usize, value: T) {
+            v.clear();
+            v.resize(len, value);
+        }
+
+        self.count = 1;
+        fill(&mut self.next_sigma, num_nodes, Ix::default());
+        fill(&mut self.next_on_path, num_nodes, Ix::default());
+        fill(&mut self.pre, num_nodes, Ix::default());
+        fill(&mut self.lowpt, num_nodes, Ix::default());
+        fill(&mut self.num_descendants, num_nodes, Ix::new(1));
+        fill(&mut self.degrees, num_nodes, Ix::Degree::default());
+        self.visited.reset(num_nodes);
+        self.sigma.clear();
+        self.path_u = 0;
+    }
+
     pub fn mut_recur(&mut self, w: usize) {
         assert!(w < self.visited.len());
+        self.visited.set(w);
+        let count = Ix::new(self.count);
         unsafe {
-            *self.visited.get_unchecked_mut(w) = true;
-            *self.next_sigma.get_unchecked_mut(w) = w;
-            *self.next_on_path.get_unchecked_mut(w) = w;
-            *self.pre.get_unchecked_mut(w) = self.count;
-            *self.lowpt.get_unchecked_mut(w) = self.count;
+            *self.next_sigma.get_unchecked_mut(w) = Ix::new(w);
+            *self.next_on_path.get_unchecked_mut(w) = Ix::new(w);
+            *self.pre.get_unchecked_mut(w) = count;
+            *self.lowpt.get_unchecked_mut(w) = count;
         }
         self.count += 1;
     }
 
-    pub fn components(&self) -> &Vec<Vec<usize>> {
+    /// Returns an estimate of the number of bytes of heap memory used
+    /// by the state, based on the capacities of its vectors.
+    pub fn memory_usage(&self) -> usize {
+        use core::mem::size_of;
+
+        fn vec_size<T>(v: &Vec<T>) -> usize {
+            v.capacity() * size_of::<T>()
+        }
+
+        vec_size(&self.degrees)
+            + vec_size(&self.next_sigma)
+            + vec_size(&self.next_on_path)
+            + self.visited.memory_usage()
+            + vec_size(&self.pre)
+            + vec_size(&self.lowpt)
+            + vec_size(&self.num_descendants)
+            + self.sigma.memory_usage()
+    }
+
+    /// The DFS numbers of the node, once the algorithm has run, or
+    /// `None` if the node isn't in the graph or wasn't reached.
+    pub fn node_info(&self, node: usize) -> Option<NodeInfo> {
+        if node >= self.visited.len() || !self.visited.get(node) {
+            return None;
+        }
+        Some(NodeInfo {
+            pre: self.pre[node].index(),
+            lowpt: self.lowpt[node].index(),
+            num_descendants: self.num_descendants[node].index(),
+        })
+    }
+
+    /// Like `node_info`, with the node given by its name, such as in
+    /// the `names` of an `InternedGraph`.
+    #[cfg(feature = "std")]
+    pub fn node_info_by_name(
+        &self,
+        names: &crate::names::NameArena,
+        name: &[u8],
+    ) -> Option<NodeInfo> {
+        names.find(name).and_then(|node| self.node_info(node))
+    }
+
+    pub fn components(&self) -> &Components {
         &self.sigma
     }
 
@@ -53,7 +216,7 @@ impl State {
     }
 
     pub fn is_null_path(&self, u: usize) -> bool {
-        self.next_on_path[u] == u
+        self.next_on_path[u].index() == u
     }
 
     pub fn absorb_path(
@@ -62,70 +225,212 @@ impl State {
         path: usize,
         end: Option<usize>,
     ) {
+        self.absorb_path_with_visitor(root, path, end, &mut ())
+    }
+
+    /// Like `absorb_path`, calling `visitor.on_absorb` for each node
+    /// that's absorbed.
+    ///
+    /// Panics if the path has a cycle, see `try_absorb_path_with_visitor`.
+    pub fn absorb_path_with_visitor<V: Visitor + ?Sized>(
+        &mut self,
+        root: usize,
+        path: usize,
+        end: Option<usize>,
+        visitor: &mut V,
+    ) {
+        if let Err(err) =
+            self.try_absorb_path_with_visitor(root, path, end, visitor)
+        {
+            panic!("{}", err);
+        }
+    }
+
+    /// Like `absorb_path_with_visitor`, but returns an error if the
+    /// path is longer than the number of nodes, instead of following
+    /// a cycle in `next_on_path` forever.
+    pub fn try_absorb_path_with_visitor<V: Visitor + ?Sized>(
+        &mut self,
+        root: usize,
+        path: usize,
+        end: Option<usize>,
+        visitor: &mut V,
+    ) -> Result<(), CorruptState> {
         if Some(root) != end {
+            let mut steps = self.next_on_path.len();
             let mut current = root;
             let mut step = path;
             while current != step {
-                unsafe {
-                    *self.degrees.get_unchecked_mut(root) +=
-                        *self.degrees.get_unchecked_mut(step) - 2;
-                    self.next_sigma.swap(root, step);
-                    current = step;
-                    if Some(step) != end {
-                        step = *self.next_on_path.get_unchecked(step);
-                    }
-                    // self.degrees[root] += self.degrees[step] - 2;
-                    // self.next_sigma.swap(root, step);
-                    // current = step;
-                    // if Some(step) != end {
-                    //     step = self.next_on_path[step];
-                    // }
+                if steps == 0 {
+                    return Err(CorruptState::PathCycle { node: root });
+                }
+                steps -= 1;
+                let degree = self.degrees[step];
+                self.degrees[root] += degree - 2.into();
+                self.next_sigma.swap(root, step);
+                visitor.on_absorb(root, step);
+                current = step;
+                if Some(step) != end {
+                    step = self.next_on_path[step].index();
                 }
             }
         }
+        Ok(())
     }
 
-    pub fn sigma_iter(&self, start: usize) -> SigmaIter<'_> {
-        SigmaIter::new(self, start)
+    /// Absorbs the nodes at the start of the path of `w` that are
+    /// ancestors of `u`, for the incoming back-edge (u, w), and makes
+    /// the rest of the path the path of `w`: P_w[w..u] in the paper.
+    /// The nodes are checked and absorbed in the same pass, which stops
+    /// at the first node that isn't an ancestor of `u`. Each node is
+    /// only absorbed once, after which it's no longer on any path, so
+    /// this takes amortized constant time per back-edge overall.
+    ///
+    /// Returns an error if the path is longer than the number of nodes,
+    /// like `try_absorb_path_with_visitor`.
+    pub fn absorb_ancestors_with_visitor<V: Visitor + ?Sized>(
+        &mut self,
+        w: usize,
+        u: usize,
+        visitor: &mut V,
+    ) -> Result<(), CorruptState> {
+        let mut steps = self.next_on_path.len();
+        let pre_u = self.pre[u].index();
+        let mut parent = w;
+        while !self.is_null_path(parent) {
+            let child = self.next_on_path[parent].index();
+            let pre_child = self.pre[child].index();
+            // child must have been visited before u, and still be an
+            // ancestor of it
+            if pre_child > pre_u
+                || pre_u >= pre_child + self.num_descendants[child].index()
+            {
+                break;
+            }
+            if steps == 0 {
+                return Err(CorruptState::PathCycle { node: w });
+            }
+            steps -= 1;
+            let degree = self.degrees[child];
+            self.degrees[w] += degree - 2.into();
+            self.next_sigma.swap(w, child);
+            visitor.on_absorb(w, child);
+            parent = child;
+        }
+
+        self.next_on_path[w] = if self.is_null_path(parent) {
+            Ix::new(w)
+        } else {
+            self.next_on_path[parent]
+        };
+        Ok(())
     }
 
+    pub fn sigma_iter(&self, start: usize) -> SigmaIter<'_, Ix> {
+        SigmaIter::new(&self.next_sigma, start)
+    }
+
+    /// Adds the component of `start`, i.e. its sigma set, to `sigma`.
+    ///
+    /// Panics if `next_sigma` doesn't lead back to `start`, see
+    /// `try_add_component`.
     pub fn add_component(&mut self, start: usize) {
-        self.sigma.push(self.sigma_iter(start).collect());
+        if let Err(err) = self.try_add_component(start) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Like `add_component`, but returns an error, without adding
+    /// anything, if following `next_sigma` from `start` doesn't lead
+    /// back to it within the number of nodes.
+    pub fn try_add_component(
+        &mut self,
+        start: usize,
+    ) -> Result<(), CorruptState> {
+        let mut iter = SigmaIter::new(&self.next_sigma, start);
+        self.sigma.push(&mut iter);
+        if iter.is_cycle_broken() {
+            self.sigma.truncate(self.sigma.len() - 1);
+            return Err(CorruptState::SigmaCycle { node: start });
+        }
+        Ok(())
     }
 }
 
-// Struct representing an iterator over a node's sigma set
-pub struct SigmaIter<'a> {
+/// An inconsistency in the linked lists of a `State`: following
+/// `next_sigma` or `next_on_path` from a node takes more steps than
+/// there are nodes, so it has a cycle that would never end. This can
+/// only happen if the public fields of the state were changed outside
+/// of the algorithm, or it was run on a graph that fails
+/// `algorithm::check_input`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorruptState {
+    /// The sigma set of the node doesn't lead back to it.
+    SigmaCycle { node: usize },
+    /// The path of the node doesn't end.
+    PathCycle { node: usize },
+}
+
+impl fmt::Display for CorruptState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CorruptState::SigmaCycle { node } => write!(
+                f,
+                "the sigma set of node {} doesn't lead back to it",
+                node
+            ),
+            CorruptState::PathCycle { node } => {
+                write!(f, "the path of node {} has a cycle", node)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CorruptState {}
+
+// Struct representing an iterator over a node's sigma set, which
+// stops after as many nodes as there are in the graph, in case the
+// set doesn't lead back to its start
+pub struct SigmaIter<'a, Ix: IndexType = usize> {
     start: usize,
     current: usize,
-    next_sigma: &'a [usize],
+    next_sigma: &'a [Ix],
     done: bool,
+    remaining: usize,
 }
 
-impl<'a> SigmaIter<'a> {
-    fn new(state: &'a State, node: usize) -> SigmaIter<'a> {
-        let next_sigma = &state.next_sigma;
+impl<'a, Ix: IndexType> SigmaIter<'a, Ix> {
+    fn new(next_sigma: &'a [Ix], node: usize) -> SigmaIter<'a, Ix> {
         SigmaIter {
             start: node,
-            current: next_sigma[node],
+            current: next_sigma[node].index(),
             next_sigma,
             done: false,
+            remaining: next_sigma.len(),
         }
     }
+
+    /// Whether the iterator stopped because the set didn't lead back
+    /// to its start within the number of nodes, see `CorruptState`.
+    pub fn is_cycle_broken(&self) -> bool {
+        self.remaining == 0 && !self.done
+    }
 }
 
-impl<'a> Iterator for SigmaIter<'a> {
+impl<'a, Ix: IndexType> Iterator for SigmaIter<'a, Ix> {
     type Item = usize;
 
     fn next(&mut self) -> Option<usize> {
-        if self.done {
+        if self.done || self.remaining == 0 {
             None
         } else {
             if self.current == self.start {
                 self.done = true;
             }
+            self.remaining -= 1;
 
-            self.current = self.next_sigma[self.current];
+            self.current = self.next_sigma[self.current].index();
             Some(self.current)
         }
     }
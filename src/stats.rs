@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use fxhash::FxHashMap;
+
+use crate::{
+    adjacency::Adjacency,
+    graph::{Graph, IndexedGraph, InternedGraph},
+};
+
+/// Node and edge counts of a graph, from `Graph::stats`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GraphStats {
+    pub num_nodes: usize,
+    pub num_edges: usize,
+    pub self_loops: usize,
+    /// The number of edges that are parallel to an earlier edge, so
+    /// that two edges between the same nodes count as one.
+    pub parallel_edges: usize,
+    /// The number of nodes with each degree, where a self-loop counts
+    /// twice.
+    pub degrees: BTreeMap<usize, usize>,
+}
+
+impl GraphStats {
+    pub fn max_degree(&self) -> usize {
+        self.degrees.keys().next_back().copied().unwrap_or(0)
+    }
+}
+
+/// Computes the stats of any graph, see `Graph::stats`.
+pub fn graph_stats<G: Adjacency + ?Sized>(graph: &G) -> GraphStats {
+    let mut stats = GraphStats {
+        num_nodes: graph.num_nodes(),
+        ..GraphStats::default()
+    };
+    let mut multiplicities: FxHashMap<usize, usize> = FxHashMap::default();
+    let mut ends = 0;
+
+    for w in graph.nodes() {
+        let neighbors = graph.neighbors(w);
+        ends += neighbors.len();
+        *stats.degrees.entry(neighbors.len()).or_default() += 1;
+
+        multiplicities.clear();
+        for &u in neighbors.iter().filter(|&&u| u >= w) {
+            *multiplicities.entry(u).or_default() += 1;
+        }
+        for (&u, &count) in multiplicities.iter() {
+            if u == w {
+                // self-loops are in the adjacency list twice
+                stats.self_loops += count / 2;
+                stats.parallel_edges += (count / 2).saturating_sub(1);
+            } else {
+                stats.parallel_edges += count - 1;
+            }
+        }
+    }
+
+    stats.num_edges = ends / 2;
+    stats
+}
+
+impl<N> Graph<N> {
+    /// Counts the nodes and edges, self-loops, and parallel edges, and
+    /// the number of nodes with each degree, e.g. for logging.
+    pub fn stats(&self) -> GraphStats {
+        graph_stats(&self.graph)
+    }
+}
+
+impl InternedGraph {
+    /// Like `Graph::stats`.
+    pub fn stats(&self) -> GraphStats {
+        graph_stats(&self.graph)
+    }
+}
+
+impl IndexedGraph {
+    /// Like `Graph::stats`, where the indices that aren't used as
+    /// names are nodes with degree 0.
+    pub fn stats(&self) -> GraphStats {
+        graph_stats(self)
+    }
+}
@@ -0,0 +1,254 @@
+use std::io::{self, BufRead};
+
+use fxhash::FxHashSet;
+
+use crate::{
+    graph::{for_each_gfa_link, AdjacencyList, IndexedGraph, MalformedLine},
+    names::NameArena,
+};
+
+/// A GFA graph that keeps the orientations of the links, for finding
+/// superbubbles, which are only defined on directed graphs. Each
+/// segment has two nodes, `2 * ix` for its forward strand and
+/// `2 * ix + 1` for its reverse strand, where `ix` is the segment's
+/// index in `names`. A link from `a+` to `b-` is an edge from `a+` to
+/// `b-`, and one from `b+` to `a-` on the opposite strands.
+///
+/// `segment_graph` gives the undirected graph of the segments, with
+/// the same indices, so that the 3-edge-connected components and the
+/// superbubbles can be found from a single parse of the GFA.
+#[derive(Default, Debug, Clone)]
+pub struct OrientedGraph {
+    pub names: NameArena,
+    // each link once, as its edge from the `from` segment
+    links: Vec<(usize, usize)>,
+    successors: Vec<AdjacencyList>,
+}
+
+/// A superbubble from `entrance` to `exit`, which are oriented nodes
+/// of an `OrientedGraph`: every path from the entrance ends at the
+/// exit, every path to the exit starts at the entrance, there are no
+/// cycles in between, and no node in between is the exit of another
+/// superbubble with the same entrance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Superbubble {
+    pub entrance: usize,
+    pub exit: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Unseen,
+    Seen,
+    Pushed,
+    Visited,
+}
+
+impl OrientedGraph {
+    /// Constructs the graph from the links of the given GFA stream,
+    /// like `Graph::from_gfa_reader`, so segments without links aren't
+    /// in the graph.
+    pub fn from_gfa_reader<T: BufRead>(
+        reader: &mut T,
+    ) -> io::Result<OrientedGraph> {
+        OrientedGraph::from_gfa_reader_with(reader, |_| Ok(()))
+    }
+
+    /// Like `from_gfa_reader`, with the link lines that can't be
+    /// parsed handled like in `graph::gfa_links_with`.
+    pub fn from_gfa_reader_with<T, F>(
+        reader: &mut T,
+        on_malformed: F,
+    ) -> io::Result<OrientedGraph>
+    where
+        T: BufRead,
+        F: FnMut(MalformedLine) -> io::Result<()>,
+    {
+        let mut graph = OrientedGraph::default();
+        for_each_gfa_link(reader, on_malformed, |link| {
+            let from = graph.names.get_or_insert(link.from_segment);
+            let to = graph.names.get_or_insert(link.to_segment);
+            graph.add_link(
+                2 * from + link.from_reverse as usize,
+                2 * to + link.to_reverse as usize,
+            );
+            Ok(())
+        })?;
+        Ok(graph)
+    }
+
+    fn add_link(&mut self, from: usize, to: usize) {
+        let len = 2 * self.names.len();
+        if self.successors.len() < len {
+            self.successors.resize_with(len, Default::default);
+        }
+        self.links.push((from, to));
+        self.successors[from].push(to);
+        self.successors[to ^ 1].push(from ^ 1);
+    }
+
+    pub fn num_segments(&self) -> usize {
+        self.names.len()
+    }
+
+    /// The number of oriented nodes, twice the number of segments.
+    pub fn num_nodes(&self) -> usize {
+        self.successors.len()
+    }
+
+    pub fn num_links(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Each link once, as the edge between oriented nodes that it was
+    /// given as.
+    pub fn links(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.links.iter().copied()
+    }
+
+    pub fn successors(&self, node: usize) -> &[usize] {
+        &self.successors[node]
+    }
+
+    /// The nodes with an edge to the given node, which are the
+    /// opposite strands of the successors of its opposite strand.
+    pub fn predecessors(
+        &self,
+        node: usize,
+    ) -> impl Iterator<Item = usize> + '_ {
+        self.successors[node ^ 1].iter().map(|&u| u ^ 1)
+    }
+
+    /// The name of an oriented node, as the segment name followed by
+    /// `+` or `-`.
+    pub fn node_name(&self, node: usize) -> Vec<u8> {
+        let mut name = self.names.get(node / 2).to_vec();
+        name.push(if node & 1 == 0 { b'+' } else { b'-' });
+        name
+    }
+
+    /// The undirected graph of the segments, with one edge per link,
+    /// and the same segment indices as `names`.
+    pub fn segment_graph(&self) -> IndexedGraph {
+        IndexedGraph::from_edges(
+            self.links.iter().map(|&(from, to)| (from / 2, to / 2)),
+        )
+    }
+
+    /// Finds the superbubbles with at least one node between the
+    /// entrance and the exit, using the algorithm of Onodera et al.
+    /// (2013), which searches from each node as a possible entrance.
+    /// This takes time proportional to the number of nodes times the
+    /// size of the largest superbubbles, or that of the parts of the
+    /// graph that are searched before finding that a node isn't an
+    /// entrance, which is fast on assembly graphs.
+    ///
+    /// Every superbubble is also one on the opposite strands, from the
+    /// reverse of its exit to the reverse of its entrance; only the one
+    /// of the two with the smaller entrance is returned. The
+    /// superbubbles are sorted by entrance.
+    pub fn superbubbles(&self) -> Vec<Superbubble> {
+        let mut marks = vec![Mark::Unseen; self.num_nodes()];
+        let mut touched = Vec::new();
+        let mut stack = Vec::new();
+        let mut bubbles = Vec::new();
+
+        for entrance in 0..self.num_nodes() {
+            let exit =
+                self.find_exit(entrance, &mut marks, &mut touched, &mut stack);
+            // every node the search visited, other than the entrance,
+            // is in the bubble; with only the exit, it's a single edge
+            let interior = touched.len() > 2;
+            for &w in touched.iter() {
+                marks[w] = Mark::Unseen;
+            }
+            touched.clear();
+
+            if let Some(exit) =
+                exit.filter(|&exit| interior && entrance <= exit ^ 1)
+            {
+                bubbles.push(Superbubble { entrance, exit });
+            }
+        }
+        bubbles
+    }
+
+    /// The search of Onodera et al.: nodes are visited once all their
+    /// predecessors have been, and if at some point the only node that
+    /// has been seen but not visited can also be visited, it's the
+    /// exit.
+    fn find_exit(
+        &self,
+        entrance: usize,
+        marks: &mut [Mark],
+        touched: &mut Vec<usize>,
+        stack: &mut Vec<usize>,
+    ) -> Option<usize> {
+        stack.clear();
+        stack.push(entrance);
+        touched.push(entrance);
+        // the number of nodes that are seen or pushed
+        let mut num_seen = 0;
+
+        while let Some(v) = stack.pop() {
+            if marks[v] != Mark::Unseen {
+                num_seen -= 1;
+            }
+            marks[v] = Mark::Visited;
+
+            let successors = self.successors(v);
+            if successors.is_empty() {
+                // a tip
+                return None;
+            }
+            for &u in successors {
+                if u == entrance || u == v {
+                    // a cycle through the entrance, or a self-loop
+                    return None;
+                }
+                if marks[u] == Mark::Unseen {
+                    marks[u] = Mark::Seen;
+                    touched.push(u);
+                    num_seen += 1;
+                }
+                if marks[u] == Mark::Seen
+                    && self.predecessors(u).all(|p| marks[p] == Mark::Visited)
+                {
+                    marks[u] = Mark::Pushed;
+                    stack.push(u);
+                }
+            }
+
+            if stack.len() == 1 && num_seen == 1 {
+                let exit = stack[0];
+                if self.successors(exit).contains(&entrance) {
+                    return None;
+                }
+                return Some(exit);
+            }
+        }
+        None
+    }
+
+    /// The nodes of the superbubble, including the entrance and the
+    /// exit, in the order they're reached from the entrance.
+    pub fn superbubble_nodes(&self, bubble: Superbubble) -> Vec<usize> {
+        let mut nodes = vec![bubble.entrance];
+        let mut seen = FxHashSet::default();
+        seen.insert(bubble.entrance);
+        let mut next = 0;
+        while next < nodes.len() {
+            let w = nodes[next];
+            next += 1;
+            if w == bubble.exit {
+                continue;
+            }
+            for &u in self.successors(w) {
+                if seen.insert(u) {
+                    nodes.push(u);
+                }
+            }
+        }
+        nodes
+    }
+}
@@ -0,0 +1,138 @@
+use std::io::{self, BufRead};
+
+use fxhash::{FxHashMap, FxHashSet};
+
+/// The edges of a graph in the Trivial Graph Format, from `tgf_edges`.
+pub struct TgfEdges<R> {
+    reader: R,
+    line: Vec<u8>,
+    // whether the `#` line between the nodes and the edges was read
+    in_edges: bool,
+    labels: FxHashMap<Vec<u8>, Vec<u8>>,
+    // the names of the nodes with labels, once they're all read
+    names: FxHashSet<Vec<u8>>,
+    failed: bool,
+}
+
+/// Parses the edges of a graph in the Trivial Graph Format, the `.tgf`
+/// files of yEd and other graph editors: a node id and an optional
+/// label on each line, then a line with a `#`, then the ids of the two
+/// nodes of an edge on each line, with an optional label that's
+/// ignored. The nodes are named by their labels, or by their ids if
+/// they don't have one, and the names must be distinct. Nodes that
+/// aren't listed, but are in an edge, are named by their ids.
+pub fn tgf_edges<R: BufRead>(reader: R) -> TgfEdges<R> {
+    TgfEdges {
+        reader,
+        line: Vec::new(),
+        in_edges: false,
+        labels: FxHashMap::default(),
+        names: FxHashSet::default(),
+        failed: false,
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Splits off the first whitespace-separated field of the line, and
+/// returns it with the rest, trimmed.
+fn split_field(line: &[u8]) -> (&[u8], &[u8]) {
+    let end = line
+        .iter()
+        .position(u8::is_ascii_whitespace)
+        .unwrap_or(line.len());
+    let rest = &line[end..];
+    let start = rest
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(rest.len());
+    (&line[..end], &rest[start..])
+}
+
+impl<R: BufRead> TgfEdges<R> {
+    /// Checks that the labels are distinct, and that none of them is
+    /// the id of another node without a label, before they're used as
+    /// names.
+    fn check_labels(&mut self) -> io::Result<()> {
+        for (id, label) in &self.labels {
+            let name = if label.is_empty() { id } else { label };
+            if !self.names.insert(name.clone()) {
+                return Err(invalid(format!(
+                    "TGF node {:?} has the same name as another, {:?}",
+                    String::from_utf8_lossy(id),
+                    String::from_utf8_lossy(name)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn name(&self, id: &[u8]) -> io::Result<Vec<u8>> {
+        match self.labels.get(id) {
+            Some(label) if !label.is_empty() => Ok(label.clone()),
+            Some(_) => Ok(id.to_vec()),
+            None if self.names.contains(id) => Err(invalid(format!(
+                "TGF node {:?} isn't listed, but is the name of another",
+                String::from_utf8_lossy(id)
+            ))),
+            None => Ok(id.to_vec()),
+        }
+    }
+
+    fn parse_line(&mut self) -> io::Result<Option<(Vec<u8>, Vec<u8>)>> {
+        let line = self.line.trim_ascii();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        if !self.in_edges {
+            if line == b"#" {
+                self.in_edges = true;
+                self.check_labels()?;
+                return Ok(None);
+            }
+            let (id, label) = split_field(line);
+            if self.labels.insert(id.to_vec(), label.to_vec()).is_some() {
+                return Err(invalid(format!(
+                    "TGF node {:?} is listed twice",
+                    String::from_utf8_lossy(id)
+                )));
+            }
+            return Ok(None);
+        }
+        let (from, rest) = split_field(line);
+        let (to, _) = split_field(rest);
+        if to.is_empty() {
+            return Err(invalid(format!(
+                "missing node in TGF edge {:?}",
+                String::from_utf8_lossy(line)
+            )));
+        }
+        Ok(Some((self.name(from)?, self.name(to)?)))
+    }
+}
+
+impl<R: BufRead> Iterator for TgfEdges<R> {
+    type Item = io::Result<(Vec<u8>, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed {
+            self.line.clear();
+            let result = match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => self.parse_line(),
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(None) => (),
+                Ok(Some(edge)) => return Some(Ok(edge)),
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
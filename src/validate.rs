@@ -0,0 +1,199 @@
+use std::fmt;
+
+use fxhash::FxHashMap;
+
+use crate::graph::{FxMapGraph, Graph, IndexedGraph, InternedGraph};
+
+/// An inconsistency found by `Graph::validate`, any of which can make
+/// the algorithm panic or give wrong results.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Problem {
+    /// A node whose index isn't below the number of nodes, so some
+    /// smaller index isn't a node.
+    NodeOutOfRange { node: usize, num_nodes: usize },
+    /// A neighbor of `node` that isn't a node of the graph.
+    MissingNeighbor { node: usize, neighbor: usize },
+    /// `to` is in the neighbors of `from` a different number of times
+    /// than `from` is in the neighbors of `to`.
+    Asymmetric {
+        from: usize,
+        to: usize,
+        forward: usize,
+        backward: usize,
+    },
+    /// The number of names doesn't match the number of nodes.
+    NameCount { num_names: usize, num_nodes: usize },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::NodeOutOfRange { node, num_nodes } => write!(
+                f,
+                "node {} is out of range for a graph with {} nodes",
+                node, num_nodes
+            ),
+            Problem::MissingNeighbor { node, neighbor } => write!(
+                f,
+                "node {} has neighbor {}, which is not a node",
+                node, neighbor
+            ),
+            Problem::Asymmetric {
+                from,
+                to,
+                forward,
+                backward,
+            } => write!(
+                f,
+                "node {} is a neighbor of node {} {} times, but {} is a \
+                 neighbor of {} {} times",
+                to, from, forward, from, to, backward
+            ),
+            Problem::NameCount {
+                num_names,
+                num_nodes,
+            } => write!(
+                f,
+                "graph has {} names for {} nodes",
+                num_names, num_nodes
+            ),
+        }
+    }
+}
+
+/// The result of `Graph::validate`, with every problem found.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub num_nodes: usize,
+    pub problems: Vec<Problem>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "graph with {} nodes is valid", self.num_nodes);
+        }
+        write!(
+            f,
+            "graph with {} nodes has {} problems:",
+            self.num_nodes,
+            self.problems.len()
+        )?;
+        for problem in self.problems.iter() {
+            write!(f, "\n  {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationReport {}
+
+impl<N> Graph<N> {
+    /// Checks that the nodes are numbered `0..n`, that each edge is in
+    /// the neighbor lists of both of its ends, as many times as there
+    /// are parallel edges, and that there is a name for each node.
+    /// This is always the case for graphs built by the constructors
+    /// in this crate, but not necessarily for graphs put together by
+    /// hand.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = validate_map(&self.graph);
+        check_names(&mut report, self.inv_names.len());
+        report
+    }
+}
+
+impl InternedGraph {
+    /// Like `Graph::validate`.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = validate_map(&self.graph);
+        check_names(&mut report, self.names.len());
+        report
+    }
+}
+
+impl IndexedGraph {
+    /// Like `Graph::validate`, without the names. The nodes are always
+    /// numbered `0..n`.
+    pub fn validate(&self) -> ValidationReport {
+        let num_nodes = self.adjacency.len();
+        let lists = self
+            .adjacency
+            .iter()
+            .enumerate()
+            .map(|(w, adj)| (w, &adj[..]));
+        validate_lists(lists, num_nodes, |u| u < num_nodes, Vec::new())
+    }
+}
+
+fn validate_map(graph: &FxMapGraph) -> ValidationReport {
+    let num_nodes = graph.len();
+    let problems = graph
+        .keys()
+        .filter(|&&node| node >= num_nodes)
+        .map(|&node| Problem::NodeOutOfRange { node, num_nodes })
+        .collect();
+
+    let lists = graph.iter().map(|(&w, adj)| (w, &adj[..]));
+    validate_lists(lists, num_nodes, |u| graph.contains_key(&u), problems)
+}
+
+fn validate_lists<'a, I, F>(
+    lists: I,
+    num_nodes: usize,
+    is_node: F,
+    mut problems: Vec<Problem>,
+) -> ValidationReport
+where
+    I: Iterator<Item = (usize, &'a [usize])>,
+    F: Fn(usize) -> bool,
+{
+    // for each pair of nodes, the number of times the larger one is a
+    // neighbor of the smaller one, and the other way around
+    let mut counts: FxHashMap<(usize, usize), (usize, usize)> =
+        FxHashMap::default();
+
+    for (w, neighbors) in lists {
+        for &u in neighbors {
+            if !is_node(u) {
+                problems.push(Problem::MissingNeighbor {
+                    node: w,
+                    neighbor: u,
+                });
+            } else if w < u {
+                counts.entry((w, u)).or_default().0 += 1;
+            } else if w > u {
+                counts.entry((u, w)).or_default().1 += 1;
+            }
+        }
+    }
+
+    problems.extend(counts.into_iter().filter(|(_, (f, b))| f != b).map(
+        |((from, to), (forward, backward))| Problem::Asymmetric {
+            from,
+            to,
+            forward,
+            backward,
+        },
+    ));
+    problems.sort();
+
+    ValidationReport {
+        num_nodes,
+        problems,
+    }
+}
+
+fn check_names(report: &mut ValidationReport, num_names: usize) {
+    if num_names != report.num_nodes {
+        report.problems.push(Problem::NameCount {
+            num_names,
+            num_nodes: report.num_nodes,
+        });
+    }
+}
@@ -0,0 +1,201 @@
+use std::io::{self, BufRead};
+
+/// The edges of a graph in vg's Protobuf format, from `vg_edges`.
+pub struct VgEdges<R> {
+    reader: R,
+    // the number of messages left in the current group
+    group_left: u64,
+    first_in_group: bool,
+    message: Vec<u8>,
+    edges: std::vec::IntoIter<(i64, i64)>,
+    failed: bool,
+}
+
+/// Parses the edges of a graph in vg's Protobuf format, the `.vg`
+/// files of `vg construct` and `vg view -v`, with the node ids as the
+/// node names. The stream must already be decompressed, e.g. with
+/// `gzip -d`, as `.vg` files are BGZF-compressed. The orientations of
+/// the edges are ignored, and nodes without edges aren't in the graph,
+/// like segments without links in GFA. Files in vg's other formats,
+/// such as the PackedGraph of `vg convert -p`, are an error.
+pub fn vg_edges<R: BufRead>(reader: R) -> VgEdges<R> {
+    VgEdges {
+        reader,
+        group_left: 0,
+        first_in_group: false,
+        message: Vec::new(),
+        edges: Vec::new().into_iter(),
+        failed: false,
+    }
+}
+
+// the largest message that vg writes is well below this, so anything
+// larger is from a file in some other format
+const MAX_MESSAGE_SIZE: u64 = 1 << 30;
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid vg file: {}", message.into()),
+    )
+}
+
+/// Reads a varint, or returns `None` at the end of the stream if it's
+/// the first byte.
+fn read_varint<R: BufRead>(reader: &mut R) -> io::Result<Option<u64>> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = match reader.fill_buf()?.first() {
+            Some(&byte) => byte,
+            None if shift == 0 => return Ok(None),
+            None => return Err(invalid("truncated varint")),
+        };
+        reader.consume(1);
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+    }
+    Err(invalid("varint is too long"))
+}
+
+/// A field of a Protobuf message, other than the fixed-size ones,
+/// which are skipped.
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if buf.len() < len {
+        return Err(invalid("truncated field"));
+    }
+    let (value, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(value)
+}
+
+/// Decodes the next field of a Protobuf message, as its number and
+/// value.
+fn next_field<'a>(buf: &mut &'a [u8]) -> io::Result<Option<(u64, Field<'a>)>> {
+    let truncated = || invalid("truncated field");
+    let key = match read_varint(buf)? {
+        None => return Ok(None),
+        Some(key) => key,
+    };
+    let value = match key & 7 {
+        0 => Field::Varint(read_varint(buf)?.ok_or_else(truncated)?),
+        1 => take(buf, 8).map(|_| Field::Fixed)?,
+        2 => {
+            let len = read_varint(buf)?.ok_or_else(truncated)?;
+            Field::Bytes(take(buf, len as usize)?)
+        }
+        5 => take(buf, 4).map(|_| Field::Fixed)?,
+        wire_type => {
+            return Err(invalid(format!("unknown wire type {}", wire_type)))
+        }
+    };
+    Ok(Some((key >> 3, value)))
+}
+
+/// Adds the edges of a `Graph` message, whose field 2 holds the
+/// `Edge` messages, which have the node ids in fields 1 and 2.
+fn decode_graph(
+    mut graph: &[u8],
+    edges: &mut Vec<(i64, i64)>,
+) -> io::Result<()> {
+    while let Some((field, value)) = next_field(&mut graph)? {
+        if let (2, Field::Bytes(mut edge)) = (field, value) {
+            let (mut from, mut to) = (None, None);
+            while let Some((field, value)) = next_field(&mut edge)? {
+                match (field, value) {
+                    (1, Field::Varint(id)) => from = Some(id as i64),
+                    (2, Field::Varint(id)) => to = Some(id as i64),
+                    _ => (),
+                }
+            }
+            match (from, to) {
+                (Some(from), Some(to)) => edges.push((from, to)),
+                _ => return Err(invalid("edge without node ids")),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The type tags at the start of each group of messages in the
+/// current format, such as `VG`, are short and alphanumeric, which a
+/// `Graph` message never is, as its field keys aren't.
+fn is_type_tag(message: &[u8]) -> bool {
+    !message.is_empty()
+        && message.len() <= 25
+        && message.iter().all(|b| b.is_ascii_alphanumeric())
+}
+
+impl<R: BufRead> VgEdges<R> {
+    /// Decodes the next `Graph` message into `self.edges`, or returns
+    /// false at the end of the stream.
+    fn next_message(&mut self) -> io::Result<bool> {
+        while self.group_left == 0 {
+            match read_varint(&mut self.reader)? {
+                None => return Ok(false),
+                Some(count) => {
+                    self.group_left = count;
+                    self.first_in_group = true;
+                }
+            }
+        }
+        self.group_left -= 1;
+
+        let len = read_varint(&mut self.reader)?
+            .ok_or_else(|| invalid("truncated message"))?;
+        if len > MAX_MESSAGE_SIZE {
+            return Err(invalid(format!("message of {} bytes", len)));
+        }
+        self.message.resize(len as usize, 0);
+        self.reader
+            .read_exact(&mut self.message)
+            .map_err(|_| invalid("truncated message"))?;
+
+        if std::mem::take(&mut self.first_in_group)
+            && is_type_tag(&self.message)
+        {
+            if self.message != b"VG" {
+                return Err(invalid(format!(
+                    "contains {} messages instead of a graph",
+                    String::from_utf8_lossy(&self.message)
+                )));
+            }
+            return Ok(true);
+        }
+
+        let mut edges = Vec::new();
+        decode_graph(&self.message, &mut edges)?;
+        self.edges = edges.into_iter();
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> Iterator for VgEdges<R> {
+    type Item = io::Result<(Vec<u8>, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed {
+            if let Some((from, to)) = self.edges.next() {
+                let name = |id: i64| id.to_string().into_bytes();
+                return Some(Ok((name(from), name(to))));
+            }
+            match self.next_message() {
+                Ok(true) => (),
+                Ok(false) => return None,
+                Err(err) => {
+                    // the rest of the stream can't be decoded
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
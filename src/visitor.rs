@@ -0,0 +1,29 @@
+/// Callbacks invoked by `algorithm::three_edge_connect_with_visitor`
+/// as the algorithm runs, for instrumenting it or collecting
+/// statistics. All methods do nothing by default, and `()` is the
+/// visitor that does nothing, which `three_edge_connect` uses.
+pub trait Visitor {
+    /// Called when the DFS first visits a node.
+    fn on_discover(&mut self, _node: usize) {}
+
+    /// Called when the DFS reaches a node from its parent, along an
+    /// edge of the DFS tree, just after `on_discover` for the child.
+    /// The roots of the DFS trees have no parent, and no call.
+    fn on_tree_edge(&mut self, _parent: usize, _child: usize) {}
+
+    /// Called once for each back-edge, i.e. each edge that's not in
+    /// the DFS tree, from a node to its ancestor, when the ancestor
+    /// reaches the edge. Self-loops are skipped by the algorithm, and
+    /// aren't reported.
+    fn on_back_edge(&mut self, _descendant: usize, _ancestor: usize) {}
+
+    /// Called when the component of `node` is absorbed into that of
+    /// `root`, merging them.
+    fn on_absorb(&mut self, _root: usize, _node: usize) {}
+
+    /// Called when a component is complete, with its nodes, just
+    /// after it's added to `state.sigma`.
+    fn on_component(&mut self, _component: &[usize]) {}
+}
+
+impl Visitor for () {}
@@ -0,0 +1,75 @@
+//! A minimal C ABI for calling the algorithm from JavaScript, when
+//! built for `wasm32-unknown-unknown` with the `wasm` feature. It
+//! doesn't depend on `wasm-bindgen`; instead, edge lists and results
+//! are passed as `u32` arrays in the module's linear memory, which
+//! `wasm/three_edge_connected.js` wraps in a
+//! `findComponents(edgeList) -> components` function.
+
+use crate::{algorithm, graph::Graph};
+
+/// Finds the 3-edge-connected components of the graph whose edges are
+/// given as consecutive pairs of `u32` node IDs. The components are
+/// returned as a flat array: the number of components, followed by
+/// each component as its length and then its node IDs.
+pub fn find_components_flat(edges: &[u32]) -> Vec<u32> {
+    let graph = Graph::from_edges(
+        edges
+            .chunks_exact(2)
+            .map(|pair| (pair[0] as usize, pair[1] as usize)),
+    );
+    let components = algorithm::find_components(&graph.graph);
+
+    let mut result =
+        Vec::with_capacity(1 + components.len() + graph.inv_names.len());
+    result.push(components.len() as u32);
+    for component in components {
+        result.push(component.len() as u32);
+        result.extend(component.iter().map(|&ix| graph.inv_names[ix] as u32));
+    }
+    result
+}
+
+/// Allocates a buffer for `len` `u32`s, for passing the edge list to
+/// `tec_find_components`.
+#[no_mangle]
+pub extern "C" fn tec_alloc(len: usize) -> *mut u32 {
+    let mut buf = vec![0u32; len].into_boxed_slice();
+    let ptr = buf.as_mut_ptr();
+    std::mem::forget(buf);
+    ptr
+}
+
+/// Frees a buffer returned by `tec_alloc` or `tec_find_components`.
+///
+/// # Safety
+///
+/// `ptr` and `len` must be exactly as returned by one of those.
+#[no_mangle]
+pub unsafe extern "C" fn tec_free(ptr: *mut u32, len: usize) {
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)));
+}
+
+/// Runs `find_components_flat` on the `num_edges` edges in `edges`,
+/// and returns a buffer with the length of the result in the first
+/// element, followed by the result. The buffer must be freed with
+/// `tec_free`, passing the length plus one.
+///
+/// # Safety
+///
+/// `edges` must point to `2 * num_edges` initialized `u32`s.
+#[no_mangle]
+pub unsafe extern "C" fn tec_find_components(
+    edges: *const u32,
+    num_edges: usize,
+) -> *mut u32 {
+    let edges = std::slice::from_raw_parts(edges, 2 * num_edges);
+    let components = find_components_flat(edges);
+
+    let mut result = Vec::with_capacity(1 + components.len());
+    result.push(components.len() as u32);
+    result.extend(components);
+    let mut result = result.into_boxed_slice();
+    let ptr = result.as_mut_ptr();
+    std::mem::forget(result);
+    ptr
+}
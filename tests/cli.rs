@@ -0,0 +1,622 @@
+//! Tests the gfa_components command line tool by running the binary,
+//! for its exit codes and the files it writes
+
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Child, Command, Output, Stdio},
+};
+
+// a, b, c, and d are 3-edge-connected, and d, e, and f are a triangle
+const EDGES: &str = "a b\na c\na d\nb c\nb d\nc d\nd e\ne f\nf d\n";
+
+// a, b, and c are linked twice in each direction, and c, d, and e are
+// a triangle
+const GFA: &str = "S\ta\t*\nS\tb\t*\nS\tc\t*\nS\td\t*\nS\te\t*\n\
+                   L\ta\t+\tb\t+\t0M\nL\tb\t+\tc\t+\t0M\nL\tc\t+\ta\t+\t0M\n\
+                   L\ta\t+\tb\t-\t0M\nL\tb\t+\tc\t-\t0M\nL\tc\t+\ta\t-\t0M\n\
+                   L\tc\t+\td\t+\t5M\nL\td\t+\te\t+\t0M\nL\te\t+\tc\t+\t0M\n";
+
+/// An empty directory for the files of a test, removed on drop.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> TempDir {
+        let dir = std::env::temp_dir().join(format!(
+            "three-edge-connected-cli-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    /// The path of the file in the directory.
+    fn path(&self, name: &str) -> String {
+        self.0.join(name).to_str().unwrap().to_string()
+    }
+
+    /// Writes the file in the directory, returning its path.
+    fn file(&self, name: &str, contents: &[u8]) -> String {
+        let path = self.path(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_gfa_components"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// The stdout of a run that must have succeeded.
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "failed with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn read(path: &str) -> String {
+    fs::read_to_string(path).unwrap()
+}
+
+/// The exit code tells the kind of error: invalid arguments, I/O, a
+/// parse error, or an invalid graph
+#[test]
+fn exit_codes() {
+    let dir = TempDir::new("exit-codes");
+    let edges = dir.file("g.txt", EDGES.as_bytes());
+    assert_eq!(stdout(&run(&["-i", &edges])), "c\tb\ta\td\n");
+
+    let output = run(&["-i", &edges, "--min-size", "x"]);
+    assert_eq!(output.status.code(), Some(1));
+    let output = run(&["-i", &dir.path("missing.gfa")]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing.gfa"));
+
+    let bad = dir.file("bad.gfa", b"L\tx\n");
+    let output = run(&["-i", &bad, "--strict"]);
+    assert_eq!(output.status.code(), Some(3));
+
+    let bridge = dir.file("bridge.txt", b"a b\nb c\nc a\nc d\n");
+    let output = run(&["validate", "-i", &bridge]);
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(
+        stdout(&run(&["validate", "-i", &edges])),
+        "nodes\t6\nedges\t9\nconnected_components\t1\nbridges\t0\n"
+    );
+}
+
+/// Only errors are logged with `-q`, and `-v` and `-vv` add the info
+/// and debug messages
+#[test]
+fn logging() {
+    let dir = TempDir::new("logging");
+    let gfa = dir.file("t.gfa", format!("{}L\tx\n", GFA).as_bytes());
+
+    let stderr = |args: &[&str]| {
+        let output = run(args);
+        assert!(output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+    let default = stderr(&["-i", &gfa]);
+    assert!(default.contains("[warn]"));
+    assert!(!default.contains("[info]"));
+    assert_eq!(stderr(&["-i", &gfa, "-q"]), "");
+    let verbose = stderr(&["-i", &gfa, "-v"]);
+    assert!(verbose.contains("[info] Built graph with 5 nodes and 9 edges"));
+    assert!(!verbose.contains("[debug]"));
+    assert!(stderr(&["-i", &gfa, "-vv"]).contains("[debug]"));
+}
+
+/// `stats` reports the sizes and the degree distribution of the graph
+#[test]
+fn stats() {
+    let dir = TempDir::new("stats");
+    let edges = dir.file("g.txt", EDGES.as_bytes());
+    assert_eq!(
+        stdout(&run(&["stats", "-i", &edges])),
+        "nodes\t6\nedges\t9\nself_loops\t0\nparallel_edges\t0\n\
+         connected_components\t1\ndegree\t2\t2\ndegree\t3\t3\ndegree\t5\t1\n"
+    );
+}
+
+/// `completions` prints a completion script for the shell
+#[test]
+fn completions() {
+    assert!(stdout(&run(&["completions", "bash"])).contains("complete -F"));
+    let output = run(&["completions", "tcsh"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+/// `batch` writes the components of each input to a file named after
+/// it in the output directory
+#[test]
+fn batch() {
+    let dir = TempDir::new("batch");
+    let edges = dir.file("g.txt", EDGES.as_bytes());
+    let gfa = dir.file("t.gfa", GFA.as_bytes());
+    let out_dir = dir.path("out");
+    stdout(&run(&["batch", &edges, &gfa, "-d", &out_dir]));
+    assert_eq!(read(&dir.path("out/g.txt")), "c\tb\ta\td\n");
+    assert_eq!(read(&dir.path("out/t.txt")), "b\ta\tc\n");
+}
+
+/// Output files ending in `.gz` are compressed, and compressed input is
+/// decompressed, whatever its extension
+#[test]
+fn compression() {
+    let dir = TempDir::new("compression");
+    let edges = dir.file("g.txt", EDGES.as_bytes());
+    let out = dir.path("out.txt.gz");
+    stdout(&run(&["-i", &edges, "-o", &out]));
+    let decompressed =
+        Command::new("gzip").args(["-dc", &out]).output().unwrap();
+    assert_eq!(stdout(&decompressed), "c\tb\ta\td\n");
+
+    let compressed = Command::new("gzip").args(["-c", &edges]).output();
+    let input = dir.file("g.edges", &compressed.unwrap().stdout);
+    assert_eq!(stdout(&run(&["-i", &input])), "c\tb\ta\td\n");
+}
+
+/// `generate` writes the graph to the output file
+#[test]
+fn generate() {
+    let dir = TempDir::new("generate");
+    let out = dir.path("grid.txt");
+    let args = ["generate", "-o", &out, "--format", "edgelist", "grid"];
+    let output = run(&[&args[..], &["2", "3"]].concat());
+    assert_eq!(stdout(&output), "");
+    assert_eq!(read(&out), "0\t1\n0\t3\n1\t2\n1\t4\n2\t5\n3\t4\n4\t5\n");
+}
+
+/// `--orphans-out` lists the nodes without a component, and
+/// `--edges-out` the graph as it was built, which reads back as the
+/// same graph
+#[test]
+fn orphans_and_edges_out() {
+    let dir = TempDir::new("orphans");
+    let edges = dir.file("g.txt", EDGES.as_bytes());
+    let orphans = dir.path("orphans.txt");
+    let edges_out = dir.path("edges.txt");
+    let output = run(&[
+        "-i",
+        &edges,
+        "--orphans-out",
+        &orphans,
+        "--edges-out",
+        &edges_out,
+    ]);
+    assert_eq!(stdout(&output), "c\tb\ta\td\n");
+    let mut names: Vec<String> =
+        read(&orphans).lines().map(String::from).collect();
+    names.sort();
+    assert_eq!(names, ["e", "f"]);
+
+    let written = read(&edges_out);
+    assert_eq!(written.lines().count(), 9);
+    assert!(written.lines().any(|line| line == "d\te\t3\t4"));
+    assert_eq!(stdout(&run(&["-i", &edges_out])), "c\tb\ta\td\n");
+}
+
+/// `--dry-run` prints the size of the graph without finding the
+/// components
+#[test]
+fn dry_run() {
+    let dir = TempDir::new("dry-run");
+    let edges = dir.file("g.txt", EDGES.as_bytes());
+    let printed = stdout(&run(&["-i", &edges, "--dry-run"]));
+    assert!(printed.starts_with("nodes\t6\nedges\t9\ngraph_memory\t"));
+    assert!(!printed.contains("c\tb\ta\td"));
+}
+
+/// `--max-memory` fails before building the graph if even the compact
+/// state doesn't fit, and otherwise gives the same components
+#[test]
+fn max_memory() {
+    let dir = TempDir::new("max-memory");
+    let gfa = dir.file("t.gfa", GFA.as_bytes());
+    let out = dir.path("out.txt");
+    let output = run(&["-i", &gfa, "-o", &out, "--max-memory", "1"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(fs::metadata(&out).is_err());
+    stdout(&run(&["-i", &gfa, "-o", &out, "--max-memory", "1K"]));
+    assert_eq!(read(&out), "b\ta\tc\n");
+}
+
+/// `--boundary-links` writes the links of the input between different
+/// components as they were
+#[test]
+fn boundary_links() {
+    let dir = TempDir::new("boundary-links");
+    let gfa = dir.file("t.gfa", GFA.as_bytes());
+    let links = dir.path("links.gfa");
+    let output = run(&["-i", &gfa, "--boundary-links", &links]);
+    assert_eq!(stdout(&output), "b\ta\tc\n");
+    assert_eq!(
+        read(&links),
+        "L\tc\t+\td\t+\t5M\nL\td\t+\te\t+\t0M\nL\te\t+\tc\t+\t0M\n"
+    );
+}
+
+/// The DFS numbers of `--node-table` are those of the spanning forest
+/// of `--forest-out`, on the row of each node
+#[test]
+fn node_table() {
+    let dir = TempDir::new("node-table");
+    let edges = dir.file("g.txt", b"1 2\n1 3\n1 4\n2 3\n2 4\n3 4\n4 5\n");
+    let forest = dir.path("forest.txt");
+    let table = dir.path("nodes.tsv");
+    let output = run(&[
+        "-i",
+        &edges,
+        "--forest-out",
+        &forest,
+        "--node-table",
+        &table,
+    ]);
+    stdout(&output);
+    assert_eq!(read(&forest), "1\t2\n2\t3\n3\t4\n4\t5\n");
+    assert_eq!(
+        read(&table),
+        "node\tdegree\tcomponent\tpre\tlowpt\tdescendants\n\
+         1\t3\t0\t1\t1\t5\n\
+         2\t3\t0\t2\t1\t4\n\
+         3\t3\t0\t3\t1\t3\n\
+         4\t4\t0\t4\t1\t2\n\
+         5\t1\t\t5\t5\t1\n"
+    );
+
+    // on a larger graph, each child is numbered after its parent, and
+    // has fewer descendants
+    let edges = dir.file(
+        "grid.txt",
+        &run(&["generate", "--format", "edgelist", "grid", "6", "7"]).stdout,
+    );
+    let output = run(&[
+        "-i",
+        &edges,
+        "--forest-out",
+        &forest,
+        "--node-table",
+        &table,
+    ]);
+    stdout(&output);
+    let table = read(&table);
+    let numbers = |name: &str| -> (usize, usize) {
+        let row = table
+            .lines()
+            .find(|line| line.split('\t').next() == Some(name))
+            .unwrap();
+        let fields: Vec<&str> = row.split('\t').collect();
+        (fields[3].parse().unwrap(), fields[5].parse().unwrap())
+    };
+    let forest = read(&forest);
+    assert_eq!(forest.lines().count(), 41);
+    for line in forest.lines() {
+        let (parent, child) = line.split_once('\t').unwrap();
+        let (parent_pre, parent_descendants) = numbers(parent);
+        let (child_pre, child_descendants) = numbers(child);
+        assert!(parent_pre < child_pre);
+        assert!(child_pre < parent_pre + parent_descendants);
+        assert!(child_descendants < parent_descendants);
+    }
+}
+
+/// `serve` answers the queries of each line on the socket, and errors
+/// with an `error` object, also for values nested too deeply
+#[cfg(unix)]
+#[test]
+fn serve() {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixStream,
+        thread,
+        time::Duration,
+    };
+
+    let dir = TempDir::new("serve");
+    let edges = dir.file("g.txt", EDGES.as_bytes());
+    let socket = dir.path("serve.sock");
+    // kills the server when the test is done, also when it fails
+    struct Server(Child);
+    impl Drop for Server {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+    let _server = Server(
+        Command::new(env!("CARGO_BIN_EXE_gfa_components"))
+            .args(["serve", "-i", &edges, "--socket", &socket])
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+
+    let mut stream = None;
+    for _ in 0..100 {
+        if let Ok(connected) = UnixStream::connect(&socket) {
+            stream = Some(connected);
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    let stream = stream.expect("the server didn't listen on the socket");
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut query = |line: &str| {
+        (&stream)
+            .write_all(format!("{}\n", line).as_bytes())
+            .unwrap();
+        let mut response = String::new();
+        reader.read_line(&mut response).unwrap();
+        response
+    };
+
+    assert_eq!(
+        query(r#"{"query": "component", "segment": "a"}"#),
+        "{\"component\":2,\"nodes\":[\"c\",\"b\",\"a\",\"d\"]}\n"
+    );
+    assert_eq!(
+        query(r#"{"query": "same_component", "segments": ["a", "d"]}"#),
+        "{\"same_component\":true}\n"
+    );
+    assert_eq!(
+        query(r#"{"query": "same_component", "segments": ["a", "e"]}"#),
+        "{\"same_component\":false}\n"
+    );
+    assert_eq!(
+        query(r#"{"query": "components", "min_size": 1}"#),
+        "{\"components\":[[\"f\"],[\"e\"],[\"c\",\"b\",\"a\",\"d\"]]}\n"
+    );
+    assert_eq!(
+        query(r#"{"query": "component", "segment": "x"}"#),
+        "{\"error\":\"unknown segment x\"}\n"
+    );
+    assert_eq!(
+        query(r#"{"query": "component""#),
+        "{\"error\":\"expected ',' or '}' at byte 22\"}\n"
+    );
+
+    let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+    assert_eq!(
+        query(&deep),
+        "{\"error\":\"values nested deeper than 64 at byte 64\"}\n"
+    );
+    let nested = format!(
+        r#"{{"query": "components", "x": {}{}}}"#,
+        "[".repeat(63),
+        "]".repeat(63)
+    );
+    assert!(query(&nested).starts_with("{\"components\":"));
+}
+
+/// `--cache` keeps the output under the SHA-256 digest of its key, and
+/// only writes it again while the key kept next to it matches
+#[test]
+fn cache() {
+    let dir = TempDir::new("cache");
+    let edges = dir.file("g.txt", EDGES.as_bytes());
+    let cache = dir.path("cache");
+    assert_eq!(
+        stdout(&run(&["-i", &edges, "--cache", &cache])),
+        "c\tb\ta\td\n"
+    );
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(&cache)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+    assert_eq!(entries.len(), 2);
+    let (key, out) = (&entries[0], &entries[1]);
+    assert_eq!(key.extension().unwrap(), "key");
+    assert_eq!(out.file_stem(), key.file_stem());
+    assert_eq!(key.file_stem().unwrap().len(), 64);
+    let kept = fs::read_to_string(key).unwrap();
+    assert!(kept.lines().any(|line| line
+        == "input\t36\t\
+            2e4ba236ad493857688cd37207f38825a5978c5f6f1f6c8342f534c071530202"));
+
+    // a hit writes the kept output
+    fs::write(out, "kept\n").unwrap();
+    assert_eq!(stdout(&run(&["-i", &edges, "--cache", &cache])), "kept\n");
+
+    // and an entry with some other key is replaced
+    fs::write(key, kept.replace("input\t36", "input\t37")).unwrap();
+    assert_eq!(
+        stdout(&run(&["-i", &edges, "--cache", &cache])),
+        "c\tb\ta\td\n"
+    );
+    assert_eq!(fs::read_to_string(key).unwrap(), kept);
+    assert_eq!(read(out.to_str().unwrap()), "c\tb\ta\td\n");
+
+    let output = run(&["-i", &edges, "--cache", &cache, "--min-size", "5"]);
+    assert_eq!(stdout(&output), "");
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 4);
+}
+
+/// `--watch` runs again when the input changes, and logs how the
+/// components that were written changed
+#[test]
+fn watch() {
+    use std::{
+        io::{BufRead, BufReader},
+        sync::mpsc,
+        thread,
+        time::Duration,
+    };
+
+    let dir = TempDir::new("watch");
+    let edges = dir.file("g.txt", EDGES.as_bytes());
+    let out = dir.path("out.txt");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_gfa_components"))
+        .args(["-i", &edges, "-o", &out, "--watch", "--min-size", "1", "-v"])
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stderr = BufReader::new(child.stderr.take().unwrap());
+    // kills the watching process when the test is done, also when it
+    // fails
+    struct Watching(Child);
+    impl Drop for Watching {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+    let _child = Watching(child);
+
+    let (send, lines) = mpsc::channel();
+    thread::spawn(move || {
+        for line in stderr.lines() {
+            if send.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    let wait_for = |text: &str| loop {
+        let line = lines.recv_timeout(Duration::from_secs(30)).unwrap();
+        if line.contains(text) {
+            return line;
+        }
+    };
+
+    wait_for(&format!("Watching {:?} for changes", edges));
+    assert_eq!(read(&out), "f\ne\nc\tb\ta\td\n");
+    fs::write(&edges, format!("{}e a\nf b\n", EDGES)).unwrap();
+    let summary = wait_for("Components since the last run");
+    assert!(summary.ends_with("Components since the last run: 1 merged"));
+    assert_eq!(read(&out), "e\td\tc\tb\ta\tf\n");
+}
+
+/// Whether the `duckdb` program is installed, which the Parquet tests
+/// are skipped without.
+fn has_duckdb() -> bool {
+    let found = Command::new("duckdb").arg("-version").output().is_ok();
+    if !found {
+        eprintln!("duckdb isn't installed, skipping the test");
+    }
+    found
+}
+
+// the 3-edge-connected K4, with names that must be quoted
+const QUOTED_CSV: &str = "\"a\tb\",\"\"\"q\"\"\"\n\"a\tb\",c\n\"a\tb\",d\n\
+                          \"\"\"q\"\"\",c\n\"\"\"q\"\"\",d\nc,d\n";
+
+/// `--output-format parquet` needs the `parquet` feature, and writes
+/// the names as they were, tabs and quotes too
+#[test]
+fn parquet_output() {
+    let dir = TempDir::new("parquet-output");
+    let edges = dir.file("g.csv", QUOTED_CSV.as_bytes());
+    let out = dir.path("out.parquet");
+    let args = ["-i", &edges, "--format", "csv", "-o", &out];
+    let output = run(&[&args[..], &["--output-format", "parquet"]].concat());
+    if !cfg!(feature = "parquet") {
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("needs the parquet feature"));
+        return;
+    }
+    if !has_duckdb() {
+        return;
+    }
+    stdout(&output);
+    let query = format!(
+        "SELECT string_agg(node, '|' ORDER BY node), min(component), \
+         max(component) FROM read_parquet('{}')",
+        out
+    );
+    let read = Command::new("duckdb")
+        .args(["-noheader", "-list", "-separator", ",", "-c", &query])
+        .output()
+        .unwrap();
+    assert_eq!(stdout(&read), "\"q\"|a\tb|c|d,0,0\n");
+}
+
+/// Parquet input needs the `parquet` feature, and reads the `from` and
+/// `to` columns as the names, tabs and quotes too
+#[test]
+fn parquet_input() {
+    let dir = TempDir::new("parquet-input");
+    if !cfg!(feature = "parquet") {
+        let input = dir.file("g.bin", b"PAR1\0\0\0\0");
+        let output = run(&["-i", &input]);
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("needs the parquet feature"));
+        return;
+    }
+    if !has_duckdb() {
+        return;
+    }
+    let edges = dir.file("g.csv", QUOTED_CSV.as_bytes());
+    let input = dir.path("g.parquet");
+    let query = format!(
+        "COPY (SELECT * FROM read_csv('{}', header = false, delim = ',', \
+         quote = '\"', escape = '\"', \
+         columns = {{'from': 'VARCHAR', 'to': 'VARCHAR'}})) \
+         TO '{}' (FORMAT parquet)",
+        edges, input
+    );
+    stdout(
+        &Command::new("duckdb")
+            .args(["-c", &query])
+            .output()
+            .unwrap(),
+    );
+    let text = stdout(&run(&["-i", &input, "--output-format", "tsv"]));
+    let mut rows: Vec<&str> = text.lines().collect();
+    rows.sort();
+    assert_eq!(
+        rows,
+        ["0\t\"q\"", "0\ta\tb", "0\tc", "0\td", "component\tnode"]
+    );
+
+    // integer columns are read as their digits
+    let query = format!(
+        "COPY (SELECT i AS \"from\", (i + 1) % 4 AS \"to\" FROM range(4) \
+         t(i)) TO '{}' (FORMAT parquet)",
+        input
+    );
+    stdout(
+        &Command::new("duckdb")
+            .args(["-c", &query])
+            .output()
+            .unwrap(),
+    );
+    let output = run(&["-i", &input, "--format", "parquet", "--min-size", "1"]);
+    assert_eq!(stdout(&output).lines().count(), 4);
+}
+
+/// `--output-format gfa` tags each segment with its component, and the
+/// output reads back with the same components
+#[test]
+fn gfa_output() {
+    let dir = TempDir::new("gfa-output");
+    let gfa = dir.file("t.gfa", GFA.as_bytes());
+    let out = dir.path("out.gfa");
+    let args = ["-i", &gfa, "-o", &out, "--output-format", "gfa"];
+    stdout(&run(&[&args[..], &["--stable-ids"]].concat()));
+    let written = read(&out);
+    assert!(written.starts_with(
+        "H\tVN:Z:1.0\nS\ta\t*\tcc:Z:3ecc_a\nS\tb\t*\tcc:Z:3ecc_a\n\
+         S\tc\t*\tcc:Z:3ecc_a\nS\td\t*\nS\te\t*\n"
+    ));
+    assert_eq!(written.lines().filter(|l| l.starts_with("L\t")).count(), 9);
+    assert_eq!(stdout(&run(&["-i", &out])), "b\ta\tc\n");
+}
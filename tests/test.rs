@@ -1,71 +1,16 @@
-use three_edge_connected::{algorithm, Graph};
+//! Tests the correctness of the algorithm by running it against
+//! graphs for which the 3EC components are known
 
-/// Tests the correctness of the algorithm by running it against
-/// graphs for which the 3EC components are known
-
-fn k_graph_edges(offset: usize, n: usize) -> Vec<(usize, usize)> {
-    let mut edges = Vec::new();
-
-    let start = offset;
-    let end = offset + n;
-
-    for i in start..end {
-        for j in i..end {
-            if i != j {
-                edges.push((i, j));
-            }
-        }
-    }
-
-    edges
-}
-
-fn bridged_k_graphs(k_a: usize, k_b: usize, bridges: usize) -> Graph<usize> {
-    let a_edges = k_graph_edges(0, k_a);
-    let last_a = a_edges.last().unwrap().1;
-
-    let first_b = last_a + 1;
-
-    let mut b_edges = k_graph_edges(first_b, k_b);
-
-    let mut edges = a_edges;
-    edges.append(&mut b_edges);
-
-    for _ in 0..bridges {
-        edges.push((last_a, first_b));
-    }
-
-    Graph::from_edges(edges.into_iter())
-}
-
-fn complete_graph(n: usize) -> Graph<usize> {
-    let mut edges = Vec::new();
-
-    for i in 0..n {
-        for j in i..n {
-            if i != j {
-                edges.push((i, j));
-            }
-        }
-    }
-
-    Graph::from_edges(edges.into_iter())
-}
-
-fn bipartite_graph(k: usize, l: usize) -> Graph<usize> {
-    let mut edges = Vec::new();
-
-    for a in 0..k {
-        for b in 0..l {
-            let pa = 2 * a;
-            let pb = 2 * b + 1;
-
-            edges.push((pa, pb));
-        }
-    }
-
-    Graph::from_edges(edges.into_iter())
-}
+use three_edge_connected::{
+    algorithm, connectivity,
+    generators::{
+        self, bipartite_graph, bridged_k_edges, bridged_k_graphs,
+        complete_edges, complete_graph,
+    },
+    graph,
+    output::{self, ComponentIds},
+    Graph,
+};
 
 /// The complete graph with 3 vertices is not 3EC-connected
 #[test]
@@ -157,3 +102,3304 @@ fn two_k_4_parallel() {
     assert_eq!(comps.len(), 1);
     assert_eq!(comps[0].len(), 8);
 }
+
+#[test]
+fn min_size_filter() {
+    let graph = bridged_k_graphs(4, 5, 1);
+    let comps = algorithm::find_components(&graph.graph);
+    assert_eq!(comps.len(), 2);
+
+    let comps = algorithm::filter_min_size(comps, 5);
+    assert_eq!(comps.len(), 1);
+    assert_eq!(comps[0].len(), 5);
+}
+
+#[test]
+fn edge_list_input() {
+    let input = b"# two triangles sharing an edge\n\
+                  a b\n\
+                  b c\n\
+                  c a\n\
+                  \n\
+                  b\td\n\
+                  d c  ignored\n\
+                  lonely\n";
+
+    let graph = Graph::from_edge_list_reader(&mut &input[..]).unwrap();
+    assert_eq!(graph.graph.len(), 4);
+    assert_eq!(graph.inv_names, vec![b"a", b"b", b"c", b"d"]);
+    assert_eq!(graph.graph.values().map(|a| a.len()).sum::<usize>(), 10);
+
+    // b and c are connected by three edge-disjoint paths
+    let comps =
+        graph.invert_components(algorithm::find_components(&graph.graph));
+    assert_eq!(comps.len(), 1);
+    let mut comp = comps[0].clone();
+    comp.sort();
+    assert_eq!(comp, vec![b"b".to_vec(), b"c".to_vec()]);
+}
+
+#[test]
+fn output_formats() {
+    let names: Vec<Vec<u8>> =
+        vec![b"a".to_vec(), b"b\"".to_vec(), b"c".to_vec()];
+    let components = vec![vec![0, 1], vec![2]];
+
+    let mut out = Vec::new();
+    output::write_text(&mut out, &names, &components, ComponentIds::Index)
+        .unwrap();
+    assert_eq!(out, b"a\tb\"\nc\n");
+
+    let mut out = Vec::new();
+    output::write_tsv(&mut out, &names, &components, ComponentIds::Index)
+        .unwrap();
+    assert_eq!(out, b"component\tnode\n0\ta\n0\tb\"\n1\tc\n");
+
+    let mut out = Vec::new();
+    output::write_json(&mut out, &names, &components, ComponentIds::Index)
+        .unwrap();
+    assert_eq!(out, b"[\n  [\"a\", \"b\\\"\"],\n  [\"c\"]\n]\n");
+
+    let graph = Graph::from_edges(vec![(0, 1), (1, 1), (1, 2)].into_iter());
+    let mut out = Vec::new();
+    output::write_dot(
+        &mut out,
+        &graph.graph,
+        &names,
+        &components,
+        ComponentIds::Index,
+    )
+    .unwrap();
+    let dot = String::from_utf8(out).unwrap();
+    assert!(dot.starts_with("graph {\n  subgraph cluster_0 {\n"));
+    assert_eq!(dot.matches(" -- ").count(), 3);
+    assert!(dot.contains("\"b\\\"\" -- \"b\\\"\";"));
+}
+
+/// The stable ids are named after the smallest node name, shortest
+/// first, and don't depend on the other components that are written
+#[test]
+fn stable_component_ids() {
+    let ids = ComponentIds::Stable;
+    let names: Vec<Vec<u8>> =
+        vec![b"10".to_vec(), b"9".to_vec(), b"3".to_vec(), b"12".to_vec()];
+    let components = vec![vec![0, 1, 3], vec![2]];
+    assert_eq!(output::component_id(&names, &components[0]), b"3ecc_9");
+
+    let mut out = Vec::new();
+    output::write_text(&mut out, &names, &components, ids).unwrap();
+    assert_eq!(out, b"3ecc_9\t10\t9\t12\n3ecc_3\t3\n");
+
+    let mut out = Vec::new();
+    output::write_tsv(&mut out, &names, &components[1..], ids).unwrap();
+    assert_eq!(out, b"component\tnode\n3ecc_3\t3\n");
+
+    let mut out = Vec::new();
+    output::write_json(&mut out, &names, &components[1..], ids).unwrap();
+    assert_eq!(out, b"[\n  {\"id\": \"3ecc_3\", \"nodes\": [\"3\"]}\n]\n");
+
+    let mut out = Vec::new();
+    output::write_odgi(&mut out, &names, &components, ids).unwrap();
+    let odgi = String::from_utf8(out).unwrap();
+    assert!(odgi.contains("\n3\t3ecc_3\t1\n9\t3ecc_9\t3\n"));
+
+    let graph = Graph::from_edges(vec![(0, 1), (1, 3), (3, 0)].into_iter());
+    let mut out = Vec::new();
+    output::write_dot(&mut out, &graph.graph, &names, &components, ids)
+        .unwrap();
+    let dot = String::from_utf8(out).unwrap();
+    assert!(dot.starts_with("graph {\n  subgraph \"cluster_3ecc_9\" {\n"));
+}
+
+/// The Bandage CSV gives each component a colour, and quotes names
+#[test]
+fn bandage_csv() {
+    let names: Vec<Vec<u8>> =
+        vec![b"a".to_vec(), b"b,c".to_vec(), b"d".to_vec(), b"e".to_vec()];
+    let components = vec![vec![0, 1], vec![2]];
+
+    let mut out = Vec::new();
+    output::write_bandage_csv(
+        &mut out,
+        &names,
+        &components,
+        ComponentIds::Stable,
+    )
+    .unwrap();
+    let csv = String::from_utf8(out).unwrap();
+    let rows: Vec<Vec<&str>> = csv
+        .lines()
+        .map(|line| line.rsplitn(3, ',').collect())
+        .collect();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[0], vec!["Component", "Colour", "Name"]);
+    assert_eq!((rows[1][0], rows[1][2]), ("3ecc_a", "a"));
+    assert_eq!((rows[2][0], rows[2][2]), ("3ecc_a", "\"b,c\""));
+    assert_eq!((rows[3][0], rows[3][2]), ("3ecc_d", "d"));
+    assert_eq!(rows[1][1], rows[2][1]);
+    assert_ne!(rows[1][1], rows[3][1]);
+    assert!(rows[1..].iter().all(|row| row[1].len() == 7
+        && row[1].starts_with('#')
+        && u32::from_str_radix(&row[1][1..], 16).is_ok()));
+}
+
+/// The GFA output tags each segment with its component, and reads back
+/// as the same graph
+#[test]
+fn gfa_output() {
+    let names: Vec<Vec<u8>> = vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()];
+    let graph = Graph::from_edges(vec![(0, 1), (1, 1), (1, 2)].into_iter());
+    let components = vec![vec![0, 1]];
+
+    let mut out = Vec::new();
+    output::write_gfa(
+        &mut out,
+        &graph.graph,
+        &names,
+        &components,
+        ComponentIds::Index,
+    )
+    .unwrap();
+    assert_eq!(
+        out,
+        b"H\tVN:Z:1.0\nS\ta\t*\tcc:i:0\nS\tb\t*\tcc:i:0\nS\tc\t*\n\
+          L\ta\t+\tb\t+\t*\nL\tb\t+\tb\t+\t*\nL\tb\t+\tc\t+\t*\n"
+    );
+
+    let mut out = Vec::new();
+    output::write_gfa(
+        &mut out,
+        &graph.graph,
+        &names,
+        &components,
+        ComponentIds::Stable,
+    )
+    .unwrap();
+    assert!(out.starts_with(b"H\tVN:Z:1.0\nS\ta\t*\tcc:Z:3ecc_a\n"));
+
+    let graph = bridged_k_graphs(4, 4, 3);
+    let names: Vec<Vec<u8>> = graph
+        .inv_names
+        .iter()
+        .map(|n| n.to_string().into_bytes())
+        .collect();
+    let components = algorithm::find_components(&graph.graph);
+    let mut out = Vec::new();
+    output::write_gfa(
+        &mut out,
+        &graph.graph,
+        &names,
+        &components,
+        ComponentIds::Index,
+    )
+    .unwrap();
+    let read = Graph::from_gfa_reader(&mut out.as_slice()).unwrap();
+    assert_eq!(read.inv_names, names);
+    for (node, neighbors) in graph.graph.iter() {
+        let mut read_neighbors = read.graph[node].clone();
+        read_neighbors.sort_unstable();
+        let mut neighbors = neighbors.clone();
+        neighbors.sort_unstable();
+        assert_eq!(read_neighbors, neighbors);
+    }
+    assert_eq!(read.graph.len(), graph.graph.len());
+}
+
+/// The GEXF output has the component of each node as an attribute
+#[test]
+fn gexf_output() {
+    let names: Vec<Vec<u8>> =
+        vec![b"a".to_vec(), b"b&".to_vec(), b"c".to_vec()];
+    let graph = Graph::from_edges(vec![(0, 1), (1, 1), (1, 2)].into_iter());
+    let components = vec![vec![0, 1]];
+
+    let mut out = Vec::new();
+    output::write_gexf(
+        &mut out,
+        &graph.graph,
+        &names,
+        &components,
+        ComponentIds::Index,
+    )
+    .unwrap();
+    let gexf = String::from_utf8(out).unwrap();
+    assert!(gexf.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(gexf.contains("title=\"component\" type=\"integer\"/>"));
+    assert!(gexf.contains(
+        "<node id=\"1\" label=\"b&amp;\">\n        \
+         <attvalues><attvalue for=\"0\" value=\"0\"/></attvalues>\n"
+    ));
+    assert!(gexf.contains("<node id=\"2\" label=\"c\"/>\n"));
+    assert!(gexf.contains(
+        "<edge id=\"0\" source=\"0\" target=\"1\"/>\n      \
+         <edge id=\"1\" source=\"1\" target=\"1\"/>\n      \
+         <edge id=\"2\" source=\"1\" target=\"2\"/>\n    </edges>"
+    ));
+    assert!(gexf.ends_with("</graph>\n</gexf>\n"));
+}
+
+/// The GraphML output keeps the names as node ids, with the components
+#[test]
+fn graphml_output() {
+    let names: Vec<Vec<u8>> =
+        vec![b"a".to_vec(), b"b<".to_vec(), b"c".to_vec()];
+    let graph = Graph::from_edges(vec![(0, 1), (1, 1), (1, 2)].into_iter());
+    let components = vec![vec![0, 1]];
+
+    let mut out = Vec::new();
+    output::write_graphml(
+        &mut out,
+        &graph.graph,
+        &names,
+        &components,
+        ComponentIds::Stable,
+    )
+    .unwrap();
+    let graphml = String::from_utf8(out).unwrap();
+    assert!(graphml.contains("attr.name=\"component\" attr.type=\"string\"/>"));
+    assert!(graphml.contains(
+        "    <node id=\"a\"><data key=\"component\">3ecc_a</data></node>\n\
+         \x20   <node id=\"b&lt;\"><data key=\"component\">3ecc_a</data></node>\n\
+         \x20   <node id=\"c\"/>\n\
+         \x20   <edge source=\"a\" target=\"b&lt;\"/>\n\
+         \x20   <edge source=\"b&lt;\" target=\"b&lt;\"/>\n\
+         \x20   <edge source=\"b&lt;\" target=\"c\"/>\n\
+         \x20 </graph>\n</graphml>\n"
+    ));
+}
+
+/// The GML outputs have the graph with its components, and the graph
+/// of the components
+#[test]
+fn gml_output() {
+    let names: Vec<Vec<u8>> =
+        vec![b"a".to_vec(), b"b\"".to_vec(), b"c".to_vec(), b"d".to_vec()];
+    let graph = Graph::from_edges(
+        vec![(0, 1), (1, 1), (1, 2), (1, 2), (2, 0), (2, 3)].into_iter(),
+    );
+    let components = vec![vec![0, 1], vec![2], vec![3]];
+
+    let mut out = Vec::new();
+    output::write_gml(
+        &mut out,
+        &graph.graph,
+        &names,
+        &components[..2],
+        ComponentIds::Index,
+    )
+    .unwrap();
+    let gml = String::from_utf8(out).unwrap();
+    assert!(gml.starts_with("graph [\n  directed 0\n  multigraph 1\n"));
+    assert!(gml.contains(
+        "  node [\n    id 1\n    label \"b&quot;\"\n    component 0\n  ]\n"
+    ));
+    assert!(gml.contains("  node [\n    id 3\n    label \"d\"\n  ]\n"));
+    assert_eq!(gml.matches("  edge [").count(), 6);
+    assert!(gml.contains("  edge [\n    source 1\n    target 1\n  ]\n"));
+
+    let mut out = Vec::new();
+    output::write_condensed_gml(
+        &mut out,
+        &graph.graph,
+        &names,
+        &components,
+        ComponentIds::Stable,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "graph [\n  directed 0\n\
+         \x20 node [\n    id 0\n    label \"3ecc_a\"\n    size 2\n  ]\n\
+         \x20 node [\n    id 1\n    label \"3ecc_c\"\n    size 1\n  ]\n\
+         \x20 node [\n    id 2\n    label \"3ecc_d\"\n    size 1\n  ]\n\
+         \x20 edge [\n    source 0\n    target 1\n    weight 3\n  ]\n\
+         \x20 edge [\n    source 1\n    target 2\n    weight 1\n  ]\n\
+         ]\n"
+    );
+}
+
+/// The SQL output creates and fills the nodes and components tables
+#[test]
+fn sql_output() {
+    let names: Vec<Vec<u8>> =
+        vec![b"a".to_vec(), b"b's".to_vec(), b"c".to_vec()];
+    let components = vec![vec![0, 1], vec![2]];
+
+    let mut out = Vec::new();
+    output::write_sql(&mut out, &names, &components, ComponentIds::Index)
+        .unwrap();
+    let sql = String::from_utf8(out).unwrap();
+    assert!(sql.starts_with("BEGIN;\nDROP TABLE IF EXISTS nodes;\n"));
+    assert!(sql.contains(
+        "CREATE TABLE components (id INTEGER PRIMARY KEY, size INTEGER NOT \
+         NULL);\n"
+    ));
+    assert!(sql.contains("INSERT INTO components VALUES\n(0, 2),\n(1, 1);\n"));
+    assert!(sql.contains(
+        "INSERT INTO nodes VALUES\n('a', 0),\n('b''s', 0),\n('c', 1);\n"
+    ));
+    assert!(sql.ends_with("COMMIT;\n"));
+
+    let mut out = Vec::new();
+    output::write_sql(&mut out, &names, &components, ComponentIds::Stable)
+        .unwrap();
+    let sql = String::from_utf8(out).unwrap();
+    assert!(sql.contains("(name TEXT PRIMARY KEY, component TEXT NOT NULL"));
+    assert!(sql.contains("('b''s', '3ecc_a')"));
+
+    let mut out = Vec::new();
+    output::write_sql(&mut out, &names, &[], ComponentIds::Index).unwrap();
+    assert!(!String::from_utf8(out).unwrap().contains("INSERT"));
+}
+
+/// The quoted TSV output quotes the fields that CSV readers would
+/// split or unquote, and no others
+#[test]
+fn quoted_tsv_output() {
+    let names: Vec<Vec<u8>> = vec![
+        b"a".to_vec(),
+        b"b\tc".to_vec(),
+        b"\"d\"\ne".to_vec(),
+        b"".to_vec(),
+    ];
+    let components = vec![vec![0, 1, 2], vec![3]];
+
+    let mut out = Vec::new();
+    output::write_quoted_tsv(
+        &mut out,
+        &names,
+        &components,
+        ComponentIds::Index,
+    )
+    .unwrap();
+    assert_eq!(
+        out,
+        b"component\tnode\n0\ta\n0\t\"b\tc\"\n0\t\"\"\"d\"\"\ne\"\n1\t\"\"\n"
+    );
+
+    let mut out = Vec::new();
+    output::write_quoted_tsv(
+        &mut out,
+        &names,
+        &[vec![1, 2]],
+        ComponentIds::Stable,
+    )
+    .unwrap();
+    assert_eq!(
+        out,
+        b"component\tnode\n\"3ecc_b\tc\"\t\"b\tc\"\n\
+          \"3ecc_b\tc\"\t\"\"\"d\"\"\ne\"\n"
+    );
+}
+
+#[test]
+fn bridges_and_connected_components() {
+    let graph = bridged_k_graphs(4, 4, 1);
+    assert_eq!(connectivity::connected_components(&graph.graph).len(), 1);
+    assert_eq!(connectivity::bridges(&graph.graph).len(), 1);
+
+    // parallel edges are not bridges
+    let graph = bridged_k_graphs(4, 4, 2);
+    assert!(connectivity::bridges(&graph.graph).is_empty());
+
+    let mut edges = complete_edges(0, 3);
+    edges.extend(complete_edges(3, 2));
+    let graph = Graph::from_edges(edges.into_iter());
+    let mut comps = connectivity::connected_components(&graph.graph);
+    comps.iter_mut().for_each(|c| c.sort());
+    assert_eq!(comps, vec![vec![0, 1, 2], vec![3, 4]]);
+    assert_eq!(connectivity::bridges(&graph.graph), vec![(3, 4)]);
+
+    // two K_4s and the bridge between them are three blocks, while
+    // parallel edges are kept in the same block
+    let graph = bridged_k_graphs(4, 4, 1);
+    let mut sizes: Vec<usize> =
+        connectivity::biconnected_components(&graph.graph)
+            .iter()
+            .map(|block| block.len())
+            .collect();
+    sizes.sort_unstable();
+    assert_eq!(sizes, vec![1, 6, 6]);
+
+    let graph = bridged_k_graphs(4, 4, 2);
+    let blocks = connectivity::biconnected_components(&graph.graph);
+    assert_eq!(blocks.len(), 3);
+    assert!(blocks.iter().any(|block| block.len() == 2));
+}
+
+#[test]
+fn induced_subgraph() {
+    let graph = bridged_k_graphs(4, 4, 3);
+    let nodes: Vec<usize> = vec![0, 1, 2, 3, 42];
+    let subgraph = graph.induced_subgraph(nodes.iter());
+
+    assert_eq!(subgraph.inv_names, vec![0, 1, 2, 3]);
+    assert_eq!(subgraph.graph.len(), 4);
+    assert!(subgraph.graph.values().all(|adj| adj.len() == 3));
+
+    let comps = algorithm::find_components(&subgraph.graph);
+    assert_eq!(comps.len(), 1);
+    assert_eq!(comps[0].len(), 4);
+}
+
+#[test]
+fn separate_gfa_parsing() {
+    let gfa = b"H\tVN:Z:1.0\n\
+                S\t1\tA\n\
+                S\t2\tA\n\
+                L\t1\t+\t2\t+\t0M\n\
+                L\t2\t-\t3\t+\t0M\n";
+
+    let links = graph::gfa_links(&gfa[..])
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        links,
+        vec![
+            (b"1".to_vec(), b"2".to_vec()),
+            (b"2".to_vec(), b"3".to_vec())
+        ]
+    );
+
+    let from_links = Graph::from_named_edges(links);
+    let from_reader = Graph::from_gfa_reader(&mut &gfa[..]).unwrap();
+    assert_eq!(from_links.inv_names, from_reader.inv_names);
+    assert_eq!(from_links.graph, from_reader.graph);
+}
+
+#[test]
+fn generators() {
+    assert_eq!(
+        generators::erdos_renyi_edges(50, 0.1, 3),
+        generators::erdos_renyi_edges(50, 0.1, 3)
+    );
+    assert_ne!(
+        generators::erdos_renyi_edges(50, 0.1, 3),
+        generators::erdos_renyi_edges(50, 0.1, 4)
+    );
+
+    // the corners of a grid have degree 2, every other border node
+    // has degree 3, and the whole interior is 3-edge-connected
+    let graph = generators::grid_graph(4, 4);
+    assert_eq!(graph.graph.len(), 16);
+    let comps = algorithm::find_components(&graph.graph);
+    assert_eq!(comps.iter().filter(|c| c.len() == 1).count(), 4);
+    assert_eq!(comps.iter().filter(|c| c.len() == 12).count(), 1);
+}
+
+/// Sorts the nodes in each component, and the components by their
+/// first node, so that partitions can be compared.
+fn normalize<N: Ord>(mut components: Vec<Vec<N>>) -> Vec<Vec<N>> {
+    components.iter_mut().for_each(|c| c.sort_unstable());
+    components.sort_unstable();
+    components
+}
+
+#[test]
+fn planted_partitions() {
+    for seed in 0..200 {
+        let planted = generators::PlantedPartition::random(seed, 12, 7);
+        let graph = planted.graph();
+        assert!(connectivity::bridges(&graph.graph).is_empty());
+
+        let comps = algorithm::find_components(&graph.graph);
+        let comps = normalize(graph.invert_components(comps));
+        assert_eq!(comps, planted.components, "seed {}", seed);
+    }
+}
+
+/// Compares the algorithm against the brute-force definition of
+/// 3-edge-connectivity on small random multigraphs, including
+/// self-loops and parallel edges, parsed from GFA
+#[test]
+fn brute_force_agreement() {
+    let mut rng = generators::SplitMix64(588);
+
+    for _ in 0..2000 {
+        let len = 1 + 2 * rng.next_below(generators::FUZZ_MAX_EDGES + 1);
+        let data: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+        let gfa = generators::gfa_from_bytes(&data);
+
+        let graph =
+            Graph::from_gfa_reader(&mut std::io::Cursor::new(&gfa)).unwrap();
+        let expected =
+            three_edge_connected::brute_force::find_components(&graph.graph);
+        let comps = normalize(algorithm::find_components(&graph.graph));
+
+        assert_eq!(comps, expected, "{}", String::from_utf8_lossy(&gfa));
+    }
+}
+
+/// The flat edge list interface used by the WebAssembly bindings
+#[cfg(feature = "wasm")]
+#[test]
+fn wasm_flat_components() {
+    use three_edge_connected::wasm;
+
+    // two K_4s with node IDs starting from 10, joined by two edges
+    let mut edges = Vec::new();
+    for (a, b) in generators::bridged_k_edges(4, 4, 2) {
+        edges.push(10 + a as u32);
+        edges.push(10 + b as u32);
+    }
+
+    let flat = wasm::find_components_flat(&edges);
+    assert_eq!(flat[0], 2);
+
+    let mut components = Vec::new();
+    let mut rest = &flat[1..];
+    while let Some((&len, tail)) = rest.split_first() {
+        components.push(tail[..len as usize].to_vec());
+        rest = &tail[len as usize..];
+    }
+    let expected = vec![vec![10, 11, 12, 13], vec![14, 15, 16, 17]];
+    assert_eq!(normalize(components), expected);
+
+    unsafe {
+        let ptr = wasm::tec_find_components(edges.as_ptr(), edges.len() / 2);
+        let len = *ptr as usize;
+        assert_eq!(std::slice::from_raw_parts(ptr.add(1), len), &flat[..]);
+        wasm::tec_free(ptr, len + 1);
+    }
+}
+
+/// The algorithm runs on any `Adjacency`, including the plain
+/// `Vec<Vec<usize>>` that's available without `std`
+#[test]
+fn vec_adjacency() {
+    let graph = bridged_k_graphs(4, 5, 2);
+    let mut adjacency = vec![Vec::new(); graph.graph.len()];
+    for (&node, adj) in graph.graph.iter() {
+        adjacency[node] = adj.to_vec();
+    }
+
+    let expected = normalize(algorithm::find_components(&graph.graph));
+    let comps = normalize(algorithm::find_components(&adjacency));
+    assert_eq!(comps, expected);
+    assert_eq!(comps.len(), 2);
+    assert_eq!(
+        normalize(algorithm::find_components(&adjacency[..])),
+        expected
+    );
+}
+
+/// The parallel version splits the graph into blocks, so it's
+/// checked on graphs with many cut vertices, as well as against the
+/// brute force on small multigraphs
+#[test]
+fn parallel_components() {
+    use three_edge_connected::parallel;
+
+    for seed in 0..50 {
+        let partition = generators::PlantedPartition::random(seed, 20, 7);
+        let graph = partition.graph();
+        let expected = normalize(algorithm::find_components(&graph.graph));
+        assert_eq!(parallel::find_components(&graph.graph, 4), expected);
+
+        // joining the cliques at single nodes gives cut vertices with
+        // 3-edge-connected components on both sides
+        let mut edges = Vec::new();
+        for i in 0..(seed as usize % 5 + 2) {
+            edges.extend(complete_edges(3 * i, 4));
+        }
+        let graph = Graph::from_edges(edges.into_iter());
+        let comps = parallel::find_components(&graph.graph, 3);
+        assert_eq!(comps, normalize(algorithm::find_components(&graph.graph)));
+        assert_eq!(comps.len(), 1);
+    }
+
+    let mut rng = generators::SplitMix64(594);
+    for _ in 0..500 {
+        let len = 1 + 2 * rng.next_below(generators::FUZZ_MAX_EDGES + 1);
+        let data: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+        let graph =
+            Graph::from_edges(generators::edges_from_bytes(&data).into_iter());
+        let expected =
+            three_edge_connected::brute_force::find_components(&graph.graph);
+        assert_eq!(parallel::find_components(&graph.graph, 2), expected);
+    }
+}
+
+/// The concurrent union-find finds the same connected components as
+/// the DFS, and the parallel version runs the small ones whole and
+/// splits the large ones into blocks, with the same result
+#[test]
+fn parallel_connected_parts() {
+    use three_edge_connected::parallel;
+
+    for seed in 0..20 {
+        // one large part with cut vertices, and many small ones
+        let mut edges = Vec::new();
+        let chain = seed as usize % 5 + 4;
+        for i in 0..chain {
+            edges.extend(complete_edges(3 * i, 4));
+        }
+        let mut next = 3 * chain + 1;
+        let mut rng = generators::SplitMix64(seed);
+        for _ in 0..rng.next_below(10) + 5 {
+            let size = rng.next_below(4) + 1;
+            edges.extend(complete_edges(next, size));
+            if size == 1 {
+                edges.push((next, next));
+            }
+            next += size;
+        }
+        let graph = Graph::from_edges(edges.into_iter());
+
+        for threads in [1, 3, 8] {
+            let parts = parallel::connected_parts(&graph.graph, threads);
+            assert!(parts.windows(2).all(|w| w[0].len() >= w[1].len()));
+            assert_eq!(
+                normalize(parts),
+                normalize(connectivity::connected_components(&graph.graph))
+            );
+            assert_eq!(
+                parallel::find_components(&graph.graph, threads),
+                normalize(algorithm::find_components(&graph.graph))
+            );
+        }
+    }
+}
+
+/// The memory-mapped graph gives the same components as the
+/// in-memory one, and can be reopened from its files
+#[test]
+fn mmap_graph() {
+    use three_edge_connected::{adjacency::Adjacency, mmap::MmapGraph};
+
+    let dir = std::env::temp_dir()
+        .join(format!("three-edge-connected-mmap-{}", std::process::id()));
+
+    for seed in 0..10 {
+        let partition = generators::PlantedPartition::random(seed, 10, 6);
+        let mut gfa = Vec::new();
+        generators::write_gfa(&mut gfa, &partition.edges).unwrap();
+
+        let graph =
+            Graph::from_gfa_reader(&mut std::io::Cursor::new(&gfa)).unwrap();
+        let (mmap_graph, names) =
+            MmapGraph::build(graph::gfa_links(gfa.as_slice()), &dir).unwrap();
+        assert_eq!(names, graph.inv_names);
+        assert_eq!(mmap_graph.num_nodes(), graph.graph.len());
+
+        let expected = normalize(algorithm::find_components(&graph.graph));
+        let comps = normalize(algorithm::find_components(&mmap_graph));
+        assert_eq!(comps, expected);
+
+        let reopened = MmapGraph::open(&dir).unwrap();
+        assert_eq!(reopened.num_edges(), partition.edges.len());
+    }
+
+    let (empty, names) =
+        MmapGraph::build(std::iter::empty::<std::io::Result<(u8, u8)>>(), &dir)
+            .unwrap();
+    assert!(names.is_empty());
+    assert!(algorithm::find_components(&empty).is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// The memory usage estimates account for at least the contents of
+/// the graph and state
+#[test]
+fn memory_usage() {
+    use std::mem::size_of;
+    use three_edge_connected::state::State;
+
+    let graph = generators::grid_graph(20, 30);
+    let num_nodes = graph.graph.len();
+    let adjacency = graph.graph.values().map(|a| a.len()).sum::<usize>();
+    assert!(
+        graph.memory_usage() >= (num_nodes + adjacency) * size_of::<usize>()
+    );
+
+    let mut state = State::initialize(&graph.graph);
+    let initial = state.memory_usage();
+    assert!(initial >= 6 * num_nodes * size_of::<usize>());
+
+    algorithm::three_edge_connect(&graph.graph, &mut state);
+    assert!(state.memory_usage() >= initial + num_nodes * size_of::<usize>());
+    assert_eq!(
+        normalize(state.sigma.into_inner()),
+        normalize(algorithm::find_components(&graph.graph))
+    );
+}
+
+/// The compact state with `u32` indices finds the same components,
+/// in less memory
+#[test]
+fn compact_state() {
+    use three_edge_connected::state::State;
+
+    for seed in 0..20 {
+        let graph = generators::PlantedPartition::random(seed, 15, 8).graph();
+        assert_eq!(
+            algorithm::find_components_with_index::<u32, _>(&graph.graph),
+            algorithm::find_components(&graph.graph)
+        );
+    }
+
+    let graph = generators::grid_graph(20, 30);
+    let wide = State::initialize(&graph.graph);
+    let compact: State<u32> = State::initialize_with_index(&graph.graph);
+    assert!(compact.memory_usage() * 10 < wide.memory_usage() * 6);
+}
+
+/// Adjacency lists are stored inline up to four neighbors, and move
+/// to the heap after that
+#[test]
+fn small_vec_adjacency() {
+    use three_edge_connected::small_vec::SmallVec;
+
+    let mut adj: SmallVec<usize, 4> = SmallVec::new();
+    adj.extend(0..4);
+    assert!(!adj.spilled());
+    assert_eq!(adj.heap_size(), 0);
+    adj.push(4);
+    assert!(adj.spilled());
+    assert_eq!(adj.as_slice(), &[0, 1, 2, 3, 4]);
+    assert_eq!(SmallVec::<usize, 4>::from((0..5).collect::<Vec<_>>()), adj);
+
+    let graph = complete_graph(4);
+    assert!(graph.graph.values().all(|adj| !adj.spilled()));
+    let graph = complete_graph(6);
+    assert!(graph.graph.values().all(|adj| adj.spilled()));
+}
+
+/// Interned names get the same indices as with `Graph`, in the order
+/// they're first seen, and survive the table growing
+#[test]
+fn interned_names() {
+    use three_edge_connected::{graph::InternedGraph, names::NameArena};
+
+    let mut arena = NameArena::new();
+    let names: Vec<Vec<u8>> =
+        (0..1000).map(|n| n.to_string().into_bytes()).collect();
+    for (ix, name) in names.iter().enumerate() {
+        assert_eq!(arena.get_or_insert(name), ix);
+    }
+    assert_eq!(arena.get_or_insert(b"42"), 42);
+    assert_eq!(arena.len(), 1000);
+    assert_eq!(arena.find(b"999"), Some(999));
+    assert_eq!(arena.find(b"1000"), None);
+    assert_eq!(arena.get(7), b"7");
+    assert!(arena.iter().eq(names.iter().map(|n| n.as_slice())));
+
+    let gfa = b"H\tVN:Z:1.0\n\
+                L\t1\t+\t2\t+\t0M\n\
+                L\t2\t-\t3\t+\t0M\n\
+                L\t3\t+\t1\t+\t0M\n\
+                L\t1\t+\t2\t-\t0M\n";
+    let graph = Graph::from_gfa_reader(&mut &gfa[..]).unwrap();
+    let interned = InternedGraph::from_gfa_reader(&mut &gfa[..]).unwrap();
+    assert_eq!(interned.graph, graph.graph);
+    assert!(interned
+        .names
+        .iter()
+        .eq(graph.inv_names.iter().map(|n| &n[..])));
+
+    let components = algorithm::find_components(&interned.graph);
+    let mut expected = Vec::new();
+    output::write_text(
+        &mut expected,
+        &graph.inv_names,
+        &components,
+        ComponentIds::Index,
+    )
+    .unwrap();
+    let mut out = Vec::new();
+    output::write_text(
+        &mut out,
+        &interned.names,
+        &components,
+        ComponentIds::Index,
+    )
+    .unwrap();
+    assert_eq!(out, expected);
+
+    let nodes: Vec<&[u8]> = vec![b"2", b"3", b"4"];
+    let subgraph = interned.induced_subgraph(nodes);
+    assert!(subgraph.names.iter().eq(vec![&b"2"[..], &b"3"[..]]));
+    assert_eq!(subgraph.graph.values().map(|a| a.len()).sum::<usize>(), 2);
+}
+
+/// With integer names used as the node indices, the components are
+/// the planted ones directly, without mapping indices back to names
+#[test]
+fn indexed_graph() {
+    use three_edge_connected::{
+        graph::{parse_index, IndexedGraph},
+        mmap::MmapGraph,
+        names::IndexNames,
+    };
+
+    assert_eq!(parse_index(b"0").unwrap(), 0);
+    assert_eq!(parse_index(b"1234").unwrap(), 1234);
+    for name in [
+        &b""[..],
+        b"007",
+        b"-1",
+        b"+1",
+        b"12a",
+        b"99999999999999999999",
+    ] {
+        assert!(parse_index(name).is_err());
+    }
+
+    let dir = std::env::temp_dir().join(format!(
+        "three-edge-connected-indexed-{}",
+        std::process::id()
+    ));
+
+    for seed in 0..10 {
+        let partition = generators::PlantedPartition::random(seed, 10, 6);
+        let mut gfa = Vec::new();
+        generators::write_gfa(&mut gfa, &partition.edges).unwrap();
+
+        let graph =
+            IndexedGraph::from_gfa_reader(&mut std::io::Cursor::new(&gfa))
+                .unwrap();
+        assert_eq!(graph, IndexedGraph::from_edges(partition.edges.clone()));
+        let comps = normalize(algorithm::find_components(&graph));
+        assert_eq!(comps, partition.components);
+
+        let mmap_graph =
+            MmapGraph::build_numeric(graph::gfa_links(gfa.as_slice()), &dir)
+                .unwrap();
+        let comps = normalize(algorithm::find_components(&mmap_graph));
+        assert_eq!(comps, partition.components);
+    }
+
+    // an index far beyond the number of edges is an error, instead of
+    // allocating a node for every index up to it
+    let gfa = b"S\t1\t*\nL\t1\t+\t99999999999\t+\t0M\n";
+    let err = IndexedGraph::from_gfa_reader(&mut &gfa[..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let err = MmapGraph::build_numeric(graph::gfa_links(&gfa[..]), &dir)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let input = format!("1 {}\n", usize::MAX);
+    let err =
+        IndexedGraph::from_edge_list_reader(&mut input.as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let input = b"1 2\nx 3\n";
+    let err = IndexedGraph::from_edge_list_reader(&mut &input[..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    let mut out = Vec::new();
+    output::write_text(
+        &mut out,
+        &IndexNames,
+        &[vec![3, 10]],
+        ComponentIds::Index,
+    )
+    .unwrap();
+    assert_eq!(out, b"3\t10\n");
+}
+
+/// Inserting edges one at a time gives the same components as running
+/// the algorithm on the whole graph after each edge
+#[test]
+fn incremental_insertion() {
+    use three_edge_connected::incremental::IncrementalComponents;
+
+    let mut rng = generators::SplitMix64(601);
+    for _ in 0..200 {
+        let num_nodes = 1 + rng.next_below(12);
+        let num_edges = rng.next_below(30);
+        let edges: Vec<(usize, usize)> = (0..num_edges)
+            .map(|_| (rng.next_below(num_nodes), rng.next_below(num_nodes)))
+            .collect();
+
+        let split = rng.next_below(num_edges + 1);
+        let mut incremental =
+            IncrementalComponents::from_edges(edges[..split].iter().copied());
+        for &(from, to) in edges[split..].iter() {
+            incremental.add_nodes(from.max(to) + 1);
+            let before = incremental.components();
+            let merged = incremental.insert_edge(from, to);
+
+            let graph = incremental.graph().to_vec();
+            let expected = normalize(algorithm::find_components(&graph));
+            assert_eq!(incremental.components(), expected, "{:?}", edges);
+            assert_eq!(merged, before != expected);
+            assert_eq!(
+                incremental.same_component(from, to),
+                expected
+                    .iter()
+                    .any(|c| c.contains(&from) && c.contains(&to))
+            );
+        }
+    }
+
+    // a third edge between two K_4s merges them, and the component is
+    // identified by its smallest node
+    let mut incremental =
+        IncrementalComponents::from_edges(generators::bridged_k_edges(4, 4, 2));
+    assert_eq!(incremental.component(6), 4);
+    assert!(!incremental.insert_edge(1, 2));
+    assert!(incremental.insert_edge(0, 7));
+    assert_eq!(incremental.component(6), 0);
+    assert_eq!(incremental.components().len(), 1);
+}
+
+/// Removing edges and rerunning the algorithm with a reset state gives
+/// the same components as a fresh run, and keeps the node indices
+#[test]
+fn edge_deletion() {
+    use three_edge_connected::{graph::InternedGraph, state::State};
+
+    // cutting one of three edges between two K_4s separates them
+    let mut graph = bridged_k_graphs(4, 4, 3);
+    let mut state = State::initialize(&graph.graph);
+    algorithm::three_edge_connect(&graph.graph, &mut state);
+    assert_eq!(state.sigma.len(), 1);
+
+    let pre = state.pre.as_ptr();
+    assert!(graph.remove_edge(3, 4));
+    assert!(!graph.remove_edge(0, 7));
+    state.reset(&graph.graph);
+    algorithm::three_edge_connect(&graph.graph, &mut state);
+    assert_eq!(state.pre.as_ptr(), pre);
+    assert_eq!(
+        normalize(state.sigma.clone().into_inner()),
+        vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7]]
+    );
+
+    let mut rng = generators::SplitMix64(602);
+    for _ in 0..200 {
+        let edges = generators::edges_from_bytes(
+            &(0..49).map(|_| rng.next_u64() as u8).collect::<Vec<_>>(),
+        );
+        let mut graph = Graph::from_edges(edges.iter().copied());
+        let mut state = State::initialize(&graph.graph);
+        for &(from, to) in edges.iter() {
+            let (from, to) = (
+                graph.inv_names.iter().position(|&n| n == from).unwrap(),
+                graph.inv_names.iter().position(|&n| n == to).unwrap(),
+            );
+            assert!(graph.remove_edge(from, to));
+            state.reset(&graph.graph);
+            algorithm::three_edge_connect(&graph.graph, &mut state);
+            assert_eq!(
+                normalize(state.sigma.clone().into_inner()),
+                three_edge_connected::brute_force::find_components(
+                    &graph.graph
+                )
+            );
+        }
+        assert!(graph.graph.values().all(|adj| adj.is_empty()));
+    }
+
+    let input = b"a b\nb c\nc a\nc c\n";
+    let mut graph =
+        InternedGraph::from_edge_list_reader(&mut &input[..]).unwrap();
+    assert!(graph.remove_named_edge(b"c", b"c"));
+    assert!(!graph.remove_named_edge(b"c", b"c"));
+    assert!(!graph.remove_named_edge(b"a", b"d"));
+    assert_eq!(graph.graph[&2].len(), 2);
+}
+
+/// Batches of insertions and removals give the same components as
+/// running the algorithm on the whole edited graph
+#[test]
+fn batch_edits() {
+    use three_edge_connected::incremental::{Edit, IncrementalComponents};
+
+    let mut rng = generators::SplitMix64(603);
+    for _ in 0..200 {
+        let num_nodes = 1 + rng.next_below(12);
+        let mut incremental = IncrementalComponents::new();
+        incremental.add_nodes(num_nodes);
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+
+        for _ in 0..20 {
+            let mut batch = Vec::new();
+            for _ in 0..1 + rng.next_below(4) {
+                if !edges.is_empty() && rng.next_below(3) == 0 {
+                    let (from, to) =
+                        edges.swap_remove(rng.next_below(edges.len()));
+                    batch.push(Edit::Remove(from, to));
+                } else {
+                    let edge =
+                        (rng.next_below(num_nodes), rng.next_below(num_nodes));
+                    edges.push(edge);
+                    batch.push(Edit::Insert(edge.0, edge.1));
+                }
+            }
+
+            let before = incremental.components();
+            let changed = incremental.apply(batch.iter().copied());
+            let graph = incremental.graph().to_vec();
+            let expected = normalize(algorithm::find_components(&graph));
+            assert_eq!(incremental.components(), expected, "{:?}", batch);
+            assert_eq!(changed, before != expected);
+        }
+    }
+
+    // removing one of three edges between two K_4s splits them, and a
+    // missing edge is ignored
+    let mut incremental =
+        IncrementalComponents::from_edges(generators::bridged_k_edges(4, 4, 3));
+    assert!(!incremental.remove_edge(0, 7));
+    assert!(incremental.remove_edge(3, 4));
+    assert_eq!(incremental.component(6), 4);
+    assert!(!incremental.apply(vec![Edit::Remove(1, 2), Edit::Insert(2, 1)]));
+}
+
+/// A visitor sees every node discovered once, every edge outside the
+/// DFS tree once, an absorption for each merge, and every component
+#[test]
+fn visitor_hooks() {
+    use three_edge_connected::{state::State, visitor::Visitor};
+
+    #[derive(Default)]
+    struct Counts {
+        discovered: Vec<usize>,
+        back_edges: usize,
+        absorbed: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Visitor for Counts {
+        fn on_discover(&mut self, node: usize) {
+            self.discovered.push(node);
+        }
+
+        fn on_back_edge(&mut self, _descendant: usize, _ancestor: usize) {
+            self.back_edges += 1;
+        }
+
+        fn on_absorb(&mut self, _root: usize, _node: usize) {
+            self.absorbed += 1;
+        }
+
+        fn on_component(&mut self, component: &[usize]) {
+            self.components.push(component.to_vec());
+        }
+    }
+
+    let mut rng = generators::SplitMix64(604);
+    for _ in 0..500 {
+        let len = 1 + 2 * rng.next_below(generators::FUZZ_MAX_EDGES + 1);
+        let data: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+        let graph =
+            Graph::from_edges(generators::edges_from_bytes(&data).into_iter());
+
+        let mut counts = Counts::default();
+        let mut state = State::initialize(&graph.graph);
+        algorithm::three_edge_connect_with_visitor(
+            &graph.graph,
+            &mut state,
+            &mut counts,
+        );
+
+        let num_nodes = graph.graph.len();
+        let loops: usize = graph
+            .graph
+            .iter()
+            .map(|(w, adj)| adj.iter().filter(|&u| u == w).count() / 2)
+            .sum();
+        let num_edges =
+            graph.graph.values().map(|adj| adj.len()).sum::<usize>() / 2;
+        let connected = connectivity::connected_components(&graph.graph).len();
+
+        counts.discovered.sort_unstable();
+        assert_eq!(counts.discovered, (0..num_nodes).collect::<Vec<_>>());
+        assert_eq!(
+            counts.back_edges,
+            num_edges - loops - (num_nodes - connected)
+        );
+        assert_eq!(counts.absorbed, num_nodes - state.sigma.len());
+        assert_eq!(counts.components, state.sigma.into_inner());
+    }
+}
+
+/// Setting the cancellation flag stops the algorithm with the
+/// components found so far, and makes the parsers fail
+#[test]
+fn cancellation() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use three_edge_connected::{
+        cancel::{is_cancelled, CancellableReader, Cancelled},
+        state::State,
+    };
+
+    // a path of K_4s, each its own component
+    let mut edges = Vec::new();
+    for k in 0..1000 {
+        edges.extend(complete_edges(4 * k, 4));
+        if k > 0 {
+            edges.push((4 * k - 1, 4 * k));
+        }
+    }
+    let graph = Graph::from_edges(edges.into_iter());
+    let expected = normalize(algorithm::find_components(&graph.graph));
+
+    let cancel = AtomicBool::new(false);
+    let mut state = State::initialize(&graph.graph);
+    assert_eq!(
+        algorithm::three_edge_connect_cancellable(
+            &graph.graph,
+            &mut state,
+            &cancel
+        ),
+        Ok(())
+    );
+    assert_eq!(normalize(state.sigma.clone().into_inner()), expected);
+
+    // the flag is only checked every few thousand steps, so some
+    // components are found before stopping
+    cancel.store(true, Ordering::Relaxed);
+    state.reset(&graph.graph);
+    assert_eq!(
+        algorithm::three_edge_connect_cancellable(
+            &graph.graph,
+            &mut state,
+            &cancel
+        ),
+        Err(Cancelled)
+    );
+    assert!(state.sigma.len() < expected.len());
+    assert!(state
+        .sigma
+        .iter()
+        .all(|c| expected.contains(&normalize(vec![c.to_vec()])[0])));
+
+    let gfa = generators::gfa_from_bytes(&[3, 0, 1, 1, 2, 2, 0]);
+    let mut reader = CancellableReader::new(gfa.as_slice(), &cancel);
+    let err = Graph::from_gfa_reader(&mut reader).err().unwrap();
+    assert!(is_cancelled(&err));
+
+    cancel.store(false, Ordering::Relaxed);
+    let mut reader = CancellableReader::new(gfa.as_slice(), &cancel);
+    assert!(Graph::from_gfa_reader(&mut reader).is_ok());
+}
+
+/// Progress is reported while the algorithm runs and at the end, and
+/// while reading the input
+#[test]
+fn progress_reports() {
+    use std::io::Read;
+    use three_edge_connected::{
+        progress::{Progress, ProgressReader},
+        state::State,
+    };
+
+    let graph = generators::grid_graph(100, 100);
+    let mut reports: Vec<Progress> = Vec::new();
+    let mut state = State::initialize(&graph.graph);
+    algorithm::three_edge_connect_with_progress(
+        &graph.graph,
+        &mut state,
+        None,
+        |progress| reports.push(progress),
+    )
+    .unwrap();
+
+    assert!(reports.len() > 2);
+    assert!(reports
+        .windows(2)
+        .all(|w| w[0].nodes <= w[1].nodes && w[0].edges <= w[1].edges));
+    assert_eq!(
+        reports.last(),
+        Some(&Progress {
+            nodes: 100 * 100,
+            edges: 2 * 100 * 99
+        })
+    );
+    assert_eq!(
+        normalize(state.sigma.into_inner()),
+        normalize(algorithm::find_components(&graph.graph))
+    );
+
+    let mut gfa = Vec::new();
+    generators::write_gfa(&mut gfa, &generators::grid_edges(150, 150)).unwrap();
+    let mut bytes = Vec::new();
+    let mut reader = ProgressReader::new(gfa.as_slice(), |b| bytes.push(b));
+    let graph = Graph::from_gfa_reader(&mut reader).unwrap();
+    assert_eq!(graph.graph.len(), 150 * 150);
+    assert_eq!(reader.bytes_read(), gfa.len() as u64);
+    let mut rest = Vec::new();
+    assert_eq!(reader.read_to_end(&mut rest).unwrap(), 0);
+    assert!(!bytes.is_empty());
+    assert!(bytes.windows(2).all(|w| w[0] < w[1]));
+}
+
+/// The edges within each component are given in component-local
+/// indices, keeping parallel edges and self-loops
+#[test]
+fn per_component_edges() {
+    let mut edges = generators::bridged_k_edges(4, 4, 2);
+    edges.push((5, 5));
+    edges.push((6, 7));
+    let graph = Graph::from_edges(edges.into_iter());
+    let components = normalize(algorithm::find_components(&graph.graph));
+    assert_eq!(components, vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7]]);
+
+    let local = graph::component_edges(&graph.graph, &components);
+    assert_eq!(local[0].len(), 6);
+    assert_eq!(local[1].len(), 8);
+    assert_eq!(local[1].iter().filter(|&&(a, b)| a == b).count(), 1);
+    assert_eq!(local[1].iter().filter(|&&e| e == (2, 3)).count(), 2);
+    assert!(local.iter().flatten().all(|&(a, b)| a <= b && b < 4));
+}
+
+/// Links with overlaps shorter than the threshold are dropped, and
+/// links with unknown overlaps are kept
+#[test]
+fn min_overlap_filter() {
+    assert_eq!(graph::overlap_length(b"*").unwrap(), None);
+    assert_eq!(graph::overlap_length(b"0M").unwrap(), Some(0));
+    assert_eq!(graph::overlap_length(b"5M2I3M").unwrap(), Some(10));
+    assert_eq!(graph::overlap_length(b"5M4D3M").unwrap(), Some(12));
+    assert!(graph::overlap_length(b"M").is_err());
+    assert!(graph::overlap_length(b"5M3").is_err());
+    assert!(graph::overlap_length(b"5Q").is_err());
+
+    let gfa = b"S\t1\t*\nS\t2\t*\nS\t3\t*\n\
+        L\t1\t+\t2\t+\t10M\n\
+        L\t1\t+\t2\t+\t3M\n\
+        L\t2\t+\t3\t+\t*\n\
+        L\t1\t+\t3\t+\t4M1I6M\n";
+    let links = |min_overlap| {
+        graph::gfa_links_min_overlap(&gfa[..], min_overlap)
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap()
+    };
+    assert_eq!(links(0).len(), 4);
+    assert_eq!(links(4).len(), 3);
+    assert_eq!(
+        links(11),
+        vec![
+            (b"2".to_vec(), b"3".to_vec()),
+            (b"1".to_vec(), b"3".to_vec())
+        ]
+    );
+
+    let invalid = b"L\t1\t+\t2\t+\t10Z\n";
+    let err = graph::gfa_links_min_overlap(&invalid[..], 5)
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+/// Directed edges are kept as they are, reduced to the reciprocated
+/// pairs, or rejected if any edge is given in both directions
+#[test]
+fn directed_edge_lists() {
+    use graph::Symmetrize;
+
+    let input = b"1 2\n2 3\n2 1\n1 2\n3 1\n4 4\n2 1\n";
+    let edges = |policy| {
+        graph::symmetrize_edges(graph::edge_list_edges(&input[..]), policy)
+            .collect::<std::io::Result<Vec<_>>>()
+    };
+    let edge = |a: &[u8], b: &[u8]| (a.to_vec(), b.to_vec());
+
+    assert_eq!(edges(Symmetrize::Undirected).unwrap().len(), 7);
+    assert_eq!(
+        edges(Symmetrize::Reciprocated).unwrap(),
+        vec![edge(b"1", b"2"), edge(b"4", b"4"), edge(b"1", b"2")]
+    );
+    let err = edges(Symmetrize::Reject).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    let input = b"1 2\n2 3\n3 1\n1 2\n4 4\n";
+    let edges = graph::symmetrize_edges(
+        graph::edge_list_edges(&input[..]),
+        Symmetrize::Reject,
+    );
+    assert_eq!(edges.collect::<std::io::Result<Vec<_>>>().unwrap().len(), 5);
+}
+
+/// Tables of edges are read from the chosen columns, after the header
+/// row, with quoted fields containing the delimiter
+#[test]
+fn edge_tables() {
+    use graph::EdgeTable;
+
+    let edge = |a: &[u8], b: &[u8]| (a.to_vec(), b.to_vec());
+    let csv = b"id,from,kind,to\r\n1,a,x,\"b, \"\"c\"\"\"\r\n\r\n2,d,y,a\r\n";
+    let table = EdgeTable {
+        delimiter: Some(b','),
+        from: 1,
+        to: 3,
+        header: true,
+    };
+    let edges = graph::edge_table_edges(&csv[..], table)
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(edges, vec![edge(b"a", b"b, \"c\""), edge(b"d", b"a")]);
+
+    // without a delimiter, the fields are split at whitespace
+    let table = EdgeTable {
+        from: 2,
+        to: 0,
+        ..EdgeTable::default()
+    };
+    let edges = graph::edge_table_edges(&b"# c\na  b\tc\n"[..], table)
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(edges, vec![edge(b"c", b"a")]);
+
+    let table = EdgeTable {
+        delimiter: Some(b','),
+        ..EdgeTable::default()
+    };
+    for input in [&b"a\n"[..], b"a,\n", b",b\n"] {
+        let err = graph::edge_table_edges(input, table)
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+
+/// The rows of the `from` and `to` columns that `duckdb` writes for
+/// Parquet input, tab-separated and quoted like CSV, are read as
+/// edges, and a null name, which is written as an empty field, is an
+/// error
+#[test]
+fn parquet_rows() {
+    use graph::EdgeTable;
+
+    let table = EdgeTable {
+        delimiter: Some(b'\t'),
+        ..EdgeTable::default()
+    };
+    let rows = b"1\t2\na b\t\"c\td\"\n\"say \"\"hi\"\"\"\t1\n";
+    let edges = graph::edge_table_edges(&rows[..], table)
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    let edge = |a: &[u8], b: &[u8]| (a.to_vec(), b.to_vec());
+    assert_eq!(
+        edges,
+        vec![
+            edge(b"1", b"2"),
+            edge(b"a b", b"c\td"),
+            edge(b"say \"hi\"", b"1")
+        ]
+    );
+
+    let err = graph::edge_table_edges(&b"1\t2\n3\t\n"[..], table)
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+/// Adjacency lists have a directed edge from each node to each
+/// neighbor, and the edges listed from both nodes are paired up when
+/// reciprocated, so a triangle is three edges, without any component
+#[test]
+fn adjacency_lists() {
+    use graph::Symmetrize;
+
+    let input = b"# a triangle\na: b c\n\nb: a c\nc: a b\nchr1:5: a\nd:\n";
+    let edges = graph::adjacency_list_edges(&input[..])
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    let edge = |a: &[u8], b: &[u8]| (a.to_vec(), b.to_vec());
+    assert_eq!(edges.len(), 7);
+    assert_eq!(edges[1], edge(b"a", b"c"));
+    assert_eq!(edges[3], edge(b"b", b"c"));
+    assert_eq!(edges[6], edge(b"chr1:5", b"a"));
+
+    let triangle = &b"a: b c\nb: a c\nc: a b\n"[..];
+    let edges = graph::symmetrize_edges(
+        graph::adjacency_list_edges(triangle),
+        Symmetrize::Reciprocated,
+    )
+    .collect::<std::io::Result<Vec<_>>>()
+    .unwrap();
+    assert_eq!(
+        edges,
+        vec![edge(b"a", b"b"), edge(b"a", b"c"), edge(b"b", b"c")]
+    );
+    let graph = Graph::from_named_edges(edges);
+    assert!(algorithm::find_components(&graph.graph)
+        .iter()
+        .all(|c| c.len() == 1));
+
+    for input in [&b"a b c\n"[..], b": a\n", b"a:b c\n"] {
+        let err = graph::adjacency_list_edges(input)
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+
+/// Integer names are interned by value, including ones far larger than
+/// the number of names, up to `usize::MAX`, and keep their indices
+/// when a name that isn't an integer switches the arena to hashing
+#[test]
+fn numeric_names() {
+    use three_edge_connected::names::NameArena;
+
+    let mut arena = NameArena::new();
+    let values: Vec<usize> = (0..20_000)
+        .map(|i| (i * 7919) % 20_000)
+        .chain(vec![usize::MAX, 1 << 40, 123_456_789])
+        .collect();
+    let names: Vec<Vec<u8>> =
+        values.iter().map(|v| v.to_string().into_bytes()).collect();
+    for (ix, name) in names.iter().enumerate() {
+        assert_eq!(arena.get_or_insert(name), ix);
+    }
+    for (ix, name) in names.iter().enumerate().rev() {
+        assert_eq!(arena.get_or_insert(name), ix);
+        assert_eq!(arena.find(name), Some(ix));
+    }
+    assert_eq!(arena.find(b"20000"), None);
+    assert_eq!(arena.find(b"abc"), None);
+    assert_eq!(arena.get(names.len() - 2), b"1099511627776");
+
+    assert_eq!(arena.get_or_insert(b"007"), names.len());
+    assert_eq!(
+        arena.get_or_insert(b"7"),
+        values.iter().position(|&v| v == 7).unwrap()
+    );
+    for (ix, name) in names.iter().enumerate() {
+        assert_eq!(arena.find(name), Some(ix));
+    }
+    assert_eq!(arena.get_or_insert(b"20000"), names.len() + 1);
+    assert_eq!(arena.len(), names.len() + 2);
+    assert!(arena
+        .iter()
+        .take(names.len())
+        .eq(names.iter().map(|n| &n[..])));
+}
+
+/// Relabeling the nodes keeps the components, and in DFS order, the
+/// algorithm visits the relabeled nodes in index order and finds the
+/// components in the same order as on the original graph
+#[test]
+fn relabeled_graphs() {
+    use three_edge_connected::{relabel::Relabeling, state::State};
+
+    for seed in 0..50 {
+        let graph = generators::erdos_renyi_graph(60, 0.08, seed);
+        let components = algorithm::find_components(&graph.graph);
+
+        for relabeling in
+            [Relabeling::dfs(&graph.graph), Relabeling::bfs(&graph.graph)]
+        {
+            assert_eq!(relabeling.len(), graph.graph.len());
+            for ix in 0..relabeling.len() {
+                let new = relabeling.new_index(ix);
+                assert_eq!(relabeling.old_index(new), ix);
+            }
+            let relabeled = relabeling.apply(&graph.graph);
+            let num_ends: usize = graph.graph.values().map(|a| a.len()).sum();
+            assert_eq!(relabeled.num_edges() * 2, num_ends);
+            let restored =
+                relabeling.restore(algorithm::find_components(&relabeled));
+            assert_eq!(normalize(restored), normalize(components.clone()));
+        }
+
+        let relabeling = Relabeling::dfs(&graph.graph);
+        let relabeled = relabeling.apply(&graph.graph);
+        let mut state: State = State::initialize(&relabeled);
+        algorithm::three_edge_connect(&relabeled, &mut state);
+        assert!(state.pre.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(relabeling.restore(state.sigma.into_inner()), components);
+
+        let inv_names: Vec<Vec<u8>> = graph
+            .inv_names
+            .iter()
+            .map(|n| n.to_string().into_bytes())
+            .collect();
+        let relabeled_components = algorithm::find_components(&relabeled);
+        let mut out = Vec::new();
+        output::write_text(
+            &mut out,
+            &relabeling.names(&inv_names),
+            &relabeled_components,
+            ComponentIds::Index,
+        )
+        .unwrap();
+        let mut expected = Vec::new();
+        output::write_text(
+            &mut expected,
+            &inv_names,
+            &components,
+            ComponentIds::Index,
+        )
+        .unwrap();
+        assert_eq!(out, expected);
+    }
+}
+
+/// Graphs from the constructors are valid, and hand-built graphs with
+/// missing nodes, one-sided edges, or the wrong number of names are
+/// reported
+#[test]
+fn graph_validation() {
+    use three_edge_connected::{
+        graph::{FxMapGraph, IndexedGraph, InternedGraph},
+        validate::Problem,
+    };
+
+    let graph = generators::bridged_k_graphs(4, 5, 2);
+    assert!(graph.validate().is_valid());
+    let gfa = b"L\t1\t+\t2\t+\t0M\nL\t2\t+\t3\t+\t0M\nL\t3\t+\t3\t+\t0M\n";
+    assert!(InternedGraph::from_gfa_reader(&mut &gfa[..])
+        .unwrap()
+        .validate()
+        .is_valid());
+    assert!(IndexedGraph::from_edges(vec![(0, 5), (5, 2)])
+        .validate()
+        .is_valid());
+
+    let mut adjacency = FxMapGraph::default();
+    adjacency.insert(0, vec![1, 1, 7].into());
+    adjacency.insert(1, vec![0].into());
+    adjacency.insert(3, vec![].into());
+    let graph = Graph {
+        graph: adjacency,
+        inv_names: vec!["a", "b"],
+        edge_labels: None,
+    };
+    let report = graph.validate();
+    assert!(!report.is_valid());
+    assert_eq!(
+        report.problems,
+        vec![
+            Problem::NodeOutOfRange {
+                node: 3,
+                num_nodes: 3
+            },
+            Problem::MissingNeighbor {
+                node: 0,
+                neighbor: 7
+            },
+            Problem::Asymmetric {
+                from: 0,
+                to: 1,
+                forward: 2,
+                backward: 1
+            },
+            Problem::NameCount {
+                num_names: 2,
+                num_nodes: 3
+            },
+        ]
+    );
+    assert!(report.to_string().starts_with("graph with 3 nodes has 4"));
+
+    let indexed = IndexedGraph {
+        adjacency: vec![vec![1].into(), vec![].into(), vec![3].into()],
+    };
+    assert_eq!(indexed.validate().problems.len(), 2);
+}
+
+/// The stats count self-loops and parallel edges once each, and the
+/// degree histogram covers every node
+#[test]
+fn graph_stats() {
+    use three_edge_connected::graph::IndexedGraph;
+
+    let graph = complete_graph(4);
+    let stats = graph.stats();
+    assert_eq!((stats.num_nodes, stats.num_edges), (4, 6));
+    assert_eq!((stats.self_loops, stats.parallel_edges), (0, 0));
+    assert_eq!(stats.degrees.into_iter().collect::<Vec<_>>(), vec![(3, 4)]);
+
+    let edges = vec![(0, 1), (1, 0), (0, 1), (1, 2), (2, 2), (2, 2), (3, 3)];
+    let graph = Graph::from_edges(edges.clone().into_iter());
+    let stats = graph.stats();
+    assert_eq!((stats.num_nodes, stats.num_edges), (4, 7));
+    assert_eq!((stats.self_loops, stats.parallel_edges), (3, 3));
+    assert_eq!(
+        stats
+            .degrees
+            .iter()
+            .map(|(&d, &n)| (d, n))
+            .collect::<Vec<_>>(),
+        vec![(2, 1), (3, 1), (4, 1), (5, 1)]
+    );
+    assert_eq!(stats.max_degree(), 5);
+
+    let indexed =
+        IndexedGraph::from_edges(edges.into_iter().chain(Some((7, 1))));
+    let indexed_stats = indexed.stats();
+    assert_eq!(indexed_stats.num_nodes, 8);
+    assert_eq!(indexed_stats.degrees[&0], 3);
+}
+
+/// Superbubbles are found once per pair of opposite strands, with
+/// links on either strand, and not in cycles or at tips
+#[test]
+fn superbubbles() {
+    use three_edge_connected::superbubble::{OrientedGraph, Superbubble};
+
+    let gfa = |links: &[(&str, &str, &str, &str)]| {
+        let mut gfa = Vec::new();
+        for (from, from_orient, to, to_orient) in links {
+            let line = format!(
+                "L\t{}\t{}\t{}\t{}\t0M\n",
+                from, from_orient, to, to_orient
+            );
+            gfa.extend_from_slice(line.as_bytes());
+        }
+        OrientedGraph::from_gfa_reader(&mut gfa.as_slice()).unwrap()
+    };
+    let named = |graph: &OrientedGraph, bubbles: Vec<Superbubble>| {
+        bubbles
+            .into_iter()
+            .map(|b| {
+                let entrance = graph.node_name(b.entrance);
+                let exit = graph.node_name(b.exit);
+                (
+                    String::from_utf8(entrance).unwrap(),
+                    String::from_utf8(exit).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // two bubbles in a row, the second with a reversed segment
+    let graph = gfa(&[
+        ("1", "+", "2", "+"),
+        ("1", "+", "3", "+"),
+        ("2", "+", "4", "+"),
+        ("3", "+", "4", "+"),
+        ("4", "+", "5", "-"),
+        ("4", "+", "6", "+"),
+        ("5", "-", "7", "+"),
+        ("7", "-", "6", "-"),
+    ]);
+    assert_eq!(graph.num_nodes(), 14);
+    let bubbles = graph.superbubbles();
+    assert_eq!(
+        named(&graph, bubbles.clone()),
+        vec![
+            ("1+".to_string(), "4+".to_string()),
+            ("4+".to_string(), "7+".to_string())
+        ]
+    );
+    let mut nodes = graph.superbubble_nodes(bubbles[0]);
+    nodes.sort_unstable();
+    assert_eq!(nodes, vec![0, 2, 4, 6]);
+
+    // every node in a bubble only has edges within it, other than the
+    // edges into the entrance and out of the exit
+    for bubble in bubbles {
+        let nodes = graph.superbubble_nodes(bubble);
+        for &w in nodes.iter() {
+            if w != bubble.exit {
+                assert!(graph.successors(w).iter().all(|u| nodes.contains(u)));
+            }
+            if w != bubble.entrance {
+                assert!(graph.predecessors(w).all(|u| nodes.contains(&u)));
+            }
+        }
+    }
+
+    // the same bubble, closed into a cycle, and with a tip inside
+    let cycle = gfa(&[
+        ("1", "+", "2", "+"),
+        ("1", "+", "3", "+"),
+        ("2", "+", "4", "+"),
+        ("3", "+", "4", "+"),
+        ("4", "+", "1", "+"),
+    ]);
+    assert!(cycle.superbubbles().is_empty());
+    let tip = gfa(&[
+        ("1", "+", "2", "+"),
+        ("1", "+", "3", "+"),
+        ("2", "+", "4", "+"),
+        ("3", "+", "4", "+"),
+        ("2", "+", "5", "+"),
+    ]);
+    assert!(tip.superbubbles().is_empty());
+
+    // a chain has no bubbles with anything inside them
+    let chain = gfa(&[("1", "+", "2", "+"), ("2", "+", "3", "+")]);
+    assert!(chain.superbubbles().is_empty());
+
+    let segments = graph.segment_graph();
+    assert_eq!(segments.num_edges(), 8);
+    assert_eq!(segments.adjacency.len(), graph.num_segments());
+}
+
+/// The snarl decomposition nests the chains of a bubble inside the
+/// bubble, on either strand, and covers circular graphs
+#[test]
+fn snarl_decomposition() {
+    use three_edge_connected::superbubble::OrientedGraph;
+
+    let gfa = |links: &[(&str, &str, &str, &str)]| {
+        let mut gfa = Vec::new();
+        for (from, from_orient, to, to_orient) in links {
+            let line = format!(
+                "L\t{}\t{}\t{}\t{}\t0M\n",
+                from, from_orient, to, to_orient
+            );
+            gfa.extend_from_slice(line.as_bytes());
+        }
+        OrientedGraph::from_gfa_reader(&mut gfa.as_slice()).unwrap()
+    };
+    // each snarl as its start and end names and depth
+    let snarls = |graph: &OrientedGraph| {
+        let decomposition = graph.snarls();
+        let mut snarls = (0..decomposition.snarls.len())
+            .map(|ix| {
+                let snarl = decomposition.snarls[ix];
+                let name =
+                    |node| String::from_utf8(graph.node_name(node)).unwrap();
+                (name(snarl.start), name(snarl.end), decomposition.depth(ix))
+            })
+            .collect::<Vec<_>>();
+        snarls.sort();
+        snarls
+    };
+    let snarl = |start: &str, end: &str, depth| {
+        (start.to_string(), end.to_string(), depth)
+    };
+
+    // a bubble with another bubble on one of its alleles, given on
+    // the reverse strand
+    let nested = gfa(&[
+        ("5", "-", "1", "-"),
+        ("6", "-", "5", "-"),
+        ("7", "-", "5", "-"),
+        ("8", "-", "6", "-"),
+        ("8", "-", "7", "-"),
+        ("4", "-", "8", "-"),
+        ("3", "-", "1", "-"),
+        ("4", "-", "3", "-"),
+    ]);
+    assert_eq!(
+        snarls(&nested),
+        vec![snarl("1+", "4+", 0), snarl("5+", "8+", 1)]
+    );
+    let decomposition = nested.snarls();
+    let cactus = nested.cactus_graph();
+    assert_eq!(cactus.num_edges(), nested.num_segments());
+    assert!(cactus.tips().is_some());
+    let inner = decomposition
+        .snarls
+        .iter()
+        .find(|s| s.parent.is_some())
+        .unwrap();
+    let outer = decomposition.top_level().next().unwrap();
+    assert_eq!(inner.parent, Some(outer));
+    assert_eq!(decomposition.chains[inner.chain].segments.len(), 2);
+
+    // a chain has a snarl between each pair of adjacent segments
+    let chain = gfa(&[("1", "+", "2", "-"), ("2", "-", "3", "+")]);
+    assert_eq!(
+        snarls(&chain),
+        vec![snarl("1+", "2-", 0), snarl("2-", "3+", 0)]
+    );
+
+    // a cycle without tips has one snarl fewer than segments
+    let cycle = gfa(&[
+        ("1", "+", "2", "+"),
+        ("2", "+", "3", "+"),
+        ("3", "+", "1", "+"),
+    ]);
+    assert_eq!(cycle.cactus_graph().tips(), None);
+    assert_eq!(snarls(&cycle).len(), 2);
+}
+
+/// GFA paths are parsed with their orientations, and their crossings
+/// between components are found at the steps where they enter a
+/// different component
+#[test]
+fn path_crossings() {
+    use three_edge_connected::paths::{self, Crossing};
+
+    let mut gfa = Vec::new();
+    // the bridge is between 3 and 4
+    for (from, to) in generators::bridged_k_edges(4, 4, 1) {
+        gfa.extend_from_slice(
+            format!("L\t{}\t+\t{}\t+\t0M\n", from, to).as_bytes(),
+        );
+    }
+    gfa.extend_from_slice(
+        b"P\tp1\t1+,3-,4+,5+\t*\nP\tp2\t1+,2+\t*\nP\tp3\tx+,1+\t*\n",
+    );
+
+    let graph =
+        graph::InternedGraph::from_gfa_reader(&mut gfa.as_slice()).unwrap();
+    let components = algorithm::find_components(&graph.graph);
+    let node_components =
+        paths::node_components(&components, graph.names.len());
+    let component =
+        |name: &[u8]| graph.names.find(name).and_then(|ix| node_components[ix]);
+
+    let paths: Vec<_> = paths::gfa_paths(gfa.as_slice())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(paths.len(), 3);
+    assert_eq!(paths[0].name, b"p1");
+    assert_eq!(paths[0].steps.len(), 4);
+    assert!(paths[0].steps[1].reverse);
+
+    let crossings = paths[0].crossings(component);
+    assert_eq!(crossings.len(), 1);
+    let Crossing { step, from, to } = crossings[0];
+    assert_eq!(step, 2);
+    assert_ne!(from, to);
+    assert!(paths[1].crossings(component).is_empty());
+    // a segment without links isn't in any component
+    assert_eq!(paths[2].crossings(component)[0].from, None);
+}
+
+/// The odgi table is sorted by numeric node id, and rejects names
+/// that aren't node ids
+#[test]
+fn odgi_output() {
+    let names: Vec<Vec<u8>> =
+        vec![b"10".to_vec(), b"2".to_vec(), b"3".to_vec()];
+    let components = vec![vec![0, 1], vec![2]];
+
+    let mut out = Vec::new();
+    output::write_odgi(&mut out, &names, &components, ComponentIds::Index)
+        .unwrap();
+    assert_eq!(
+        out,
+        b"#node.id\tcomponent.id\tcomponent.size\n2\t0\t2\n3\t1\t1\n10\t0\t2\n"
+    );
+
+    let names: Vec<Vec<u8>> = vec![b"0".to_vec(), b"a".to_vec()];
+    let err = output::write_odgi(
+        &mut Vec::new(),
+        &names,
+        &[vec![0]],
+        ComponentIds::Index,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(output::write_odgi(
+        &mut Vec::new(),
+        &names,
+        &[vec![1]],
+        ComponentIds::Index
+    )
+    .is_err());
+}
+
+/// vg's Protobuf graphs are read with the node ids as names, in groups
+/// with or without a type tag
+#[test]
+fn vg_input() {
+    use three_edge_connected::vg;
+
+    fn varint(mut n: u64, out: &mut Vec<u8>) {
+        while n >= 0x80 {
+            out.push((n as u8 & 0x7f) | 0x80);
+            n >>= 7;
+        }
+        out.push(n as u8);
+    }
+    fn bytes_field(num: u64, payload: &[u8], out: &mut Vec<u8>) {
+        varint(num << 3 | 2, out);
+        varint(payload.len() as u64, out);
+        out.extend_from_slice(payload);
+    }
+    // a `Graph` message with a node, to be skipped, and the edges
+    let graph = |edges: &[(u64, u64)]| {
+        let mut graph = Vec::new();
+        bytes_field(1, &[0x0a, 1, b'A', 0x18, 1], &mut graph);
+        for &(from, to) in edges {
+            let mut edge = Vec::new();
+            for (num, id) in [(1, from), (2, to), (3, 1)] {
+                varint(num << 3, &mut edge);
+                varint(id, &mut edge);
+            }
+            bytes_field(2, &edge, &mut graph);
+        }
+        graph
+    };
+    let group = |messages: &[Vec<u8>], out: &mut Vec<u8>| {
+        varint(messages.len() as u64, out);
+        for message in messages {
+            varint(message.len() as u64, out);
+            out.extend_from_slice(message);
+        }
+    };
+
+    let mut data = Vec::new();
+    group(
+        &[b"VG".to_vec(), graph(&[(1, 2), (2, 3)]), graph(&[(3, 1)])],
+        &mut data,
+    );
+    group(&[graph(&[(300, 1)])], &mut data);
+    let edges: Vec<_> = vg::vg_edges(data.as_slice())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let names =
+        |a: &str, b: &str| (a.as_bytes().to_vec(), b.as_bytes().to_vec());
+    assert_eq!(
+        edges,
+        vec![
+            names("1", "2"),
+            names("2", "3"),
+            names("3", "1"),
+            names("300", "1")
+        ]
+    );
+
+    let graph = graph::InternedGraph::try_from_named_edges(vg::vg_edges(
+        data.as_slice(),
+    ))
+    .unwrap();
+    assert_eq!(graph.graph.len(), 4);
+    assert_eq!(graph.names.find(b"300"), Some(3));
+
+    // other types of messages, and truncated messages, are errors
+    let mut alignments = Vec::new();
+    group(&[b"GAM".to_vec(), vec![0x0a, 0]], &mut alignments);
+    let mut results = vg::vg_edges(alignments.as_slice());
+    assert_eq!(
+        results.next().unwrap().unwrap_err().kind(),
+        std::io::ErrorKind::InvalidData
+    );
+    assert!(results.next().is_none());
+    assert!(vg::vg_edges(&data[..data.len() - 1]).any(|edge| edge.is_err()));
+}
+
+/// The stable coordinates of rGFA segments are read from their tags,
+/// and written with the components
+#[test]
+fn rgfa_coordinates() {
+    use three_edge_connected::rgfa;
+
+    let gfa = b"H\tVN:Z:1.0\n\
+                S\ts1\tACGT\tSN:Z:chr1\tSO:i:0\tSR:i:0\n\
+                S\ts2\t*\tSR:i:1\tLN:i:7\tSO:i:4\tSN:Z:alt\n\
+                S\ts3\tA\n\
+                L\ts1\t+\ts2\t+\t0M\n";
+    let coordinates = rgfa::rgfa_coordinates(&gfa[..]).unwrap();
+    assert_eq!(coordinates.len(), 2);
+    let s2 = &coordinates[&b"s2".to_vec()];
+    assert_eq!(
+        (s2.name.as_slice(), s2.offset, s2.rank),
+        (&b"alt"[..], 4, 1)
+    );
+    assert_eq!(s2.end(), Some(11));
+    assert_eq!(coordinates[&b"s1".to_vec()].length, Some(4));
+
+    let names: Vec<Vec<u8>> = vec![b"s1".to_vec(), b"s3".to_vec()];
+    let mut out = Vec::new();
+    output::write_rgfa_tsv(
+        &mut out,
+        &names,
+        &[vec![0, 1]],
+        ComponentIds::Index,
+        |node| coordinates.get(&names[node]),
+    )
+    .unwrap();
+    assert_eq!(
+        out,
+        b"component\tnode\tSN\tSO\tSR\tLN\n0\ts1\tchr1\t0\t0\t4\n0\ts3\t*\t*\t*\t*\n"
+    );
+
+    // all three tags are required
+    let err = rgfa::rgfa_coordinates(&b"S\ts1\tA\tSN:Z:chr1\tSO:i:0\n"[..])
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(
+        rgfa::rgfa_coordinates(&b"S\ts1\tA\tSN:Z:c\tSO:i:x\tSR:i:0\n"[..])
+            .is_err()
+    );
+}
+
+/// PAF overlap graphs have an edge for each pair of reads with a
+/// dovetail overlap that passes the filter
+#[test]
+fn paf_overlap_graph() {
+    use three_edge_connected::paf::{self, PafFilter};
+
+    let paf = b"a\t10000\t8000\t10000\t+\tb\t10000\t0\t2000\t1900\t2000\t60\n\
+                b\t10000\t0\t2000\t+\ta\t10000\t8000\t10000\t1900\t2000\t60\n\
+                b\t10000\t9000\t10000\t-\te\t5000\t4000\t5000\t990\t1000\t60\n\
+                c\t1000\t0\t1000\t+\ta\t10000\t3000\t4000\t990\t1000\t60\n\
+                a\t10000\t3000\t5000\t+\td\t10000\t3000\t5000\t1990\t2000\t60\n\
+                a\t10000\t0\t500\t+\ta\t10000\t9500\t10000\t500\t500\t60\n\
+                f\t3000\t2500\t3000\t+\ta\t10000\t0\t500\t495\t500\t5\n";
+    let edges = |filter| -> Vec<(Vec<u8>, Vec<u8>)> {
+        paf::paf_edges(&paf[..], filter)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    };
+    let pair = |a: &[u8], b: &[u8]| (a.to_vec(), b.to_vec());
+
+    // the containment, internal match, and self-alignment are dropped,
+    // and a-b is only given once
+    assert_eq!(
+        edges(PafFilter::default()),
+        vec![pair(b"a", b"b"), pair(b"b", b"e"), pair(b"a", b"f")]
+    );
+    assert_eq!(
+        edges(PafFilter {
+            min_mapq: 10,
+            min_identity: 0.96,
+            ..PafFilter::default()
+        }),
+        vec![pair(b"b", b"e")]
+    );
+    assert_eq!(
+        edges(PafFilter {
+            min_overlap: 1000,
+            ..PafFilter::default()
+        }),
+        vec![pair(b"a", b"b"), pair(b"b", b"e")]
+    );
+    // the overlaps reach the ends of the reads exactly, including the
+    // one on the reverse strand, so they're kept without any overhang
+    assert_eq!(
+        edges(PafFilter {
+            max_overhang: 0,
+            ..PafFilter::default()
+        }),
+        vec![pair(b"a", b"b"), pair(b"b", b"e"), pair(b"a", b"f")]
+    );
+
+    let err = paf::paf_edges(&b"a\t10\t0\t5\t+\tb\n"[..], PafFilter::default())
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+/// Pajek networks are read with the vertex labels as names, from both
+/// kinds of edge sections
+#[test]
+fn pajek_input() {
+    use three_edge_connected::pajek;
+
+    let edges = |net: &[u8]| -> std::io::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        pajek::pajek_edges(net).collect()
+    };
+    let pair = |a: &[u8], b: &[u8]| (a.to_vec(), b.to_vec());
+
+    let net = b"% comment\r\n\
+                *Network test\r\n\
+                *Vertices 4\r\n\
+                1 \"a b\" 0.1 0.2 ic Red\r\n\
+                2 c\r\n\
+                3\r\n\
+                *Edges\r\n\
+                1 2 1.5\r\n\
+                *arcs\r\n\
+                2 3\r\n\
+                *EdgesList\r\n\
+                3 1 4\r\n";
+    assert_eq!(
+        edges(net).unwrap(),
+        vec![
+            pair(b"a b", b"c"),
+            pair(b"c", b"3"),
+            pair(b"3", b"a b"),
+            pair(b"3", b"4"),
+        ]
+    );
+    // without a vertices section, the numbers are the names
+    assert_eq!(edges(b"*Edges\n1 2\n").unwrap(), vec![pair(b"1", b"2")]);
+
+    for net in [
+        &b"*Vertices 2\n*Edges\n1 3\n"[..],
+        b"*Vertices 2\n1 x\n2 x\n*Edges\n1 2\n",
+        b"*Vertices 2\n2 1\n*Edges\n1 2\n",
+        b"*Edges\n1\n",
+        b"*Matrix\n0 1\n1 0\n",
+        b"*Vertices 2\n\"abc\n",
+        b"*Edges\n\"1 2\n",
+    ] {
+        let err = edges(net).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+
+/// TGF graphs are read with the node labels as names, and the ids of
+/// the nodes without one
+#[test]
+fn tgf_input() {
+    use three_edge_connected::tgf;
+
+    let edges = |tgf: &[u8]| -> std::io::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        tgf::tgf_edges(tgf).collect()
+    };
+    let pair = |a: &[u8], b: &[u8]| (a.to_vec(), b.to_vec());
+
+    let tgf = b"1 a\r\n2 b c\r\n3\r\n#\r\n1 2 an edge\r\n\r\n2 3\r\n3 4\r\n";
+    assert_eq!(
+        edges(tgf).unwrap(),
+        vec![pair(b"a", b"b c"), pair(b"b c", b"3"), pair(b"3", b"4")]
+    );
+
+    for tgf in [
+        &b"1 a\n2 a\n#\n1 2\n"[..],
+        b"1 2\n2\n#\n1 2\n",
+        b"1 2\n#\n1 2\n",
+        b"1 a\n1 b\n#\n",
+        b"#\n1\n",
+    ] {
+        let err = edges(tgf).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+
+/// LEMON graphs are read with the node labels as names, wherever the
+/// label column is
+#[test]
+fn lgf_input() {
+    use three_edge_connected::lgf;
+
+    let edges = |lgf: &[u8]| -> std::io::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        lgf::lgf_edges(lgf).collect()
+    };
+    let pair = |a: &[u8], b: &[u8]| (a.to_vec(), b.to_vec());
+
+    let lgf = b"# comment\n\
+                @nodes\n\
+                coords\tlabel\n\
+                (0,0)\t0\n\
+                (1,0)\t\"a \\\"b\\\"\"\n\
+                (0,1)\t2\n\
+                @arcs\n\
+                \t\tlabel\tcost\n\
+                0\t\"a \\\"b\\\"\"\t0\t1.5\n\
+                \n\
+                2\t0\t1\t2\n\
+                @attributes\n\
+                source 0\n";
+    assert_eq!(
+        edges(lgf).unwrap(),
+        vec![pair(b"0", b"a \"b\""), pair(b"2", b"0")]
+    );
+    // the edges of a file without nodes aren't checked
+    assert_eq!(
+        edges(b"@edges\nlabel\nx y 0\n").unwrap(),
+        vec![pair(b"x", b"y")]
+    );
+
+    for lgf in [
+        &b"@nodes\nlabel\n0\n@edges\nlabel\n0 1 0\n"[..],
+        b"@nodes\nlabel\n0\n0\n",
+        b"@nodes\ncoords\n(0,0)\n",
+        b"@edges\nlabel\n0 \"1\n",
+        b"@edges\nlabel\n0\n",
+    ] {
+        let err = edges(lgf).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+
+/// Regions select the segments on a stable sequence or along a path,
+/// and their neighborhood adds the flanking context
+#[test]
+fn region_subgraph() {
+    use three_edge_connected::region::{self, Region};
+
+    let region: Region = "chr1:5-1,000".parse().unwrap();
+    assert_eq!((region.name.as_slice(), region.start), (&b"chr1"[..], 4));
+    assert_eq!(region.end, Some(1000));
+    let region: Region = "GRCh38#chr1".parse().unwrap();
+    assert_eq!((region.start, region.end), (0, None));
+    let region: Region = "a:b:7".parse().unwrap();
+    assert_eq!((region.name.as_slice(), region.start), (&b"a:b"[..], 6));
+    assert!("chr1:0-10".parse::<Region>().is_err());
+    assert!("chr1:10-5".parse::<Region>().is_err());
+
+    let gfa = b"S\ts1\tACGT\tSN:Z:chr1\tSO:i:0\tSR:i:0\n\
+                S\ts2\tACGT\tSN:Z:chr1\tSO:i:4\tSR:i:0\n\
+                S\ts3\t*\tLN:i:10\tSN:Z:chr1\tSO:i:8\tSR:i:0\n\
+                S\ts4\tAC\tSN:Z:alt\tSO:i:0\tSR:i:1\n\
+                P\tp1\ts4+,s2-,s3+\t*\n";
+    let segments = |region: &str| {
+        region::region_segments(&gfa[..], &region.parse().unwrap()).unwrap()
+    };
+    assert_eq!(segments("chr1:5-9"), vec![b"s2".to_vec(), b"s3".to_vec()]);
+    assert_eq!(segments("chr1:4-4"), vec![b"s1".to_vec()]);
+    // along the path, s4 covers 1-2, s2 3-6, and s3 7-16
+    assert_eq!(segments("p1:2-3"), vec![b"s4".to_vec(), b"s2".to_vec()]);
+    assert_eq!(segments("p1:10"), vec![b"s3".to_vec()]);
+    let err = region::region_segments(&gfa[..], &"chr2".parse().unwrap())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    // a path of 0 - 1 - 2 - 3 - 4
+    let graph = graph::IndexedGraph::from_edges((0..4).map(|i| (i, i + 1)));
+    assert_eq!(region::neighborhood(&graph, vec![2], 0), vec![2]);
+    assert_eq!(region::neighborhood(&graph, vec![2], 1), vec![2, 1, 3]);
+    assert_eq!(region::neighborhood(&graph, vec![0, 4], 2).len(), 5);
+    // the search stops once no new nodes are found
+    assert_eq!(region::neighborhood(&graph, vec![2], usize::MAX).len(), 5);
+}
+
+/// Sorting by node index lists the components and their nodes in the
+/// order the nodes first appear in the input
+#[test]
+fn keep_input_order() {
+    let mut edges: Vec<(String, String)> = Vec::new();
+    for prefix in ["z", "a"] {
+        for (a, b) in complete_edges(1, 4) {
+            edges
+                .push((format!("{}{}", prefix, a), format!("{}{}", prefix, b)));
+        }
+        if prefix == "z" {
+            edges.push(("a1".to_string(), "z1".to_string()));
+        }
+    }
+    let graph = graph::InternedGraph::from_named_edges(
+        edges.iter().map(|(a, b)| (a.as_bytes(), b.as_bytes())),
+    );
+
+    let mut components = algorithm::find_components(&graph.graph);
+    three_edge_connected::sort_components_by_key(&mut components, |n| n);
+    let names: Vec<Vec<&[u8]>> = components
+        .iter()
+        .map(|c| c.iter().map(|&n| graph.names.get(n)).collect())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            vec![&b"z1"[..], b"z2", b"z3", b"z4"],
+            vec![&b"a1"[..], b"a2", b"a3", b"a4"],
+        ]
+    );
+
+    // sorting by the reverse index reverses both orders
+    three_edge_connected::sort_components_by_key(&mut components, |n| {
+        std::cmp::Reverse(n)
+    });
+    assert_eq!(components[0][0], graph.names.find(b"a4").unwrap());
+    assert_eq!(*components[1].last().unwrap(), 0);
+}
+
+/// Components are projected onto a path as BED intervals, merging the
+/// consecutive steps in the same component
+#[test]
+fn bed_projection() {
+    use three_edge_connected::region;
+
+    let gfa = b"S\ts1\tACGT\n\
+                S\ts2\tAC\n\
+                S\ts3\t*\tLN:i:10\n\
+                S\ts4\tA\n\
+                P\tref\ts1+,s2-,s3+,s4+,s1-\t*\n";
+    let intervals = region::reference_intervals(&gfa[..], b"ref").unwrap();
+    let bounds: Vec<(usize, usize)> =
+        intervals.iter().map(|i| (i.start, i.end)).collect();
+    assert_eq!(bounds, vec![(0, 4), (4, 6), (6, 16), (16, 17), (17, 21)]);
+
+    // s4 isn't in any component, so it splits the runs of component 0
+    let component = |segment: &[u8]| match segment {
+        b"s1" | b"s2" => Some(0),
+        b"s3" => Some(1),
+        _ => None,
+    };
+    let mut out = Vec::new();
+    output::write_bed(&mut out, b"ref", &intervals, component).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "ref\t0\t6\t0\nref\t6\t16\t1\nref\t17\t21\t0\n"
+    );
+}
+
+/// `Components` gives the sizes, the component of each node, and the
+/// named components
+#[test]
+fn components_type() {
+    use three_edge_connected::{state::State, Components};
+
+    let graph = graph::InternedGraph::from_named_edges(
+        bridged_k_edges(4, 3, 1).into_iter().map(|(a, b)| {
+            (a.to_string().into_bytes(), b.to_string().into_bytes())
+        }),
+    );
+    let mut components = Components::find(&graph.graph);
+    assert_eq!(components.len(), 4);
+    let mut sizes: Vec<usize> = components.sizes().collect();
+    sizes.sort_unstable();
+    assert_eq!(sizes, vec![1, 1, 1, 4]);
+
+    let node = graph.names.find(b"2").unwrap();
+    let ix = components.component_of(node).unwrap();
+    assert_eq!(components.get(ix).unwrap().len(), 4);
+    assert_eq!(
+        components.node_components(graph.names.len())[node],
+        Some(ix)
+    );
+    assert_eq!(components.component_of(graph.names.len()), None);
+
+    components.retain_min_size(2);
+    let mut named = components.clone().into_named(&graph.names);
+    named[0].sort();
+    assert_eq!(
+        named,
+        vec![vec![
+            b"0".to_vec(),
+            b"1".to_vec(),
+            b"2".to_vec(),
+            b"3".to_vec()
+        ]]
+    );
+
+    let mut state = State::initialize(&graph.graph);
+    algorithm::three_edge_connect(&graph.graph, &mut state);
+    let mut found = state.take_components();
+    found.retain_min_size(2);
+    assert_eq!(found, components);
+    assert!(state.components().is_empty());
+}
+
+/// The checked algorithm returns errors for invalid input instead of
+/// panicking
+#[test]
+fn checked_algorithm() {
+    use three_edge_connected::{algorithm::InvalidInput, state::State};
+
+    let graph: Vec<Vec<usize>> =
+        vec![vec![1, 2, 3], vec![0, 2, 3], vec![0, 1, 3], vec![0, 1, 2]];
+    let mut state = State::initialize(&graph);
+    algorithm::three_edge_connect_checked(&graph, &mut state).unwrap();
+    assert_eq!(state.components().len(), 1);
+
+    let smaller: Vec<Vec<usize>> = vec![vec![1], vec![0]];
+    let mut state = State::initialize(&smaller);
+    assert_eq!(
+        algorithm::three_edge_connect_checked(&graph, &mut state),
+        Err(InvalidInput::StateLength { state: 2, graph: 4 })
+    );
+
+    let broken: Vec<Vec<usize>> = vec![vec![1], vec![0, 5], vec![]];
+    let mut state = State::initialize(&broken);
+    let err =
+        algorithm::three_edge_connect_checked(&broken, &mut state).unwrap_err();
+    assert_eq!(
+        err,
+        InvalidInput::NeighborOutOfRange {
+            node: 1,
+            neighbor: 5
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "node 1 has neighbor 5, which is out of range"
+    );
+    assert!(state.components().is_empty());
+}
+
+/// Malformed GFA link lines are reported with their line numbers, and
+/// either skipped or turned into an error
+#[test]
+fn malformed_gfa_lines() {
+    let gfa = b"H\tVN:Z:1.0\n\
+                S\ts1\tA\n\
+                L\ts1\t+\ts2\t+\t0M\n\
+                L\ts2\t+\ts3\n\
+                W\tsample\t0\tchr1\t0\t1\t>s1\n\
+                L\ts2\t?\ts3\t+\t0M\n\
+                L\ts3\t+\ts1\t+\t0M\n";
+
+    let mut malformed = Vec::new();
+    let links: Vec<(Vec<u8>, Vec<u8>)> =
+        graph::gfa_links_with(&gfa[..], |line| {
+            malformed.push(line.line_number);
+            Ok(())
+        })
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(links.len(), 2);
+    assert_eq!(malformed, vec![4, 6]);
+
+    // the lines are skipped silently by default
+    assert_eq!(graph::gfa_links(&gfa[..]).count(), 2);
+
+    let err = graph::gfa_links_min_overlap_with(&gfa[..], 0, |line| {
+        Err(line.into_error())
+    })
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().starts_with("malformed GFA line 4: "));
+}
+
+/// The algorithm runs on borrowed neighbor slices and CSR arrays,
+/// with the same components as on the owned neighbor lists
+#[test]
+fn borrowed_adjacency() {
+    use three_edge_connected::adjacency::CsrAdjacency;
+
+    let graph = generators::bridged_k_graphs(4, 4, 1);
+    let owned: Vec<Vec<usize>> = (0..graph.graph.len())
+        .map(|node| graph.graph[&node].to_vec())
+        .collect();
+    let mut expected = algorithm::find_components(&owned);
+    expected.iter_mut().for_each(|c| c.sort_unstable());
+    expected.sort();
+
+    let slices: Vec<&[usize]> = owned.iter().map(|n| n.as_slice()).collect();
+    let mut offsets = vec![0];
+    let mut neighbors = Vec::new();
+    for list in &owned {
+        neighbors.extend_from_slice(list);
+        offsets.push(neighbors.len());
+    }
+    let csr = CsrAdjacency::new(&offsets, &neighbors).unwrap();
+
+    for mut found in [
+        algorithm::find_components(slices.as_slice()),
+        algorithm::find_components(&csr),
+    ] {
+        found.iter_mut().for_each(|c| c.sort_unstable());
+        found.sort();
+        assert_eq!(found, expected);
+    }
+    assert_eq!(expected.len(), 2);
+
+    assert!(CsrAdjacency::new(&[], &[]).is_none());
+    assert!(CsrAdjacency::new(&[0, 2, 1], &[1]).is_none());
+    assert!(CsrAdjacency::new(&[0, 1], &[0, 1]).is_none());
+}
+
+/// The links are parsed in place, with the graphs built from them the
+/// same as from the owned names of `gfa_links`
+#[test]
+fn borrowed_gfa_links() {
+    use three_edge_connected::graph::{GfaLink, IndexedGraph, InternedGraph};
+
+    let gfa = b"S\t1\t*\n\
+                L\t1\t+\t2\t-\t10M\tID:Z:a\n\
+                L\t2\t-\t3\t+\t2M\n\
+                L\t*3\t+\t1\t+\t0M\n\
+                L\t3\t+\t1\t+\t*\n";
+
+    let mut links = Vec::new();
+    let mut malformed = Vec::new();
+    graph::for_each_gfa_link(
+        &gfa[..],
+        |line| {
+            malformed.push(line.line_number);
+            Ok(())
+        },
+        |link| {
+            links.push((link.from_segment.to_vec(), link.to_reverse));
+            Ok(())
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        links,
+        vec![
+            (b"1".to_vec(), true),
+            (b"2".to_vec(), false),
+            (b"3".to_vec(), false)
+        ]
+    );
+    assert_eq!(malformed, vec![4]);
+
+    let mut first = None;
+    graph::for_each_gfa_link(
+        &gfa[..],
+        |_| Ok(()),
+        |link: GfaLink<'_>| {
+            first.get_or_insert(link.overlap.to_vec());
+            Ok(())
+        },
+    )
+    .unwrap();
+    assert_eq!(first.as_deref(), Some(&b"10M"[..]));
+
+    let interned =
+        InternedGraph::from_gfa_reader_with(&gfa[..], Some(5), |_| Ok(()))
+            .unwrap();
+    let owned = InternedGraph::try_from_named_edges(
+        graph::gfa_links_min_overlap(&gfa[..], 5),
+    )
+    .unwrap();
+    assert_eq!(interned.names.len(), 3);
+    assert_eq!(interned.graph, owned.graph);
+
+    let indexed =
+        IndexedGraph::from_gfa_reader_with(&gfa[..], None, |_| Ok(())).unwrap();
+    assert_eq!(indexed.num_edges(), 3);
+}
+
+/// The 4-edge-connected components agree with the brute-force
+/// definition, and split the 3-edge-connected components where three
+/// edges separate them
+#[test]
+fn four_edge_components() {
+    use three_edge_connected::{brute_force, four_edge};
+
+    let k5_pair = |bridges: usize| {
+        let mut edges = complete_edges(0, 5);
+        edges.extend(complete_edges(5, 5));
+        edges.extend((0..bridges).map(|i| (i, 5 + i)));
+        Graph::from_edges(edges.into_iter())
+    };
+    assert_eq!(four_edge::find_components(&k5_pair(4).graph).len(), 1);
+    let split = normalize(four_edge::find_components(&k5_pair(3).graph));
+    assert_eq!(split.len(), 2);
+    assert_eq!(
+        normalize(algorithm::find_components(&k5_pair(3).graph)).len(),
+        1
+    );
+    // K4 is 3-regular, so each node is cut off by its three edges
+    assert_eq!(
+        four_edge::find_components(&complete_graph(4).graph).len(),
+        4
+    );
+
+    let mut rng = generators::SplitMix64(633);
+    for _ in 0..300 {
+        let num_nodes = 2 + rng.next_below(6);
+        let num_edges = rng.next_below(20);
+        let mut graph = vec![Vec::new(); num_nodes];
+        for _ in 0..num_edges {
+            let from = rng.next_below(num_nodes);
+            let to = rng.next_below(num_nodes);
+            graph[from].push(to);
+            graph[to].push(from);
+        }
+        assert_eq!(
+            normalize(four_edge::find_components(&graph)),
+            brute_force::find_four_edge_components(&graph),
+            "{:?}",
+            graph
+        );
+    }
+}
+
+/// Cut vertices and separation pairs agree with removing the nodes
+#[test]
+fn separation_pairs() {
+    // a cycle is separated by any two nodes that aren't adjacent
+    let cycle: Vec<(usize, usize)> = (0..6).map(|i| (i, (i + 1) % 6)).collect();
+    let graph = Graph::from_edges(cycle.into_iter());
+    assert!(connectivity::cut_vertices(&graph.graph).is_empty());
+    let pairs = connectivity::separation_pairs(&graph.graph);
+    assert_eq!(pairs.len(), 9);
+    assert!(pairs.contains(&(0, 3)) && !pairs.contains(&(0, 1)));
+
+    // two K_5s sharing two nodes are one 3-edge-connected component,
+    // which the shared nodes separate
+    let mut edges = complete_edges(0, 5);
+    edges.extend(complete_edges(3, 5));
+    let graph = Graph::from_edges(edges.into_iter());
+    assert_eq!(algorithm::find_components(&graph.graph).len(), 1);
+    assert_eq!(connectivity::separation_pairs(&graph.graph), vec![(3, 4)]);
+    assert!(connectivity::separation_pairs(&complete_graph(5).graph).is_empty());
+
+    let components = |graph: &graph::FxMapGraph, removed: &[usize]| {
+        let graph: graph::FxMapGraph = graph
+            .iter()
+            .map(|(&node, adj)| {
+                let adj: Vec<usize> = if removed.contains(&node) {
+                    Vec::new()
+                } else {
+                    adj.iter()
+                        .copied()
+                        .filter(|u| !removed.contains(u))
+                        .collect()
+                };
+                (node, adj.into())
+            })
+            .collect();
+        connectivity::connected_components(&graph).len() - removed.len()
+    };
+    let mut rng = generators::SplitMix64(634);
+    for _ in 0..200 {
+        let num_nodes = 2 + rng.next_below(8);
+        let edges: Vec<(usize, usize)> = (0..rng.next_below(16))
+            .map(|_| (rng.next_below(num_nodes), rng.next_below(num_nodes)))
+            .collect();
+        let graph = Graph::from_edges(edges.into_iter());
+        let graph = &graph.graph;
+        let count = components(graph, &[]);
+        let mut cut = Vec::new();
+        let mut pairs = Vec::new();
+        let mut nodes: Vec<usize> = graph.keys().copied().collect();
+        nodes.sort_unstable();
+        for &u in &nodes {
+            if components(graph, &[u]) > count {
+                cut.push(u);
+            }
+        }
+        for &u in &nodes {
+            for &v in nodes.iter().filter(|&&v| v > u) {
+                if !cut.contains(&u)
+                    && !cut.contains(&v)
+                    && components(graph, &[u, v]) > count
+                {
+                    pairs.push((u, v));
+                }
+            }
+        }
+        assert_eq!(connectivity::cut_vertices(graph), cut, "{:?}", graph);
+        assert_eq!(connectivity::separation_pairs(graph), pairs, "{:?}", graph);
+    }
+}
+
+/// The DFS spanning forest is recorded along with the components
+#[test]
+fn spanning_forest() {
+    let mut edges = complete_edges(0, 4);
+    edges.extend(complete_edges(4, 3));
+    edges.push((0, 9));
+    let graph = Graph::from_edges(edges.into_iter());
+    let (components, forest) =
+        algorithm::find_components_with_forest(&graph.graph);
+    assert_eq!(components, algorithm::find_components(&graph.graph));
+
+    // one tree per connected component, with every other node a child
+    // of one of its neighbors, reached after it
+    let num_nodes = graph.graph.len();
+    let roots: Vec<usize> = forest.roots().collect();
+    assert_eq!(roots.len(), 2);
+    assert_eq!(forest.preorder().len(), num_nodes);
+    let edges: Vec<(usize, usize)> = forest.tree_edges().collect();
+    assert_eq!(edges.len(), num_nodes - roots.len());
+    let position = |node: usize| {
+        forest.preorder().iter().position(|&n| n == node).unwrap()
+    };
+    for &(parent, child) in &edges {
+        assert_eq!(forest.parent(child), Some(parent));
+        assert!(graph.graph[&parent].contains(&child));
+        assert!(position(parent) < position(child));
+    }
+    assert!(roots.iter().all(|&root| forest.parent(root).is_none()));
+}
+
+/// The DFS numbers of each node can be read from the state by name
+#[test]
+fn node_dfs_info() {
+    use three_edge_connected::state::State;
+
+    let edges = [("a", "b"), ("b", "c"), ("c", "a"), ("c", "d")];
+    let graph = graph::InternedGraph::from_named_edges(
+        edges.iter().map(|(a, b)| (a.as_bytes(), b.as_bytes())),
+    );
+    let mut state = State::initialize(&graph.graph);
+    assert_eq!(state.node_info(0), None);
+    algorithm::three_edge_connect(&graph.graph, &mut state);
+
+    let info = |name: &str| {
+        state
+            .node_info_by_name(&graph.names, name.as_bytes())
+            .unwrap()
+    };
+    let mut pre: Vec<usize> = ["a", "b", "c", "d"]
+        .iter()
+        .map(|name| info(name).pre)
+        .collect();
+    pre.sort_unstable();
+    assert_eq!(pre, vec![1, 2, 3, 4]);
+    let root = ["a", "b", "c", "d"]
+        .iter()
+        .find(|name| info(name).pre == 1)
+        .unwrap();
+    assert_eq!(info(root).num_descendants, 4);
+    assert_eq!(info("d").num_descendants, 1);
+    // the nodes of the triangle reach the first of them by back-edges,
+    // while `d` only has the edge to its parent
+    assert_eq!(info("d").lowpt, info("d").pre);
+    assert!(["a", "b", "c"].iter().all(|name| info(name).lowpt == 1));
+    assert_eq!(state.node_info_by_name(&graph.names, b"e"), None);
+    assert_eq!(state.node_info(4), None);
+}
+
+/// The node table has a row per node with its component and DFS numbers
+#[test]
+fn node_table() {
+    use three_edge_connected::state::State;
+
+    let edges = [("a", "b"), ("b", "a"), ("a", "b"), ("b", "c")];
+    let graph = graph::InternedGraph::from_named_edges(
+        edges.iter().map(|(a, b)| (a.as_bytes(), b.as_bytes())),
+    );
+    let mut state = State::initialize(&graph.graph);
+    algorithm::three_edge_connect(&graph.graph, &mut state);
+    let components = three_edge_connected::filter_min_size(
+        state.components().clone().into_inner(),
+        2,
+    );
+
+    let mut out = Vec::new();
+    output::write_node_table(
+        &mut out,
+        &graph.graph,
+        &graph.names,
+        |node| state.node_info(node),
+        &components,
+        ComponentIds::Index,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "node\tdegree\tcomponent\tpre\tlowpt\tdescendants\n\
+         a\t3\t0\t1\t1\t3\n\
+         b\t4\t0\t2\t2\t2\n\
+         c\t1\t\t3\t3\t1\n"
+    );
+
+    let mut out = Vec::new();
+    output::write_node_table(
+        &mut out,
+        &graph.graph,
+        &graph.names,
+        |_| None,
+        &[],
+        ComponentIds::Index,
+    )
+    .unwrap();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .ends_with("\nc\t1\t\t\t\t\n"));
+}
+
+/// The bridge tree joins the 2-edge-connected components by bridges
+#[test]
+fn bridge_tree() {
+    // two K_4s joined by a path through node 8
+    let mut edges = complete_edges(0, 4);
+    edges.extend(complete_edges(4, 4));
+    edges.extend([(3, 8), (8, 4)]);
+    let graph = Graph::from_edges(edges.into_iter());
+    let tree = connectivity::bridge_tree(&graph.graph);
+    assert_eq!(
+        tree.components,
+        vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7], vec![8]]
+    );
+    assert_eq!(tree.node_components[8], 2);
+    let mut joined: Vec<(usize, usize)> = tree
+        .edges
+        .iter()
+        .map(|edge| {
+            let (a, b) = edge.components;
+            assert_eq!(tree.node_components[edge.bridge.0], a);
+            (a.min(b), a.max(b))
+        })
+        .collect();
+    joined.sort_unstable();
+    assert_eq!(joined, vec![(0, 2), (1, 2)]);
+
+    let names: Vec<Vec<u8>> =
+        (0..9).map(|n| format!("n{}", n).into_bytes()).collect();
+    let tree = connectivity::bridge_tree(&bridged_k_graphs(4, 4, 1).graph);
+    let mut out = Vec::new();
+    output::write_bridge_tree_json(&mut out, &tree, &names).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "{\n  \"components\": [\n    [\"n0\", \"n1\", \"n2\", \"n3\"],\n    \
+         [\"n4\", \"n5\", \"n6\", \"n7\"]\n  ],\n  \"bridges\": [\n    \
+         {\"components\": [0, 1], \"edge\": [\"n3\", \"n4\"]}\n  ]\n}\n"
+    );
+    let mut out = Vec::new();
+    output::write_bridge_tree_dot(&mut out, &tree, &names).unwrap();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("  0 -- 1 [label=\"n3 -- n4\"];\n"));
+}
+
+/// The three levels of components are nested in each other
+#[test]
+fn nested_decomposition() {
+    // two K_4s joined by a path through node 8, and a separate cycle
+    let mut edges = complete_edges(0, 4);
+    edges.extend(complete_edges(4, 4));
+    edges.extend([(3, 8), (8, 4), (9, 10), (10, 11), (11, 9)]);
+    let graph = Graph::from_edges(edges.into_iter());
+    let decomposition = connectivity::decompose(&graph.graph);
+    assert_eq!(decomposition.connected.len(), 2);
+    assert_eq!(decomposition.two_edge.len(), 4);
+    assert_eq!(
+        normalize(decomposition.three_edge.clone()),
+        normalize(algorithm::find_components(&graph.graph))
+    );
+    assert_eq!(decomposition.connected_children[0], vec![0, 1, 2]);
+    assert_eq!(decomposition.two_edge_children[3].len(), 3);
+
+    for (ix, children) in decomposition.connected_children.iter().enumerate() {
+        let mut nodes: Vec<usize> = children
+            .iter()
+            .flat_map(|&c| decomposition.two_edge[c].iter().copied())
+            .collect();
+        nodes.sort_unstable();
+        assert_eq!(nodes, decomposition.connected[ix]);
+    }
+    for (ix, children) in decomposition.two_edge_children.iter().enumerate() {
+        let mut nodes: Vec<usize> = children
+            .iter()
+            .flat_map(|&c| decomposition.three_edge[c].iter().copied())
+            .collect();
+        nodes.sort_unstable();
+        assert_eq!(nodes, decomposition.two_edge[ix]);
+    }
+
+    let names: Vec<Vec<u8>> =
+        (0..12).map(|n| format!("n{}", n).into_bytes()).collect();
+    let mut out = Vec::new();
+    output::write_decomposition_json(&mut out, &decomposition, &names).unwrap();
+    let json = String::from_utf8(out).unwrap();
+    assert!(json.starts_with("[\n  {\"nodes\": [\"n0\", \"n1\""));
+    assert!(json.contains("{\"nodes\": [\"n8\"], \"three_edge\": [[\"n8\"]]}"));
+    assert_eq!(json.matches("\"two_edge\"").count(), 2);
+}
+
+/// Changes between two sets of components are grouped by shared nodes
+#[test]
+fn component_diff() {
+    use three_edge_connected::diff::{diff_components, ChangeKind};
+
+    let before = vec![
+        vec!["a", "b", "c", "d"],
+        vec!["e", "f"],
+        vec!["g", "h"],
+        vec!["i", "j"],
+        vec!["k", "l"],
+        vec!["x", "y"],
+    ];
+    let after = vec![
+        vec!["a", "b"],
+        vec!["c", "d"],
+        vec!["e", "f", "g", "h"],
+        vec!["i", "j"],
+        vec!["k", "l", "m"],
+        vec!["z", "w"],
+    ];
+    let changes: Vec<(ChangeKind, Vec<usize>, Vec<usize>)> =
+        diff_components(&before, &after)
+            .into_iter()
+            .map(|change| (change.kind, change.before, change.after))
+            .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (ChangeKind::Split, vec![0], vec![0, 1]),
+            (ChangeKind::Merged, vec![1, 2], vec![2]),
+            (ChangeKind::Unchanged, vec![3], vec![3]),
+            (ChangeKind::Changed, vec![4], vec![4]),
+            (ChangeKind::Vanished, vec![5], vec![]),
+            (ChangeKind::Appeared, vec![], vec![5]),
+        ]
+    );
+
+    let before = vec![vec![1, 2], vec![3, 4]];
+    let after = vec![vec![1, 3], vec![2, 4]];
+    let changes = diff_components(&before, &after);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].kind, ChangeKind::Rearranged);
+}
+
+/// Components of chunks with their own node indices can be merged
+#[test]
+fn merged_components() {
+    use three_edge_connected::Components;
+
+    // two K_4s sharing node 3, as two chunks with local indices
+    let mut edges = complete_edges(0, 4);
+    edges.extend(complete_edges(3, 4));
+    let graph = Graph::from_edges(edges.into_iter());
+    let chunk = Components::find(&complete_graph(4).graph);
+    let first = chunk.clone().map_nodes(&[0, 1, 2, 3]);
+    let second = chunk.map_nodes(&[3, 4, 5, 6]);
+    let merged = first.merge(second);
+    assert_eq!(
+        normalize(merged.into_inner()),
+        normalize(algorithm::find_components(&graph.graph))
+    );
+
+    // a chain of overlapping components is merged into one, and the
+    // nodes that are only in one result keep their components
+    let first = Components::from(vec![vec![0, 1], vec![2, 3], vec![7]]);
+    let second = Components::from(vec![vec![1, 2], vec![5, 4], vec![7]]);
+    assert_eq!(
+        first.merge(second).into_inner(),
+        vec![vec![0, 1, 2, 3], vec![7], vec![5, 4]]
+    );
+}
+
+/// The second algorithm finds the same components as the first
+#[cfg(feature = "cross-check")]
+#[test]
+fn cut_pair_components() {
+    use three_edge_connected::{brute_force, cut_pairs};
+
+    for graph in [
+        bridged_k_graphs(4, 4, 1),
+        bridged_k_graphs(4, 5, 2),
+        bridged_k_graphs(5, 5, 3),
+        complete_graph(3),
+        bipartite_graph(3, 3),
+    ] {
+        cut_pairs::cross_check(&graph.graph).unwrap();
+    }
+
+    let mut rng = generators::SplitMix64(641);
+    for _ in 0..500 {
+        let num_nodes = 1 + rng.next_below(8);
+        let mut graph = vec![Vec::new(); num_nodes];
+        for _ in 0..rng.next_below(20) {
+            let from = rng.next_below(num_nodes);
+            let to = rng.next_below(num_nodes);
+            graph[from].push(to);
+            graph[to].push(from);
+        }
+        assert_eq!(
+            cut_pairs::find_components(&graph),
+            brute_force::find_components(&graph),
+            "{:?}",
+            graph
+        );
+    }
+
+    // a partition that isn't the components is reported
+    let graph = bridged_k_graphs(4, 4, 1);
+    let mismatch =
+        cut_pairs::cross_check_components(&graph.graph, vec![(0..8).collect()])
+            .unwrap_err();
+    assert_eq!(mismatch.cut_pairs.len(), 2);
+    assert_eq!(mismatch.node, 4);
+}
+
+/// Nodes with a huge number of neighbors are handled one edge at a time
+#[test]
+fn hub_nodes() {
+    let num = 100_000;
+
+    // a wheel, where every node of the rim has degree 3
+    let mut wheel: Vec<Vec<usize>> = vec![Vec::new(); num + 1];
+    for rim in 1..=num {
+        let next = rim % num + 1;
+        wheel[0].push(rim);
+        wheel[rim].push(0);
+        wheel[rim].push(next);
+        wheel[next].push(rim);
+    }
+    let components = algorithm::find_components(&wheel);
+    assert_eq!(components.len(), 1);
+    assert_eq!(components[0].len(), num + 1);
+
+    // a star, whose leaves are only connected by one edge, except for
+    // the ones with three parallel edges and a self-loop
+    let mut star: Vec<Vec<usize>> = vec![Vec::new(); num + 1];
+    for leaf in 1..=num {
+        let edges = if leaf % 10 == 0 { 3 } else { 1 };
+        for _ in 0..edges {
+            star[0].push(leaf);
+            star[leaf].push(0);
+        }
+        if leaf % 10 == 0 {
+            star[leaf].extend([leaf, leaf]);
+        }
+    }
+    let components = normalize(algorithm::find_components(&star));
+    assert_eq!(components.len(), num - num / 10 + 1);
+    let hub = components.iter().find(|c| c.contains(&0)).unwrap();
+    assert_eq!(*hub, (0..=num).step_by(10).collect::<Vec<_>>());
+}
+
+/// The components are stored in one flat vector, with slices of it
+#[test]
+fn flat_components() {
+    use three_edge_connected::Components;
+
+    let lists = vec![vec![3, 1], vec![0], vec![2, 5, 4], vec![6]];
+    let mut components = Components::from(lists.clone());
+    assert_eq!(components.nodes(), &[3, 1, 0, 2, 5, 4, 6]);
+    assert_eq!(components.iter().len(), 4);
+    assert_eq!(&components[2], &[2, 5, 4]);
+    assert_eq!(components.last(), Some(&[6][..]));
+    assert_eq!(components.get(4), None);
+    assert_eq!(components.component_of(4), Some(2));
+    assert_eq!(components.clone().into_inner(), lists);
+
+    components.push([7, 8]);
+    components.retain_min_size(2);
+    let kept: Vec<&[usize]> = (&components).into_iter().collect();
+    assert_eq!(kept, vec![&[3, 1][..], &[2, 5, 4], &[7, 8]]);
+    assert_eq!(components.nodes(), &[3, 1, 2, 5, 4, 7, 8]);
+    components.retain_min_size(3);
+    assert_eq!(components.into_inner(), vec![vec![2, 5, 4]]);
+
+    // the state collects the components the same way
+    let graph = bridged_k_graphs(4, 3, 2);
+    let found = Components::find(&graph.graph);
+    assert_eq!(
+        normalize(found.into_inner()),
+        normalize(algorithm::find_components(&graph.graph))
+    );
+}
+
+/// Cycles in the state's linked lists are errors instead of hanging
+#[test]
+fn corrupt_state_cycles() {
+    use three_edge_connected::state::{CorruptState, State};
+
+    let graph: Vec<Vec<usize>> = vec![vec![1], vec![0, 2], vec![1]];
+    let mut state = State::initialize(&graph);
+    algorithm::three_edge_connect(&graph, &mut state);
+    let found = state.components().len();
+
+    // 0 -> 1 -> 2 -> 1 never gets back to 0
+    state.next_sigma = vec![1, 2, 1];
+    assert_eq!(state.sigma_iter(0).count(), 3);
+    assert!(!state.sigma_iter(1).is_cycle_broken());
+    assert_eq!(
+        state.try_add_component(0),
+        Err(CorruptState::SigmaCycle { node: 0 })
+    );
+    assert_eq!(state.components().len(), found);
+    state.try_add_component(1).unwrap();
+    assert_eq!(state.components().last(), Some(&[1, 2][..]));
+
+    state.next_on_path = vec![1, 2, 1];
+    let err = state
+        .try_absorb_path_with_visitor(0, 1, None, &mut ())
+        .unwrap_err();
+    assert_eq!(err, CorruptState::PathCycle { node: 0 });
+    assert_eq!(err.to_string(), "the path of node 0 has a cycle");
+}
+
+/// The invariants of the state hold after a run, and broken ones are
+/// found
+#[test]
+fn state_invariants() {
+    use three_edge_connected::{invariants::InvariantViolation, state::State};
+
+    let graph = bridged_k_graphs(4, 5, 2);
+    let mut state = State::initialize(&graph.graph);
+    state.check_invariants(&graph.graph).unwrap();
+    algorithm::three_edge_connect(&graph.graph, &mut state);
+    state.check_invariants(&graph.graph).unwrap();
+
+    let adjacency: Vec<Vec<usize>> = vec![vec![1, 2], vec![0, 2], vec![0, 1]];
+    let mut state = State::initialize(&adjacency);
+    algorithm::three_edge_connect(&adjacency, &mut state);
+    state.check_invariants(&adjacency).unwrap();
+
+    let mut broken = state.clone();
+    broken.next_sigma = vec![1, 1, 2];
+    assert_eq!(
+        broken.check_invariants(&adjacency),
+        Err(InvariantViolation::SigmaNotPermutation { node: 1 })
+    );
+    let mut broken = state.clone();
+    broken.next_on_path = vec![1, 2, 0];
+    assert_eq!(
+        broken.check_invariants(&adjacency),
+        Err(InvariantViolation::PathCycle { node: 0 })
+    );
+    let mut broken = state.clone();
+    broken.degrees[2] = 5;
+    assert_eq!(
+        broken.check_invariants(&adjacency),
+        Err(InvariantViolation::DegreeTooLarge { node: 2 })
+    );
+    // the three nodes are separate components
+    let mut broken = state;
+    broken.next_sigma = vec![1, 0, 2];
+    assert!(matches!(
+        broken.check_invariants(&adjacency),
+        Err(InvariantViolation::ComponentNotSigma { .. })
+    ));
+}
+
+/// With `debug-invariants`, the invariants are checked after every
+/// step of the algorithm, which panics if one doesn't hold
+#[cfg(feature = "debug-invariants")]
+#[test]
+fn debug_invariants_runs() {
+    for seed in 0..100 {
+        let mut rng = generators::SplitMix64(seed);
+        let num_nodes = 2 + rng.next_below(30);
+        let num_edges = rng.next_below(3 * num_nodes);
+        let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); num_nodes];
+        for _ in 0..num_edges {
+            let a = rng.next_below(num_nodes);
+            let b = rng.next_below(num_nodes);
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+        }
+        assert_eq!(
+            normalize(algorithm::find_components(&adjacency)),
+            normalize(three_edge_connected::brute_force::find_components(
+                &adjacency
+            ))
+        );
+    }
+}
+
+/// A run can be paused, saved, and resumed with the same components
+#[test]
+fn checkpoint_resume() {
+    use three_edge_connected::checkpoint::Checkpoint;
+
+    let planted = generators::PlantedPartition::random(647, 2000, 8);
+    let graph = Graph::from_edges(planted.edges.iter().copied());
+    let expected = algorithm::find_components(&graph.graph);
+
+    let mut checkpoint = Checkpoint::<u32>::new(&graph.graph);
+    let mut pauses = 0;
+    while !checkpoint.resume(&graph.graph, Some(1), None).unwrap() {
+        pauses += 1;
+        let mut saved = Vec::new();
+        checkpoint.write_to(&mut saved, &graph.graph).unwrap();
+        checkpoint =
+            Checkpoint::read_from(&mut saved.as_slice(), &graph.graph).unwrap();
+    }
+    assert!(pauses > 10);
+    assert!(checkpoint.is_finished());
+    assert_eq!(checkpoint.nodes_visited(), graph.graph.len());
+    assert_eq!(checkpoint.into_components().into_inner(), expected);
+
+    // a checkpoint is only read for the same graph and index type
+    let checkpoint = Checkpoint::<u32>::new(&graph.graph);
+    let mut saved = Vec::new();
+    checkpoint.write_to(&mut saved, &graph.graph).unwrap();
+    let other = complete_graph(5);
+    let err = Checkpoint::<u32>::read_from(&mut saved.as_slice(), &other.graph)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid checkpoint: written for a different graph"
+    );
+    assert!(Checkpoint::<usize>::read_from(
+        &mut saved.as_slice(),
+        &graph.graph
+    )
+    .is_err());
+    saved[100] ^= 0xff;
+    assert!(
+        Checkpoint::<u32>::read_from(&mut saved.as_slice(), &graph.graph)
+            .is_err()
+    );
+}
+
+/// Saved graphs are loaded with the same names, node indices, and
+/// neighbor order, so the components are the same
+#[test]
+fn saved_graph() {
+    use three_edge_connected::graph::InternedGraph;
+
+    let planted = generators::PlantedPartition::random(3, 200, 6);
+    let mut gfa = Vec::new();
+    generators::write_gfa(&mut gfa, &planted.edges).unwrap();
+    let mut graph =
+        InternedGraph::from_gfa_reader(&mut gfa.as_slice()).unwrap();
+    let (from, to) = planted.edges[0];
+    let (from, to) = (from.to_string(), to.to_string());
+    assert!(graph.remove_named_edge(from.as_bytes(), to.as_bytes()));
+
+    let mut saved = Vec::new();
+    graph.write_to(&mut saved).unwrap();
+    let loaded = InternedGraph::read_from(&mut saved.as_slice()).unwrap();
+    assert!(loaded.names.iter().eq(graph.names.iter()));
+    for (node, neighbors) in graph.graph.iter() {
+        assert_eq!(loaded.graph[node].as_slice(), neighbors.as_slice());
+    }
+    assert_eq!(
+        normalize(algorithm::find_components(&loaded.graph)),
+        normalize(algorithm::find_components(&graph.graph))
+    );
+
+    let path = std::env::temp_dir().join(format!(
+        "three-edge-connected-graph-{}.3ecg",
+        std::process::id()
+    ));
+    let graph = Graph::from_gfa_reader(&mut gfa.as_slice()).unwrap();
+    graph.save(&path).unwrap();
+    let loaded = Graph::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.inv_names, graph.inv_names);
+    assert_eq!(loaded.graph, graph.graph);
+
+    for len in [0, 20, saved.len() - 1] {
+        let err = InternedGraph::read_from(&mut &saved[..len]).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+
+/// The overlaps and the chosen tags of the links are kept as the labels
+/// of their edges, through subgraphs and edge removals
+#[test]
+fn gfa_edge_labels() {
+    let gfa = b"S\t1\t*\n\
+                L\t1\t+\t2\t-\t10M\tID:Z:a\tRC:i:4\n\
+                L\t2\t-\t1\t+\t5M\tID:Z:b\n\
+                L\t2\t+\t3\t+\t*\n\
+                L\t3\t+\t3\t-\t0M\tXX:Z:c\tID:Z:d\n";
+
+    let graph = Graph::from_gfa_reader_labeled(&mut &gfa[..], &["ID"]).unwrap();
+    let labels = graph.edge_labels.as_ref().unwrap();
+    assert_eq!(labels.len(), 4);
+    let between = labels.get(1, 0);
+    assert_eq!(between.len(), 2);
+    assert_eq!(between[0].overlap, b"10M");
+    assert_eq!(between[0].tags, vec![b"ID:Z:a".to_vec()]);
+    assert_eq!(between[0].tag(b"ID"), Some(&b"a"[..]));
+    assert_eq!(between[0].tag(b"RC"), None);
+    assert_eq!(between[1].tag(b"ID"), Some(&b"b"[..]));
+    assert_eq!(labels.get(1, 2)[0].overlap, b"*");
+    assert!(labels.get(1, 2)[0].tags.is_empty());
+    assert_eq!(labels.get(2, 2)[0].tag(b"ID"), Some(&b"d"[..]));
+    assert!(labels.get(0, 2).is_empty());
+    assert!(Graph::from_gfa_reader(&mut &gfa[..])
+        .unwrap()
+        .edge_labels
+        .is_none());
+
+    let names = [b"2".to_vec(), b"3".to_vec()];
+    let subgraph = graph.induced_subgraph(names.iter());
+    let labels = subgraph.edge_labels.as_ref().unwrap();
+    assert_eq!(labels.len(), 2);
+    assert_eq!(labels.get(0, 1)[0].overlap, b"*");
+    assert_eq!(labels.get(1, 1)[0].tag(b"ID"), Some(&b"d"[..]));
+
+    let mut graph = graph;
+    assert!(graph.remove_edge(0, 1));
+    let labels = graph.edge_labels.as_ref().unwrap();
+    assert_eq!(labels.len(), 3);
+    assert_eq!(labels.get(0, 1)[0].tag(b"ID"), Some(&b"a"[..]));
+    assert!(graph.memory_usage() > labels.memory_usage());
+}
+
+/// The components as disjoint sets find the component of each node
+/// by its smallest node, and round-trip through their roots
+#[test]
+fn component_disjoint_sets() {
+    use three_edge_connected::components::{Components, DisjointSets};
+
+    let mut edges = complete_edges(0, 4);
+    edges.extend(complete_edges(4, 4));
+    edges.push((3, 4));
+    edges.push((7, 8));
+    let graph = Graph::from_edges(edges.into_iter());
+    let components = Components::find(&graph.graph);
+
+    let sets = components.disjoint_sets(graph.graph.len());
+    assert_eq!(sets.len(), 9);
+    assert_eq!(sets.num_sets(), 3);
+    assert_eq!(sets.roots(), &[0, 0, 0, 0, 4, 4, 4, 4, 8]);
+    assert!(sets.same_set(1, 3));
+    assert!(!sets.same_set(3, 4));
+    assert_eq!(sets.find(8), 8);
+    assert_eq!(
+        normalize(sets.to_components().into_inner()),
+        normalize(components.clone().into_inner())
+    );
+
+    let restored = DisjointSets::from_roots(sets.roots().to_vec()).unwrap();
+    assert_eq!(restored, sets);
+    assert!(DisjointSets::from_roots(vec![0, 0, 1]).is_none());
+    assert!(DisjointSets::from_roots(vec![1, 1]).is_none());
+    assert!(DisjointSets::from_roots(vec![0, 5]).is_none());
+
+    // the nodes filtered out are sets of their own
+    let mut filtered = components;
+    filtered.retain_min_size(2);
+    let sets = filtered.disjoint_sets(9);
+    assert_eq!(sets.find(8), 8);
+    assert_eq!(sets.num_sets(), 3);
+}
@@ -0,0 +1,42 @@
+// JavaScript wrapper for the WebAssembly build of three-edge-connected.
+//
+// Build the module with
+//
+//   cargo rustc --release --lib --target wasm32-unknown-unknown \
+//     --no-default-features --features wasm --crate-type cdylib
+//
+// and load it with `load(fetch("three_edge_connected.wasm"))`.
+
+export async function load(source) {
+  const { instance } = await WebAssembly.instantiateStreaming(source);
+  const wasm = instance.exports;
+
+  // Takes an array of [from, to] pairs of non-negative integer node
+  // IDs, and returns the 3-edge-connected components as arrays of
+  // node IDs.
+  function findComponents(edgeList) {
+    const len = 2 * edgeList.length;
+    const edgesPtr = wasm.tec_alloc(len);
+    new Uint32Array(wasm.memory.buffer, edgesPtr, len).set(edgeList.flat());
+
+    const resultPtr = wasm.tec_find_components(edgesPtr, edgeList.length);
+    wasm.tec_free(edgesPtr, len);
+
+    // the memory may have grown, so the view is created afterwards
+    const resultLen = new Uint32Array(wasm.memory.buffer, resultPtr, 1)[0];
+    const result = new Uint32Array(wasm.memory.buffer, resultPtr + 4, resultLen);
+
+    const components = [];
+    let i = 1;
+    for (let c = 0; c < result[0]; c++) {
+      const size = result[i];
+      components.push(Array.from(result.subarray(i + 1, i + 1 + size)));
+      i += 1 + size;
+    }
+
+    wasm.tec_free(resultPtr, resultLen + 1);
+    return components;
+  }
+
+  return { findComponents };
+}